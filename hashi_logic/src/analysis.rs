@@ -0,0 +1,139 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use crate::session::{Event, EventKind, GameSession};
+
+const MOVE_COLOR: &str = "dodgerblue";
+const HINT_COLOR: &str = "gold";
+const UNDO_COLOR: &str = "crimson";
+
+///
+/// Where the player spent the time of a game, based on the times of the moves.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Analysis {
+    /// Time played when each move of the history was made and the time spent on it since the previous move
+    pub moves: Vec<(f64, f64)>,
+    /// Islands with the time spent on the moves touching them, the longest first
+    pub islands: Vec<(usize, f64)>,
+    pub events: Vec<Event>,
+}
+
+impl Analysis {
+    pub fn new(session: &GameSession) -> Self {
+        let history = session.history();
+        let moves: Vec<(f64, f64)> = history
+            .iter()
+            .scan(0.0, |previous, m| {
+                let spent = f64::max(0.0, m.at_ms - *previous);
+                *previous = m.at_ms;
+                Some((m.at_ms, spent))
+            })
+            .collect();
+        let mut islands = BTreeMap::<usize, f64>::new();
+        for (m, (_, spent)) in history.iter().zip(&moves) {
+            *islands.entry(m.bridge.0).or_default() += spent;
+            *islands.entry(m.bridge.1).or_default() += spent;
+        }
+        let mut islands: Vec<(usize, f64)> = islands.into_iter().collect();
+        islands.sort_by(|a, b| b.1.total_cmp(&a.1));
+        Analysis {
+            moves,
+            islands,
+            events: session.events().to_vec(),
+        }
+    }
+
+    ///
+    /// Get the indices of the `count` moves the player spent the most time on, the longest first.
+    ///
+    pub fn slowest_moves(&self, count: usize) -> Vec<usize> {
+        let mut indices: Vec<usize> = (0..self.moves.len()).collect();
+        indices.sort_by(|a, b| self.moves[*b].1.total_cmp(&self.moves[*a].1));
+        indices.truncate(count);
+        indices
+    }
+
+    ///
+    /// Draw the timeline of the game as SVG image `width` × `height` pixels.
+    ///
+    /// Each move is a bar as high as the time spent on it. Hints and undos are vertical lines.
+    ///
+    pub fn timeline_svg(&self, width: u32, height: u32) -> String {
+        let (width, height) = (width as f64, height as f64);
+        let end = self
+            .moves
+            .iter()
+            .map(|(at, _)| *at)
+            .chain(self.events.iter().map(|e| e.at_ms))
+            .fold(1.0, f64::max);
+        let longest = self.moves.iter().map(|(_, s)| *s).fold(1.0, f64::max);
+        let mut svg = String::new();
+        // Writing to a String cannot fail.
+        let _ = write!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width:.0}" height="{height:.0}">"#
+        );
+        for (at, spent) in &self.moves {
+            let bar = spent / longest * height;
+            let _ = write!(
+                svg,
+                r#"<rect x="{:.1}" y="{:.1}" width="2" height="{bar:.1}" fill="{MOVE_COLOR}"/>"#,
+                at / end * (width - 2.0),
+                height - bar
+            );
+        }
+        for event in &self.events {
+            let x = event.at_ms / end * (width - 2.0) + 1.0;
+            let color = match event.kind {
+                EventKind::Hint => HINT_COLOR,
+                EventKind::Undo => UNDO_COLOR,
+            };
+            let _ = write!(
+                svg,
+                r#"<line x1="{x:.1}" y1="0" x2="{x:.1}" y2="{height:.0}" stroke="{color}"/>"#
+            );
+        }
+        svg.push_str("</svg>");
+        svg
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        hex::{HexSystem, Island},
+        session::{EventKind, GameSession},
+    };
+
+    use super::Analysis;
+
+    #[test]
+    fn analysis() {
+        let mut islands = vec![Island::Empty; 22];
+        islands[0] = Island::Bridged(1);
+        islands[4] = Island::Bridged(2);
+        islands[6] = Island::Bridged(1);
+        let mut session = GameSession::new(HexSystem::from_islands(4, 5, islands));
+        session.set_clock(1_000.0);
+        session.cycle_bridge(0, 4).unwrap();
+        session.set_clock(5_000.0);
+        session.hint();
+        session.set_clock(11_000.0);
+        session.cycle_bridge(4, 6).unwrap();
+
+        let analysis = Analysis::new(&session);
+        assert_eq!(analysis.moves, [(1_000.0, 1_000.0), (11_000.0, 10_000.0)]);
+        assert_eq!(analysis.slowest_moves(1), [1]);
+        assert_eq!(analysis.slowest_moves(5), [1, 0]);
+        // Island 4 is part of both moves.
+        assert_eq!(
+            analysis.islands,
+            [(4, 11_000.0), (6, 10_000.0), (0, 1_000.0)]
+        );
+        assert_eq!(analysis.events[0].kind, EventKind::Hint);
+
+        let svg = analysis.timeline_svg(200, 40);
+        assert_eq!(svg.matches("<rect").count(), 2);
+        assert_eq!(svg.matches("<line").count(), 1);
+    }
+}
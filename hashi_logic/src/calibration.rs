@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    hex::{BridgeState, HexSystem},
+    session::Move,
+    solver::{NODE_BUDGET, Technique},
+};
+
+/// Number of weights of the model: one per technique, one for guessing and one per search node
+const WEIGHTS: usize = Technique::ALL.len() + 2;
+/// Pull of the fitted weights towards the current ones, e.g. for techniques missing in the replays
+const RIDGE: f64 = 1.0;
+
+///
+/// Weights estimating how long players take to solve a puzzle.
+///
+/// A puzzle takes the cost of the most advanced technique it needs for each bridge of its solution
+/// and the node cost for each node the solver searches.
+/// Only the par time of shared puzzles is estimated this way; `rate` and the generator do not use it.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RatingModel {
+    /// Milliseconds per bridge for each technique in the order of `Technique::ALL` and for guessing last
+    pub technique_ms: [f64; 4],
+    /// Milliseconds per search node
+    pub node_ms: f64,
+}
+
+impl Default for RatingModel {
+    fn default() -> Self {
+        RatingModel {
+            technique_ms: [3_000.0, 5_000.0, 8_000.0, 12_000.0],
+            node_ms: 50.0,
+        }
+    }
+}
+
+impl RatingModel {
+    ///
+    /// Estimate the time in milliseconds to solve `system` from its start.
+    ///
+    /// Returns `None` if the solver cannot solve the puzzle within its node budget.
+    ///
+    pub fn predict_ms(&self, system: &HexSystem) -> Option<f64> {
+        features(system).map(|x| dot(&x, &self.weights()))
+    }
+
+    ///
+    /// Fit the weights to the solve times of `replays`, i.e. puzzles with the moves that solved them.
+    ///
+    /// The time of a replay is the time of its last move. Replays without moves and puzzles the solver
+    /// cannot solve are skipped. Weights without any replay to fit them to stay as they are.
+    ///
+    /// This is an offline analysis: nothing loads the fitted weights, they replace the defaults by hand.
+    ///
+    pub fn calibrate(&self, replays: &[(HexSystem, Vec<Move>)]) -> RatingModel {
+        // Normal equations of the least squares fit, pulled towards the current weights
+        let current = self.weights();
+        let mut matrix = [[0.0; WEIGHTS]; WEIGHTS];
+        let mut vector = current.map(|w| RIDGE * w);
+        for (i, row) in matrix.iter_mut().enumerate() {
+            row[i] = RIDGE;
+        }
+        for (system, moves) in replays {
+            let (Some(last), Some(x)) = (moves.last(), features(system)) else {
+                continue;
+            };
+            for i in 0..WEIGHTS {
+                for j in 0..WEIGHTS {
+                    matrix[i][j] += x[i] * x[j];
+                }
+                vector[i] += x[i] * last.at_ms;
+            }
+        }
+        let weights = solve(matrix, vector).unwrap_or(current);
+        RatingModel {
+            technique_ms: [weights[0], weights[1], weights[2], weights[3]],
+            node_ms: weights[4],
+        }
+    }
+
+    fn weights(&self) -> [f64; WEIGHTS] {
+        let t = self.technique_ms;
+        [t[0], t[1], t[2], t[3], self.node_ms]
+    }
+}
+
+///
+/// Get the bridges of the solution in the column of the most advanced technique needed and the search nodes.
+///
+fn features(system: &HexSystem) -> Option<[f64; WEIGHTS]> {
+    let mut start = system.clone();
+    start.apply_start();
+    let nodes = start.rate_by_nodes(NODE_BUDGET)?;
+    let technique = Technique::ALL
+        .into_iter()
+        .position(|t| start.is_solvable_with(t))
+        .unwrap_or(Technique::ALL.len());
+    let bridges = system
+        .bridges
+        .values()
+        .filter(|b| *b.get_solution() != BridgeState::Empty)
+        .count();
+    let mut x = [0.0; WEIGHTS];
+    x[technique] = bridges as f64;
+    x[WEIGHTS - 1] = nodes as f64;
+    Some(x)
+}
+
+fn dot(a: &[f64; WEIGHTS], b: &[f64; WEIGHTS]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+///
+/// Solve the linear equations `matrix` × x = `vector` by Gaussian elimination.
+///
+fn solve(
+    mut matrix: [[f64; WEIGHTS]; WEIGHTS],
+    mut vector: [f64; WEIGHTS],
+) -> Option<[f64; WEIGHTS]> {
+    for column in 0..WEIGHTS {
+        let pivot = (column..WEIGHTS).max_by(|a, b| {
+            matrix[*a][column]
+                .abs()
+                .total_cmp(&matrix[*b][column].abs())
+        })?;
+        if matrix[pivot][column].abs() < f64::EPSILON {
+            return None;
+        }
+        matrix.swap(column, pivot);
+        vector.swap(column, pivot);
+        for row in column + 1..WEIGHTS {
+            let pivot_row = matrix[column];
+            let factor = matrix[row][column] / pivot_row[column];
+            for (value, pivot) in matrix[row][column..].iter_mut().zip(&pivot_row[column..]) {
+                *value -= factor * pivot;
+            }
+            vector[row] -= factor * vector[column];
+        }
+    }
+    let mut x = [0.0; WEIGHTS];
+    for row in (0..WEIGHTS).rev() {
+        let rest: f64 = (row + 1..WEIGHTS).map(|k| matrix[row][k] * x[k]).sum();
+        x[row] = (vector[row] - rest) / matrix[row][row];
+    }
+    Some(x)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        difficulty::Difficulty,
+        hex::{BridgeState, GameParameters, HexSystem},
+        session::Move,
+    };
+
+    use super::RatingModel;
+
+    #[test]
+    fn calibrate() {
+        let players = RatingModel {
+            technique_ms: [2_000.0, 4_000.0, 7_000.0, 15_000.0],
+            node_ms: 100.0,
+        };
+        let replays: Vec<_> = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard]
+            .iter()
+            .flat_map(|difficulty| (0..5).map(|seed| GameParameters::preset(difficulty, seed)))
+            .map(|params| {
+                let (_, system) = HexSystem::generate_verified(params);
+                let solved = Move {
+                    bridge: (0, 0),
+                    from: BridgeState::Empty,
+                    to: BridgeState::Partial,
+                    at_ms: players.predict_ms(&system).unwrap(),
+                };
+                (system, vec![solved])
+            })
+            .collect();
+        let model = RatingModel::default().calibrate(&replays);
+        assert_ne!(model, RatingModel::default());
+        for (system, moves) in &replays {
+            let predicted = model.predict_ms(system).unwrap();
+            assert!((predicted - moves[0].at_ms).abs() < moves[0].at_ms * 0.01);
+        }
+        // Without replays, nothing changes.
+        assert_eq!(
+            RatingModel::default().calibrate(&[]),
+            RatingModel::default()
+        );
+    }
+}
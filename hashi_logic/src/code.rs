@@ -0,0 +1,294 @@
+use std::fmt::Display;
+
+use crate::{
+    hex::{BridgeState, HexSystem, Island},
+    pack::sha256,
+    shape::BoardShape,
+};
+
+/// Last part of the code of a puzzle without double bridges
+const SINGLE_CODE: &str = "single";
+/// Smallest and largest number of columns and rows of a board read from a code
+const CODE_SIDES: std::ops::RangeInclusive<usize> = 2..=256;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CodeError;
+
+impl Display for CodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Invalid puzzle code")
+    }
+}
+
+impl std::error::Error for CodeError {}
+
+impl HexSystem {
+    ///
+    /// Get a short code of the puzzle to share it, e.g. in an URL.
+    ///
+    /// The code contains the size, the islands and the solution, but not the bridges placed so far.
+    /// It only uses characters that need no escaping in URLs: `{columns}.{rows}.{islands}.{solution}`,
+    /// followed by `.{colors}` in the colored variant, `.{shape}` for boards other than parallelograms
+    /// and `.single` without double bridges.
+    ///
+    pub fn to_code(&self) -> String {
+        let solution: String = self
+            .bridges
+            .values()
+            .map(|b| match b.get_solution() {
+                BridgeState::Empty => '0',
+                BridgeState::Partial => '1',
+                BridgeState::Full => '2',
+            })
+            .collect();
+        format!("{}.{}{}", self.board_code(), solution, self.variant_code())
+    }
+
+    ///
+    /// Get the parts of the code with the rules of the variant, empty in the classic game.
+    ///
+    fn variant_code(&self) -> String {
+        let mut code = String::new();
+        if !self.colors.is_empty() {
+            let colors: String = self
+                .colors
+                .iter()
+                .map(|c| char::from_digit(*c as u32, 36).unwrap_or('0'))
+                .collect();
+            code.push('.');
+            code.push_str(&colors);
+        }
+        if self.shape != BoardShape::Parallelogram {
+            code.push('.');
+            code.push_str(self.shape.as_str());
+        }
+        if self.single_bridges {
+            code.push('.');
+            code.push_str(SINGLE_CODE);
+        }
+        code
+    }
+
+    ///
+    /// Get the part of the code describing the board: `{columns}.{rows}.{islands}`
+    ///
+    fn board_code(&self) -> String {
+        let islands: String = self
+            .islands
+            .iter()
+            .map(|i| match i {
+                Island::Empty => '0',
+                Island::Blocked => 'x',
+                Island::Bridged(target) => char::from_digit(*target as u32, 36).unwrap_or('0'),
+            })
+            .collect();
+        format!("{}.{}.{}", self.columns, self.rows, islands)
+    }
+
+    ///
+    /// Read a puzzle from a `code` created by `to_code`.
+    ///
+    pub fn from_code(code: &str) -> Result<HexSystem, CodeError> {
+        let mut parts = code.trim().split('.');
+        let mut next = || parts.next().ok_or(CodeError);
+        let columns: usize = next()?.parse().map_err(|_| CodeError)?;
+        let rows: usize = next()?.parse().map_err(|_| CodeError)?;
+        if !CODE_SIDES.contains(&columns) || !CODE_SIDES.contains(&rows) {
+            return Err(CodeError);
+        }
+        // Blocked cells only exist while a puzzle is generated.
+        let islands = next()?
+            .chars()
+            .map(|c| match c {
+                '0' => Ok(Island::Empty),
+                c => c
+                    .to_digit(36)
+                    .map(|target| Island::Bridged(target as usize))
+                    .ok_or(CodeError),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let solution = next()?;
+        let mut colors = vec![];
+        let mut single_bridges = false;
+        let mut shape = BoardShape::Parallelogram;
+        // The parts of the variant come in the order `to_code` writes them.
+        for (index, part) in parts.enumerate() {
+            if part == SINGLE_CODE && !single_bridges {
+                single_bridges = true;
+            } else if let Ok(s) = part.parse()
+                && shape == BoardShape::Parallelogram
+                && !single_bridges
+            {
+                shape = s;
+            } else if index == 0 {
+                colors = part
+                    .chars()
+                    .map(|c| c.to_digit(36).map(|c| c as u8).ok_or(CodeError))
+                    .collect::<Result<Vec<_>, _>>()?;
+            } else {
+                return Err(CodeError);
+            }
+        }
+        if islands.len() != HexSystem::get_size(columns, rows)
+            || !(colors.is_empty() || colors.len() == islands.len())
+        {
+            return Err(CodeError);
+        }
+        let mut system = HexSystem {
+            colors,
+            single_bridges,
+            shape,
+            ..HexSystem::from_islands(columns, rows, islands)
+        };
+        system.retain_same_colors();
+        if solution.len() != system.bridges.len() || (single_bridges && solution.contains('2')) {
+            return Err(CodeError);
+        }
+        for (bridge, c) in system.bridges.values_mut().zip(solution.chars()) {
+            bridge.set_solution(match c {
+                '0' => BridgeState::Empty,
+                '1' => BridgeState::Partial,
+                '2' => BridgeState::Full,
+                _ => return Err(CodeError),
+            });
+        }
+        Ok(system)
+    }
+
+    ///
+    /// Get an identifier of the puzzle that is the same wherever the puzzle came from.
+    ///
+    /// It is a hash of the board only, so neither the bridges placed so far
+    /// nor the solution the puzzle was generated with change it.
+    ///
+    pub fn puzzle_id(&self) -> String {
+        sha256(format!("{}{}", self.board_code(), self.variant_code()).as_bytes())[..16].to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        difficulty::Difficulty,
+        hex::{GameParameters, HexSystem},
+        shape::BoardShape,
+        variant::GameVariant,
+    };
+
+    use super::CodeError;
+
+    #[test]
+    fn roundtrip() {
+        let system = HexSystem::generate_new(GameParameters {
+            seed: 3,
+            num_islands: 30,
+            max_bridge_length: 5,
+            ratio_big_island: 0.2,
+            ..Default::default()
+        });
+        let code = system.to_code();
+        let imported = HexSystem::from_code(&code).unwrap();
+        assert_eq!(imported.islands, system.islands);
+        assert_eq!(
+            imported.bridges.keys().collect::<Vec<_>>(),
+            system.bridges.keys().collect::<Vec<_>>()
+        );
+        assert!(
+            imported
+                .bridges
+                .iter()
+                .all(|(k, b)| b.get_solution() == system.bridges[k].get_solution())
+        );
+        assert_eq!(imported.to_code(), code);
+        assert_eq!(imported.puzzle_id(), system.puzzle_id());
+        assert_eq!(system.puzzle_id().len(), 16);
+    }
+
+    #[test]
+    fn puzzle_id() {
+        let board = format!("4.5.10002010{}", "0".repeat(14));
+        let id = HexSystem::from_code(&format!("{}.11", board))
+            .unwrap()
+            .puzzle_id();
+        // Another solution of the same board
+        assert!(HexSystem::from_code(&format!("{}.21", board)).is_ok_and(|s| s.puzzle_id() == id));
+        let code = format!("4.5.20002010{}.11", "0".repeat(14));
+        assert!(HexSystem::from_code(&code).is_ok_and(|s| s.puzzle_id() != id));
+    }
+
+    #[test]
+    fn colored() {
+        let system = HexSystem::generate_variant(
+            GameParameters::preset(&Difficulty::Easy, 2),
+            GameVariant::Colored,
+        );
+        let code = system.to_code();
+        let imported = HexSystem::from_code(&code).unwrap();
+        assert_eq!(imported.colors, system.colors);
+        assert_eq!(
+            imported.bridges.keys().collect::<Vec<_>>(),
+            system.bridges.keys().collect::<Vec<_>>()
+        );
+        assert_eq!(imported.to_code(), code);
+        assert_eq!(imported.puzzle_id(), system.puzzle_id());
+        // A color is needed for each island.
+        assert_eq!(
+            HexSystem::from_code(&format!("{}0", code)).unwrap_err(),
+            CodeError
+        );
+    }
+
+    #[test]
+    fn single() {
+        let system = HexSystem::generate_variant(
+            GameParameters::preset(&Difficulty::Easy, 2),
+            GameVariant::Single,
+        );
+        let code = system.to_code();
+        assert!(code.ends_with(".single"));
+        let imported = HexSystem::from_code(&code).unwrap();
+        assert_eq!(imported.variant(), GameVariant::Single);
+        assert_eq!(imported.to_code(), code);
+        let classic = code.trim_end_matches(".single");
+        assert!(HexSystem::from_code(classic).is_ok_and(|s| s.puzzle_id() != system.puzzle_id()));
+        assert_eq!(
+            HexSystem::from_code(&format!("{}.single", code)).unwrap_err(),
+            CodeError
+        );
+    }
+
+    #[test]
+    fn hexagon() {
+        let system = HexSystem::generate_shaped(
+            GameParameters::preset(&Difficulty::Easy, 2),
+            BoardShape::Hexagon,
+        );
+        let code = system.to_code();
+        assert!(code.ends_with(".hexagon"));
+        let imported = HexSystem::from_code(&code).unwrap();
+        assert_eq!(imported.shape, BoardShape::Hexagon);
+        assert_eq!(imported.to_code(), code);
+        assert!(HexSystem::from_code(&format!("{}.hexagon", code)).is_err());
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(HexSystem::from_code("").unwrap_err(), CodeError);
+        assert_eq!(HexSystem::from_code("4.5.00.").unwrap_err(), CodeError);
+        assert_eq!(
+            HexSystem::from_code(&format!("4.5.{}.", "?".repeat(22))).unwrap_err(),
+            CodeError
+        );
+        assert!(HexSystem::from_code(&format!("4.5.{}.", "0".repeat(22))).is_ok());
+        // Blocked cells next to an island and one cell further away
+        let blocked = format!("4.5.1x1{}.", "0".repeat(19));
+        assert_eq!(HexSystem::from_code(&blocked).unwrap_err(), CodeError);
+        let blocked = format!("4.5.10x1{}.", "0".repeat(18));
+        assert_eq!(HexSystem::from_code(&blocked).unwrap_err(), CodeError);
+        assert_eq!(
+            HexSystem::from_code("99999999999.99999999999.0.").unwrap_err(),
+            CodeError
+        );
+        assert_eq!(HexSystem::from_code("1.1.1.").unwrap_err(), CodeError);
+    }
+}
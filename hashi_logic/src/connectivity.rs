@@ -0,0 +1,108 @@
+use std::collections::BTreeSet;
+
+use crate::hex::{HexSystem, Island};
+
+impl HexSystem {
+    ///
+    /// Get the groups of islands connected by the placed bridges.
+    ///
+    pub fn connected_components(&self) -> Vec<BTreeSet<usize>> {
+        self.components(|from, to| self.is_bridged(from, to))
+    }
+
+    ///
+    /// Check if the islands can still be joined to a single group, or one group per color
+    /// in the colored variant.
+    ///
+    /// The placed bridges are extended by the bridges that can still be added. If that does not
+    /// connect all islands, some groups are cut off for good, e.g. because all their islands have
+    /// their number of bridges already.
+    ///
+    pub fn can_be_connected(&self) -> bool {
+        self.components(|from, to| {
+            self.is_bridged(from, to) || self.remaining_capacity(from, to) > 0
+        })
+        .len()
+            <= self.networks()
+    }
+
+    ///
+    /// Check if the puzzle falls apart into parts that can be solved on their own.
+    ///
+    /// That is the case if a single possible bridge is the only connection between two groups of
+    /// at least two islands each, e.g. a pair of islands hanging on one bridge or a corridor between
+    /// two halves of the board. Single islands at the end of a bridge are common and fine.
+    ///
+    pub fn is_trivially_decomposable(&self) -> bool {
+        self.bridges.keys().any(|cut| {
+            let parts = self.components(|from, to| (from.min(to), from.max(to)) != *cut);
+            parts.len() > 1 && parts.iter().all(|part| part.len() >= 2)
+        })
+    }
+
+    fn is_bridged(&self, from: usize, to: usize) -> bool {
+        self.get_bridge(from, to)
+            .is_some_and(|bridge| bridge.get_count() > 0)
+    }
+
+    ///
+    /// Get the groups of islands where neighbors are `connected`.
+    ///
+    fn components(&self, connected: impl Fn(usize, usize) -> bool) -> Vec<BTreeSet<usize>> {
+        let mut components = vec![];
+        let mut visited = BTreeSet::new();
+        for (start, island) in self.islands.iter().enumerate() {
+            if !matches!(island, Island::Bridged(_)) || !visited.insert(start) {
+                continue;
+            }
+            let mut component = BTreeSet::from([start]);
+            let mut next = vec![start];
+            while let Some(from) = next.pop() {
+                for to in self.get_connected_islands(from) {
+                    if connected(from, to) && visited.insert(to) {
+                        component.insert(to);
+                        next.push(to);
+                    }
+                }
+            }
+            components.push(component);
+        }
+        components
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use crate::hex::{HexSystem, Island};
+
+    #[test]
+    fn components() {
+        // Four islands in the first row that each need one bridge
+        let mut islands = vec![Island::Empty; 22];
+        for island in islands.iter_mut().take(4) {
+            *island = Island::Bridged(1);
+        }
+        let mut sys = HexSystem::from_islands(4, 5, islands);
+        assert_eq!(sys.connected_components().len(), 4);
+        assert!(sys.can_be_connected());
+
+        // The first two islands are complete and cut off from the others.
+        assert_eq!(sys.cycle_bridge(0, 1), Ok(false));
+        assert_eq!(
+            sys.connected_components(),
+            [
+                BTreeSet::from([0, 1]),
+                BTreeSet::from([2]),
+                BTreeSet::from([3])
+            ]
+        );
+        assert!(!sys.can_be_connected());
+        // The bridge in the middle is the only way between two pairs of islands.
+        assert!(sys.is_trivially_decomposable());
+        sys.islands[3] = Island::Empty;
+        sys.bridges.remove(&(2, 3));
+        assert!(!sys.is_trivially_decomposable());
+    }
+}
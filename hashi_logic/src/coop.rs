@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{hex::BridgeState, session::GameSession};
+
+///
+/// Change of a bridge by one of the clients editing a shared board.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Operation {
+    pub bridge: (usize, usize),
+    pub state: BridgeState,
+    /// Lamport timestamp of the operation
+    pub timestamp: u64,
+    pub client: u32,
+}
+
+impl Operation {
+    ///
+    /// Operations are ordered by timestamp; operations with the same timestamp by client.
+    ///
+    fn version(&self) -> (u64, u32) {
+        (self.timestamp, self.client)
+    }
+}
+
+///
+/// Log of the operations on a board shared by several clients.
+///
+/// Concurrent changes of the same bridge are resolved by keeping the last one written,
+/// so all clients end up with the same board once they have seen all operations.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OperationLog {
+    client: u32,
+    clock: u64,
+    operations: Vec<Operation>,
+    /// Version of the last operation applied to each bridge
+    versions: BTreeMap<(usize, usize), (u64, u32)>,
+}
+
+impl OperationLog {
+    ///
+    /// Create an empty log for `client`, which must be unique among the clients sharing the board.
+    ///
+    pub fn new(client: u32) -> Self {
+        OperationLog {
+            client,
+            clock: 0,
+            operations: vec![],
+            versions: BTreeMap::new(),
+        }
+    }
+
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    ///
+    /// Record the last move made locally in `session` as operation to be sent to the other clients.
+    ///
+    pub fn record(&mut self, session: &GameSession) -> Option<Operation> {
+        let last = session.history().last()?;
+        self.clock += 1;
+        let operation = Operation {
+            bridge: last.bridge,
+            state: last.to.clone(),
+            timestamp: self.clock,
+            client: self.client,
+        };
+        self.versions.insert(operation.bridge, operation.version());
+        self.operations.push(operation.clone());
+        Some(operation)
+    }
+
+    ///
+    /// Apply `operation` received from another client to `session`.
+    ///
+    /// Returns `true` if the board changed, i.e. the operation is newer than the last one of the same bridge
+    /// and its bridge does not cross one of the board.
+    ///
+    pub fn apply(&mut self, session: &mut GameSession, operation: Operation) -> bool {
+        self.clock = self.clock.max(operation.timestamp);
+        let newer = self
+            .versions
+            .get(&operation.bridge)
+            .is_none_or(|v| operation.version() > *v);
+        if !newer {
+            return false;
+        }
+        let (from, to) = operation.bridge;
+        if session
+            .set_bridge_state(from, to, operation.state.clone())
+            .is_err()
+        {
+            return false;
+        }
+        self.versions.insert(operation.bridge, operation.version());
+        self.operations.push(operation);
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        hex::{BridgeState, GameParameters, HexSystem, Island},
+        session::GameSession,
+    };
+
+    use super::OperationLog;
+
+    #[test]
+    fn last_writer_wins() {
+        let system = HexSystem::generate_new(GameParameters {
+            seed: 1,
+            max_columns: 4,
+            max_rows: 5,
+            num_islands: 8,
+            ratio_long_bridge: 0.0,
+            ..Default::default()
+        });
+        let (from, to) = *system.bridges.keys().next().unwrap();
+        let mut a = GameSession::new(system.clone());
+        let mut b = GameSession::new(system);
+        let mut log_a = OperationLog::new(1);
+        let mut log_b = OperationLog::new(2);
+
+        // Concurrent changes of the same bridge
+        a.cycle_bridge(from, to).unwrap();
+        let op_a = log_a.record(&a).unwrap();
+        b.cycle_bridge(from, to).unwrap();
+        b.cycle_bridge(from, to).unwrap();
+        let op_b = log_b.record(&b).unwrap();
+        assert_eq!(op_a.timestamp, op_b.timestamp);
+
+        assert!(log_a.apply(&mut a, op_b.clone()));
+        assert!(!log_b.apply(&mut b, op_a.clone()));
+        let state = |s: &GameSession| s.system().get_bridge(from, to).unwrap().get_state().clone();
+        assert_eq!(state(&a), BridgeState::Full);
+        assert_eq!(state(&b), BridgeState::Full);
+
+        // Later change wins
+        a.cycle_bridge(from, to).unwrap();
+        let op_a = log_a.record(&a).unwrap();
+        assert!(op_a.timestamp > op_b.timestamp);
+        assert!(log_b.apply(&mut b, op_a));
+        assert_eq!(state(&b), BridgeState::Empty);
+        assert!(!log_b.apply(&mut b, op_b));
+        assert_eq!(log_a.operations().len(), 3);
+    }
+
+    #[test]
+    fn no_crossing() {
+        let mut islands = vec![Island::Empty; 22];
+        islands[0] = Island::Bridged(1);
+        islands[4] = Island::Bridged(1);
+        islands[6] = Island::Bridged(1);
+        islands[15] = Island::Bridged(1);
+        let system = HexSystem::from_islands(4, 5, islands);
+        let mut a = GameSession::new(system.clone());
+        let mut b = GameSession::new(system);
+        let mut log_a = OperationLog::new(1);
+        let mut log_b = OperationLog::new(2);
+
+        // Concurrent bridges crossing each other
+        a.cycle_bridge(0, 15).unwrap();
+        log_a.record(&a).unwrap();
+        b.cycle_bridge(4, 6).unwrap();
+        let op_b = log_b.record(&b).unwrap();
+        assert!(!log_a.apply(&mut a, op_b));
+        assert_eq!(
+            a.system().get_bridge(4, 6).unwrap().get_state(),
+            &BridgeState::Empty
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use crate::hex::HexSystem;
+
+///
+/// Distribution of the islands over the board.
+///
+/// The tour generating a puzzle places new islands less often where the density is low.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Density {
+    /// Islands anywhere on the board
+    #[default]
+    Uniform,
+    /// More islands in the middle of the board, fewer at its edges
+    Center,
+    /// More islands at the edges of the board, fewer in its middle
+    Edges,
+    /// Weight from 0 to 1 for each cell of the board by its index; cells without weight get 1
+    Map(Vec<f64>),
+}
+
+/// Weight of the cells where the density is lowest
+const MIN_WEIGHT: f64 = 0.2;
+
+impl Density {
+    ///
+    /// Get the chance from 0 to 1 that a new island is placed at `index` of a board with `columns` and `rows`.
+    ///
+    pub fn weight(&self, columns: usize, rows: usize, index: usize) -> f64 {
+        let distance = || {
+            let (row, column) = HexSystem::row_column(columns, index);
+            // Doubled coordinates: the cells of odd rows start half a cell further left.
+            let x = 2 * column + if row.is_multiple_of(2) { 1 } else { 0 };
+            let dx = (x as f64 - columns as f64).abs() / columns.max(1) as f64;
+            let middle = rows.saturating_sub(1) as f64 / 2.0;
+            let dy = (row as f64 - middle).abs() / middle.max(1.0);
+            dx.max(dy).min(1.0)
+        };
+        match self {
+            Density::Uniform => 1.0,
+            Density::Center => 1.0 - (1.0 - MIN_WEIGHT) * distance(),
+            Density::Edges => MIN_WEIGHT + (1.0 - MIN_WEIGHT) * distance(),
+            Density::Map(weights) => weights.get(index).copied().unwrap_or(1.0).clamp(0.0, 1.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        difficulty::Difficulty,
+        hex::{GameParameters, HexSystem, Island},
+    };
+
+    use super::Density;
+
+    #[test]
+    fn weights() {
+        // The middle cell of a 5×5 board and a corner
+        assert_eq!(Density::Center.weight(5, 5, 13), 1.0);
+        assert!(Density::Center.weight(5, 5, 0) < 0.5);
+        assert!(Density::Edges.weight(5, 5, 0) > 0.5);
+        assert_eq!(Density::Uniform.weight(5, 5, 0), 1.0);
+        assert_eq!(Density::Map(vec![0.5]).weight(5, 5, 0), 0.5);
+        assert_eq!(Density::Map(vec![0.5]).weight(5, 5, 1), 1.0);
+        // Boards without rows do not underflow.
+        assert!(Density::Center.weight(0, 0, 0) <= 1.0);
+    }
+
+    #[test]
+    fn generate() {
+        // Share of the islands in the middle of the board
+        let central = |density: Density| {
+            let (mut middle, mut all) = (0, 0);
+            for seed in 0..20 {
+                let params = GameParameters {
+                    density: density.clone(),
+                    ..GameParameters::preset(&Difficulty::Hard, seed)
+                };
+                let system = HexSystem::generate_new(params);
+                for (index, island) in system.islands.iter().enumerate() {
+                    if let Island::Bridged(_) = island {
+                        all += 1;
+                        if Density::Center.weight(system.columns, system.rows, index) > 0.6 {
+                            middle += 1;
+                        }
+                    }
+                }
+            }
+            middle as f64 / all as f64
+        };
+        assert!(central(Density::Center) > central(Density::Uniform));
+        assert!(central(Density::Edges) < central(Density::Uniform));
+    }
+}
@@ -0,0 +1,182 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::hex::GameParameters;
+
+///
+/// Difficulty of a puzzle
+///
+/// It is serialized by its identifier, e.g. "hard", as in URLs and command line arguments.
+/// Parsing ignores the case, so that difficulties stored by older versions, e.g. "Hard", can still be read.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Extreme,
+}
+
+impl Difficulty {
+    /// All difficulties from the easiest to the hardest
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Extreme,
+    ];
+
+    ///
+    /// Get the identifier of the difficulty, e.g. "hard".
+    ///
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+            Difficulty::Extreme => "extreme",
+        }
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Difficulty::Easy => f.write_str("Easy"),
+            Difficulty::Medium => f.write_str("Medium"),
+            Difficulty::Hard => f.write_str("Hard"),
+            Difficulty::Extreme => f.write_str("Extreme"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DifficultyConversionError;
+
+impl Display for DifficultyConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Cannot convert to difficulty")
+    }
+}
+
+impl std::error::Error for DifficultyConversionError {}
+
+impl FromStr for Difficulty {
+    type Err = DifficultyConversionError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "easy" => Ok(Difficulty::Easy),
+            "medium" => Ok(Difficulty::Medium),
+            "hard" => Ok(Difficulty::Hard),
+            "extreme" => Ok(Difficulty::Extreme),
+            _ => Err(DifficultyConversionError),
+        }
+    }
+}
+
+impl Serialize for Difficulty {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Difficulty {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl GameParameters {
+    ///
+    /// Get the parameters for generating a puzzle of `difficulty` from `seed`.
+    ///
+    pub fn preset(difficulty: &Difficulty, seed: u64) -> Self {
+        match difficulty {
+            Difficulty::Medium => GameParameters {
+                seed,
+                ..Default::default()
+            },
+            Difficulty::Hard => GameParameters {
+                seed,
+                num_islands: 25,
+                max_bridge_length: 5,
+                ratio_long_bridge: 0.5,
+                ..Default::default()
+            },
+            Difficulty::Extreme => GameParameters {
+                seed,
+                num_islands: 50,
+                max_bridge_length: 7,
+                ratio_long_bridge: 1.0,
+                ..Default::default()
+            },
+            Difficulty::Easy => GameParameters {
+                seed,
+                num_islands: 10,
+                max_bridge_length: 1,
+                ratio_long_bridge: 0.1,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hex::{GameParameters, HexSystem, Island};
+
+    use super::Difficulty;
+
+    #[test]
+    fn presets() {
+        let islands: Vec<_> = Difficulty::ALL
+            .iter()
+            .map(|d| GameParameters::preset(d, 1))
+            .map(|p| (p.num_islands, p.max_bridge_length))
+            .collect();
+        assert_eq!(islands, vec![(10, 1), (20, 3), (25, 5), (50, 7)]);
+
+        // The same seed always generates the same puzzle.
+        let first = HexSystem::generate_new(GameParameters::preset(&Difficulty::Medium, 7));
+        let second = HexSystem::generate_new(GameParameters::preset(&Difficulty::Medium, 7));
+        assert_eq!(first.islands, second.islands);
+        assert!(
+            first
+                .islands
+                .iter()
+                .filter(|i| matches!(i, Island::Bridged(_)))
+                .count()
+                <= 20
+        );
+    }
+
+    #[test]
+    fn parse() {
+        for difficulty in Difficulty::ALL {
+            assert_eq!(
+                difficulty.as_str().parse::<Difficulty>().unwrap(),
+                difficulty
+            );
+            assert_eq!(
+                difficulty.to_string().parse::<Difficulty>().unwrap(),
+                difficulty
+            );
+        }
+        assert!("impossible".parse::<Difficulty>().is_err());
+    }
+
+    #[test]
+    fn serde() {
+        assert_eq!(
+            serde_json::to_string(&Difficulty::Hard).unwrap(),
+            "\"hard\""
+        );
+        let old: Difficulty = serde_json::from_str("\"Hard\"").unwrap();
+        assert_eq!(old, Difficulty::Hard);
+        assert!(serde_json::from_str::<Difficulty>("\"impossible\"").is_err());
+    }
+}
@@ -4,9 +4,16 @@ use std::{
 };
 
 use rand::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq, PartialOrd, Eq)]
+use crate::{density::Density, shape::BoardShape};
+
+/// Number of times the tour may skip a new island because of a low density
+const MAX_REJECTIONS: usize = 500;
+
+#[derive(Clone, Debug, Default, PartialEq, PartialOrd, Eq, Serialize, Deserialize)]
 pub enum BridgeState {
+    #[default]
     Empty,
     Partial,
     Full,
@@ -15,16 +22,23 @@ pub enum BridgeState {
 ///
 /// Type for Bridge
 ///
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct HexBridge {
     state: BridgeState,
     gap_indices: Vec<usize>,
+    /// State of the bridge in the solution the puzzle was generated from
+    #[serde(default)]
+    solution: BridgeState,
+    /// Locked bridges cannot be changed
+    #[serde(default)]
+    locked: bool,
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum BridgeError {
     NotFound,
     Blocked,
+    Locked,
 }
 
 impl Display for BridgeError {
@@ -32,16 +46,41 @@ impl Display for BridgeError {
         match self {
             BridgeError::NotFound => f.write_str("Bridge is not found."),
             BridgeError::Blocked => f.write_str("Bridge is blocked."),
+            BridgeError::Locked => f.write_str("Bridge is locked."),
         }
     }
 }
 
 impl std::error::Error for BridgeError {}
 
+///
+/// How far a puzzle is solved
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// Islands with exactly their number of bridges
+    pub complete: usize,
+    /// All islands
+    pub total: usize,
+}
+
+impl Progress {
+    ///
+    /// Get the share of complete islands from 0 to 1.
+    ///
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.complete as f64 / self.total as f64
+        }
+    }
+}
+
 ///
 /// Type for Island
 ///
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Island {
     Empty,
     Bridged(usize), // Target number of bridges
@@ -54,12 +93,50 @@ pub enum Island {
 /// 0 is top left
 /// All odd rows have one more column.
 ///
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct HexSystem {
     pub columns: usize,
     pub rows: usize,
     pub islands: Vec<Island>,
+    #[serde(with = "bridge_map")]
     pub bridges: BTreeMap<(usize, usize), HexBridge>,
+    /// Color of each island in the colored variant, empty in the classic game
+    #[serde(default)]
+    pub colors: Vec<u8>,
+    /// Only single bridges are allowed in the variant without double bridges
+    #[serde(default)]
+    pub single_bridges: bool,
+    /// Outline of the cells the islands are placed in
+    #[serde(default)]
+    pub shape: BoardShape,
+    /// Longest bridge the generator was allowed to place, unknown for puzzles made otherwise
+    #[serde(default)]
+    pub max_bridge_length: Option<usize>,
+}
+
+///
+/// (De-)serialize the bridges as list of entries, since tuples are not supported as keys by all formats (e.g. JSON).
+///
+mod bridge_map {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::HexBridge;
+
+    pub fn serialize<S: Serializer>(
+        bridges: &BTreeMap<(usize, usize), HexBridge>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(bridges.iter())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<(usize, usize), HexBridge>, D::Error> {
+        let entries = Vec::<((usize, usize), HexBridge)>::deserialize(deserializer)?;
+        Ok(entries.into_iter().collect())
+    }
 }
 
 impl Display for HexSystem {
@@ -101,6 +178,7 @@ impl Display for HexSystem {
     }
 }
 
+#[derive(Clone, Debug)]
 pub struct GameParameters {
     pub seed: u64,
     pub max_columns: usize,
@@ -109,39 +187,306 @@ pub struct GameParameters {
     pub max_bridge_length: usize,
     pub ratio_big_island: f64,
     pub ratio_long_bridge: f64,
+    /// Where new islands are placed
+    pub density: Density,
+    /// Limits for long bridges of the solution, if any
+    pub bridge_limits: Option<BridgeLimits>,
+    /// Keep new islands off cells next to other islands, except the one they are connected to
+    pub spaced_islands: bool,
+}
+
+///
+/// Limits keeping long bridges from dominating a generated puzzle.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct BridgeLimits {
+    /// Bridges longer than this count as long.
+    pub long_length: usize,
+    /// Most long bridges in a puzzle
+    pub max_long: usize,
+    /// Length of the longest bridge as a fraction of the shorter side of the board
+    pub max_span: f64,
+}
+
+impl Default for GameParameters {
+    ///
+    /// Get the parameters of a medium puzzle from seed 0.
+    ///
+    fn default() -> Self {
+        GameParameters {
+            seed: 0,
+            max_columns: 10,
+            max_rows: 10,
+            num_islands: 20,
+            max_bridge_length: 3,
+            ratio_big_island: 0.0,
+            ratio_long_bridge: 0.2,
+            density: Density::Uniform,
+            bridge_limits: None,
+            spaced_islands: false,
+        }
+    }
 }
 
 impl HexSystem {
+    ///
+    /// Create a classic board of `columns` and `rows` with `islands` and all bridges possible between them.
+    ///
+    pub fn from_islands(columns: usize, rows: usize, islands: Vec<Island>) -> Self {
+        let bridges = HexSystem::fill_bridges(&islands, columns, rows, None);
+        HexSystem {
+            columns,
+            rows,
+            islands,
+            bridges,
+            colors: vec![],
+            single_bridges: false,
+            shape: BoardShape::Parallelogram,
+            max_bridge_length: None,
+        }
+    }
+
     pub fn generate_new(params: GameParameters) -> Self {
+        HexSystem::generate_in(params, BoardShape::Parallelogram)
+    }
+
+    ///
+    /// Generate a puzzle whose islands all lie within `shape`.
+    ///
+    pub(crate) fn generate_in(params: GameParameters, shape: BoardShape) -> Self {
         let size = HexSystem::get_size(params.max_columns, params.max_rows);
+        let inside = |index: usize| shape.contains(params.max_columns, params.max_rows, index);
 
         let mut rng = SmallRng::seed_from_u64(params.seed);
 
-        let mut indices =
-            vec![Island::Empty; HexSystem::get_size(params.max_columns, params.max_rows)];
+        // Cells outside the shape stop the tour like blocked cells.
+        let mut indices: Vec<Island> = (0..size)
+            .map(|index| {
+                if inside(index) {
+                    Island::Empty
+                } else {
+                    Island::Blocked
+                }
+            })
+            .collect();
         let mut start_index = rng.random_range(0..size);
+        while !inside(start_index) {
+            start_index = rng.random_range(0..size);
+        }
         indices[start_index] = Island::Bridged(0);
         let mut bridges: BTreeMap<(usize, usize), HexBridge> = BTreeMap::new();
+        HexSystem::walk(
+            &params,
+            &mut rng,
+            &mut indices,
+            start_index,
+            &mut bridges,
+            params.num_islands,
+        );
+        let (mut islands, bridges) =
+            HexSystem::from_solution(bridges, params.max_columns, params.max_rows);
+        let (columns, rows) = HexSystem::crop(&mut islands, params.max_columns, params.max_rows);
+
+        HexSystem {
+            columns,
+            rows,
+            islands,
+            bridges,
+            colors: vec![],
+            single_bridges: false,
+            shape,
+            max_bridge_length: Some(params.max_bridge_length),
+        }
+    }
+
+    ///
+    /// Grow the board to `params.max_rows` rows with `params.num_islands` new islands attached to the current ones.
+    ///
+    /// The rows are added at the bottom, so that the islands and bridges of the board keep their indices.
+    /// The number of columns stays the same, whatever `params.max_columns` says.
+    /// The board may be solved or partly solved: the placed bridges keep their states and locks,
+    /// and the solution of the grown board includes the old solution.
+    /// Islands the new ones are attached to need more bridges than before.
+    ///
+    pub fn extend(&self, params: GameParameters) -> HexSystem {
+        let (columns, rows) = (self.columns, params.max_rows.max(self.rows));
+        let params = GameParameters {
+            max_columns: columns,
+            max_rows: rows,
+            ..params
+        };
+        let size = HexSystem::get_size(columns, rows);
+        let mut rng = SmallRng::seed_from_u64(params.seed);
 
+        // Placed bridges and the bridges of the solution must not be crossed by new ones.
+        let mut indices: Vec<Island> = (0..size)
+            .map(|index| match self.islands.get(index) {
+                Some(Island::Bridged(_)) => Island::Bridged(0),
+                _ if self.shape.contains(columns, rows, index) => Island::Empty,
+                _ => Island::Blocked,
+            })
+            .collect();
+        let mut solution = BTreeMap::new();
+        for (key, bridge) in &self.bridges {
+            if bridge.solution == BridgeState::Empty && bridge.state == BridgeState::Empty {
+                continue;
+            }
+            for gap in &bridge.gap_indices {
+                indices[*gap] = Island::Blocked;
+            }
+            if bridge.solution != BridgeState::Empty {
+                solution.insert(
+                    *key,
+                    HexBridge {
+                        state: bridge.solution.clone(),
+                        gap_indices: vec![],
+                        solution: BridgeState::Empty,
+                        locked: false,
+                    },
+                );
+            }
+        }
+
+        // The new islands grow from an island at the lowest row with space next to it.
+        let edge: Vec<usize> = (0..size)
+            .filter(|index| {
+                indices[*index] == Island::Bridged(0)
+                    && HexSystem::get_connected_indices(columns, rows, *index)
+                        .into_iter()
+                        .flatten()
+                        .any(|next| indices[next] == Island::Empty)
+            })
+            .collect();
+        let Some(lowest) = edge.last() else {
+            return self.clone();
+        };
+        let (lowest_row, _) = HexSystem::row_column(columns, *lowest);
+        let edge: Vec<usize> = edge
+            .into_iter()
+            .filter(|index| HexSystem::row_column(columns, *index).0 == lowest_row)
+            .collect();
+        let start_index = edge[rng.random_range(0..edge.len())];
+        let existing = indices
+            .iter()
+            .filter(|i| matches!(i, Island::Bridged(_)))
+            .count();
+        HexSystem::walk(
+            &params,
+            &mut rng,
+            &mut indices,
+            start_index,
+            &mut solution,
+            existing + params.num_islands,
+        );
+        if self.single_bridges {
+            for bridge in solution.values_mut() {
+                bridge.state = BridgeState::Partial;
+            }
+        }
+
+        let (islands, mut bridges) = HexSystem::from_solution(solution, columns, rows);
+        for (key, bridge) in &mut bridges {
+            if let Some(old) = self.bridges.get(key) {
+                bridge.state = old.state.clone();
+                bridge.locked = old.locked;
+            }
+        }
+        let mut colors = self.colors.clone();
+        if !colors.is_empty() {
+            colors.resize(size, 0);
+        }
+        HexSystem {
+            columns,
+            rows,
+            islands,
+            bridges,
+            colors,
+            single_bridges: self.single_bridges,
+            shape: self.shape,
+            max_bridge_length: self
+                .max_bridge_length
+                .map(|max| max.max(params.max_bridge_length)),
+        }
+    }
+
+    ///
+    /// Get the islands and all possible bridges for the bridges of the `solution` found by the walk.
+    ///
+    /// The walk keeps the number of bridges in the state, the bridges returned have it as their solution.
+    ///
+    fn from_solution(
+        solution: BTreeMap<(usize, usize), HexBridge>,
+        columns: usize,
+        rows: usize,
+    ) -> (Vec<Island>, BTreeMap<(usize, usize), HexBridge>) {
+        // Create islands from bridges
+        let mut islands: Vec<Island> = vec![Island::Empty; HexSystem::get_size(columns, rows)];
+        solution.iter().for_each(|((i1, i2), bw)| {
+            let mut apply = |i: usize| {
+                let is = &mut islands[i];
+                let width = bw.get_count();
+                *is = match is {
+                    Island::Empty => Island::Bridged(width),
+                    Island::Bridged(c) => Island::Bridged(*c + width),
+                    Island::Blocked => Island::Empty,
+                }
+            };
+            apply(*i1);
+            apply(*i2);
+        });
+        // Fill bridges between existing islands that do not contribute to solution.
+        let mut bridges = HexSystem::fill_bridges(&islands, columns, rows, None);
+        // Remember the solution, the bridges themselves start empty.
+        for (key, solved) in solution {
+            if let Some(bridge) = bridges.get_mut(&key) {
+                bridge.solution = solved.state;
+            }
+        }
+        (islands, bridges)
+    }
+
+    ///
+    /// Randomly walk a tour on the grid from `start_index`, adding islands to `indices` and the bridges
+    /// of the solution to `bridges` until there are `num_islands` islands or the walk gets stuck.
+    ///
+    fn walk(
+        params: &GameParameters,
+        rng: &mut SmallRng,
+        indices: &mut [Island],
+        mut start_index: usize,
+        bridges: &mut BTreeMap<(usize, usize), HexBridge>,
+        num_islands: usize,
+    ) {
         let mut limit = 50;
+        let mut rejections = 0;
+        let mut long_bridges = 0;
+        let span = params.bridge_limits.as_ref().map(|limits| {
+            ((limits.max_span * params.max_columns.min(params.max_rows) as f64) as usize).max(1)
+        });
 
         // Randomly walk a tour on the grid randomly selecting direction, width and length of bridge
         while indices
             .iter()
             .filter(|i| matches!(i, Island::Bridged(_)))
             .count()
-            < params.num_islands
+            < num_islands
             && limit > 0
         {
             let direction = rng.random_range(0..6);
             let mut bridge_length = *(1..params.max_bridge_length)
                 .collect::<Vec<usize>>()
                 .as_slice()
-                .choose_weighted(&mut rng, |x| {
+                .choose_weighted(rng, |x| {
                     params.ratio_big_island * params.max_bridge_length as f64
                         / (*x as f64 * *x as f64 * params.ratio_long_bridge)
                 })
                 .unwrap_or(&1);
+            if let (Some(limits), Some(span)) = (&params.bridge_limits, span) {
+                bridge_length = bridge_length.min(span);
+                if long_bridges >= limits.max_long {
+                    bridge_length = bridge_length.min(limits.long_length);
+                }
+            }
             let orig_bridge_length = bridge_length;
             let bridge_width = rng.random_range(1..=2);
 
@@ -151,6 +496,7 @@ impl HexSystem {
             // c) an existing island is reached, or
             // d) the bridge is blocked (i.e. the index is marked as blocked).
             let mut next_index = start_index;
+            let mut marked = vec![];
             // Loop terminates at latest, when bridge length is reached.
             let end_index = loop {
                 let next_connections = HexSystem::get_connected_indices(
@@ -172,14 +518,53 @@ impl HexSystem {
                 // Mark island as blank.
                 if orig_bridge_length > 1 {
                     indices[next_index] = Island::Blocked;
+                    marked.push(next_index);
                 }
             };
+            // A new island next to another one would crowd the board, so the walk tries again.
+            if params.spaced_islands
+                && start_index != end_index
+                && indices[end_index] == Island::Empty
+                && HexSystem::get_connected_indices(params.max_columns, params.max_rows, end_index)
+                    .into_iter()
+                    .flatten()
+                    .any(|i| i != start_index && matches!(indices[i], Island::Bridged(_)))
+            {
+                limit -= 1;
+                for index in marked {
+                    indices[index] = Island::Empty;
+                }
+                continue;
+            }
+            // New islands are placed less often where the density is low.
+            if params.density != Density::Uniform
+                && start_index != end_index
+                && indices[end_index] == Island::Empty
+                && rejections < MAX_REJECTIONS
+                && rng.random::<f64>()
+                    >= params
+                        .density
+                        .weight(params.max_columns, params.max_rows, end_index)
+            {
+                rejections += 1;
+                for index in marked {
+                    indices[index] = Island::Empty;
+                }
+                continue;
+            }
             if start_index != end_index && indices[end_index] != Island::Blocked {
+                let key = (
+                    std::cmp::min(start_index, end_index),
+                    std::cmp::max(start_index, end_index),
+                );
+                if let Some(limits) = &params.bridge_limits
+                    && !bridges.contains_key(&key)
+                    && orig_bridge_length - bridge_length > limits.long_length
+                {
+                    long_bridges += 1;
+                }
                 bridges
-                    .entry((
-                        std::cmp::min(start_index, end_index),
-                        std::cmp::max(start_index, end_index),
-                    ))
+                    .entry(key)
                     .and_modify(|e| {
                         e.state = match e.state {
                             BridgeState::Empty => unreachable!(),
@@ -194,6 +579,8 @@ impl HexSystem {
                             _ => unreachable!(),
                         },
                         gap_indices: vec![], // Not important here
+                        solution: BridgeState::Empty,
+                        locked: false,
                     });
                 indices[end_index] = Island::Bridged(0);
                 start_index = end_index;
@@ -201,33 +588,6 @@ impl HexSystem {
                 limit -= 1;
             }
         }
-        // Create islands from bridges
-        let mut islands: Vec<Island> = vec![Island::Empty; indices.len()];
-        bridges.iter_mut().for_each(|((i1, i2), bw)| {
-            let mut apply = |i: usize| {
-                let is = &mut islands[i];
-                let width = bw.get_count();
-                *is = match is {
-                    Island::Empty => Island::Bridged(width),
-                    Island::Bridged(c) => Island::Bridged(*c + width),
-                    Island::Blocked => Island::Empty,
-                }
-            };
-            apply(*i1);
-            apply(*i2);
-            // Reset bridge state, otherwise puzzle would be returned solved.
-            bw.state = BridgeState::Empty;
-        });
-        // Fill bridges between existing islands that do not contribute to solution.
-        let bridges = HexSystem::fill_bridges(&islands, params.max_columns, params.max_rows);
-        let (columns, rows) = HexSystem::crop(&mut islands, params.max_columns, params.max_rows);
-
-        HexSystem {
-            columns,
-            rows,
-            islands,
-            bridges,
-        }
     }
 
     ///
@@ -288,7 +648,7 @@ impl HexSystem {
     ///
     /// Get size of vector needed to store a `columns` x `rows` puzzle.
     ///
-    fn get_size(columns: usize, rows: usize) -> usize {
+    pub(crate) fn get_size(columns: usize, rows: usize) -> usize {
         columns * rows + rows / 2
     }
 
@@ -303,10 +663,13 @@ impl HexSystem {
     ///
     /// Also remember the indicies of the "gap islands". This is used later for checking of blocked bridges.
     ///
-    fn fill_bridges(
+    /// Bridges longer than `max_length`, if given, are left out.
+    ///
+    pub(crate) fn fill_bridges(
         islands: &[Island],
         columns: usize,
         rows: usize,
+        max_length: Option<usize>,
     ) -> BTreeMap<(usize, usize), HexBridge> {
         let mut bridges = BTreeMap::new();
         for start_index in 0..islands.len() {
@@ -314,10 +677,12 @@ impl HexSystem {
                 let connections = HexSystem::get_connected_indices(columns, rows, start_index);
                 for (direction, opt_con) in connections.iter().enumerate() {
                     let mut end_index = None;
-                    if let Some(con) = *opt_con {
+                    // Cells below the last row of boards with an even number of rows do not exist.
+                    if let Some(con) = opt_con.filter(|con| *con < islands.len()) {
                         let mut gaps = vec![];
                         match islands[con] {
-                            Island::Blocked => unreachable!(),
+                            // Bridges cannot cross blocked cells.
+                            Island::Blocked => {}
                             Island::Bridged(_) => {
                                 end_index = Some(con);
                             }
@@ -328,22 +693,28 @@ impl HexSystem {
                                     let next_con =
                                         HexSystem::get_connected_indices(columns, rows, next_index)
                                             [direction];
-                                    if let Some(next_island) = next_con {
-                                        if let Island::Bridged(_) = islands[next_island] {
+                                    let Some(next_island) =
+                                        next_con.filter(|next| *next < islands.len())
+                                    else {
+                                        break;
+                                    };
+                                    match islands[next_island] {
+                                        Island::Bridged(_) => {
                                             end_index = Some(next_island);
                                             break;
                                         }
-                                        if let Island::Empty = islands[next_island] {
+                                        Island::Empty => {
                                             gaps.push(next_island);
                                             next_index = next_island;
                                         }
-                                    } else {
-                                        break;
+                                        Island::Blocked => break,
                                     }
                                 }
                             }
                         }
-                        if let Some(end_index) = end_index {
+                        if let Some(end_index) = end_index
+                            && max_length.is_none_or(|max| gaps.len() < max)
+                        {
                             bridges.insert(
                                 (
                                     std::cmp::min(start_index, end_index),
@@ -352,6 +723,8 @@ impl HexSystem {
                                 HexBridge {
                                     state: BridgeState::Empty,
                                     gap_indices: gaps,
+                                    solution: BridgeState::Empty,
+                                    locked: false,
                                 },
                             );
                         }
@@ -384,26 +757,39 @@ impl HexSystem {
     /// Cycle through the states of bridge between `from` and `to`.
     ///
     pub fn cycle_bridge(&mut self, from: usize, to: usize) -> Result<bool, BridgeError> {
+        self.change_bridge(from, to, HexBridge::cycle)
+    }
+
+    ///
+    /// Cycle through the states of bridge between `from` and `to` in reverse order.
+    ///
+    pub fn cycle_bridge_back(&mut self, from: usize, to: usize) -> Result<bool, BridgeError> {
+        self.change_bridge(from, to, HexBridge::cycle_back)
+    }
+
+    ///
+    /// Apply `change` to the bridge between `from` and `to` unless it is locked or blocked.
+    ///
+    fn change_bridge(
+        &mut self,
+        from: usize,
+        to: usize,
+        change: fn(&mut HexBridge) -> Option<usize>,
+    ) -> Result<bool, BridgeError> {
         let cur_bridge = (std::cmp::min(from, to), std::cmp::max(from, to));
         if let Some(bridge) = self.bridges.get(&cur_bridge) {
-            let gaps = BTreeSet::from_iter(bridge.gap_indices.iter());
-            let blocked = self
-                .bridges
-                .iter()
-                .filter(|(b, _)| **b != cur_bridge)
-                .any(|(_, b)| {
-                    b.state != BridgeState::Empty
-                        && !b
-                            .gap_indices
-                            .iter()
-                            .collect::<BTreeSet<_>>()
-                            .is_disjoint(&gaps)
-                });
-            if blocked {
+            if bridge.locked {
+                return Err(BridgeError::Locked);
+            }
+            if self.is_blocked(cur_bridge, bridge) {
                 Err(BridgeError::Blocked)
             } else {
                 let bridge = self.bridges.get_mut(&cur_bridge).unwrap(); // unwrap ok, since already checked above
-                bridge.cycle();
+                change(bridge);
+                // Without double bridges, cycling skips the full state.
+                if self.single_bridges && bridge.state == BridgeState::Full {
+                    change(bridge);
+                }
                 Ok(self.is_solved())
             }
         } else {
@@ -411,6 +797,151 @@ impl HexSystem {
         }
     }
 
+    ///
+    /// Check if `bridge` with key `key` crosses any other bridge that is not empty.
+    ///
+    fn is_blocked(&self, key: (usize, usize), bridge: &HexBridge) -> bool {
+        self.blocking_bridges(key, bridge).next().is_some()
+    }
+
+    ///
+    /// Get the keys of the bridges that are not empty and cross `bridge` with key `key`.
+    ///
+    fn blocking_bridges<'a>(
+        &'a self,
+        key: (usize, usize),
+        bridge: &'a HexBridge,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        let gaps = BTreeSet::from_iter(bridge.gap_indices.iter());
+        self.bridges
+            .iter()
+            .filter(move |(b, _)| **b != key)
+            .filter(move |(_, b)| {
+                b.state != BridgeState::Empty
+                    && !b
+                        .gap_indices
+                        .iter()
+                        .collect::<BTreeSet<_>>()
+                        .is_disjoint(&gaps)
+            })
+            .map(|(k, _)| *k)
+    }
+
+    ///
+    /// Get the bridges that prevent changing the bridge between `from` and `to`.
+    ///
+    /// Empty if the bridge does not exist or is not blocked.
+    ///
+    pub fn get_blocking_bridges(&self, from: usize, to: usize) -> Vec<(usize, usize)> {
+        let key = (std::cmp::min(from, to), std::cmp::max(from, to));
+        self.bridges
+            .get(&key)
+            .map(|bridge| self.blocking_bridges(key, bridge).collect())
+            .unwrap_or_default()
+    }
+
+    ///
+    /// Remove the bridges longer than `max_bridge_length`, so that they are not offered anymore.
+    ///
+    /// Bridges placed already are kept. Puzzles without a known limit keep all bridges.
+    ///
+    pub fn limit_bridge_length(&mut self) {
+        let Some(max_length) = self.max_bridge_length else {
+            return;
+        };
+        let allowed =
+            HexSystem::fill_bridges(&self.islands, self.columns, self.rows, Some(max_length));
+        self.bridges
+            .retain(|key, b| allowed.contains_key(key) || b.state != BridgeState::Empty);
+    }
+
+    ///
+    /// Get the bridges that can currently be incremented.
+    ///
+    /// A bridge is available if it is neither full nor locked, does not cross another bridge
+    /// and both its islands still need more bridges.
+    ///
+    pub fn available_moves(&self) -> Vec<(usize, usize)> {
+        let below_target = |index: usize| match self.islands[index] {
+            Island::Bridged(target) => self.get_actual_bridges(index) < target,
+            _ => false,
+        };
+        self.bridges
+            .iter()
+            .filter(|(key, bridge)| {
+                bridge.get_count() < self.max_width()
+                    && !bridge.locked
+                    && below_target(key.0)
+                    && below_target(key.1)
+                    && !self.is_blocked(**key, bridge)
+            })
+            .map(|(key, _)| *key)
+            .collect()
+    }
+
+    ///
+    /// Get the number of bridges that can still be added between `from` and `to`.
+    ///
+    /// It is limited by the bridges both islands still need and is zero for bridges that are not available.
+    ///
+    pub fn remaining_capacity(&self, from: usize, to: usize) -> usize {
+        let key = (std::cmp::min(from, to), std::cmp::max(from, to));
+        let Some(bridge) = self.bridges.get(&key) else {
+            return 0;
+        };
+        if bridge.locked || self.is_blocked(key, bridge) {
+            return 0;
+        }
+        let missing = |index: usize| match self.islands[index] {
+            Island::Bridged(target) => target.saturating_sub(self.get_actual_bridges(index)),
+            _ => 0,
+        };
+        let free = self.max_width().saturating_sub(bridge.get_count());
+        free.min(missing(key.0)).min(missing(key.1))
+    }
+
+    ///
+    /// Check quickly if the island at `index` can still get its number of bridges.
+    ///
+    /// This only looks at the island itself: it must not have too many bridges, and the bridges
+    /// it can still get towards its neighbors must add up to the ones it is missing.
+    /// A `false` proves that the puzzle cannot be solved from here, a `true` proves nothing.
+    /// Places without a number are always satisfiable.
+    ///
+    pub fn island_satisfiable(&self, index: usize) -> bool {
+        let Island::Bridged(target) = self.islands[index] else {
+            return true;
+        };
+        let actual = self.get_actual_bridges(index);
+        let capacity: usize = self
+            .get_connected_islands(index)
+            .into_iter()
+            .map(|to| self.remaining_capacity(index, to))
+            .sum();
+        actual <= target && actual + capacity >= target
+    }
+
+    ///
+    /// Get the number of bridges allowed between two islands, 1 without double bridges.
+    ///
+    pub fn max_width(&self) -> usize {
+        if self.single_bridges { 1 } else { 2 }
+    }
+
+    ///
+    /// Lock or unlock the bridge between `from` and `to`.
+    ///
+    /// Returns `true` if the bridge is locked now.
+    ///
+    pub fn toggle_lock(&mut self, from: usize, to: usize) -> Result<bool, BridgeError> {
+        let bridge = self
+            .bridges
+            .get_mut(&(std::cmp::min(from, to), std::cmp::max(from, to)))
+            .ok_or(BridgeError::NotFound)?;
+        bridge.locked = !bridge.locked;
+        Ok(bridge.locked)
+    }
+
     ///
     /// Get the bridge between `from` and `to`.
     ///
@@ -419,16 +950,100 @@ impl HexSystem {
             .get(&(std::cmp::min(from, to), std::cmp::max(from, to)))
     }
 
+    ///
+    /// Set the state of the bridge between `from` and `to` without checking for blocking bridges.
+    ///
+    /// Used to restore a previous state, e.g. when undoing a move.
+    ///
+    pub(crate) fn set_bridge_state(
+        &mut self,
+        from: usize,
+        to: usize,
+        state: BridgeState,
+    ) -> Result<(), BridgeError> {
+        let bridge = self
+            .bridges
+            .get_mut(&(std::cmp::min(from, to), std::cmp::max(from, to)))
+            .ok_or(BridgeError::NotFound)?;
+        bridge.state = state;
+        Ok(())
+    }
+
+    ///
+    /// Get the bridges that have more bridges than in the solution the puzzle was generated from.
+    ///
+    /// Puzzles may have more than one solution, so these are not necessarily wrong.
+    ///
+    pub fn get_wrong_bridges(&self) -> Vec<(usize, usize)> {
+        self.bridges
+            .iter()
+            .filter(|(_, b)| b.state > b.solution)
+            .map(|(k, _)| *k)
+            .collect()
+    }
+
+    ///
+    /// Get the bridges that have fewer bridges than in the solution the puzzle was generated from.
+    ///
+    pub fn get_missing_bridges(&self) -> Vec<(usize, usize)> {
+        self.bridges
+            .iter()
+            .filter(|(_, b)| b.state < b.solution)
+            .map(|(k, _)| *k)
+            .collect()
+    }
+
+    ///
+    /// Remove all bridges and locks, i.e. reset the puzzle to its start.
+    ///
+    pub(crate) fn apply_start(&mut self) {
+        for bridge in self.bridges.values_mut() {
+            bridge.state = BridgeState::Empty;
+            bridge.locked = false;
+        }
+    }
+
+    ///
+    /// Set all bridges to the solution the puzzle was generated from.
+    ///
+    pub(crate) fn apply_solution(&mut self) {
+        for bridge in self.bridges.values_mut() {
+            bridge.state = bridge.solution.clone();
+        }
+    }
+
     ///
     /// Get row, column for `from` index of island.
     ///
     pub fn get_row_column_for_index(&self, from: usize) -> (usize, usize) {
-        let even_row = from % (2 * self.columns + 1) < self.columns;
-        let row = 2 * (from / (2 * self.columns + 1)) + if even_row { 0 } else { 1 };
-        let column = from % (2 * self.columns + 1) - if even_row { 0 } else { self.columns };
+        HexSystem::row_column(self.columns, from)
+    }
+
+    ///
+    /// Get the row and column of the cell at `from` of a board with `columns`.
+    ///
+    pub(crate) const fn row_column(columns: usize, from: usize) -> (usize, usize) {
+        let even_row = from % (2 * columns + 1) < columns;
+        let row = 2 * (from / (2 * columns + 1)) + if even_row { 0 } else { 1 };
+        let column = from % (2 * columns + 1) - if even_row { 0 } else { columns };
         (row, column)
     }
 
+    ///
+    /// Get the label of the cell with `index` made of the letter of its row and the number of its column, e.g. "C4".
+    ///
+    pub fn get_label(&self, index: usize) -> String {
+        let (row, column) = self.get_row_column_for_index(index);
+        // Continue with "AA" after "Z" like spreadsheets do.
+        let mut letters = vec![];
+        let mut n = row + 1;
+        while n > 0 {
+            letters.push((b'A' + ((n - 1) % 26) as u8) as char);
+            n = (n - 1) / 26;
+        }
+        letters.iter().rev().collect::<String>() + &(column + 1).to_string()
+    }
+
     ///
     /// Get actual number of bridges for an island with index `from`.
     ///
@@ -446,79 +1061,76 @@ impl HexSystem {
     }
 
     ///
-    /// Check if game is solved.
+    /// Get the number of islands the island at `index` can be connected to.
+    ///
+    pub fn degree(&self, index: usize) -> usize {
+        self.get_connected_islands(index).len()
+    }
+
+    ///
+    /// Get the most bridges the island at `index` can take, i.e. as many as allowed towards each
+    /// island it can reach.
     ///
+    /// An island with a higher number can never be completed.
     ///
-    pub fn is_solved(&self) -> bool {
-        let mut bridged_islands = self
-            .islands
+    pub fn max_capacity(&self, index: usize) -> usize {
+        self.max_width() * self.degree(index)
+    }
+
+    ///
+    /// Get the indices of the islands with a higher number than they can take, see `max_capacity`.
+    ///
+    pub fn over_capacity(&self) -> Vec<usize> {
+        self.islands
             .iter()
             .enumerate()
-            .filter_map(|(i, t)| {
-                if let Island::Bridged(_) = t {
-                    Some(i)
-                } else {
-                    None
-                }
+            .filter_map(|(index, island)| match island {
+                Island::Bridged(target) if *target > self.max_capacity(index) => Some(index),
+                _ => None,
             })
-            .collect::<BTreeSet<_>>();
-        let mut visited_islands = BTreeSet::new();
-        let start_island = self
+            .collect()
+    }
+
+    ///
+    /// Get the number of islands that have exactly their number of bridges.
+    ///
+    /// All islands can be complete without the puzzle being solved, e.g. if they are not connected.
+    ///
+    pub fn progress(&self) -> Progress {
+        let targets = self
             .islands
             .iter()
             .enumerate()
-            .filter_map(|(index, target)| {
-                if let Island::Bridged(target) = target {
+            .filter_map(|(index, island)| {
+                if let Island::Bridged(target) = island {
                     Some((index, *target))
                 } else {
                     None
                 }
-            })
-            .nth(0)
-            .unwrap();
-        visited_islands.insert(start_island.0);
-        bridged_islands.remove(&start_island.0);
-        let mut next_islands: Vec<usize> = self
-            .get_connected_islands(start_island.0)
-            .into_iter()
-            .filter(|to| {
-                self.bridges
-                    .get(&(
-                        std::cmp::min(start_island.0, *to),
-                        std::cmp::max(start_island.0, *to),
-                    ))
-                    .map(|b| b.get_count())
-                    .is_some_and(|x| x > 0)
-            })
-            .collect::<Vec<_>>();
-        loop {
-            for ni in &next_islands {
-                if !visited_islands.contains(ni) {
-                    if self.islands[*ni] == Island::Bridged(self.get_actual_bridges(*ni)) {
-                        bridged_islands.remove(ni);
-                    } else {
-                        return false;
-                    }
-                    visited_islands.insert(*ni);
-                }
-            }
-            next_islands = next_islands
-                .iter()
-                .flat_map(|i| {
-                    self.get_connected_islands(*i).into_iter().filter(|to| {
-                        self.bridges
-                            .get(&(std::cmp::min(*i, *to), std::cmp::max(*i, *to)))
-                            .map(|b| b.get_count())
-                            .is_some_and(|x| x > 0)
-                            && !visited_islands.contains(to)
-                    })
-                })
-                .collect::<Vec<_>>();
-            if next_islands.is_empty() {
-                break;
+            });
+        let mut progress = Progress::default();
+        for (index, target) in targets {
+            progress.total += 1;
+            if self.get_actual_bridges(index) == target {
+                progress.complete += 1;
             }
         }
-        bridged_islands.is_empty()
+        progress
+    }
+
+    ///
+    /// Check if game is solved, i.e. all islands have their number of bridges and each network
+    /// of islands is connected.
+    ///
+    pub fn is_solved(&self) -> bool {
+        self.islands
+            .iter()
+            .enumerate()
+            .all(|(index, island)| match island {
+                Island::Bridged(target) => *target == self.get_actual_bridges(index),
+                _ => true,
+            })
+            && self.connected_components().len() == self.networks()
     }
 }
 
@@ -536,6 +1148,15 @@ impl HexBridge {
         }
     }
 
+    pub fn cycle_back(&mut self) -> Option<usize> {
+        self.state = match self.state {
+            BridgeState::Empty => BridgeState::Full,
+            BridgeState::Partial => BridgeState::Empty,
+            BridgeState::Full => BridgeState::Partial,
+        };
+        Some(self.get_count())
+    }
+
     pub fn get_count(&self) -> usize {
         match self.state {
             BridgeState::Empty => 0,
@@ -547,6 +1168,26 @@ impl HexBridge {
     pub fn get_state(&self) -> &BridgeState {
         &self.state
     }
+
+    pub(crate) fn set_state(&mut self, state: BridgeState) {
+        self.state = state;
+    }
+
+    pub fn get_gap_indices(&self) -> &[usize] {
+        &self.gap_indices
+    }
+
+    pub fn get_solution(&self) -> &BridgeState {
+        &self.solution
+    }
+
+    pub(crate) fn set_solution(&mut self, solution: BridgeState) {
+        self.solution = solution;
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
 }
 
 #[cfg(test)]
@@ -557,7 +1198,7 @@ mod test {
 
     use super::{BridgeState, Island};
 
-    use super::{HexBridge, HexSystem};
+    use super::{BridgeLimits, HexBridge, HexSystem, Progress};
 
     // NW, NE, E, SE, SW, W
     #[test]
@@ -593,6 +1234,22 @@ mod test {
         }
     }
 
+    #[test]
+    fn blocked_and_missing_cells() {
+        let mut islands = vec![Island::Empty; 22];
+        islands[0] = Island::Bridged(1);
+        islands[1] = Island::Blocked;
+        islands[2] = Island::Bridged(1);
+        islands[6] = Island::Blocked;
+        islands[14] = Island::Bridged(1);
+        let sys = HexSystem::from_islands(4, 5, islands);
+        assert!(sys.bridges.is_empty());
+
+        // The last row of a board with an even number of rows has no cells below.
+        let sys = HexSystem::from_islands(2, 2, vec![Island::Bridged(1); 5]);
+        assert!(sys.bridges.keys().all(|(from, to)| *from < 5 && *to < 5));
+    }
+
     #[test]
     fn size_calc() {
         assert_eq!(HexSystem::get_size(4, 5), 22);
@@ -619,8 +1276,8 @@ mod test {
             max_rows: 5,
             num_islands: 5,
             max_bridge_length: 2,
-            ratio_big_island: 0.0,
             ratio_long_bridge: 0.0,
+            ..Default::default()
         };
         let hex = HexSystem::generate_new(params);
         println!("{}", hex);
@@ -633,9 +1290,8 @@ mod test {
             max_columns: 4,
             max_rows: 5,
             num_islands: 8,
-            max_bridge_length: 3,
-            ratio_big_island: 0.0,
             ratio_long_bridge: 0.0,
+            ..Default::default()
         };
         let hex = HexSystem::generate_new(params);
         println!("{}", hex);
@@ -649,8 +1305,8 @@ mod test {
             max_rows: 15,
             num_islands: 28,
             max_bridge_length: 7,
-            ratio_big_island: 0.0,
             ratio_long_bridge: 0.0,
+            ..Default::default()
         };
         let hex = HexSystem::generate_new(params);
         println!("{}", hex);
@@ -660,12 +1316,10 @@ mod test {
     fn random_hashi() {
         let params = GameParameters {
             seed: 63,
-            max_columns: 10,
-            max_rows: 10,
             num_islands: 40,
             max_bridge_length: 10,
-            ratio_big_island: 0.0,
             ratio_long_bridge: 0.0,
+            ..Default::default()
         };
         let hex = HexSystem::generate_new(params);
         println!("{}", hex);
@@ -681,13 +1335,13 @@ mod test {
             HexBridge {
                 state: BridgeState::Full,
                 gap_indices: vec![],
+                solution: BridgeState::Empty,
+                locked: false,
             },
         )]);
         let hex = HexSystem {
-            columns: 4,
-            rows: 5,
-            islands,
             bridges,
+            ..HexSystem::from_islands(4, 5, islands)
         };
         assert!(hex.is_solved());
     }
@@ -705,6 +1359,8 @@ mod test {
                 HexBridge {
                     state: BridgeState::Full,
                     gap_indices: vec![],
+                    solution: BridgeState::Empty,
+                    locked: false,
                 },
             ),
             (
@@ -712,6 +1368,8 @@ mod test {
                 HexBridge {
                     state: BridgeState::Empty,
                     gap_indices: vec![],
+                    solution: BridgeState::Empty,
+                    locked: false,
                 },
             ),
             (
@@ -719,6 +1377,8 @@ mod test {
                 HexBridge {
                     state: BridgeState::Empty,
                     gap_indices: vec![],
+                    solution: BridgeState::Empty,
+                    locked: false,
                 },
             ),
             (
@@ -726,6 +1386,8 @@ mod test {
                 HexBridge {
                     state: BridgeState::Partial,
                     gap_indices: vec![],
+                    solution: BridgeState::Empty,
+                    locked: false,
                 },
             ),
             (
@@ -733,14 +1395,14 @@ mod test {
                 HexBridge {
                     state: BridgeState::Partial,
                     gap_indices: vec![],
+                    solution: BridgeState::Empty,
+                    locked: false,
                 },
             ),
         ]);
         let hex = HexSystem {
-            columns: 4,
-            rows: 5,
-            islands,
             bridges,
+            ..HexSystem::from_islands(4, 5, islands)
         };
         assert!(hex.is_solved());
     }
@@ -752,7 +1414,7 @@ mod test {
         islands[2] = Island::Bridged(1);
         islands[3] = Island::Bridged(1);
         islands[15] = Island::Bridged(1);
-        let bridges = HexSystem::fill_bridges(&islands, 4, 5);
+        let bridges = HexSystem::fill_bridges(&islands, 4, 5, None);
         assert_eq!(
             bridges.keys().collect::<Vec<_>>(),
             vec![&(0usize, 2usize), &(0, 15), &(2, 3), &(3, 15)]
@@ -772,7 +1434,7 @@ mod test {
         islands[16] = Island::Bridged(1);
         islands[19] = Island::Bridged(1);
         islands[21] = Island::Bridged(1);
-        let bridges = HexSystem::fill_bridges(&islands, 4, 5);
+        let bridges = HexSystem::fill_bridges(&islands, 4, 5, None);
         assert_eq!(
             bridges.keys().collect::<Vec<_>>(),
             vec![
@@ -804,13 +1466,13 @@ mod test {
             HexBridge {
                 state: BridgeState::Full,
                 gap_indices: vec![],
+                solution: BridgeState::Empty,
+                locked: false,
             },
         )]);
         let hex = HexSystem {
-            columns: 4,
-            rows: 5,
-            islands,
             bridges,
+            ..HexSystem::from_islands(4, 5, islands)
         };
         assert!(!hex.is_solved());
     }
@@ -825,31 +1487,26 @@ mod test {
             HexBridge {
                 state: BridgeState::Partial,
                 gap_indices: vec![],
+                solution: BridgeState::Empty,
+                locked: false,
             },
         )]);
         let hex = HexSystem {
-            columns: 4,
-            rows: 5,
-            islands,
             bridges,
+            ..HexSystem::from_islands(4, 5, islands)
         };
         assert!(!hex.is_solved());
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn cycle_bridges_good() {
         let mut islands = vec![Island::Empty; 22];
         islands[0] = Island::Bridged(1);
         islands[2] = Island::Bridged(1);
         islands[3] = Island::Bridged(1);
         islands[15] = Island::Bridged(1);
-        let bridges = HexSystem::fill_bridges(&islands, 4, 5);
-        let mut sys = HexSystem {
-            columns: 4,
-            rows: 5,
-            islands,
-            bridges,
-        };
+        let mut sys = HexSystem::from_islands(4, 5, islands);
         let b = sys.get_bridge(0, 2);
         assert!(b.is_some());
         assert_eq!(b.unwrap().get_state(), &BridgeState::Empty);
@@ -863,25 +1520,265 @@ mod test {
     }
 
     #[test]
+    fn cycle_bridges_back() {
+        let mut islands = vec![Island::Empty; 22];
+        islands[0] = Island::Bridged(2);
+        islands[2] = Island::Bridged(2);
+        let mut sys = HexSystem::from_islands(4, 5, islands);
+        assert_eq!(sys.cycle_bridge_back(0, 2), Ok(true));
+        assert_eq!(
+            sys.get_bridge(0, 2).unwrap().get_state(),
+            &BridgeState::Full
+        );
+        assert_eq!(sys.cycle_bridge_back(2, 0), Ok(false));
+        assert_eq!(
+            sys.get_bridge(0, 2).unwrap().get_state(),
+            &BridgeState::Partial
+        );
+        assert_eq!(sys.cycle_bridge_back(0, 2), Ok(false));
+        assert_eq!(
+            sys.get_bridge(0, 2).unwrap().get_state(),
+            &BridgeState::Empty
+        );
+        assert_eq!(sys.toggle_lock(0, 2), Ok(true));
+        assert_eq!(sys.cycle_bridge_back(0, 2), Err(BridgeError::Locked));
+    }
+
+    #[test]
+    fn progress() {
+        let mut islands = vec![Island::Empty; 22];
+        islands[0] = Island::Bridged(1);
+        islands[4] = Island::Bridged(2);
+        islands[6] = Island::Bridged(1);
+        let mut sys = HexSystem::from_islands(4, 5, islands);
+        assert_eq!(
+            sys.progress(),
+            Progress {
+                complete: 0,
+                total: 3
+            }
+        );
+        assert_eq!(sys.cycle_bridge(0, 4), Ok(false));
+        assert_eq!(sys.progress().complete, 1);
+        assert_eq!(sys.cycle_bridge(4, 6), Ok(true));
+        assert_eq!(sys.progress().complete, 3);
+        assert!((sys.progress().fraction() - 1.0).abs() < f64::EPSILON);
+        assert_eq!(Progress::default().fraction(), 0.0);
+    }
+
+    #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn cycle_bridges_blocked() {
         let mut islands = vec![Island::Empty; 22];
         islands[0] = Island::Bridged(1);
         islands[4] = Island::Bridged(1);
         islands[6] = Island::Bridged(1);
         islands[15] = Island::Bridged(1);
-        let bridges = HexSystem::fill_bridges(&islands, 4, 5);
-        let mut sys = HexSystem {
-            columns: 4,
-            rows: 5,
-            islands,
-            bridges,
-        };
+        let mut sys = HexSystem::from_islands(4, 5, islands);
         let c = sys.cycle_bridge(0, 15);
         assert!(c.is_ok());
         assert_eq!(c.unwrap(), false);
         let b = sys.cycle_bridge(4, 6);
         assert!(b.is_err());
         assert_eq!(b.unwrap_err(), BridgeError::Blocked);
+        assert_eq!(sys.get_blocking_bridges(6, 4), [(0, 15)]);
+        assert!(sys.get_blocking_bridges(0, 15).is_empty());
+    }
+
+    #[test]
+    fn available_moves() {
+        let mut islands = vec![Island::Empty; 22];
+        islands[0] = Island::Bridged(2);
+        islands[4] = Island::Bridged(1);
+        islands[6] = Island::Bridged(1);
+        islands[15] = Island::Bridged(1);
+        let mut sys = HexSystem::from_islands(4, 5, islands);
+        assert_eq!(sys.available_moves(), vec![(0, 4), (0, 15), (4, 6)]);
+        // Island 4 needs only one bridge.
+        assert_eq!(sys.remaining_capacity(0, 4), 1);
+        // Island 15 is complete and the bridge blocks the one between 4 and 6.
+        assert!(sys.cycle_bridge(0, 15).is_ok());
+        assert_eq!(sys.available_moves(), vec![(0, 4)]);
+        assert_eq!(sys.toggle_lock(0, 4), Ok(true));
+        assert!(sys.available_moves().is_empty());
+        assert_eq!(sys.toggle_lock(0, 4), Ok(false));
+        // All islands but 6 are complete now.
+        assert!(sys.cycle_bridge(0, 4).is_ok());
+        assert!(sys.available_moves().is_empty());
+    }
+
+    #[test]
+    fn max_capacity() {
+        let mut islands = vec![Island::Empty; 22];
+        islands[0] = Island::Bridged(5);
+        islands[4] = Island::Bridged(1);
+        islands[6] = Island::Bridged(1);
+        islands[15] = Island::Bridged(1);
+        let mut sys = HexSystem::from_islands(4, 5, islands);
+        // Island 0 reaches 4 and 15, so its 5 bridges cannot be placed.
+        assert_eq!((sys.degree(0), sys.max_capacity(0)), (2, 4));
+        assert_eq!((sys.degree(6), sys.max_capacity(6)), (1, 2));
+        assert_eq!(sys.max_capacity(1), 0);
+        assert_eq!(sys.over_capacity(), [0]);
+        assert!(sys.solve().is_none());
+        sys.single_bridges = true;
+        assert_eq!(sys.max_capacity(0), 2);
+    }
+
+    #[test]
+    fn bridge_length() {
+        let mut sys = HexSystem::generate_new(GameParameters {
+            seed: 4,
+            max_bridge_length: 4,
+            ratio_big_island: 0.2,
+            ..Default::default()
+        });
+        assert_eq!(sys.max_bridge_length, Some(4));
+        assert!(
+            sys.bridges
+                .values()
+                .filter(|b| *b.get_solution() != BridgeState::Empty)
+                .all(|b| b.get_gap_indices().len() < 4)
+        );
+        let long: Vec<_> = sys
+            .bridges
+            .iter()
+            .filter(|(_, b)| b.get_gap_indices().len() >= 4)
+            .map(|(key, _)| *key)
+            .collect();
+        assert!(!long.is_empty());
+        let (from, to) = long[0];
+        assert!(sys.cycle_bridge(from, to).is_ok());
+        let short = sys.bridges.len() - long.len();
+        let mut unlimited = HexSystem {
+            max_bridge_length: None,
+            ..sys.clone()
+        };
+        sys.limit_bridge_length();
+        // The placed bridge is kept, the other long bridges are gone.
+        assert_eq!(sys.bridges.len(), short + 1);
+        assert!(sys.get_bridge(from, to).is_some());
+        // Without a known limit all bridges are offered.
+        unlimited.limit_bridge_length();
+        assert_eq!(unlimited.bridges.len(), short + long.len());
+    }
+
+    #[test]
+    fn bridge_limits() {
+        let lengths = |bridge_limits| {
+            let sys = HexSystem::generate_new(GameParameters {
+                seed: 7,
+                max_columns: 12,
+                max_rows: 12,
+                num_islands: 30,
+                max_bridge_length: 10,
+                ratio_big_island: 0.5,
+                ratio_long_bridge: 0.1,
+                bridge_limits,
+                ..Default::default()
+            });
+            sys.bridges
+                .values()
+                .filter(|b| *b.get_solution() != BridgeState::Empty)
+                .map(|b| b.get_gap_indices().len() + 1)
+                .collect::<Vec<_>>()
+        };
+        assert!(lengths(None).iter().any(|l| *l > 4));
+        let limited = lengths(Some(BridgeLimits {
+            long_length: 2,
+            max_long: 3,
+            max_span: 0.4,
+        }));
+        assert!(!limited.is_empty());
+        // The span is 0.4 of 12 cells.
+        assert!(limited.iter().all(|l| *l <= 4));
+        assert!(limited.iter().filter(|l| **l > 2).count() <= 3);
+    }
+
+    #[test]
+    fn spaced_islands() {
+        let adjacent = |spaced_islands| {
+            let sys = HexSystem::generate_new(GameParameters {
+                seed: 3,
+                num_islands: 25,
+                max_bridge_length: 4,
+                ratio_big_island: 0.3,
+                ratio_long_bridge: 0.3,
+                spaced_islands,
+                ..Default::default()
+            });
+            sys.bridges
+                .values()
+                .filter(|b| b.get_gap_indices().is_empty())
+                .filter(|b| *b.get_solution() == BridgeState::Empty)
+                .count()
+        };
+        assert!(adjacent(false) > 0);
+        // Neighboring islands are always connected in the solution.
+        assert_eq!(adjacent(true), 0);
+    }
+
+    #[test]
+    fn island_satisfiable() {
+        let mut islands = vec![Island::Empty; 22];
+        islands[0] = Island::Bridged(2);
+        islands[4] = Island::Bridged(1);
+        islands[15] = Island::Bridged(1);
+        let mut sys = HexSystem::from_islands(4, 5, islands);
+        assert!(sys.island_satisfiable(1));
+        assert!(sys.island_satisfiable(0));
+        assert!(sys.cycle_bridge(0, 4).is_ok());
+        assert!(sys.island_satisfiable(0));
+        // Too many bridges for island 4 take the last one island 15 could get.
+        assert!(sys.cycle_bridge(0, 4).is_ok());
+        assert!(sys.island_satisfiable(0));
+        assert!(!sys.island_satisfiable(4));
+        assert!(!sys.island_satisfiable(15));
+    }
+
+    #[test]
+    fn available_moves_full() {
+        let mut islands = vec![Island::Empty; 22];
+        islands[0] = Island::Bridged(3);
+        islands[15] = Island::Bridged(3);
+        let mut sys = HexSystem::from_islands(4, 5, islands);
+        assert_eq!(sys.remaining_capacity(15, 0), 2);
+        // A double bridge is not enough.
+        assert!(!sys.island_satisfiable(0));
+        assert!(sys.cycle_bridge(0, 15).is_ok());
+        assert_eq!(sys.available_moves(), vec![(0, 15)]);
+        assert_eq!(sys.remaining_capacity(0, 15), 1);
+        // Both islands still need a bridge, but a full bridge cannot be incremented.
+        assert!(sys.cycle_bridge(0, 15).is_ok());
+        assert!(sys.available_moves().is_empty());
+        assert_eq!(sys.remaining_capacity(0, 15), 0);
+        assert_eq!(sys.remaining_capacity(0, 4), 0);
+    }
+
+    #[test]
+    fn labels() {
+        let sys = HexSystem::from_islands(4, 60, vec![Island::Empty; 270]);
+        assert_eq!(sys.get_label(0), "A1");
+        assert_eq!(sys.get_label(3), "A4");
+        assert_eq!(sys.get_label(4), "B1");
+        assert_eq!(sys.get_label(8), "B5");
+        assert_eq!(sys.get_label(21), "E4");
+        assert_eq!(sys.get_label(26 / 2 * 9 + 1), "AA2");
+    }
+
+    #[test]
+    fn cycle_bridges_locked() {
+        let mut islands = vec![Island::Empty; 22];
+        islands[0] = Island::Bridged(1);
+        islands[15] = Island::Bridged(1);
+        let mut sys = HexSystem::from_islands(4, 5, islands);
+        assert!(sys.cycle_bridge(0, 15).is_ok());
+        assert_eq!(sys.toggle_lock(15, 0), Ok(true));
+        assert_eq!(sys.cycle_bridge(0, 15), Err(BridgeError::Locked));
+        assert_eq!(sys.get_bridge(0, 15).unwrap().get_count(), 1);
+        assert_eq!(sys.toggle_lock(0, 15), Ok(false));
+        assert!(sys.cycle_bridge(0, 15).is_ok());
+        assert_eq!(sys.toggle_lock(14, 15), Err(BridgeError::NotFound));
     }
 
     #[test]
@@ -891,13 +1788,7 @@ mod test {
         islands[4] = Island::Bridged(1);
         islands[6] = Island::Bridged(1);
         islands[15] = Island::Bridged(1);
-        let bridges = HexSystem::fill_bridges(&islands, 4, 5);
-        let mut sys = HexSystem {
-            columns: 4,
-            rows: 5,
-            islands,
-            bridges,
-        };
+        let mut sys = HexSystem::from_islands(4, 5, islands);
         let b = sys.cycle_bridge(14, 15);
         assert!(b.is_err());
         assert_eq!(b.unwrap_err(), BridgeError::NotFound);
@@ -910,13 +1801,7 @@ mod test {
         islands[4] = Island::Bridged(1);
         islands[6] = Island::Bridged(1);
         islands[15] = Island::Bridged(1);
-        let bridges = HexSystem::fill_bridges(&islands, 4, 5);
-        let sys = HexSystem {
-            columns: 4,
-            rows: 5,
-            islands,
-            bridges,
-        };
+        let sys = HexSystem::from_islands(4, 5, islands);
         let c = sys.get_bridge(1, 3);
         assert!(c.is_none());
     }
@@ -928,13 +1813,7 @@ mod test {
         islands[4] = Island::Bridged(1);
         islands[6] = Island::Bridged(1);
         islands[15] = Island::Bridged(1);
-        let bridges = HexSystem::fill_bridges(&islands, 4, 5);
-        let sys = HexSystem {
-            columns: 4,
-            rows: 5,
-            islands,
-            bridges,
-        };
+        let sys = HexSystem::from_islands(4, 5, islands);
         let rc = sys.get_row_column_for_index(0);
         assert_eq!(rc, (0, 0));
         let rc = sys.get_row_column_for_index(21);
@@ -942,4 +1821,129 @@ mod test {
         let rc = sys.get_row_column_for_index(4);
         assert_eq!(rc, (1, 0));
     }
+
+    #[test]
+    fn serde_roundtrip() {
+        let mut islands = vec![Island::Empty; 22];
+        islands[0] = Island::Bridged(1);
+        islands[2] = Island::Bridged(1);
+        islands[3] = Island::Bridged(1);
+        islands[15] = Island::Bridged(1);
+        let mut sys = HexSystem::from_islands(4, 5, islands);
+        sys.cycle_bridge(0, 2).unwrap();
+        let json = serde_json::to_string(&sys).unwrap();
+        let restored: HexSystem = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.islands, sys.islands);
+        assert_eq!(
+            restored.bridges.keys().collect::<Vec<_>>(),
+            sys.bridges.keys().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            restored.get_bridge(0, 2).unwrap().get_state(),
+            &BridgeState::Partial
+        );
+    }
+
+    #[test]
+    fn generated_solution() {
+        let params = GameParameters {
+            seed: 63,
+            num_islands: 40,
+            max_bridge_length: 10,
+            ratio_long_bridge: 0.0,
+            ..Default::default()
+        };
+        let mut hex = HexSystem::generate_new(params);
+        assert!(hex.get_wrong_bridges().is_empty());
+        let solution = hex
+            .bridges
+            .iter()
+            .map(|(k, b)| (*k, b.get_solution().clone()))
+            .collect::<Vec<_>>();
+        for ((from, to), state) in solution {
+            hex.set_bridge_state(from, to, state).unwrap();
+        }
+        assert!(hex.is_solved());
+    }
+
+    ///
+    /// Get the parameters to generate or extend a board of 8 columns.
+    ///
+    fn extend_params(seed: u64, max_rows: usize, num_islands: usize) -> GameParameters {
+        GameParameters {
+            seed,
+            max_columns: 8,
+            max_rows,
+            num_islands,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn extend() {
+        let mut hex = HexSystem::generate_new(extend_params(5, 6, 12));
+        hex.apply_solution();
+        assert!(hex.is_solved());
+        let count = |sys: &HexSystem| {
+            sys.islands
+                .iter()
+                .filter(|i| matches!(i, Island::Bridged(_)))
+                .count()
+        };
+
+        let mut grown = hex.extend(extend_params(7, 10, 6));
+        assert_eq!((grown.columns, grown.rows), (8, 10));
+        assert!(count(&grown) > count(&hex));
+        // The islands stay where they were and keep at least their bridges.
+        for (index, island) in hex.islands.iter().enumerate() {
+            match (island, &grown.islands[index]) {
+                (Island::Bridged(old), Island::Bridged(new)) => assert!(new >= old),
+                (Island::Empty, _) => (),
+                (old, new) => panic!("island {index} changed from {old:?} to {new:?}"),
+            }
+        }
+        // The old solution is placed, the new part is not.
+        for (key, bridge) in &hex.bridges {
+            if *bridge.get_solution() != BridgeState::Empty {
+                assert_eq!(grown.bridges[key].get_state(), bridge.get_solution());
+            }
+        }
+        assert!(!grown.is_solved());
+        grown.apply_solution();
+        assert!(grown.is_solved());
+    }
+
+    #[test]
+    fn extend_partial() {
+        let mut hex = HexSystem::generate_new(extend_params(11, 6, 12));
+        // Place one bridge of the solution and lock it, and one bridge that is wrong.
+        let (&right, _) = hex
+            .bridges
+            .iter()
+            .find(|(_, b)| *b.get_solution() != BridgeState::Empty)
+            .unwrap();
+        let (&wrong, _) = hex
+            .bridges
+            .iter()
+            .find(|(key, b)| {
+                *b.get_solution() == BridgeState::Empty
+                    && hex.get_blocking_bridges(key.0, key.1).is_empty()
+            })
+            .unwrap();
+        hex.cycle_bridge(right.0, right.1).unwrap();
+        hex.toggle_lock(right.0, right.1).unwrap();
+        hex.cycle_bridge(wrong.0, wrong.1).unwrap();
+
+        let grown = hex.extend(extend_params(13, 10, 6));
+        for (key, bridge) in &hex.bridges {
+            if *bridge.get_state() != BridgeState::Empty {
+                let kept = &grown.bridges[key];
+                assert_eq!(kept.get_state(), bridge.get_state());
+                assert_eq!(kept.is_locked(), bridge.is_locked());
+                assert_eq!(kept.get_gap_indices(), bridge.get_gap_indices());
+            }
+        }
+        assert!(grown.bridges[&right].is_locked());
+        assert_eq!(grown.get_wrong_bridges(), [wrong]);
+    }
 }
@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    hex::{BridgeError, HexSystem, Island},
+    session::GameSession,
+};
+
+///
+/// Moves and completed islands of one player.
+///
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlayerScore {
+    pub moves: u32,
+    /// Islands that got their last bridge from this player
+    pub islands: u32,
+}
+
+///
+/// Turns of players alternating moves on the same board.
+///
+/// The player completing an island by placing its last bridge scores it.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HotSeat {
+    current: usize,
+    players: Vec<PlayerScore>,
+}
+
+impl HotSeat {
+    ///
+    /// Start with the first of `players`, which must be at least two to take turns.
+    ///
+    pub fn new(players: usize) -> Self {
+        assert!(players >= 2, "hot-seat games need at least two players");
+        HotSeat {
+            current: 0,
+            players: vec![PlayerScore::default(); players],
+        }
+    }
+
+    ///
+    /// Get the index of the player whose turn it is.
+    ///
+    pub fn current_player(&self) -> usize {
+        self.current
+    }
+
+    pub fn scores(&self) -> &[PlayerScore] {
+        &self.players
+    }
+
+    ///
+    /// Cycle the bridge between `from` and `to` in `session` for the current player and pass the turn.
+    ///
+    /// Invalid moves do not pass the turn. Returns if the puzzle is solved.
+    ///
+    pub fn cycle_bridge(
+        &mut self,
+        session: &mut GameSession,
+        from: usize,
+        to: usize,
+    ) -> Result<bool, BridgeError> {
+        self.take_turn(session, from, to, GameSession::cycle_bridge)
+    }
+
+    ///
+    /// Cycle the bridge between `from` and `to` in `session` back for the current player and pass the turn.
+    ///
+    /// Invalid moves do not pass the turn. Returns if the puzzle is solved.
+    ///
+    pub fn cycle_bridge_back(
+        &mut self,
+        session: &mut GameSession,
+        from: usize,
+        to: usize,
+    ) -> Result<bool, BridgeError> {
+        self.take_turn(session, from, to, GameSession::cycle_bridge_back)
+    }
+
+    fn take_turn(
+        &mut self,
+        session: &mut GameSession,
+        from: usize,
+        to: usize,
+        change: fn(&mut GameSession, usize, usize) -> Result<bool, BridgeError>,
+    ) -> Result<bool, BridgeError> {
+        let before = [from, to].map(|i| is_complete(session.system(), i));
+        let solved = change(session, from, to)?;
+        let completed = [from, to]
+            .iter()
+            .zip(before)
+            .filter(|(i, before)| !before && is_complete(session.system(), **i))
+            .count() as u32;
+        let player = &mut self.players[self.current];
+        player.moves += 1;
+        player.islands += completed;
+        self.current = (self.current + 1) % self.players.len();
+        Ok(solved)
+    }
+
+    ///
+    /// Get the player who completed the most islands, or `None` on a draw.
+    ///
+    pub fn winner(&self) -> Option<usize> {
+        let best = self.players.iter().map(|p| p.islands).max()?;
+        let mut leaders = self
+            .players
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.islands == best);
+        match (leaders.next(), leaders.next()) {
+            (Some((index, _)), None) => Some(index),
+            _ => None,
+        }
+    }
+}
+
+fn is_complete(system: &HexSystem, index: usize) -> bool {
+    matches!(system.islands[index], Island::Bridged(target) if system.get_actual_bridges(index) == target)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        hex::{BridgeError, HexSystem, Island},
+        session::GameSession,
+    };
+
+    use super::HotSeat;
+
+    #[test]
+    fn turns() {
+        let mut islands = vec![Island::Empty; 22];
+        islands[0] = Island::Bridged(1);
+        islands[15] = Island::Bridged(2);
+        islands[17] = Island::Bridged(1);
+        let mut session = GameSession::new(HexSystem::from_islands(4, 5, islands));
+        let mut hot_seat = HotSeat::new(2);
+        assert_eq!(hot_seat.winner(), None);
+        assert_eq!(
+            hot_seat.cycle_bridge(&mut session, 14, 15),
+            Err(BridgeError::NotFound)
+        );
+        assert_eq!(hot_seat.current_player(), 0);
+        assert_eq!(hot_seat.cycle_bridge(&mut session, 0, 15), Ok(false));
+        assert_eq!(hot_seat.current_player(), 1);
+        assert_eq!(hot_seat.scores()[0].islands, 1);
+        assert_eq!(hot_seat.cycle_bridge(&mut session, 15, 17), Ok(true));
+        assert_eq!(hot_seat.scores()[1].islands, 2);
+        assert_eq!(hot_seat.scores()[1].moves, 1);
+        assert_eq!(hot_seat.current_player(), 0);
+        assert_eq!(hot_seat.winner(), Some(1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn single_player() {
+        HotSeat::new(1);
+    }
+}
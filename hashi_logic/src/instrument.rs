@@ -0,0 +1,77 @@
+use std::sync::OnceLock;
+
+///
+/// Functions measuring how long operations of the logic take.
+///
+/// They are provided by the application, since there is no clock that works everywhere,
+/// e.g. `std::time::Instant` is not available in the browser.
+///
+#[derive(Clone, Copy)]
+pub struct Hooks {
+    /// Get the current time in milliseconds.
+    pub now: fn() -> f64,
+    /// Take the name of a finished operation and its duration in milliseconds.
+    pub record: fn(&'static str, f64),
+}
+
+static HOOKS: OnceLock<Hooks> = OnceLock::new();
+
+///
+/// Measure the operations of the logic with `hooks` from now on.
+///
+/// Returns `false` if hooks were set before, which stay.
+///
+pub fn set_hooks(hooks: Hooks) -> bool {
+    HOOKS.set(hooks).is_ok()
+}
+
+///
+/// Operation being measured until it is dropped
+///
+pub struct Span {
+    name: &'static str,
+    /// Hooks and the time the operation started, if there are hooks
+    started: Option<(Hooks, f64)>,
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if let Some((hooks, started)) = self.started {
+            (hooks.record)(self.name, (hooks.now)() - started);
+        }
+    }
+}
+
+///
+/// Start measuring the operation `name`, which costs nothing without hooks.
+///
+pub fn span(name: &'static str) -> Span {
+    Span {
+        name,
+        started: HOOKS.get().map(|hooks| (*hooks, (hooks.now)())),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Mutex;
+
+    use super::{Hooks, set_hooks, span};
+
+    static RECORDED: Mutex<Vec<(&str, f64)>> = Mutex::new(Vec::new());
+
+    #[test]
+    fn hooks() {
+        set_hooks(Hooks {
+            now: || 5.0,
+            record: |name, ms| RECORDED.lock().unwrap().push((name, ms)),
+        });
+        drop(span("test"));
+        assert!(RECORDED.lock().unwrap().contains(&("test", 0.0)));
+        // Hooks cannot be replaced.
+        assert!(!set_hooks(Hooks {
+            now: || 0.0,
+            record: |_, _| (),
+        }));
+    }
+}
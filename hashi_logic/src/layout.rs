@@ -0,0 +1,306 @@
+use std::{f64::consts::PI, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+
+use crate::hex::HexSystem;
+
+///
+/// Orientation of the hexagonal grid.
+///
+/// With pointy-top, rows are horizontal; with flat-top, they are vertical.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Orientation {
+    #[default]
+    PointyTop,
+    FlatTop,
+}
+
+///
+/// Rotation by multiples of 60° and mirroring of the displayed board.
+///
+/// It only changes how the board is shown, not the puzzle itself.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ViewTransform {
+    /// Number of 60° turns clockwise
+    pub rotation: u8,
+    /// Mirror horizontally before turning
+    pub mirrored: bool,
+}
+
+impl ViewTransform {
+    ///
+    /// Get the transform turned by another 60° clockwise.
+    ///
+    pub fn rotated(self) -> Self {
+        ViewTransform {
+            rotation: (self.rotation + 1) % 6,
+            ..self
+        }
+    }
+
+    ///
+    /// Get the transform mirrored once more.
+    ///
+    pub fn flipped(self) -> Self {
+        ViewTransform {
+            mirrored: !self.mirrored,
+            ..self
+        }
+    }
+
+    ///
+    /// Get `point` transformed around `center`.
+    ///
+    pub fn apply(&self, point: (f64, f64), center: (f64, f64)) -> (f64, f64) {
+        let x = point.0 - center.0;
+        let x = if self.mirrored { -x } else { x };
+        let (x, y) = rotate((x, point.1 - center.1), self.angle());
+        (center.0 + x, center.1 + y)
+    }
+
+    ///
+    /// Get the point that is transformed to `point` around `center`, e.g. to find the cell under the mouse.
+    ///
+    pub fn invert(&self, point: (f64, f64), center: (f64, f64)) -> (f64, f64) {
+        let (x, y) = rotate((point.0 - center.0, point.1 - center.1), -self.angle());
+        let x = if self.mirrored { -x } else { x };
+        (center.0 + x, center.1 + y)
+    }
+
+    ///
+    /// Get one of the other eleven transforms picked by `choice`, e.g. to let stuck players see the board afresh.
+    ///
+    pub fn shuffled(self, choice: u64) -> Self {
+        let current = self.rotation as u64 * 2 + self.mirrored as u64;
+        let other = (current + 1 + choice % 11) % 12;
+        ViewTransform {
+            rotation: (other / 2) as u8,
+            mirrored: other % 2 == 1,
+        }
+    }
+
+    fn angle(&self) -> f64 {
+        self.rotation as f64 * PI / 3.0
+    }
+}
+
+impl Display for ViewTransform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.rotation, self.mirrored) {
+            (0, false) => f.write_str("Normal view"),
+            (0, true) => f.write_str("Mirrored"),
+            (rotation, mirrored) => {
+                write!(f, "Turned by {}°", rotation as u32 * 60)?;
+                if mirrored {
+                    f.write_str(", mirrored")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+///
+/// Rotate `point` by `angle` around the origin.
+///
+fn rotate((x, y): (f64, f64), angle: f64) -> (f64, f64) {
+    let (sin, cos) = angle.sin_cos();
+    (x * cos - y * sin, x * sin + y * cos)
+}
+
+impl HexSystem {
+    ///
+    /// Get (x, y) position of the cell with `index` for rows `hex_size` apart.
+    ///
+    /// For flat-top orientation the pointy-top layout is transposed.
+    ///
+    pub fn pixel_position(
+        &self,
+        index: usize,
+        hex_size: f64,
+        orientation: Orientation,
+    ) -> (f64, f64) {
+        let triangle_thigh = hex_size / (60.0 * PI / 180.0).sin();
+        let (row, column) = self.get_row_column_for_index(index);
+        let even_row = row % 2 == 0;
+        let along_row = triangle_thigh
+            + column as f64 * triangle_thigh
+            + if even_row { 0.0 } else { -triangle_thigh * 0.5 };
+        let across_rows = hex_size + row as f64 * hex_size;
+        match orientation {
+            Orientation::PointyTop => (along_row, across_rows),
+            Orientation::FlatTop => (across_rows, along_row),
+        }
+    }
+
+    ///
+    /// Get (width, height) of the area covered by all cells in pointy-top orientation for rows `hex_size` apart.
+    ///
+    /// The area includes a border of one row around the cells.
+    ///
+    pub fn bounding_box(&self, hex_size: f64) -> (f64, f64) {
+        let triangle_thigh = hex_size / (60.0 * PI / 180.0).sin();
+        (
+            triangle_thigh * (self.columns + 1) as f64,
+            hex_size * (self.rows + 1) as f64,
+        )
+    }
+
+    ///
+    /// Get the center of the board and the (left, top, right, bottom) extents of the area covered by all cells as displayed with `transform`.
+    ///
+    fn transformed_area(
+        &self,
+        hex_size: f64,
+        orientation: Orientation,
+        transform: ViewTransform,
+    ) -> ((f64, f64), [f64; 4]) {
+        let (width, height) = match (self.bounding_box(hex_size), orientation) {
+            (size, Orientation::PointyTop) => size,
+            ((along_row, across_rows), Orientation::FlatTop) => (across_rows, along_row),
+        };
+        let center = (width / 2.0, height / 2.0);
+        let corners = [(0.0, 0.0), (width, 0.0), (0.0, height), (width, height)]
+            .map(|corner| transform.apply(corner, center));
+        let extents = corners.iter().fold(
+            [f64::MAX, f64::MAX, f64::MIN, f64::MIN],
+            |[left, top, right, bottom], (x, y)| {
+                [left.min(*x), top.min(*y), right.max(*x), bottom.max(*y)]
+            },
+        );
+        (center, extents)
+    }
+
+    ///
+    /// Get (x, y) position of the cell with `index` as displayed with `transform`.
+    ///
+    /// The board is turned around its center and moved so that its area starts at the origin again.
+    ///
+    pub fn transformed_position(
+        &self,
+        index: usize,
+        hex_size: f64,
+        orientation: Orientation,
+        transform: ViewTransform,
+    ) -> (f64, f64) {
+        // Spare the rounding errors for the usual, untransformed board.
+        if transform == ViewTransform::default() {
+            return self.pixel_position(index, hex_size, orientation);
+        }
+        let (center, [left, top, ..]) = self.transformed_area(hex_size, orientation, transform);
+        let (x, y) = transform.apply(self.pixel_position(index, hex_size, orientation), center);
+        (x - left, y - top)
+    }
+
+    ///
+    /// Get (width, height) of the area covered by all cells as displayed with `transform`.
+    ///
+    pub fn transformed_bounding_box(
+        &self,
+        hex_size: f64,
+        orientation: Orientation,
+        transform: ViewTransform,
+    ) -> (f64, f64) {
+        let (_, [left, top, right, bottom]) =
+            self.transformed_area(hex_size, orientation, transform);
+        (right - left, bottom - top)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hex::{HexSystem, Island};
+
+    use super::{Orientation, ViewTransform};
+
+    #[test]
+    fn positions() {
+        let sys = HexSystem::from_islands(4, 5, vec![Island::Empty; 22]);
+        let thigh = 10.0 / (60.0f64).to_radians().sin();
+        let (x, y) = sys.pixel_position(0, 10.0, Orientation::PointyTop);
+        assert!((x - thigh).abs() < 1e-9);
+        assert!((y - 10.0).abs() < 1e-9);
+        // The odd row is shifted by half a cell.
+        let (x, y) = sys.pixel_position(4, 10.0, Orientation::PointyTop);
+        assert!((x - 0.5 * thigh).abs() < 1e-9);
+        assert!((y - 20.0).abs() < 1e-9);
+        assert_eq!(sys.pixel_position(4, 10.0, Orientation::FlatTop), (y, x));
+        let (width, height) = sys.bounding_box(10.0);
+        assert!((width - 5.0 * thigh).abs() < 1e-9);
+        assert!((height - 60.0).abs() < 1e-9);
+        // All cells are within the bounding box.
+        for index in 0..sys.islands.len() {
+            let (x, y) = sys.pixel_position(index, 10.0, Orientation::PointyTop);
+            assert!(x > 0.0 && x < width && y > 0.0 && y < height);
+        }
+    }
+
+    #[test]
+    fn transforms() {
+        let sys = HexSystem::from_islands(4, 5, vec![Island::Empty; 22]);
+        let close =
+            |a: (f64, f64), b: (f64, f64)| (a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9;
+        let identity = ViewTransform::default();
+        for orientation in [Orientation::PointyTop, Orientation::FlatTop] {
+            for index in 0..sys.islands.len() {
+                assert!(close(
+                    sys.transformed_position(index, 10.0, orientation, identity),
+                    sys.pixel_position(index, 10.0, orientation)
+                ));
+            }
+        }
+        // Six turns are a full circle, mirroring twice changes nothing.
+        let mut transform = identity;
+        for _ in 0..6 {
+            transform = transform.rotated();
+        }
+        assert_eq!(transform, identity);
+        assert_eq!(identity.flipped().flipped(), identity);
+        // Shuffling always changes the view.
+        let shuffled: Vec<_> = (0..11).map(|choice| identity.shuffled(choice)).collect();
+        assert!(!shuffled.contains(&identity));
+        assert!(shuffled.contains(&identity.flipped()));
+        assert_ne!(shuffled[3].shuffled(7), shuffled[3]);
+        assert_eq!(identity.to_string(), "Normal view");
+        assert_eq!(
+            identity.rotated().rotated().flipped().to_string(),
+            "Turned by 120°, mirrored"
+        );
+
+        let center = (20.0, 30.0);
+        let transform = identity.rotated().flipped();
+        let point = (35.0, 12.0);
+        assert!(close(
+            transform.invert(transform.apply(point, center), center),
+            point
+        ));
+        // Turned by 60°, a point right of the center moves down and to the left.
+        let (x, y) = identity.rotated().apply((30.0, 30.0), center);
+        assert!(close(
+            (x, y),
+            (25.0, 30.0 + 10.0 * (60.0f64).to_radians().sin())
+        ));
+
+        // All cells are within the area and neighbors stay as far apart.
+        let (width, height) = sys.transformed_bounding_box(10.0, Orientation::PointyTop, transform);
+        let position =
+            |index| sys.transformed_position(index, 10.0, Orientation::PointyTop, transform);
+        for index in 0..sys.islands.len() {
+            let (x, y) = position(index);
+            assert!(x > 0.0 && x < width && y > 0.0 && y < height);
+        }
+        let distance =
+            |a: (f64, f64), b: (f64, f64)| ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt();
+        assert!(
+            (distance(position(0), position(1))
+                - distance(
+                    sys.pixel_position(0, 10.0, Orientation::PointyTop),
+                    sys.pixel_position(1, 10.0, Orientation::PointyTop)
+                ))
+            .abs()
+                < 1e-9
+        );
+    }
+}
@@ -1 +1,19 @@
+pub mod analysis;
+pub mod calibration;
+pub mod code;
+pub mod connectivity;
+pub mod coop;
+pub mod density;
+pub mod difficulty;
 pub mod hex;
+pub mod hotseat;
+pub mod instrument;
+pub mod layout;
+pub mod pack;
+pub mod score;
+pub mod session;
+pub mod shape;
+pub mod solver;
+pub mod stats;
+pub mod svg;
+pub mod variant;
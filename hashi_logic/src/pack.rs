@@ -0,0 +1,154 @@
+use std::fmt::{Display, Write};
+
+use serde::{Deserialize, Deserializer, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{difficulty::Difficulty, hex::HexSystem};
+
+///
+/// Entry of a pack in the index of a pack server.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PackInfo {
+    pub id: String,
+    pub name: String,
+    /// Month of the pack, e.g. "2025-06"
+    pub month: String,
+    /// Path of the pack relative to the index
+    pub file: String,
+    /// SHA-256 hash of the pack file as hex string
+    pub sha256: String,
+}
+
+///
+/// Collection of puzzles published together.
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PuzzlePack {
+    pub id: String,
+    pub name: String,
+    /// Difficulty of all puzzles, `None` if it is unknown to this version
+    #[serde(default, deserialize_with = "lenient_difficulty")]
+    pub difficulty: Option<Difficulty>,
+    pub puzzles: Vec<HexSystem>,
+}
+
+///
+/// Read the difficulty of a pack, so that packs of difficulties added later can still be played.
+///
+fn lenient_difficulty<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Difficulty>, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.and_then(|d| d.parse().ok()))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PackError {
+    /// The pack does not match the hash of the index.
+    Hash,
+    /// The pack cannot be read.
+    Format(String),
+    /// The pack is not the one listed in the index.
+    Id,
+}
+
+impl Display for PackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PackError::Hash => f.write_str("Pack does not match its hash."),
+            PackError::Format(e) => write!(f, "Pack cannot be read: {}", e),
+            PackError::Id => f.write_str("Pack is not the one listed."),
+        }
+    }
+}
+
+impl std::error::Error for PackError {}
+
+///
+/// Get the SHA-256 hash of `data` as hex string.
+///
+pub fn sha256(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .fold(String::new(), |mut hex, byte| {
+            let _ = write!(hex, "{:02x}", byte);
+            hex
+        })
+}
+
+///
+/// Verify the downloaded `data` of the pack listed as `info` and read it.
+///
+pub fn verify_pack(info: &PackInfo, data: &[u8]) -> Result<PuzzlePack, PackError> {
+    if !sha256(data).eq_ignore_ascii_case(&info.sha256) {
+        return Err(PackError::Hash);
+    }
+    let pack: PuzzlePack =
+        serde_json::from_slice(data).map_err(|e| PackError::Format(e.to_string()))?;
+    if pack.id != info.id {
+        return Err(PackError::Id);
+    }
+    Ok(pack)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        difficulty::Difficulty,
+        hex::{GameParameters, HexSystem},
+    };
+
+    use super::{PackError, PackInfo, PuzzlePack, sha256, verify_pack};
+
+    #[test]
+    fn hash() {
+        assert_eq!(
+            sha256(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn verify() {
+        let pack = PuzzlePack {
+            id: "2025-06".to_string(),
+            name: "June".to_string(),
+            difficulty: None,
+            puzzles: vec![HexSystem::generate_new(GameParameters {
+                seed: 1,
+                max_columns: 4,
+                max_rows: 5,
+                num_islands: 8,
+                ratio_long_bridge: 0.0,
+                ..Default::default()
+            })],
+        };
+        let data = serde_json::to_vec(&pack).unwrap();
+        let mut info = PackInfo {
+            id: "2025-06".to_string(),
+            name: "June".to_string(),
+            month: "2025-06".to_string(),
+            file: "2025-06.json".to_string(),
+            sha256: sha256(&data).to_uppercase(),
+        };
+        assert_eq!(verify_pack(&info, &data).unwrap().puzzles.len(), 1);
+        let mut tampered = data.clone();
+        tampered.push(b' ');
+        assert_eq!(verify_pack(&info, &tampered).unwrap_err(), PackError::Hash);
+        info.id = "2025-07".to_string();
+        assert_eq!(verify_pack(&info, &data).unwrap_err(), PackError::Id);
+    }
+
+    #[test]
+    fn unknown_difficulty() {
+        let read = |difficulty: &str| {
+            let data = format!(r#"{{"id":"x","name":"x",{}"puzzles":[]}}"#, difficulty);
+            serde_json::from_str::<PuzzlePack>(&data)
+                .unwrap()
+                .difficulty
+        };
+        assert_eq!(read(r#""difficulty":"hard","#), Some(Difficulty::Hard));
+        assert_eq!(read(r#""difficulty":"nightmare","#), None);
+        assert_eq!(read(""), None);
+    }
+}
@@ -0,0 +1,155 @@
+use std::{collections::BTreeSet, fmt::Display};
+
+use crate::{
+    hex::BridgeState,
+    session::{GameSession, HintLevel},
+};
+
+/// Points deducted for each hint showing a bridge
+pub const HINT_PENALTY: u32 = 100;
+/// Points deducted for each hint showing an island only
+pub const ISLAND_HINT_PENALTY: u32 = 50;
+/// Points deducted for each hint making the move
+pub const MOVE_HINT_PENALTY: u32 = 150;
+/// Points deducted for each check
+pub const CHECK_PENALTY: u32 = 50;
+/// Points deducted for each second over par time
+pub const TIME_PENALTY: f64 = 1.0;
+/// Most moves per necessary move for the grades S, A and B
+const GRADE_LIMITS: [(Grade, f64); 3] = [(Grade::S, 1.0), (Grade::A, 1.25), (Grade::B, 1.5)];
+
+///
+/// Grade of a solve by the moves made compared to the fewest moves needed.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Grade {
+    /// No move was wasted.
+    S,
+    A,
+    B,
+    C,
+}
+
+impl Display for Grade {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Grade::S => f.write_str("S"),
+            Grade::A => f.write_str("A"),
+            Grade::B => f.write_str("B"),
+            Grade::C => f.write_str("C"),
+        }
+    }
+}
+
+///
+/// Compute the score of the solved `session` that took `elapsed_ms`, starting from `base` points.
+///
+/// Hints, checks and time over `par_ms` reduce the score, which never drops below zero.
+///
+pub fn compute_score(session: &GameSession, base: u32, elapsed_ms: f64, par_ms: f64) -> u32 {
+    let over_par = f64::max(0.0, elapsed_ms - par_ms) / 1000.0;
+    let hints = session.hints_at(HintLevel::Island) * ISLAND_HINT_PENALTY
+        + session.hints_at(HintLevel::Bridge) * HINT_PENALTY
+        + session.hints_at(HintLevel::Move) * MOVE_HINT_PENALTY;
+    base.saturating_sub(hints)
+        .saturating_sub(session.checks() * CHECK_PENALTY)
+        .saturating_sub((over_par * TIME_PENALTY) as u32)
+}
+
+///
+/// Get the fewest moves needed to solve the puzzle of `session` from its start.
+///
+/// Each bridge of the solution takes one move, since cycling back places two bridges at once.
+/// Bridges given by the start position, i.e. locked without having been moved, are not counted.
+///
+pub fn minimal_moves(session: &GameSession) -> u32 {
+    let moved: BTreeSet<_> = session.history().iter().map(|m| m.bridge).collect();
+    session
+        .system()
+        .bridges
+        .iter()
+        .filter(|(key, b)| {
+            b.get_solution() != &BridgeState::Empty && (!b.is_locked() || moved.contains(key))
+        })
+        .count() as u32
+}
+
+///
+/// Grade the solved `session` by its moves compared to `minimal_moves`.
+///
+pub fn grade(session: &GameSession) -> Grade {
+    let ratio = session.moves() as f64 / minimal_moves(session).max(1) as f64;
+    GRADE_LIMITS
+        .into_iter()
+        .find(|(_, limit)| ratio <= *limit)
+        .map_or(Grade::C, |(grade, _)| grade)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        hex::{BridgeState, GameParameters, HexSystem},
+        session::{GameSession, HintLevel},
+    };
+
+    use super::{Grade, compute_score, grade, minimal_moves};
+
+    #[test]
+    fn penalties() {
+        let mut session = GameSession::new(HexSystem::generate_new(GameParameters {
+            seed: 1,
+            max_columns: 4,
+            max_rows: 5,
+            num_islands: 8,
+            ratio_long_bridge: 0.0,
+            ..Default::default()
+        }));
+        assert_eq!(compute_score(&session, 1000, 50_000.0, 60_000.0), 1000);
+        assert_eq!(compute_score(&session, 1000, 70_000.0, 60_000.0), 990);
+        session.hint();
+        session.check();
+        assert_eq!(compute_score(&session, 1000, 70_000.0, 60_000.0), 840);
+        assert_eq!(compute_score(&session, 100, 70_000.0, 60_000.0), 0);
+        // Hints giving away less cost less.
+        session.hint_at(HintLevel::Island);
+        assert_eq!(compute_score(&session, 1000, 70_000.0, 60_000.0), 790);
+        session.hint_at(HintLevel::Move);
+        assert_eq!(compute_score(&session, 1000, 70_000.0, 60_000.0), 640);
+    }
+
+    #[test]
+    fn grades() {
+        let system = HexSystem::generate_new(GameParameters {
+            seed: 1,
+            max_columns: 4,
+            max_rows: 5,
+            num_islands: 8,
+            ratio_long_bridge: 0.0,
+            ..Default::default()
+        });
+        let solution: Vec<_> = system
+            .bridges
+            .iter()
+            .filter(|(_, b)| b.get_solution() != &BridgeState::Empty)
+            .map(|(key, b)| (*key, b.get_solution().clone()))
+            .collect();
+        let play = |retry: bool| {
+            let mut session = GameSession::new(system.clone());
+            assert_eq!(minimal_moves(&session), solution.len() as u32);
+            for ((from, to), state) in &solution {
+                if retry {
+                    session.cycle_bridge(*from, *to).unwrap();
+                    session.undo();
+                }
+                // Placing double bridges by cycling back wastes no move.
+                match state {
+                    BridgeState::Full => session.cycle_bridge_back(*from, *to).unwrap(),
+                    _ => session.cycle_bridge(*from, *to).unwrap(),
+                };
+            }
+            grade(&session)
+        };
+        assert_eq!(play(false), Grade::S);
+        assert_eq!(play(true), Grade::C);
+    }
+}
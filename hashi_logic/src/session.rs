@@ -0,0 +1,797 @@
+use serde::{Deserialize, Serialize};
+
+use crate::hex::{BridgeError, BridgeState, GameParameters, HexSystem};
+
+///
+/// Change of the bridge between the islands `bridge` from state `from` to state `to`.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Move {
+    pub bridge: (usize, usize),
+    pub from: BridgeState,
+    pub to: BridgeState,
+    /// Time played in milliseconds when the move was made
+    #[serde(default)]
+    pub at_ms: f64,
+}
+
+///
+/// What the player did besides moves, e.g. to analyze the game afterwards.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum EventKind {
+    Hint,
+    Undo,
+}
+
+///
+/// Hint or undo at `at_ms` milliseconds of time played.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    pub at_ms: f64,
+    pub kind: EventKind,
+}
+
+///
+/// Differences between the bridges of the player and the solution.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SolutionDiff {
+    /// Bridges with more bridges than in the solution
+    pub wrong: Vec<(usize, usize)>,
+    /// Bridges with fewer bridges than in the solution
+    pub missing: Vec<(usize, usize)>,
+}
+
+///
+/// A bridge that differs from the solution the puzzle was generated from, or an island of it.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum Hint {
+    /// Island of a bridge that differs from the solution
+    Island(usize),
+    /// Bridge with more bridges than in the solution
+    Wrong((usize, usize)),
+    /// Bridge with fewer bridges than in the solution
+    Missing((usize, usize)),
+}
+
+///
+/// How much a hint gives away, from the least to the most.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum HintLevel {
+    /// An island where progress is possible
+    Island,
+    /// The bridge to change
+    Bridge,
+    /// The bridge to change, which the player has changed for them
+    Move,
+}
+
+impl HintLevel {
+    /// All levels from the least to the most revealing
+    pub const ALL: [HintLevel; 3] = [HintLevel::Island, HintLevel::Bridge, HintLevel::Move];
+
+    ///
+    /// Get the next level, or `None` for the last one.
+    ///
+    pub fn next(&self) -> Option<HintLevel> {
+        match self {
+            HintLevel::Island => Some(HintLevel::Bridge),
+            HintLevel::Bridge => Some(HintLevel::Move),
+            HintLevel::Move => None,
+        }
+    }
+}
+
+///
+/// A puzzle being played, including the history of moves.
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GameSession {
+    system: HexSystem,
+    history: Vec<Move>,
+    mistakes: u32,
+    /// Hints of all levels
+    #[serde(default)]
+    hints: u32,
+    #[serde(default)]
+    island_hints: u32,
+    #[serde(default)]
+    move_hints: u32,
+    #[serde(default)]
+    checks: u32,
+    /// Wrong bridges revealed by the last check
+    #[serde(default)]
+    checked_wrong: Vec<(usize, usize)>,
+    #[serde(default)]
+    undos: u32,
+    /// Time played in milliseconds, used to time the following moves and events
+    #[serde(default)]
+    clock_ms: f64,
+    #[serde(default)]
+    events: Vec<Event>,
+}
+
+impl GameSession {
+    pub fn new(system: HexSystem) -> Self {
+        GameSession {
+            system,
+            history: vec![],
+            mistakes: 0,
+            hints: 0,
+            island_hints: 0,
+            move_hints: 0,
+            checks: 0,
+            checked_wrong: vec![],
+            undos: 0,
+            clock_ms: 0.0,
+            events: vec![],
+        }
+    }
+
+    pub fn system(&self) -> &HexSystem {
+        &self.system
+    }
+
+    ///
+    /// Stop offering bridges longer than the puzzle needs, see `HexSystem::limit_bridge_length`.
+    ///
+    pub fn limit_bridge_length(&mut self) {
+        self.system.limit_bridge_length();
+    }
+
+    ///
+    /// Grow the board with new islands, see `HexSystem::extend`.
+    ///
+    pub fn extend(&mut self, params: GameParameters) {
+        self.system = self.system.extend(params);
+    }
+
+    pub fn history(&self) -> &[Move] {
+        &self.history
+    }
+
+    ///
+    /// Get the number of mistakes, i.e. wrong moves that were undone and checks that revealed wrong bridges.
+    ///
+    pub fn mistakes(&self) -> u32 {
+        self.mistakes
+    }
+
+    pub fn hints(&self) -> u32 {
+        self.hints
+    }
+
+    ///
+    /// Get the number of hints at `level`.
+    ///
+    pub fn hints_at(&self, level: HintLevel) -> u32 {
+        match level {
+            HintLevel::Island => self.island_hints,
+            // Sessions saved before the levels only have bridge hints.
+            HintLevel::Bridge => self.hints - self.island_hints - self.move_hints,
+            HintLevel::Move => self.move_hints,
+        }
+    }
+
+    pub fn undos(&self) -> u32 {
+        self.undos
+    }
+
+    ///
+    /// Get the number of moves made, including the ones that were undone.
+    ///
+    pub fn moves(&self) -> u32 {
+        self.history.len() as u32 + self.undos
+    }
+
+    pub fn checks(&self) -> u32 {
+        self.checks
+    }
+
+    ///
+    /// Set the time played so far, so that the following moves, hints and undos are stamped with it.
+    ///
+    pub fn set_clock(&mut self, elapsed_ms: f64) {
+        self.clock_ms = elapsed_ms;
+    }
+
+    ///
+    /// Get the hints and undos in the order they happened.
+    ///
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    fn record(&mut self, kind: EventKind) {
+        self.events.push(Event {
+            at_ms: self.clock_ms,
+            kind,
+        });
+    }
+
+    ///
+    /// Cycle through the states of bridge between `from` and `to` and remember the move.
+    ///
+    /// Returns if the puzzle is solved.
+    ///
+    pub fn cycle_bridge(&mut self, from: usize, to: usize) -> Result<bool, BridgeError> {
+        self.change_bridge(from, to, HexSystem::cycle_bridge)
+    }
+
+    ///
+    /// Cycle through the states of bridge between `from` and `to` in reverse order and remember the move.
+    ///
+    /// Returns if the puzzle is solved.
+    ///
+    pub fn cycle_bridge_back(&mut self, from: usize, to: usize) -> Result<bool, BridgeError> {
+        self.change_bridge(from, to, HexSystem::cycle_bridge_back)
+    }
+
+    fn change_bridge(
+        &mut self,
+        from: usize,
+        to: usize,
+        change: fn(&mut HexSystem, usize, usize) -> Result<bool, BridgeError>,
+    ) -> Result<bool, BridgeError> {
+        let previous = self
+            .system
+            .get_bridge(from, to)
+            .ok_or(BridgeError::NotFound)?
+            .get_state()
+            .clone();
+        let solved = change(&mut self.system, from, to)?;
+        // unwrap ok, since bridge was found above
+        let current = self
+            .system
+            .get_bridge(from, to)
+            .unwrap()
+            .get_state()
+            .clone();
+        self.history.push(Move {
+            bridge: (std::cmp::min(from, to), std::cmp::max(from, to)),
+            from: previous,
+            to: current,
+            at_ms: self.clock_ms,
+        });
+        Ok(solved)
+    }
+
+    ///
+    /// Get the moves so far in a text, e.g. "C4–E4: 2" for a move placing the second bridge between C4 and E4.
+    ///
+    pub fn describe_moves(&self) -> String {
+        self.history
+            .iter()
+            .map(|m| {
+                let count = match m.to {
+                    BridgeState::Empty => 0,
+                    BridgeState::Partial => 1,
+                    BridgeState::Full => 2,
+                };
+                format!(
+                    "{}–{}: {}",
+                    self.system.get_label(m.bridge.0),
+                    self.system.get_label(m.bridge.1),
+                    count
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    ///
+    /// Set the state of the bridge between `from` and `to` changed by somebody else, e.g. another player.
+    ///
+    /// Like a move, the bridge cannot be placed across another bridge. Locks only protect
+    /// against the own clicks, so they do not stop the changes of others.
+    /// The change is not part of the history, so it cannot be undone.
+    ///
+    pub(crate) fn set_bridge_state(
+        &mut self,
+        from: usize,
+        to: usize,
+        state: BridgeState,
+    ) -> Result<(), BridgeError> {
+        if state != BridgeState::Empty && !self.system.get_blocking_bridges(from, to).is_empty() {
+            return Err(BridgeError::Blocked);
+        }
+        self.system.set_bridge_state(from, to, state)
+    }
+
+    ///
+    /// Lock or unlock the bridge between `from` and `to`.
+    ///
+    /// Returns `true` if the bridge is locked now.
+    ///
+    pub fn toggle_lock(&mut self, from: usize, to: usize) -> Result<bool, BridgeError> {
+        self.system.toggle_lock(from, to)
+    }
+
+    ///
+    /// Undo the last move.
+    ///
+    /// Undoing a move that placed more bridges than in the solution counts as mistake.
+    /// Moves of locked bridges cannot be undone until the bridge is unlocked.
+    ///
+    pub fn undo(&mut self) -> Option<Move> {
+        let (from, to) = self.history.last()?.bridge;
+        if self.system.get_bridge(from, to)?.is_locked() {
+            return None;
+        }
+        let last = self.history.pop()?;
+        self.undos += 1;
+        self.record(EventKind::Undo);
+        if self.is_wrong(&last) {
+            self.mistakes += 1;
+        }
+        let (from, to) = last.bridge;
+        // Restoring a previous state cannot be blocked, since all later moves are undone already.
+        self.system
+            .set_bridge_state(from, to, last.from.clone())
+            .ok()?;
+        Some(last)
+    }
+
+    ///
+    /// Undo the last move if it placed more bridges than in the solution.
+    ///
+    /// Corrects learners right away in practice games; the undone move counts as mistake.
+    ///
+    pub fn take_back_wrong(&mut self) -> Option<Move> {
+        if self.is_wrong(self.history.last()?) {
+            self.undo()
+        } else {
+            None
+        }
+    }
+
+    ///
+    /// Did `m` place more bridges than in the solution?
+    ///
+    fn is_wrong(&self, m: &Move) -> bool {
+        let (from, to) = m.bridge;
+        self.system
+            .get_bridge(from, to)
+            .is_some_and(|b| m.to > m.from && &m.to > b.get_solution())
+    }
+
+    ///
+    /// Check the bridges placed so far against the solution.
+    ///
+    /// Returns the wrong bridges. A check revealing wrong bridges counts as mistake,
+    /// unless they are the same as revealed by the previous check.
+    ///
+    pub fn check(&mut self) -> Vec<(usize, usize)> {
+        self.checks += 1;
+        let wrong = self.system.get_wrong_bridges();
+        if !wrong.is_empty() && wrong != self.checked_wrong {
+            self.mistakes += 1;
+        }
+        self.checked_wrong = wrong.clone();
+        wrong
+    }
+
+    ///
+    /// Check if the puzzle cannot be solved anymore without removing bridges.
+    ///
+    /// Returns the index of the earliest move in the history after which the puzzle was stuck.
+    ///
+    pub fn is_stuck(&self) -> Option<usize> {
+        self.system.is_stuck(&self.history)
+    }
+
+    ///
+    /// Get the number of moves to undo until the puzzle can be solved again.
+    ///
+    pub fn moves_to_consistent(&self) -> usize {
+        let mut system = self.system.clone();
+        for (undone, m) in self.history.iter().rev().enumerate() {
+            if system.is_extendable() {
+                return undone;
+            }
+            let _ = system.set_bridge_state(m.bridge.0, m.bridge.1, m.from.clone());
+        }
+        self.history.len()
+    }
+
+    ///
+    /// Undo moves until the puzzle can be solved again.
+    ///
+    /// Returns the moves undone, the last one first.
+    ///
+    pub fn rewind_to_consistent(&mut self) -> Vec<Move> {
+        self.rewind_to(self.history.len() - self.moves_to_consistent())
+    }
+
+    ///
+    /// Undo moves until only the first `count` moves of the history are left.
+    ///
+    /// Returns the moves undone, the last one first.
+    ///
+    pub fn rewind_to(&mut self, count: usize) -> Vec<Move> {
+        (count..self.history.len())
+            .map_while(|_| self.undo())
+            .collect()
+    }
+
+    ///
+    /// Get the board as it was after the first `count` moves of the history, leaving the game as it is.
+    ///
+    pub fn system_at(&self, count: usize) -> HexSystem {
+        let mut system = self.system.clone();
+        for m in self.history.iter().skip(count).rev() {
+            let _ = system.set_bridge_state(m.bridge.0, m.bridge.1, m.from.clone());
+        }
+        system
+    }
+
+    ///
+    /// Get a hint, preferring wrong bridges over missing ones.
+    ///
+    /// Of the missing bridges, the one at the island with the fewest neighbors is shown,
+    /// since there are the fewest options to reason about.
+    ///
+    /// Returns `None` if all bridges match the solution.
+    ///
+    pub fn hint(&mut self) -> Option<Hint> {
+        self.hint_at(HintLevel::Bridge)
+    }
+
+    ///
+    /// Get a hint like `hint` that gives away as much as `level`.
+    ///
+    /// At the island level, the island of the bridge with fewer neighbors is shown.
+    /// At the move level, the caller makes the move, so that it is handled like any other move.
+    ///
+    pub fn hint_at(&mut self, level: HintLevel) -> Option<Hint> {
+        let system = &self.system;
+        let hint = if let Some(wrong) = system.get_wrong_bridges().first() {
+            Hint::Wrong(*wrong)
+        } else {
+            Hint::Missing(
+                system
+                    .get_missing_bridges()
+                    .into_iter()
+                    .min_by_key(|(from, to)| system.degree(*from).min(system.degree(*to)))?,
+            )
+        };
+        let hint = match (level, hint) {
+            (HintLevel::Island, Hint::Wrong((from, to)) | Hint::Missing((from, to))) => {
+                Hint::Island(std::cmp::min_by_key(from, to, |i| system.degree(*i)))
+            }
+            (_, hint) => hint,
+        };
+        self.hints += 1;
+        match level {
+            HintLevel::Island => self.island_hints += 1,
+            HintLevel::Bridge => {}
+            HintLevel::Move => self.move_hints += 1,
+        }
+        self.record(EventKind::Hint);
+        Some(hint)
+    }
+
+    ///
+    /// Give up and apply the solution the puzzle was generated from.
+    ///
+    /// Returns the differences of the bridges of the player to the solution.
+    ///
+    pub fn reveal(&mut self) -> SolutionDiff {
+        let diff = SolutionDiff {
+            wrong: self.system.get_wrong_bridges(),
+            missing: self.system.get_missing_bridges(),
+        };
+        self.system.apply_solution();
+        diff
+    }
+}
+
+///
+/// Verify that `moves` solve the puzzle `system` when played from its start.
+///
+/// Used to check replays submitted to a leaderboard, so all moves must be valid.
+///
+pub fn verify_replay(system: &HexSystem, moves: &[Move]) -> bool {
+    let mut session = GameSession::new(system.clone());
+    session.system.apply_start();
+    for m in moves {
+        let (from, to) = m.bridge;
+        let valid = session
+            .system
+            .get_bridge(from, to)
+            .is_some_and(|b| b.get_state() == &m.from);
+        if !valid || session.cycle_bridge(from, to).is_err() {
+            return false;
+        }
+        if session.history.last().map(|last| &last.to) != Some(&m.to) {
+            return false;
+        }
+    }
+    session.system.is_solved()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hex::{BridgeState, GameParameters, HexSystem, Island};
+
+    use super::{Event, EventKind, GameSession, Hint, HintLevel, Move, verify_replay};
+
+    fn session() -> GameSession {
+        GameSession::new(HexSystem::generate_new(GameParameters {
+            seed: 1,
+            max_columns: 4,
+            max_rows: 5,
+            num_islands: 8,
+            ratio_long_bridge: 0.0,
+            ..Default::default()
+        }))
+    }
+
+    ///
+    /// Get a bridge that is empty in the solution.
+    ///
+    fn empty_bridge(session: &GameSession) -> (usize, usize) {
+        *session
+            .system()
+            .bridges
+            .iter()
+            .find(|(_, b)| b.get_solution() == &BridgeState::Empty)
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn undo() {
+        let mut session = session();
+        let (from, to) = *session.system().bridges.keys().next().unwrap();
+        assert!(session.cycle_bridge(from, to).is_ok());
+        assert!(session.cycle_bridge(from, to).is_ok());
+        assert_eq!(session.history().len(), 2);
+        assert_eq!(
+            session.system().get_bridge(from, to).unwrap().get_state(),
+            &BridgeState::Full
+        );
+        let last = session.undo().unwrap();
+        assert_eq!(last.from, BridgeState::Partial);
+        assert_eq!(
+            session.system().get_bridge(from, to).unwrap().get_state(),
+            &BridgeState::Partial
+        );
+        session.undo();
+        assert!(session.undo().is_none());
+        assert!(session.history().is_empty());
+        assert_eq!(session.undos(), 2);
+        assert_eq!(session.moves(), 2);
+
+        // Locked bridges keep their state.
+        session.cycle_bridge(from, to).unwrap();
+        assert_eq!(session.toggle_lock(from, to), Ok(true));
+        assert!(session.undo().is_none());
+        assert_eq!(session.history().len(), 1);
+        assert_eq!(session.undos(), 2);
+        assert_eq!(session.toggle_lock(from, to), Ok(false));
+        assert!(session.undo().is_some());
+    }
+
+    #[test]
+    fn timestamps() {
+        let mut session = session();
+        let (from, to) = *session.system().bridges.keys().next().unwrap();
+        session.set_clock(1_500.0);
+        session.cycle_bridge(from, to).unwrap();
+        session.set_clock(4_000.0);
+        session.undo();
+        session.hint();
+        assert_eq!(session.undo(), None);
+        assert_eq!(
+            session.events(),
+            [
+                Event {
+                    at_ms: 4_000.0,
+                    kind: EventKind::Undo
+                },
+                Event {
+                    at_ms: 4_000.0,
+                    kind: EventKind::Hint
+                }
+            ]
+        );
+        session.cycle_bridge(from, to).unwrap();
+        assert_eq!(session.history()[0].at_ms, 4_000.0);
+    }
+
+    #[test]
+    fn describe_moves() {
+        let mut session = session();
+        let (from, to) = *session.system().bridges.keys().next().unwrap();
+        session.cycle_bridge(to, from).unwrap();
+        session.cycle_bridge(from, to).unwrap();
+        let bridge = format!(
+            "{}–{}",
+            session.system().get_label(from),
+            session.system().get_label(to)
+        );
+        assert_eq!(
+            session.describe_moves(),
+            format!("{bridge}: 1\n{bridge}: 2")
+        );
+    }
+
+    #[test]
+    fn hint() {
+        let mut session = session();
+        let (from, to) = empty_bridge(&session);
+        session.cycle_bridge(from, to).unwrap();
+        assert_eq!(session.hint(), Some(Hint::Wrong((from, to))));
+        session.undo();
+        let Some(Hint::Missing((from, to))) = session.hint() else {
+            panic!("Expected missing bridge");
+        };
+        let system = session.system();
+        let fewest = system
+            .get_missing_bridges()
+            .iter()
+            .map(|(f, t)| system.degree(*f).min(system.degree(*t)))
+            .min();
+        assert_eq!(Some(system.degree(from).min(system.degree(to))), fewest);
+        assert!(
+            session.system().get_bridge(from, to).unwrap().get_state()
+                < session
+                    .system()
+                    .get_bridge(from, to)
+                    .unwrap()
+                    .get_solution()
+        );
+        assert_eq!(session.hints(), 2);
+        session.reveal();
+        assert_eq!(session.hint(), None);
+        assert_eq!(session.hints(), 2);
+    }
+
+    #[test]
+    fn hint_levels() {
+        let mut session = session();
+        let Some(Hint::Island(island)) = session.hint_at(HintLevel::Island) else {
+            panic!("Expected island");
+        };
+        let Some(Hint::Missing((from, to))) = session.hint_at(HintLevel::Bridge) else {
+            panic!("Expected missing bridge");
+        };
+        assert!(island == from || island == to);
+        assert_eq!(
+            session.hint_at(HintLevel::Move),
+            Some(Hint::Missing((from, to)))
+        );
+        assert_eq!(session.hints(), 3);
+        for level in HintLevel::ALL {
+            assert_eq!(session.hints_at(level), 1);
+        }
+        assert_eq!(HintLevel::Island.next(), Some(HintLevel::Bridge));
+        assert_eq!(HintLevel::Move.next(), None);
+    }
+
+    #[test]
+    fn reveal() {
+        let mut session = session();
+        let (from, to) = empty_bridge(&session);
+        session.cycle_bridge(from, to).unwrap();
+        let diff = session.reveal();
+        assert_eq!(diff.wrong, vec![(from, to)]);
+        assert!(!diff.missing.is_empty());
+        assert!(!diff.missing.contains(&(from, to)));
+        assert!(session.system().is_solved());
+        assert!(session.system().get_wrong_bridges().is_empty());
+        assert!(session.system().get_missing_bridges().is_empty());
+    }
+
+    #[test]
+    fn mistakes() {
+        let mut session = session();
+        let (from, to) = empty_bridge(&session);
+        assert!(session.check().is_empty());
+        assert_eq!(session.mistakes(), 0);
+        session.cycle_bridge(from, to).unwrap();
+        assert_eq!(session.check(), vec![(from, to)]);
+        assert_eq!(session.mistakes(), 1);
+        // Checking again without changes reveals nothing new.
+        assert_eq!(session.check(), vec![(from, to)]);
+        assert_eq!(session.mistakes(), 1);
+        session.undo();
+        assert_eq!(session.mistakes(), 2);
+    }
+
+    #[test]
+    fn take_back_wrong() {
+        let mut session = session();
+        let (from, to) = empty_bridge(&session);
+        session.cycle_bridge(from, to).unwrap();
+        assert_eq!(session.take_back_wrong().unwrap().bridge, (from, to));
+        assert_eq!(
+            session.system().get_bridge(from, to).unwrap().get_state(),
+            &BridgeState::Empty
+        );
+        assert_eq!(session.mistakes(), 1);
+        assert!(session.take_back_wrong().is_none());
+
+        let (from, to) = *session
+            .system()
+            .bridges
+            .iter()
+            .find(|(_, b)| b.get_solution() != &BridgeState::Empty)
+            .unwrap()
+            .0;
+        session.cycle_bridge(from, to).unwrap();
+        assert!(session.take_back_wrong().is_none());
+        assert_eq!(session.history().len(), 1);
+    }
+
+    #[test]
+    fn replay() {
+        let mut session = session();
+        let start = session.system().clone();
+        let solution: Vec<_> = start
+            .bridges
+            .iter()
+            .map(|(k, b)| (*k, b.get_solution().clone()))
+            .collect();
+        for (bridge, state) in solution {
+            while session
+                .system()
+                .get_bridge(bridge.0, bridge.1)
+                .unwrap()
+                .get_state()
+                != &state
+            {
+                session.cycle_bridge(bridge.0, bridge.1).unwrap();
+            }
+        }
+        assert!(session.system().is_solved());
+        assert!(verify_replay(&start, session.history()));
+        assert!(verify_replay(session.system(), session.history()));
+        let incomplete = &session.history()[1..];
+        assert!(!verify_replay(&start, incomplete));
+        let mut forged = session.history().to_vec();
+        forged.push(Move {
+            bridge: (0, 0),
+            from: BridgeState::Empty,
+            to: BridgeState::Full,
+            at_ms: 0.0,
+        });
+        assert!(!verify_replay(&start, &forged));
+    }
+
+    #[test]
+    fn rewind() {
+        let mut islands = vec![Island::Empty; 22];
+        islands[0] = Island::Bridged(1);
+        islands[4] = Island::Bridged(2);
+        islands[6] = Island::Bridged(1);
+        let mut session = GameSession::new(HexSystem::from_islands(4, 5, islands));
+        assert_eq!(session.moves_to_consistent(), 0);
+        assert!(session.cycle_bridge(4, 6).is_ok());
+        assert!(session.cycle_bridge(0, 4).is_ok());
+        assert!(session.cycle_bridge(0, 4).is_ok());
+        assert_eq!(session.moves_to_consistent(), 1);
+        let undone = session.rewind_to_consistent();
+        assert_eq!(undone.len(), 1);
+        assert_eq!(undone[0].to, BridgeState::Full);
+        assert_eq!(session.history().len(), 2);
+        assert!(session.system().is_extendable());
+        assert!(session.rewind_to_consistent().is_empty());
+
+        // Looking back leaves the game alone, rewinding undoes the later moves.
+        let first = session.system_at(1);
+        assert_eq!(first.get_actual_bridges(0), 0);
+        assert_eq!(first.get_actual_bridges(6), 1);
+        assert_eq!(session.system_at(2).get_actual_bridges(0), 1);
+        assert_eq!(session.history().len(), 2);
+        assert_eq!(session.rewind_to(1).len(), 1);
+        assert_eq!(session.system().get_actual_bridges(0), 0);
+        assert_eq!(session.system().get_actual_bridges(6), 1);
+        assert!(session.rewind_to(5).is_empty());
+    }
+}
@@ -0,0 +1,155 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::hex::{GameParameters, HexSystem};
+
+///
+/// Outline of the cells of a board the islands are placed in.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum BoardShape {
+    /// All cells of the board, whose rows are shifted against each other
+    #[default]
+    Parallelogram,
+    /// The cells within a hexagon with horizontal top and bottom edges
+    Hexagon,
+}
+
+impl BoardShape {
+    pub const ALL: [BoardShape; 2] = [BoardShape::Parallelogram, BoardShape::Hexagon];
+
+    ///
+    /// Get the identifier of the shape, e.g. "hexagon".
+    ///
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BoardShape::Parallelogram => "parallelogram",
+            BoardShape::Hexagon => "hexagon",
+        }
+    }
+
+    ///
+    /// Check if the cell at `index` of a board with `columns` and `rows` lies within the shape.
+    ///
+    /// The hexagon is the largest one fitting into the board.
+    ///
+    pub fn contains(&self, columns: usize, rows: usize, index: usize) -> bool {
+        match self {
+            BoardShape::Parallelogram => true,
+            BoardShape::Hexagon => {
+                let radius = rows.min(columns).saturating_sub(1) / 2;
+                let (row, column) = HexSystem::row_column(columns, index);
+                // Doubled coordinates: the cells of odd rows start half a cell further left.
+                let x = 2 * column + if row.is_multiple_of(2) { 1 } else { 0 };
+                let center_x = 2 * radius + if radius.is_multiple_of(2) { 1 } else { 2 };
+                let (dx, dy) = (x.abs_diff(center_x), row.abs_diff(radius));
+                dy <= radius && dy + dx.saturating_sub(dy) / 2 <= radius
+            }
+        }
+    }
+}
+
+impl Display for BoardShape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BoardShape::Parallelogram => f.write_str("Parallelogram"),
+            BoardShape::Hexagon => f.write_str("Hexagon"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct BoardShapeConversionError;
+
+impl Display for BoardShapeConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Cannot convert to board shape")
+    }
+}
+
+impl std::error::Error for BoardShapeConversionError {}
+
+impl FromStr for BoardShape {
+    type Err = BoardShapeConversionError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        BoardShape::ALL
+            .into_iter()
+            .find(|shape| shape.as_str() == s.to_lowercase())
+            .ok_or(BoardShapeConversionError)
+    }
+}
+
+impl HexSystem {
+    ///
+    /// Generate a new puzzle on a board of `shape`.
+    ///
+    /// A hexagon gets a board of its own with about as many cells as the board of `params`,
+    /// so that the puzzle has the same density of islands.
+    ///
+    pub fn generate_shaped(params: GameParameters, shape: BoardShape) -> Self {
+        match shape {
+            BoardShape::Parallelogram => HexSystem::generate_new(params),
+            BoardShape::Hexagon => {
+                let cells = HexSystem::get_size(params.max_columns, params.max_rows);
+                let mut radius = 1;
+                while 3 * radius * (radius + 1) + 1 < cells {
+                    radius += 1;
+                }
+                let side = 2 * radius + 1;
+                let params = GameParameters {
+                    max_columns: side,
+                    max_rows: side,
+                    ..params
+                };
+                HexSystem::generate_in(params, shape)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        difficulty::Difficulty,
+        hex::{GameParameters, HexSystem, Island},
+    };
+
+    use super::BoardShape;
+
+    #[test]
+    fn hexagon() {
+        for radius in 1..5 {
+            let side = 2 * radius + 1;
+            let cells = (0..HexSystem::get_size(side, side))
+                .filter(|index| BoardShape::Hexagon.contains(side, side, *index))
+                .count();
+            assert_eq!(cells, 3 * radius * (radius + 1) + 1);
+        }
+        // The middle row is complete, the first one starts and ends half the radius further in.
+        assert!(BoardShape::Hexagon.contains(5, 5, 11));
+        assert!(BoardShape::Hexagon.contains(5, 5, 15));
+        assert!(!BoardShape::Hexagon.contains(5, 5, 0));
+        assert!(BoardShape::Hexagon.contains(5, 5, 1));
+        assert!(BoardShape::Hexagon.contains(5, 5, 3));
+        assert!(!BoardShape::Hexagon.contains(5, 5, 4));
+
+        for difficulty in Difficulty::ALL {
+            let system = HexSystem::generate_shaped(
+                GameParameters::preset(&difficulty, 5),
+                BoardShape::Hexagon,
+            );
+            assert_eq!(system.shape, BoardShape::Hexagon);
+            assert!(system.islands.iter().enumerate().all(|(index, island)| {
+                !matches!(island, Island::Bridged(_))
+                    || BoardShape::Hexagon.contains(system.columns, system.rows, index)
+            }));
+            assert!(system.solve().is_some());
+        }
+        assert_eq!(
+            "Hexagon".parse::<BoardShape>().unwrap(),
+            BoardShape::Hexagon
+        );
+    }
+}
@@ -0,0 +1,1024 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    str::FromStr,
+};
+
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    difficulty::Difficulty,
+    hex::{BridgeState, GameParameters, HexSystem, Island},
+    instrument::span,
+    session::Move,
+};
+
+/// Maximum number of guesses before giving up, so that checks during a game stay fast
+pub(crate) const NODE_BUDGET: usize = 100_000;
+/// Number of seeds tried to generate a puzzle the solver can solve
+const VERIFY_ATTEMPTS: u64 = 10;
+/// Number of seeds tried to generate a position that needs a technique
+const TRAINER_ATTEMPTS: u64 = 50;
+/// Number of puzzles generated for a round of a tournament to find one rated as its difficulty
+const TOURNAMENT_ATTEMPTS: u64 = 5;
+
+///
+/// Result of a search for a solution.
+///
+#[derive(Clone, Debug, PartialEq)]
+enum Outcome {
+    /// Number of bridges for each bridge in key order
+    Solved(Vec<u8>),
+    Unsolvable,
+    /// The node budget was exceeded
+    Aborted,
+}
+
+///
+/// Whether a puzzle has a single solution, as players expect.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Uniqueness {
+    Unsolvable,
+    Unique,
+    /// More than one solution, so that some bridges can only be guessed
+    Ambiguous,
+    /// The search exceeded the node budget
+    Unknown,
+}
+
+impl Display for Uniqueness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Uniqueness::Unsolvable => f.write_str("No solution"),
+            Uniqueness::Unique => f.write_str("Unique solution"),
+            Uniqueness::Ambiguous => f.write_str("Several solutions"),
+            Uniqueness::Unknown => f.write_str("Too complex to check"),
+        }
+    }
+}
+
+///
+/// Reason why a generated puzzle was rejected.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Rejection {
+    /// The puzzle falls apart into parts that can be solved on their own.
+    Decomposable,
+    /// The solver did not solve the puzzle within its node budget.
+    Unsolved,
+}
+
+///
+/// What happened while generating a verified puzzle, e.g. to tune the parameters.
+///
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GenerationReport {
+    /// Number of puzzles generated, including the returned one
+    pub attempts: u64,
+    /// Seed of each rejected puzzle and why it was rejected
+    pub rejected: Vec<(u64, Rejection)>,
+    /// Search nodes needed to solve the returned puzzle, if it was verified
+    pub nodes: Option<usize>,
+    /// Rating of the returned puzzle, if it was verified
+    pub rating: Option<Difficulty>,
+    /// Time spent in milliseconds, if a clock is available
+    pub elapsed_ms: Option<f64>,
+}
+
+///
+/// Minimum and maximum number of bridges of each bridge in key order.
+///
+type Bounds = Vec<(u8, u8)>;
+
+///
+/// Techniques to narrow down the bounds of the bridges from the simplest to the most advanced.
+///
+/// Each technique includes the simpler ones.
+///
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum Technique {
+    /// The bridges of an island add up to its target.
+    Sums,
+    /// Bridges cannot cross each other.
+    Crossings,
+    /// All islands must be connected.
+    Connectivity,
+}
+
+impl Technique {
+    /// All techniques from the simplest to the most advanced
+    pub const ALL: [Technique; 3] = [
+        Technique::Sums,
+        Technique::Crossings,
+        Technique::Connectivity,
+    ];
+
+    ///
+    /// Get the identifier of the technique, e.g. "crossings".
+    ///
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Technique::Sums => "sums",
+            Technique::Crossings => "crossings",
+            Technique::Connectivity => "connectivity",
+        }
+    }
+
+    ///
+    /// Get the next simpler technique, if there is any.
+    ///
+    pub fn simpler(&self) -> Option<Technique> {
+        match self {
+            Technique::Sums => None,
+            Technique::Crossings => Some(Technique::Sums),
+            Technique::Connectivity => Some(Technique::Crossings),
+        }
+    }
+
+    ///
+    /// Get the difficulty of the puzzles that need the technique, see `HexSystem::rate`.
+    ///
+    pub fn difficulty(&self) -> Difficulty {
+        match self {
+            Technique::Sums => Difficulty::Easy,
+            Technique::Crossings => Difficulty::Medium,
+            Technique::Connectivity => Difficulty::Hard,
+        }
+    }
+}
+
+impl Display for Technique {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Technique::Sums => f.write_str("Counting"),
+            Technique::Crossings => f.write_str("Crossings"),
+            Technique::Connectivity => f.write_str("Isolation"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct TechniqueConversionError;
+
+impl Display for TechniqueConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Cannot convert to technique")
+    }
+}
+
+impl std::error::Error for TechniqueConversionError {}
+
+impl FromStr for Technique {
+    type Err = TechniqueConversionError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Technique::ALL
+            .into_iter()
+            .find(|t| t.as_str() == s.to_lowercase())
+            .ok_or(TechniqueConversionError)
+    }
+}
+
+///
+/// Backtracking search for solutions extending the bridges placed so far.
+///
+/// Bridges can only be added, never removed, and locked bridges are kept as they are.
+/// The bounds of the bridges are narrowed down by the targets of the islands and crossing bridges
+/// before guessing the number of bridges of the least constrained bridge.
+///
+struct Solver<'a> {
+    system: &'a HexSystem,
+    keys: Vec<(usize, usize)>,
+    /// Indices of the bridges crossing each bridge
+    crossings: Vec<Vec<usize>>,
+    /// Bridges of each island in key order
+    island_bridges: Vec<Vec<usize>>,
+    technique: Technique,
+    nodes: usize,
+    budget: Option<usize>,
+}
+
+fn count(state: &BridgeState) -> u8 {
+    match state {
+        BridgeState::Empty => 0,
+        BridgeState::Partial => 1,
+        BridgeState::Full => 2,
+    }
+}
+
+impl<'a> Solver<'a> {
+    fn new(system: &'a HexSystem, budget: Option<usize>) -> Self {
+        let keys: Vec<_> = system.bridges.keys().copied().collect();
+        let gaps: Vec<BTreeSet<usize>> = system
+            .bridges
+            .values()
+            .map(|b| b.get_gap_indices().iter().copied().collect())
+            .collect();
+        let crossings = gaps
+            .iter()
+            .enumerate()
+            .map(|(i, g)| {
+                gaps.iter()
+                    .enumerate()
+                    .filter(|(j, other)| *j != i && !g.is_disjoint(other))
+                    .map(|(j, _)| j)
+                    .collect()
+            })
+            .collect();
+        let mut island_bridges = vec![vec![]; system.islands.len()];
+        for (i, (from, to)) in keys.iter().enumerate() {
+            island_bridges[*from].push(i);
+            island_bridges[*to].push(i);
+        }
+        Solver {
+            system,
+            keys,
+            crossings,
+            island_bridges,
+            technique: Technique::Connectivity,
+            nodes: 0,
+            budget,
+        }
+    }
+
+    fn solve(&mut self) -> Outcome {
+        // No need to search if an island needs more bridges than it can take.
+        if !self.system.over_capacity().is_empty() {
+            return Outcome::Unsolvable;
+        }
+        let bounds = self.initial_bounds();
+        self.search(bounds)
+    }
+
+    ///
+    /// Get the bounds of the bridges placed so far.
+    ///
+    fn initial_bounds(&self) -> Bounds {
+        // Islands of each color, all of them in the classic game
+        let mut islands = BTreeMap::new();
+        for (index, island) in self.system.islands.iter().enumerate() {
+            if let Island::Bridged(_) = island {
+                *islands.entry(self.system.color(index)).or_insert(0) += 1;
+            }
+        }
+        self.system
+            .bridges
+            .iter()
+            .map(|((from, to), b)| {
+                let c = count(b.get_state());
+                let mut max = if b.is_locked() {
+                    c
+                } else {
+                    self.system.max_width() as u8
+                };
+                // Two islands completing each other would be cut off from all other islands.
+                if let (Island::Bridged(t1), Island::Bridged(t2)) =
+                    (&self.system.islands[*from], &self.system.islands[*to])
+                    && self.technique >= Technique::Connectivity
+                    && t1 == t2
+                    && (1..=2).contains(t1)
+                    && islands[&self.system.color(*from)] > 2
+                {
+                    max = max.min(*t1 as u8 - 1);
+                }
+                (c, max)
+            })
+            .collect()
+    }
+
+    ///
+    /// Are the `bounds` narrowed down to a solution?
+    ///
+    fn is_solution(&self, bounds: &Bounds) -> bool {
+        bounds.iter().all(|(lo, hi)| lo == hi)
+            && (0..bounds.len())
+                .all(|i| bounds[i].0 == 0 || self.crossings[i].iter().all(|j| bounds[*j].0 == 0))
+            && self.is_connectable(bounds)
+    }
+
+    ///
+    /// Narrow down the `bounds` until nothing changes anymore.
+    ///
+    /// Returns `false` if the bounds contradict each other.
+    ///
+    fn propagate(&self, bounds: &mut Bounds) -> bool {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            // Bridges crossing a placed bridge are impossible.
+            for i in 0..bounds.len() {
+                if bounds[i].0 == 0 || self.technique < Technique::Crossings {
+                    continue;
+                }
+                for j in &self.crossings[i] {
+                    if bounds[*j].0 > 0 {
+                        return false;
+                    }
+                    if bounds[*j].1 > 0 {
+                        bounds[*j].1 = 0;
+                        changed = true;
+                    }
+                }
+            }
+            // The bridges of an island must add up to its target.
+            for (island, bridges) in self.island_bridges.iter().enumerate() {
+                let Island::Bridged(target) = self.system.islands[island] else {
+                    continue;
+                };
+                let target = target as i64;
+                let min: i64 = bridges.iter().map(|i| bounds[*i].0 as i64).sum();
+                let max: i64 = bridges.iter().map(|i| bounds[*i].1 as i64).sum();
+                if min > target || max < target {
+                    return false;
+                }
+                for i in bridges {
+                    let (lo, hi) = (bounds[*i].0 as i64, bounds[*i].1 as i64);
+                    let new_lo = lo.max(target - (max - hi));
+                    let new_hi = hi.min(target - (min - lo));
+                    if new_lo > new_hi {
+                        return false;
+                    }
+                    if (new_lo, new_hi) != (lo, hi) {
+                        bounds[*i] = (new_lo as u8, new_hi as u8);
+                        changed = true;
+                    }
+                }
+            }
+            // Bridges the only connection between two groups of islands is made of are needed.
+            if !changed && self.technique >= Technique::Connectivity {
+                for i in self.cut_bridges(bounds) {
+                    if bounds[i].0 == 0 {
+                        bounds[i].0 = 1;
+                        changed = true;
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    ///
+    /// Get the possible bridges whose removal would split the islands into two groups.
+    ///
+    fn cut_bridges(&self, bounds: &Bounds) -> Vec<usize> {
+        let n = self.system.islands.len();
+        let mut order = vec![usize::MAX; n];
+        let mut low = vec![0; n];
+        let mut cuts = vec![];
+        let mut counter = 0;
+        for start in 0..n {
+            if order[start] != usize::MAX || self.island_bridges[start].is_empty() {
+                continue;
+            }
+            // Depth-first search keeping the island, the bridge it was reached by and the next bridge to follow.
+            let mut stack = vec![(start, usize::MAX, 0)];
+            order[start] = counter;
+            low[start] = counter;
+            counter += 1;
+            while let Some((island, parent, next)) = stack.pop() {
+                if let Some(i) = self.island_bridges[island].get(next) {
+                    stack.push((island, parent, next + 1));
+                    if *i == parent || bounds[*i].1 == 0 {
+                        continue;
+                    }
+                    let (from, to) = self.keys[*i];
+                    let other = if from == island { to } else { from };
+                    if order[other] == usize::MAX {
+                        order[other] = counter;
+                        low[other] = counter;
+                        counter += 1;
+                        stack.push((other, *i, 0));
+                    } else {
+                        low[island] = low[island].min(order[other]);
+                    }
+                } else if parent != usize::MAX {
+                    let (from, to) = self.keys[parent];
+                    let above = if from == island { to } else { from };
+                    low[above] = low[above].min(low[island]);
+                    if low[island] > order[above] {
+                        cuts.push(parent);
+                    }
+                }
+            }
+        }
+        cuts
+    }
+
+    ///
+    /// Could all islands still be connected by the bridges that are possible within `bounds`?
+    ///
+    /// In the colored variant, the islands of each color must be connected.
+    ///
+    fn is_connectable(&self, bounds: &Bounds) -> bool {
+        let mut visited = BTreeSet::new();
+        let mut components = 0;
+        for start in 0..self.system.islands.len() {
+            if !matches!(self.system.islands[start], Island::Bridged(_)) || !visited.insert(start) {
+                continue;
+            }
+            components += 1;
+            let mut next = vec![start];
+            while let Some(island) = next.pop() {
+                for i in &self.island_bridges[island] {
+                    let (from, to) = self.keys[*i];
+                    let other = if from == island { to } else { from };
+                    if bounds[*i].1 > 0 && visited.insert(other) {
+                        next.push(other);
+                    }
+                }
+            }
+        }
+        components <= self.system.networks()
+    }
+
+    fn search(&mut self, mut bounds: Bounds) -> Outcome {
+        self.nodes += 1;
+        if self.budget.is_some_and(|budget| self.nodes > budget) {
+            return Outcome::Aborted;
+        }
+        if !self.propagate(&mut bounds) || !self.is_connectable(&bounds) {
+            return Outcome::Unsolvable;
+        }
+        let Some(guess) = self.guess(&bounds) else {
+            return Outcome::Solved(bounds.iter().map(|(lo, _)| *lo).collect());
+        };
+        let (lo, hi) = bounds[guess];
+        for c in (lo..=hi).rev() {
+            let mut next = bounds.clone();
+            next[guess] = (c, c);
+            let outcome = self.search(next);
+            if outcome != Outcome::Unsolvable {
+                return outcome;
+            }
+        }
+        Outcome::Unsolvable
+    }
+
+    ///
+    /// Count the solutions within `bounds`, but no more than `limit`.
+    ///
+    /// Returns `None` if the node budget was exceeded.
+    ///
+    fn count_solutions(&mut self, mut bounds: Bounds, limit: usize) -> Option<usize> {
+        self.nodes += 1;
+        if self.budget.is_some_and(|budget| self.nodes > budget) {
+            return None;
+        }
+        if !self.propagate(&mut bounds) || !self.is_connectable(&bounds) {
+            return Some(0);
+        }
+        let Some(guess) = self.guess(&bounds) else {
+            return Some(1);
+        };
+        let (lo, hi) = bounds[guess];
+        let mut found = 0;
+        for c in (lo..=hi).rev() {
+            let mut next = bounds.clone();
+            next[guess] = (c, c);
+            found += self.count_solutions(next, limit - found)?;
+            if found >= limit {
+                break;
+            }
+        }
+        Some(found)
+    }
+
+    ///
+    /// Get the bridge to guess, one of the island with the fewest undecided bridges.
+    ///
+    /// Returns `None` if all bridges are decided.
+    ///
+    fn guess(&self, bounds: &Bounds) -> Option<usize> {
+        let undecided = |island: usize| {
+            let bridges = &self.island_bridges[island];
+            let max: usize = bridges.iter().map(|i| bounds[*i].1 as usize).sum();
+            let target = match self.system.islands[island] {
+                Island::Bridged(target) => target,
+                _ => 0,
+            };
+            let count = bridges
+                .iter()
+                .filter(|i| bounds[**i].0 < bounds[**i].1)
+                .count();
+            (max - target, count)
+        };
+        (0..bounds.len())
+            .filter(|i| bounds[*i].0 < bounds[*i].1)
+            .min_by_key(|i| {
+                let (from, to) = self.keys[*i];
+                undecided(from).min(undecided(to))
+            })
+    }
+}
+
+impl HexSystem {
+    ///
+    /// Find a solution that keeps all bridges placed so far.
+    ///
+    /// Returns `None` if there is no such solution or none was found within the node budget.
+    ///
+    pub fn solve(&self) -> Option<HexSystem> {
+        let _span = span("solve");
+        match Solver::new(self, Some(NODE_BUDGET)).solve() {
+            Outcome::Solved(counts) => {
+                let mut solution = self.clone();
+                for (bridge, c) in solution.bridges.values_mut().zip(counts) {
+                    bridge.set_state(match c {
+                        0 => BridgeState::Empty,
+                        1 => BridgeState::Partial,
+                        _ => BridgeState::Full,
+                    });
+                }
+                Some(solution)
+            }
+            _ => None,
+        }
+    }
+
+    ///
+    /// Check if the bridges placed so far can still be extended to a solution.
+    ///
+    /// The state is assumed to be extendable, if the search exceeds the node budget.
+    ///
+    pub fn is_extendable(&self) -> bool {
+        let _span = span("extendable");
+        // The local check of each island rules out most contradictions without searching.
+        (0..self.islands.len()).all(|index| self.island_satisfiable(index))
+            && Solver::new(self, Some(NODE_BUDGET)).solve() != Outcome::Unsolvable
+    }
+
+    ///
+    /// Rate the difficulty of the puzzle from its start by the techniques needed to solve it.
+    ///
+    /// Puzzles that cannot be solved without guessing are extreme.
+    /// Returns `None` if the puzzle cannot be solved within the node budget.
+    ///
+    pub fn rate(&self) -> Option<Difficulty> {
+        let _span = span("rate");
+        let mut start = self.clone();
+        start.apply_start();
+        if let Some(technique) = Technique::ALL
+            .into_iter()
+            .find(|t| start.is_solvable_with(*t))
+        {
+            return Some(technique.difficulty());
+        }
+        matches!(
+            Solver::new(&start, Some(NODE_BUDGET)).solve(),
+            Outcome::Solved(_)
+        )
+        .then_some(Difficulty::Extreme)
+    }
+
+    ///
+    /// Check if the puzzle has exactly one solution from its start.
+    ///
+    pub fn uniqueness(&self) -> Uniqueness {
+        let _span = span("uniqueness");
+        let mut start = self.clone();
+        start.apply_start();
+        let mut solver = Solver::new(&start, Some(NODE_BUDGET));
+        let bounds = solver.initial_bounds();
+        match solver.count_solutions(bounds, 2) {
+            Some(0) => Uniqueness::Unsolvable,
+            Some(1) => Uniqueness::Unique,
+            Some(_) => Uniqueness::Ambiguous,
+            None => Uniqueness::Unknown,
+        }
+    }
+
+    ///
+    /// Check if `technique` alone solves the puzzle from the bridges placed so far without guessing.
+    ///
+    pub fn is_solvable_with(&self, technique: Technique) -> bool {
+        let mut solver = Solver::new(self, None);
+        solver.technique = technique;
+        let mut bounds = solver.initial_bounds();
+        solver.propagate(&mut bounds) && solver.is_solution(&bounds)
+    }
+
+    ///
+    /// Get the bridges `technique` proves to need more bridges than placed so far,
+    /// but the simpler techniques do not.
+    ///
+    /// Empty if the bridges placed so far contradict each other.
+    ///
+    pub fn technique_moves(&self, technique: Technique) -> Vec<(usize, usize)> {
+        let placed = |technique: Option<Technique>| -> Option<Vec<u8>> {
+            let Some(technique) = technique else {
+                return Some(
+                    self.bridges
+                        .values()
+                        .map(|b| count(b.get_state()))
+                        .collect(),
+                );
+            };
+            let mut solver = Solver::new(self, None);
+            solver.technique = technique;
+            let mut bounds = solver.initial_bounds();
+            solver
+                .propagate(&mut bounds)
+                .then(|| bounds.iter().map(|(lo, _)| *lo).collect())
+        };
+        let (Some(simpler), Some(forced)) = (placed(technique.simpler()), placed(Some(technique)))
+        else {
+            return vec![];
+        };
+        self.bridges
+            .keys()
+            .zip(simpler.into_iter().zip(forced))
+            .filter(|(_, (simpler, forced))| forced > simpler)
+            .map(|(key, _)| *key)
+            .collect()
+    }
+
+    ///
+    /// Generate a position for practicing `technique`, i.e. a partly solved puzzle that the simpler
+    /// techniques cannot complete, but `technique` can.
+    ///
+    /// The bridges placed already are locked. Tries the following seeds like `generate_verified`
+    /// and returns the seed the position was generated from.
+    /// The position of the last attempt is returned unverified.
+    ///
+    pub fn generate_for_technique(technique: Technique, seed: u64) -> (u64, HexSystem) {
+        let mut seed = seed;
+        for _ in 1..TRAINER_ATTEMPTS {
+            let (position, needed) = HexSystem::position_for_technique(technique, seed);
+            if needed {
+                return (seed, position);
+            }
+            seed = seed.wrapping_add(1);
+        }
+        (seed, HexSystem::position_for_technique(technique, seed).0)
+    }
+
+    ///
+    /// Remove bridges from the solution of the puzzle generated from `seed`, first as long as the
+    /// simpler technique can complete the position, then as long as `technique` can.
+    ///
+    /// Since removing any further bridge leaves the simpler technique stuck after the first step,
+    /// returns whether the second step removed a bridge, i.e. the position needs `technique`.
+    ///
+    fn position_for_technique(technique: Technique, seed: u64) -> (HexSystem, bool) {
+        let mut system =
+            HexSystem::generate_new(GameParameters::preset(&technique.difficulty(), seed));
+        system.apply_solution();
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let mut needed = false;
+        for step in [technique.simpler(), Some(technique)].into_iter().flatten() {
+            let mut placed = system.placed_bridges();
+            placed.shuffle(&mut rng);
+            for (from, to) in placed {
+                let _ = system.set_bridge_state(from, to, BridgeState::Empty);
+                if system.is_solvable_with(step) {
+                    needed = step == technique;
+                } else {
+                    let solution = system.bridges[&(from, to)].get_solution().clone();
+                    let _ = system.set_bridge_state(from, to, solution);
+                }
+            }
+        }
+        for (from, to) in system.placed_bridges() {
+            let _ = system.toggle_lock(from, to);
+        }
+        (system, needed)
+    }
+
+    fn placed_bridges(&self) -> Vec<(usize, usize)> {
+        self.bridges
+            .iter()
+            .filter(|(_, b)| b.get_state() != &BridgeState::Empty)
+            .map(|(key, _)| *key)
+            .collect()
+    }
+
+    ///
+    /// Rate the puzzle from its start by the number of search nodes needed to solve it.
+    ///
+    /// This is the search `rate` falls back on, without trying the techniques one by one first.
+    /// A puzzle needing more than one node cannot be solved by the techniques alone, so it is extreme,
+    /// which filters generated candidates without `rate`.
+    /// Returns `None` if the puzzle cannot be solved within `budget` nodes.
+    ///
+    pub fn rate_by_nodes(&self, budget: usize) -> Option<usize> {
+        let _span = span("rate by nodes");
+        let mut start = self.clone();
+        start.apply_start();
+        let mut solver = Solver::new(&start, Some(budget));
+        match solver.solve() {
+            Outcome::Solved(_) => Some(solver.nodes),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Generate a puzzle like `generate_new` and verify that the solver can solve it from its start.
+    ///
+    /// Puzzles falling apart into parts that can be solved on their own are boring and rejected as well.
+    /// Tries the following seeds if needed and returns the seed the puzzle was generated from.
+    /// The puzzle of the last attempt is returned unverified.
+    ///
+    pub fn generate_verified(params: GameParameters) -> (u64, HexSystem) {
+        let (seed, system, _) = HexSystem::generate_attempts(params);
+        (seed, system)
+    }
+
+    ///
+    /// Generate a puzzle like `generate_verified` and report how the generation went.
+    ///
+    /// Rating the puzzle takes extra time, so this is meant for monitoring rather than for playing.
+    ///
+    pub fn generate_reported(params: GameParameters) -> (u64, HexSystem, GenerationReport) {
+        #[cfg(not(target_arch = "wasm32"))]
+        let start = std::time::Instant::now();
+        let (seed, system, mut report) = HexSystem::generate_attempts(params);
+        if report.nodes.is_some() {
+            report.rating = system.rate();
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            report.elapsed_ms = Some(start.elapsed().as_secs_f64() * 1000.0);
+        }
+        (seed, system, report)
+    }
+
+    fn generate_attempts(params: GameParameters) -> (u64, HexSystem, GenerationReport) {
+        let _span = span("generate");
+        let mut report = GenerationReport::default();
+        let mut seed = params.seed;
+        for _ in 1..VERIFY_ATTEMPTS {
+            let system = HexSystem::generate_new(GameParameters {
+                seed,
+                ..params.clone()
+            });
+            report.attempts += 1;
+            if system.is_trivially_decomposable() {
+                report.rejected.push((seed, Rejection::Decomposable));
+            } else if let Some(nodes) = system.rate_by_nodes(NODE_BUDGET) {
+                report.nodes = Some(nodes);
+                return (seed, system, report);
+            } else {
+                report.rejected.push((seed, Rejection::Unsolved));
+            }
+            seed = seed.wrapping_add(1);
+        }
+        report.attempts += 1;
+        (
+            seed,
+            HexSystem::generate_new(GameParameters { seed, ..params }),
+            report,
+        )
+    }
+
+    ///
+    /// Get the position of the difficulty `rate` gives the puzzle in `Difficulty::ALL`.
+    ///
+    /// Puzzles the solver cannot rate come last.
+    ///
+    fn rating_level(&self) -> usize {
+        self.rate()
+            .and_then(|rating| Difficulty::ALL.iter().position(|d| *d == rating))
+            .unwrap_or(Difficulty::ALL.len())
+    }
+
+    ///
+    /// Generate a series of `rounds` verified puzzles from one `seed` getting harder from round to round.
+    ///
+    /// The rounds are spread evenly over the difficulties from easy to extreme,
+    /// preferring puzzles that `rate` gives the difficulty of their round.
+    /// The puzzles are ordered by their rating and, within a rating, by the search nodes they need.
+    /// The same seed always gives the same series, so that everyone plays the same puzzles.
+    ///
+    pub fn generate_tournament(seed: u64, rounds: usize) -> Vec<(u64, HexSystem)> {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let mut puzzles: Vec<_> = (0..rounds)
+            .map(|round| {
+                let level = round * Difficulty::ALL.len() / rounds;
+                let mut best: Option<(usize, usize, (u64, HexSystem))> = None;
+                for _ in 0..TOURNAMENT_ATTEMPTS {
+                    let params = GameParameters::preset(&Difficulty::ALL[level], rng.random());
+                    let (seed, system) = HexSystem::generate_verified(params);
+                    let nodes = system.rate_by_nodes(NODE_BUDGET);
+                    let rating = match nodes {
+                        None => Difficulty::ALL.len(),
+                        // Only guessing solves the puzzle, no need to try the techniques.
+                        Some(nodes) if nodes > 1 => Difficulty::ALL.len() - 1,
+                        Some(_) => system.rating_level(),
+                    };
+                    if best
+                        .as_ref()
+                        .is_none_or(|(r, ..)| rating.abs_diff(level) < r.abs_diff(level))
+                    {
+                        best = Some((rating, nodes.unwrap_or(NODE_BUDGET), (seed, system)));
+                    }
+                    if rating == level {
+                        break;
+                    }
+                }
+                let (rating, nodes, puzzle) = best.expect("at least one attempt");
+                ((rating, nodes), puzzle)
+            })
+            .collect();
+        puzzles.sort_by_key(|(rating, _)| *rating);
+        puzzles.into_iter().map(|(_, puzzle)| puzzle).collect()
+    }
+
+    ///
+    /// Check if the puzzle cannot be solved anymore without removing bridges.
+    ///
+    /// Returns `None` if the bridges placed so far can still be extended to a solution.
+    /// Otherwise, returns the index of the earliest move in `history` after which the puzzle was stuck,
+    /// i.e. undoing it and all later moves leads back to a consistent state.
+    /// If none of the moves is to blame, e.g. for changes of other players, returns the length of `history`.
+    ///
+    pub fn is_stuck(&self, history: &[Move]) -> Option<usize> {
+        if self.is_extendable() {
+            return None;
+        }
+        // Replay the moves from the start of the puzzle.
+        let mut replay = self.clone();
+        replay.apply_start();
+        let earliest = history.iter().position(|m| {
+            let _ = replay.set_bridge_state(m.bridge.0, m.bridge.1, m.to.clone());
+            // A move overloading one of its islands is to blame without asking the solver.
+            !replay.island_satisfiable(m.bridge.0)
+                || !replay.island_satisfiable(m.bridge.1)
+                || !replay.is_extendable()
+        });
+        Some(earliest.unwrap_or(history.len()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{NODE_BUDGET, Rejection, Technique, Uniqueness};
+    use crate::difficulty::Difficulty;
+    use crate::hex::{BridgeState, GameParameters, HexSystem, Island};
+    use crate::session::GameSession;
+
+    fn system() -> HexSystem {
+        let mut islands = vec![Island::Empty; 22];
+        islands[0] = Island::Bridged(1);
+        islands[4] = Island::Bridged(2);
+        islands[6] = Island::Bridged(1);
+        HexSystem::from_islands(4, 5, islands)
+    }
+
+    #[test]
+    fn solve_generated() {
+        for seed in 0..20 {
+            let sys = HexSystem::generate_new(GameParameters {
+                seed,
+                num_islands: 50,
+                max_bridge_length: 7,
+                ratio_long_bridge: 1.0,
+                ..Default::default()
+            });
+            let solution = sys.solve().expect("generated puzzles are solvable");
+            assert!(solution.is_solved());
+            assert!(sys.rate().is_some());
+            assert!(sys.rate_by_nodes(NODE_BUDGET).is_some());
+        }
+    }
+
+    #[test]
+    fn stuck() {
+        let mut session = GameSession::new(system());
+        assert!(session.system().is_extendable());
+        assert_eq!(session.system().is_stuck(session.history()), None);
+        assert!(session.cycle_bridge(4, 6).is_ok());
+        // A second bridge to 0 is one too many.
+        assert!(session.cycle_bridge(0, 4).is_ok());
+        assert!(session.cycle_bridge(0, 4).is_ok());
+        assert!(!session.system().is_extendable());
+        assert_eq!(session.system().is_stuck(session.history()), Some(2));
+        assert!(session.system().solve().is_none());
+    }
+
+    #[test]
+    fn rate() {
+        // The targets alone determine all bridges.
+        assert_eq!(system().rate(), Some(Difficulty::Easy));
+        // Bridges placed so far do not change the rating.
+        let mut session = GameSession::new(system());
+        assert!(session.cycle_bridge(0, 4).is_ok());
+        assert!(session.cycle_bridge(0, 4).is_ok());
+        assert_eq!(session.system().rate(), Some(Difficulty::Easy));
+        // No guess is needed.
+        assert_eq!(system().rate_by_nodes(10), Some(1));
+        assert_eq!(system().rate_by_nodes(0), None);
+        // Puzzles needing guesses are extreme.
+        for seed in 0..10 {
+            let sys = HexSystem::generate_new(GameParameters::preset(&Difficulty::Extreme, seed));
+            if sys
+                .rate_by_nodes(NODE_BUDGET)
+                .is_some_and(|nodes| nodes > 1)
+            {
+                assert_eq!(sys.rate(), Some(Difficulty::Extreme));
+            }
+        }
+    }
+
+    #[test]
+    fn uniqueness() {
+        assert_eq!(system().uniqueness(), Uniqueness::Unique);
+        // The path through all four islands can run either way round the rhombus.
+        let mut ambiguous = system();
+        ambiguous.islands = vec![Island::Empty; 22];
+        for (index, target) in [(0, 2), (1, 1), (4, 1), (5, 2)] {
+            ambiguous.islands[index] = Island::Bridged(target);
+        }
+        ambiguous.bridges = HexSystem::fill_bridges(&ambiguous.islands, 4, 5, None);
+        assert_eq!(ambiguous.uniqueness(), Uniqueness::Ambiguous);
+        ambiguous.islands[5] = Island::Bridged(5);
+        assert_eq!(ambiguous.uniqueness(), Uniqueness::Unsolvable);
+    }
+
+    #[test]
+    fn generate_reported() {
+        let params = GameParameters::preset(&Difficulty::Easy, 15);
+        let (seed, sys, report) = HexSystem::generate_reported(params.clone());
+        assert_eq!((seed, sys.to_code()), {
+            let (seed, sys) = HexSystem::generate_verified(params.clone());
+            (seed, sys.to_code())
+        });
+        assert_eq!(report.attempts, 3);
+        assert_eq!(
+            report.rejected,
+            [(15, Rejection::Decomposable), (16, Rejection::Decomposable)]
+        );
+        assert!(report.nodes.is_some());
+        assert_eq!(report.rating, sys.rate());
+        assert!(report.elapsed_ms.is_some());
+    }
+
+    #[test]
+    fn generate_tournament() {
+        let tournament = HexSystem::generate_tournament(3, 6);
+        assert_eq!(tournament.len(), 6);
+        let codes: Vec<_> = tournament.iter().map(|(_, sys)| sys.to_code()).collect();
+        let again: Vec<_> = HexSystem::generate_tournament(3, 6)
+            .iter()
+            .map(|(_, sys)| sys.to_code())
+            .collect();
+        assert_eq!(codes, again);
+        // The ratings never drop from round to round, nor the search nodes within a rating.
+        let ratings: Vec<_> = tournament
+            .iter()
+            .map(|(_, sys)| (sys.rating_level(), sys.rate_by_nodes(NODE_BUDGET).unwrap()))
+            .collect();
+        assert!(ratings.is_sorted());
+        assert!(ratings[0].0 < ratings[5].0);
+        let first = &tournament[0].1;
+        let islands = |sys: &HexSystem| {
+            sys.islands
+                .iter()
+                .filter(|i| matches!(i, Island::Bridged(_)))
+                .count()
+        };
+        assert!(islands(&tournament[5].1) > islands(first));
+    }
+
+    #[test]
+    fn generate_verified() {
+        for difficulty in Difficulty::ALL {
+            let (seed, sys) = HexSystem::generate_verified(GameParameters::preset(&difficulty, 7));
+            assert!(seed >= 7);
+            assert!(sys.rate_by_nodes(NODE_BUDGET).is_some());
+            assert!(!sys.is_trivially_decomposable());
+            assert_eq!(
+                sys.to_code(),
+                HexSystem::generate_new(GameParameters::preset(&difficulty, seed)).to_code()
+            );
+        }
+    }
+
+    #[test]
+    fn techniques() {
+        for technique in Technique::ALL {
+            assert_eq!(technique.as_str().parse::<Technique>().unwrap(), technique);
+        }
+        assert!("guessing".parse::<Technique>().is_err());
+
+        let (_, mut position) = HexSystem::generate_for_technique(Technique::Connectivity, 0);
+        assert!(position.is_solvable_with(Technique::Connectivity));
+        assert!(!position.is_solvable_with(Technique::Crossings));
+        assert!(
+            position
+                .bridges
+                .values()
+                .all(|b| b.is_locked() == (b.get_state() != &BridgeState::Empty))
+        );
+        // The moves the technique proves are part of the solution.
+        let moves = position.technique_moves(Technique::Connectivity);
+        assert!(!moves.is_empty());
+        for (from, to) in moves {
+            let bridge = position.get_bridge(from, to).unwrap();
+            assert!(bridge.get_solution() > bridge.get_state());
+            position.cycle_bridge(from, to).unwrap();
+        }
+        assert!(position.is_solvable_with(Technique::Connectivity));
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::BTreeMap;
+
+use crate::hex::{HexSystem, Island};
+
+///
+/// Key figures of the layout of a puzzle, independent of the bridges placed so far.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PuzzleStats {
+    /// Number of islands for each target number of bridges
+    pub islands_by_target: BTreeMap<usize, usize>,
+    /// Number of pairs of islands that can be connected by a bridge
+    pub candidate_bridges: usize,
+    /// Average number of candidate bridges per island
+    pub average_degree: f64,
+    /// Length of the longest candidate bridge in cells
+    pub longest_bridge: usize,
+}
+
+impl PuzzleStats {
+    ///
+    /// Get the total number of islands.
+    ///
+    pub fn islands(&self) -> usize {
+        self.islands_by_target.values().sum()
+    }
+}
+
+impl HexSystem {
+    ///
+    /// Get the key figures of the puzzle.
+    ///
+    pub fn stats(&self) -> PuzzleStats {
+        let mut islands_by_target = BTreeMap::new();
+        for island in &self.islands {
+            if let Island::Bridged(target) = island {
+                *islands_by_target.entry(*target).or_insert(0) += 1;
+            }
+        }
+        let islands: usize = islands_by_target.values().sum();
+        let candidate_bridges = self.bridges.len();
+        PuzzleStats {
+            islands_by_target,
+            candidate_bridges,
+            // Each bridge connects two islands.
+            average_degree: if islands > 0 {
+                2.0 * candidate_bridges as f64 / islands as f64
+            } else {
+                0.0
+            },
+            longest_bridge: self
+                .bridges
+                .values()
+                .map(|b| b.get_gap_indices().len() + 1)
+                .max()
+                .unwrap_or(0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use crate::hex::{HexSystem, Island};
+
+    #[test]
+    fn stats() {
+        let mut islands = vec![Island::Empty; 22];
+        islands[0] = Island::Bridged(1);
+        islands[4] = Island::Bridged(2);
+        islands[6] = Island::Bridged(1);
+        let sys = HexSystem::from_islands(4, 5, islands);
+        let stats = sys.stats();
+        assert_eq!(stats.islands_by_target, BTreeMap::from([(1, 2), (2, 1)]));
+        assert_eq!(stats.islands(), 3);
+        assert_eq!(stats.candidate_bridges, 2);
+        assert!((stats.average_degree - 4.0 / 3.0).abs() < 1e-9);
+        // Islands 4 and 6 are two cells apart.
+        assert_eq!(stats.longest_bridge, 2);
+    }
+}
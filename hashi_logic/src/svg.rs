@@ -0,0 +1,142 @@
+use std::fmt::Write;
+
+use crate::{
+    hex::{BridgeState, HexSystem, Island},
+    layout::Orientation,
+};
+
+const LINE_HEIGHT: f64 = 10.0;
+const ISLAND_SIZE: f64 = 3.0;
+const BRIDGE_COLOR: &str = "dodgerblue";
+const GRID_COLOR: &str = "dimgrey";
+const ISLAND_COLOR: (&str, &str) = ("white", "black");
+const UNFINISHED_ISLAND_COLOR: (&str, &str) = ("gold", "dimgray");
+const FINISHED_ISLAND_COLOR: (&str, &str) = ("green", "white");
+
+impl HexSystem {
+    ///
+    /// Export the current state of the puzzle as SVG image.
+    ///
+    /// The image only defines a `viewBox`, i.e. it scales to whatever size the embedding element has.
+    ///
+    pub fn to_svg(&self) -> String {
+        self.render_svg(None)
+    }
+
+    ///
+    /// Export a small preview of the current state of the puzzle as SVG image `size` pixels wide.
+    ///
+    /// The numbers of the islands would be unreadable at this size, so only their colors are shown.
+    ///
+    pub fn to_thumbnail(&self, size: u32) -> String {
+        self.render_svg(Some(size))
+    }
+
+    fn render_svg(&self, thumbnail: Option<u32>) -> String {
+        let (width, height) = self.bounding_box(LINE_HEIGHT);
+        let mut svg = String::new();
+        // Writing to a String cannot fail.
+        let _ = write!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {width:.1} {height:.1}""#
+        );
+        if let Some(size) = thumbnail {
+            let _ = write!(
+                svg,
+                r#" width="{size}" height="{:.0}""#,
+                size as f64 * height / width
+            );
+        }
+        svg.push('>');
+        // Grid
+        for (start_index, end_index) in self.bridges.keys() {
+            let (x1, y1) = self.pixel_position(*start_index, LINE_HEIGHT, Orientation::PointyTop);
+            let (x2, y2) = self.pixel_position(*end_index, LINE_HEIGHT, Orientation::PointyTop);
+            let _ = write!(
+                svg,
+                r#"<line x1="{x1:.1}" y1="{y1:.1}" x2="{x2:.1}" y2="{y2:.1}" stroke="{GRID_COLOR}" stroke-width="0.2"/>"#
+            );
+        }
+        // Bridges
+        for ((start_index, end_index), bridge) in &self.bridges {
+            let (x1, y1) = self.pixel_position(*start_index, LINE_HEIGHT, Orientation::PointyTop);
+            let (x2, y2) = self.pixel_position(*end_index, LINE_HEIGHT, Orientation::PointyTop);
+            let offsets: &[f64] = match bridge.get_state() {
+                BridgeState::Empty => &[],
+                BridgeState::Partial => &[0.0],
+                BridgeState::Full => &[-0.8, 0.8],
+            };
+            // Unit normal vector to draw double bridges side by side
+            let length = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt();
+            let (nx, ny) = ((y1 - y2) / length, (x2 - x1) / length);
+            for o in offsets {
+                let _ = write!(
+                    svg,
+                    r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="{BRIDGE_COLOR}" stroke-width="0.8"/>"#,
+                    x1 + o * nx,
+                    y1 + o * ny,
+                    x2 + o * nx,
+                    y2 + o * ny
+                );
+            }
+        }
+        // Islands
+        for (index, island) in self.islands.iter().enumerate() {
+            if let Island::Bridged(target) = island {
+                let actual = self.get_actual_bridges(index);
+                let (island_color, text_color) = if actual == 0 {
+                    ISLAND_COLOR
+                } else if actual != *target {
+                    UNFINISHED_ISLAND_COLOR
+                } else {
+                    FINISHED_ISLAND_COLOR
+                };
+                let (x, y) = self.pixel_position(index, LINE_HEIGHT, Orientation::PointyTop);
+                // Islands of the colored variant get a thick ring in their color.
+                let (stroke, stroke_width) = match self.ring_color(index) {
+                    Some(color) => (color, 0.8),
+                    None => (GRID_COLOR, 0.2),
+                };
+                let _ = write!(
+                    svg,
+                    r#"<circle cx="{x:.1}" cy="{y:.1}" r="{ISLAND_SIZE}" fill="{island_color}" stroke="{stroke}" stroke-width="{stroke_width}"/>"#
+                );
+                if thumbnail.is_some() {
+                    continue;
+                }
+                let _ = write!(
+                    svg,
+                    r#"<text x="{x:.1}" y="{y:.1}" fill="{text_color}" font-size="3.5" font-family="Arial" text-anchor="middle" dominant-baseline="central">{target}</text>"#
+                );
+            }
+        }
+        svg.push_str("</svg>");
+        svg
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hex::{HexSystem, Island};
+
+    #[test]
+    fn svg_export() {
+        let mut islands = vec![Island::Empty; 22];
+        islands[0] = Island::Bridged(1);
+        islands[2] = Island::Bridged(1);
+        let mut sys = HexSystem::from_islands(4, 5, islands);
+        sys.cycle_bridge(0, 2).unwrap();
+        let svg = sys.to_svg();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>"));
+        assert_eq!(svg.matches("<circle").count(), 2);
+        // One grid line and one bridge
+        assert_eq!(svg.matches("<line").count(), 2);
+        assert_eq!(svg.matches("<text").count(), 2);
+
+        let thumbnail = sys.to_thumbnail(80);
+        assert!(thumbnail.contains(r#" width="80" "#));
+        assert_eq!(thumbnail.matches("<circle").count(), 2);
+        assert_eq!(thumbnail.matches("<text").count(), 0);
+    }
+}
@@ -0,0 +1,321 @@
+use std::{collections::BTreeSet, fmt::Display, str::FromStr};
+
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::hex::{BridgeState, GameParameters, HexSystem, Island};
+
+/// Colors of the rings around the islands in the colored variant
+const RING_COLORS: [&str; 3] = ["#dc143c", "#ff8c00", "#9370db"];
+/// Number of island colors in the colored variant
+pub const COLORS: u8 = RING_COLORS.len() as u8;
+
+///
+/// Rules the puzzle is played by.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum GameVariant {
+    /// All islands are connected to a single network.
+    #[default]
+    Classic,
+    /// Bridges only connect islands of the same color, and the islands of each color form a network.
+    Colored,
+    /// Two islands are connected by at most one bridge.
+    Single,
+}
+
+impl GameVariant {
+    pub const ALL: [GameVariant; 3] = [
+        GameVariant::Classic,
+        GameVariant::Colored,
+        GameVariant::Single,
+    ];
+
+    ///
+    /// Get the identifier of the variant, e.g. "colored".
+    ///
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GameVariant::Classic => "classic",
+            GameVariant::Colored => "colored",
+            GameVariant::Single => "single",
+        }
+    }
+}
+
+impl Display for GameVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameVariant::Classic => f.write_str("Classic"),
+            GameVariant::Colored => f.write_str("Colored islands"),
+            GameVariant::Single => f.write_str("No double bridges"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct GameVariantConversionError;
+
+impl Display for GameVariantConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Cannot convert to game variant")
+    }
+}
+
+impl std::error::Error for GameVariantConversionError {}
+
+impl FromStr for GameVariant {
+    type Err = GameVariantConversionError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        GameVariant::ALL
+            .into_iter()
+            .find(|v| v.as_str() == s.to_lowercase())
+            .ok_or(GameVariantConversionError)
+    }
+}
+
+impl HexSystem {
+    ///
+    /// Generate a new puzzle played by the rules of `variant`.
+    ///
+    pub fn generate_variant(params: GameParameters, variant: GameVariant) -> Self {
+        let seed = params.seed;
+        let mut system = HexSystem::generate_new(params);
+        match variant {
+            GameVariant::Classic => {}
+            GameVariant::Colored => system.colorize(seed),
+            GameVariant::Single => system.make_single(),
+        }
+        system
+    }
+
+    pub fn variant(&self) -> GameVariant {
+        if self.single_bridges {
+            GameVariant::Single
+        } else if self.colors.is_empty() {
+            GameVariant::Classic
+        } else {
+            GameVariant::Colored
+        }
+    }
+
+    ///
+    /// Get the color of the island at `index`, always 0 in the classic game.
+    ///
+    pub fn color(&self, index: usize) -> u8 {
+        self.colors.get(index).copied().unwrap_or_default()
+    }
+
+    ///
+    /// Get the CSS color of the ring around the island at `index`, or `None` in the classic game.
+    ///
+    pub fn ring_color(&self, index: usize) -> Option<&'static str> {
+        self.colors
+            .get(index)
+            .map(|c| RING_COLORS[*c as usize % RING_COLORS.len()])
+    }
+
+    ///
+    /// Get the number of networks the islands must form, i.e. the number of colors of the islands.
+    ///
+    pub fn networks(&self) -> usize {
+        self.islands
+            .iter()
+            .enumerate()
+            .filter(|(_, island)| matches!(island, Island::Bridged(_)))
+            .map(|(index, _)| self.color(index))
+            .collect::<BTreeSet<_>>()
+            .len()
+    }
+
+    ///
+    /// Remove the bridges between islands of different colors.
+    ///
+    pub(crate) fn retain_same_colors(&mut self) {
+        if self.colors.is_empty() {
+            return;
+        }
+        let colors = &self.colors;
+        self.bridges
+            .retain(|(from, to), _| colors.get(*from) == colors.get(*to));
+    }
+
+    ///
+    /// Turn the double bridges of the solution into single ones and reduce the numbers of their islands.
+    ///
+    /// The islands stay connected, since every bridge of the solution is kept.
+    ///
+    fn make_single(&mut self) {
+        self.single_bridges = true;
+        for ((from, to), bridge) in self.bridges.iter_mut() {
+            if bridge.get_solution() != &BridgeState::Full {
+                continue;
+            }
+            bridge.set_solution(BridgeState::Partial);
+            for index in [*from, *to] {
+                if let Island::Bridged(target) = &mut self.islands[index] {
+                    *target -= 1;
+                }
+            }
+        }
+    }
+
+    ///
+    /// Split the network of the solution into up to `COLORS` networks of different colors.
+    ///
+    /// The networks grow from random islands along the bridges of the solution, so that each of them
+    /// stays connected. Bridges of the solution between different colors are dropped and the numbers
+    /// of the islands are reduced accordingly. Islands left without bridges are removed.
+    ///
+    fn colorize(&mut self, seed: u64) {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let mut islands: Vec<usize> = (0..self.islands.len())
+            .filter(|index| matches!(self.islands[*index], Island::Bridged(_)))
+            .collect();
+        islands.shuffle(&mut rng);
+        let mut colors: Vec<Option<u8>> = vec![None; self.islands.len()];
+        let mut next: Vec<usize> = islands.iter().take(COLORS as usize).copied().collect();
+        for (color, index) in next.iter().enumerate() {
+            colors[*index] = Some(color as u8);
+        }
+        while !next.is_empty() {
+            let island = next.remove(rng.random_range(0..next.len()));
+            for other in self.get_connected_islands(island) {
+                let solved = self
+                    .get_bridge(island, other)
+                    .is_some_and(|b| b.get_solution() != &BridgeState::Empty);
+                if solved && colors[other].is_none() {
+                    colors[other] = colors[island];
+                    next.push(other);
+                }
+            }
+        }
+        self.colors = colors.into_iter().map(Option::unwrap_or_default).collect();
+
+        let dropped: Vec<_> = self
+            .bridges
+            .iter()
+            .filter(|((from, to), _)| self.colors[*from] != self.colors[*to])
+            .map(|(key, b)| (*key, b.get_solution().clone()))
+            .collect();
+        for ((from, to), solution) in dropped {
+            let count = match solution {
+                BridgeState::Empty => 0,
+                BridgeState::Partial => 1,
+                BridgeState::Full => 2,
+            };
+            for index in [from, to] {
+                if let Island::Bridged(target) = &mut self.islands[index] {
+                    *target -= count;
+                }
+            }
+        }
+        for island in self.islands.iter_mut() {
+            if *island == Island::Bridged(0) {
+                *island = Island::Empty;
+            }
+        }
+
+        // Islands removed above may open up new bridges.
+        let solution: Vec<_> = self
+            .bridges
+            .iter()
+            .filter(|((from, to), _)| self.colors[*from] == self.colors[*to])
+            .map(|(key, b)| (*key, b.get_solution().clone()))
+            .collect();
+        self.bridges = HexSystem::fill_bridges(&self.islands, self.columns, self.rows, None);
+        self.retain_same_colors();
+        for (key, state) in solution {
+            if let Some(bridge) = self.bridges.get_mut(&key) {
+                bridge.set_solution(state);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        difficulty::Difficulty,
+        hex::{BridgeState, GameParameters, HexSystem, Island},
+        session::GameSession,
+    };
+
+    use super::{COLORS, GameVariant};
+
+    #[test]
+    fn colored() {
+        for seed in 0..10 {
+            let system = HexSystem::generate_variant(
+                GameParameters::preset(&Difficulty::Medium, seed),
+                GameVariant::Colored,
+            );
+            assert_eq!(system.variant(), GameVariant::Colored);
+            assert!((1..=COLORS as usize).contains(&system.networks()));
+            // Bridges only connect islands of the same color.
+            assert!(
+                system
+                    .bridges
+                    .keys()
+                    .all(|(from, to)| system.color(*from) == system.color(*to))
+            );
+            assert!(
+                system
+                    .islands
+                    .iter()
+                    .all(|i| !matches!(i, Island::Bridged(0)))
+            );
+
+            // The solution satisfies the rules.
+            let mut session = GameSession::new(system.clone());
+            session.reveal();
+            assert!(session.system().is_solved(), "seed {}", seed);
+            assert!(system.solve().is_some_and(|solved| solved.is_solved()));
+        }
+        let classic = HexSystem::generate_variant(
+            GameParameters::preset(&Difficulty::Medium, 1),
+            GameVariant::Classic,
+        );
+        assert_eq!(classic.variant(), GameVariant::Classic);
+        assert_eq!(classic.networks(), 1);
+        assert_eq!(classic.ring_color(0), None);
+        assert!(
+            classic
+                .bridges
+                .values()
+                .all(|b| b.get_state() == &BridgeState::Empty)
+        );
+        assert_eq!(
+            "Colored".parse::<GameVariant>().unwrap(),
+            GameVariant::Colored
+        );
+    }
+
+    #[test]
+    fn single() {
+        let mut system = HexSystem::generate_variant(
+            GameParameters::preset(&Difficulty::Medium, 3),
+            GameVariant::Single,
+        );
+        assert_eq!(system.variant(), GameVariant::Single);
+        assert!(
+            system
+                .bridges
+                .values()
+                .all(|b| b.get_solution() != &BridgeState::Full)
+        );
+        assert!(system.solve().is_some_and(|solved| solved.is_solved()));
+
+        // Cycling skips double bridges in both directions.
+        let (from, to) = *system.bridges.keys().next().unwrap();
+        let state = |system: &HexSystem| system.get_bridge(from, to).unwrap().get_state().clone();
+        assert!(system.cycle_bridge(from, to).is_ok());
+        assert_eq!(state(&system), BridgeState::Partial);
+        assert_eq!(system.remaining_capacity(from, to), 0);
+        assert!(system.cycle_bridge(from, to).is_ok());
+        assert_eq!(state(&system), BridgeState::Empty);
+        assert!(system.cycle_bridge_back(from, to).is_ok());
+        assert_eq!(state(&system), BridgeState::Partial);
+    }
+}
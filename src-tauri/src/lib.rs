@@ -1,14 +1,102 @@
+use std::{fs, io::ErrorKind, thread, time::Duration};
+
+use tauri::{Emitter, Manager, WindowEvent};
+
+/// File in the data directory of the app keeping the save slots
+const SLOTS_FILE: &str = "slots.json";
+/// Event asking the page to save the game before the window is closed
+const CLOSE_REQUESTED_EVENT: &str = "hexhashi://close-requested";
+/// Time the page has to save the game before the window is closed anyway
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(2);
+
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 #[tauri::command]
 fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
+///
+/// Decode the QR code in the image `data`, e.g. a shared puzzle.
+///
+#[tauri::command]
+fn decode_qr(data: Vec<u8>) -> Result<String, String> {
+    let image = image::load_from_memory(&data)
+        .map_err(|e| e.to_string())?
+        .to_luma8();
+    let mut image = rqrr::PreparedImage::prepare(image);
+    let grid = image
+        .detect_grids()
+        .into_iter()
+        .next()
+        .ok_or("No QR code found in image.")?;
+    let (_, content) = grid.decode().map_err(|e| e.to_string())?;
+    Ok(content)
+}
+
+///
+/// Enter or leave full screen with the `window` of the app.
+///
+#[tauri::command]
+fn set_fullscreen(window: tauri::WebviewWindow, fullscreen: bool) -> Result<(), String> {
+    window.set_fullscreen(fullscreen).map_err(|e| e.to_string())
+}
+
+///
+/// Read the save slots as JSON, empty if none were saved yet.
+///
+#[tauri::command]
+fn read_slots(app: tauri::AppHandle) -> Result<String, String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    match fs::read_to_string(dir.join(SLOTS_FILE)) {
+        Ok(json) => Ok(json),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+///
+/// Replace the save slots with `json`.
+///
+#[tauri::command]
+fn write_slots(app: tauri::AppHandle, json: String) -> Result<(), String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    fs::write(dir.join(SLOTS_FILE), json).map_err(|e| e.to_string())
+}
+
+///
+/// Close the `window` after the page saved the game.
+///
+#[tauri::command]
+fn close_window(window: tauri::WebviewWindow) -> Result<(), String> {
+    window.destroy().map_err(|e| e.to_string())
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![greet])
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            decode_qr,
+            set_fullscreen,
+            read_slots,
+            write_slots,
+            close_window
+        ])
+        .on_window_event(|window, event| {
+            // The page saves the game first and closes the window by `close_window`.
+            if let WindowEvent::CloseRequested { api, .. } = event {
+                api.prevent_close();
+                let _ = window.emit(CLOSE_REQUESTED_EVENT, ());
+                let window = window.clone();
+                thread::spawn(move || {
+                    thread::sleep(CLOSE_TIMEOUT);
+                    let _ = window.destroy();
+                });
+            }
+        })
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -0,0 +1,186 @@
+use std::f64::consts::PI;
+
+use hexhashi_logic::hex::BridgeState;
+
+/// Duration of a bridge animation in milliseconds.
+pub const ANIMATION_DURATION: f64 = 100.0;
+/// Number of back and forth movements of a blocked bridge
+const SHAKE_CYCLES: f64 = 3.0;
+
+///
+/// Change of a bridge from state `from` to state `to` starting at `start`.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct BridgeAnimation {
+    pub bridge: (usize, usize),
+    pub from: BridgeState,
+    pub to: BridgeState,
+    pub start: f64,
+}
+
+impl BridgeAnimation {
+    ///
+    /// Get the progress of the animation at time `now` between 0.0 (start) and 1.0 (done).
+    ///
+    pub fn progress(&self, now: f64) -> f64 {
+        ((now - self.start) / ANIMATION_DURATION).clamp(0.0, 1.0)
+    }
+}
+
+///
+/// Feedback on a move that is blocked by crossing bridges, starting at `start`.
+///
+/// The attempted bridge and the bridges in its way shake and flash for `duration` milliseconds.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockedMove {
+    pub attempted: (usize, usize),
+    pub conflicting: Vec<(usize, usize)>,
+    pub start: f64,
+    pub duration: f64,
+}
+
+impl BlockedMove {
+    ///
+    /// Get the progress of the feedback at time `now` between 0.0 (start) and 1.0 (done).
+    ///
+    pub fn progress(&self, now: f64) -> f64 {
+        if self.duration <= 0.0 {
+            return 1.0;
+        }
+        ((now - self.start) / self.duration).clamp(0.0, 1.0)
+    }
+
+    ///
+    /// Is `bridge` the attempted bridge or one of the bridges in its way?
+    ///
+    pub fn involves(&self, bridge: (usize, usize)) -> bool {
+        self.attempted == bridge || self.conflicting.contains(&bridge)
+    }
+
+    ///
+    /// Get the offset perpendicular to the bridges at time `now` as a fraction of the maximum.
+    ///
+    /// The shaking fades out towards the end.
+    ///
+    pub fn shake(&self, now: f64) -> f64 {
+        let progress = self.progress(now);
+        (progress * SHAKE_CYCLES * 2.0 * PI).sin() * (1.0 - progress)
+    }
+
+    ///
+    /// Get the opacity of the flash at time `now` between 0.0 and 1.0.
+    ///
+    pub fn flash(&self, now: f64) -> f64 {
+        1.0 - self.progress(now)
+    }
+}
+
+///
+/// Queue of running animations.
+///
+/// The queue is independent from the game state, i.e. the game is always updated immediately
+/// and the animations only smooth the way the changes are shown.
+///
+#[derive(Clone, Debug, Default)]
+pub struct AnimationQueue {
+    animations: Vec<BridgeAnimation>,
+    blocked: Option<BlockedMove>,
+}
+
+impl AnimationQueue {
+    ///
+    /// Start animating the change of `bridge` at time `now`.
+    ///
+    /// A still running animation of the same bridge is replaced, so that rapid clicking never lags behind.
+    ///
+    pub fn push(&mut self, bridge: (usize, usize), from: BridgeState, to: BridgeState, now: f64) {
+        self.animations.retain(|a| a.bridge != bridge);
+        self.animations.push(BridgeAnimation {
+            bridge,
+            from,
+            to,
+            start: now,
+        });
+    }
+
+    ///
+    /// Show the feedback on a blocked move, replacing any previous one.
+    ///
+    pub fn block(&mut self, blocked: BlockedMove) {
+        self.blocked = Some(blocked);
+    }
+
+    ///
+    /// Remove all animations that are done at time `now`.
+    ///
+    pub fn prune(&mut self, now: f64) {
+        self.animations.retain(|a| a.progress(now) < 1.0);
+        self.blocked.take_if(|b| b.progress(now) >= 1.0);
+    }
+
+    ///
+    /// Get the running animation of `bridge`, if there is any.
+    ///
+    pub fn get(&self, bridge: (usize, usize)) -> Option<&BridgeAnimation> {
+        self.animations.iter().find(|a| a.bridge == bridge)
+    }
+
+    ///
+    /// Get the running feedback on a blocked move, if there is any.
+    ///
+    pub fn blocked(&self) -> Option<&BlockedMove> {
+        self.blocked.as_ref()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.animations.is_empty() && self.blocked.is_none()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use hexhashi_logic::hex::BridgeState;
+
+    use super::{ANIMATION_DURATION, AnimationQueue, BlockedMove};
+
+    #[test]
+    fn queue() {
+        let mut queue = AnimationQueue::default();
+        assert!(queue.is_empty());
+        queue.push((0, 1), BridgeState::Empty, BridgeState::Partial, 0.0);
+        queue.push((0, 2), BridgeState::Empty, BridgeState::Partial, 50.0);
+        assert!((queue.get((0, 1)).unwrap().progress(50.0) - 0.5).abs() < f64::EPSILON);
+        // Replace running animation
+        queue.push((0, 1), BridgeState::Partial, BridgeState::Full, 60.0);
+        assert_eq!(queue.get((0, 1)).unwrap().to, BridgeState::Full);
+        queue.prune(ANIMATION_DURATION + 50.0);
+        assert!(queue.get((0, 2)).is_none());
+        assert!(queue.get((0, 1)).is_some());
+        queue.prune(ANIMATION_DURATION + 60.0);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn blocked() {
+        let mut queue = AnimationQueue::default();
+        queue.block(BlockedMove {
+            attempted: (4, 6),
+            conflicting: vec![(0, 15)],
+            start: 100.0,
+            duration: 600.0,
+        });
+        let blocked = queue.blocked().unwrap();
+        assert!(blocked.involves((4, 6)));
+        assert!(blocked.involves((0, 15)));
+        assert!(!blocked.involves((0, 4)));
+        assert_eq!(blocked.shake(100.0), 0.0);
+        assert!(blocked.shake(150.0).abs() > 0.0);
+        assert!((blocked.flash(400.0) - 0.5).abs() < f64::EPSILON);
+        // Independent of any input, the feedback ends after its duration.
+        queue.prune(650.0);
+        assert!(!queue.is_empty());
+        queue.prune(700.0);
+        assert!(queue.is_empty());
+    }
+}
@@ -1,10 +1,36 @@
-use crate::game::Game;
+use crate::{
+    close::handle_app_close,
+    game::{
+        ContinueGame, CoopGame, Game, GameMode, RaceGame, RaceSpectator, THUMBNAIL_SIZE,
+        format_time,
+    },
+    library::LibraryPage,
+    packs::{PackGame, PacksPage},
+    perf::{PerfOverlay, instrument_logic},
+    settings::{SettingsPage, load_settings, save_settings},
+    share::{ImportFromImage, ImportGame, PasteToImport},
+    slots::SlotsPage,
+    stats::StatsPage,
+    storage::load_game,
+    sync::start_sync,
+    trainer::{TrainerGame, TrainerPage},
+    users::{UserSelect, activate_user},
+    weekly::WeeklyPage,
+};
+use hexhashi_logic::{
+    difficulty::Difficulty,
+    hex::{GameParameters, HexSystem, Island},
+    variant::GameVariant,
+};
 use leptos::prelude::*;
 use leptos_router::path;
 use wasm_bindgen::prelude::*;
 
 use leptos_router::components::{Route, Router, Routes};
 
+/// Seed of the sample boards shown on the start screen.
+const PREVIEW_SEED: u64 = 1;
+
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"])]
@@ -13,27 +39,218 @@ extern "C" {
 
 #[component]
 pub fn App() -> impl IntoView {
+    activate_user();
+    handle_app_close();
+    instrument_logic();
+    let settings = RwSignal::new(load_settings());
+    provide_context(settings);
+    start_sync(&settings.read_untracked().sync_url);
+    Effect::new(move |_| save_settings(&settings.read()));
+    // Scale the whole user interface with the root font size.
+    Effect::new(move |_| {
+        let scale = settings.read().scale;
+        if let Some(root) = document().document_element() {
+            let _ = root.set_attribute(
+                "style",
+                &format!(
+                    "font-size: {}px; line-height: {}px",
+                    16.0 * scale,
+                    24.0 * scale
+                ),
+            );
+        }
+    });
+
     view! {
         <main class="container">
             <Router>
                 <Routes fallback=|| "Not found.">
                     <Route path=path!("/") view=GameStart/>
-                    <Route path=path!("/play/:difficulty") view=Game/>
+                    <Route path=path!("/play/:difficulty/:mode?/:variant?") view=Game/>
+                    <Route path=path!("/continue") view=ContinueGame/>
+                    <Route path=path!("/coop/:difficulty/:room") view=CoopGame/>
+                    <Route path=path!("/race/:difficulty/:room") view=RaceGame/>
+                    <Route path=path!("/race/:difficulty/:room/watch") view=RaceSpectator/>
+                    <Route path=path!("/settings") view=SettingsPage/>
+                    <Route path=path!("/stats") view=StatsPage/>
+                    <Route path=path!("/weekly") view=WeeklyPage/>
+                    <Route path=path!("/packs") view=PacksPage/>
+                    <Route path=path!("/library") view=LibraryPage/>
+                    <Route path=path!("/slots") view=SlotsPage/>
+                    <Route path=path!("/import/:difficulty/:code") view=ImportGame/>
+                    <Route path=path!("/pack/:id/:index") view=PackGame/>
+                    <Route path=path!("/trainer") view=TrainerPage/>
+                    <Route path=path!("/trainer/:technique") view=TrainerGame/>
                 </Routes>
             </Router>
+            <PerfOverlay/>
         </main>
     }
 }
 
 #[component]
 pub fn GameStart() -> impl IntoView {
+    let mode = RwSignal::new(GameMode::Normal);
+    let variant = RwSignal::new(GameVariant::Classic);
+    let description = move || match mode.get() {
+        GameMode::Normal => "The time is shown, but there is no limit.",
+        GameMode::Zen => "No timer, relaxed colors.",
+        GameMode::Timed => "Solve the puzzle within par time.",
+        GameMode::Weekly => "The puzzle of the week.",
+        GameMode::Daily => "The puzzle of the day, growing from Monday to Sunday.",
+        GameMode::HotSeat => "Two players take turns, completing islands scores.",
+        GameMode::Coop => "Solve the puzzle together in several windows.",
+        GameMode::Race => {
+            "Race another player on the same puzzle in another window, others may watch."
+        }
+        GameMode::Endless => "No timer, the board grows whenever it is solved.",
+    };
+
     view! {
             <img src="public/hexhashi.svg" class="logo hexhashi" alt="hexhashi logo"/>
             <h1>"hexhashi"</h1>
+            <UserSelect/>
+            <ContinueCard/>
+            <p>
+                <label>
+                    "Mode "
+                    <select on:change=move |ev| {
+                        mode.set(event_target_value(&ev).parse().unwrap_or_default())
+                    }>
+                        <option value="normal">"Normal"</option>
+                        <option value="zen">"Zen"</option>
+                        <option value="timed">"Timed"</option>
+                        <option value="hot-seat">"Hot-seat (2 players)"</option>
+                        {cfg!(feature = "multiplayer")
+                            .then(|| {
+                                view! {
+                                    <option value="co-op">"Co-op"</option>
+                                    <option value="race">"Race"</option>
+                                }
+                            })}
+                        <option value="endless">"Endless"</option>
+                    </select>
+                </label>
+                " " {description}
+            </p>
+            <p>
+                <label>
+                    "Variant "
+                    <select on:change=move |ev| {
+                        variant.set(event_target_value(&ev).parse().unwrap_or_default())
+                    }>
+                        {GameVariant::ALL
+                            .into_iter()
+                            .map(|v| view! { <option value=v.as_str()>{v.to_string()}</option> })
+                            .collect_view()}
+                    </select>
+                </label>
+                " "
+                {move || match variant.get() {
+                    GameVariant::Classic => "All islands are connected to a single network.",
+                    GameVariant::Colored => {
+                        "Bridges only connect islands of the same color, each color forms its own network."
+                    }
+                    GameVariant::Single => "Two islands are connected by one bridge at most, a gentle start.",
+                }}
+            </p>
             <p>"Select difficulty level to start game."</p>
-            <button onclick="location.href='/play/easy'">Easy</button>
-            <button onclick="location.href='/play/medium'">Medium</button>
-            <button onclick="location.href='/play/hard'">Hard</button>
-            <button onclick="location.href='/play/extreme'">Extreme</button>
+            <div class="row previews">
+                {Difficulty::ALL
+                    .into_iter()
+                    .map(|difficulty| view! { <DifficultyPreview difficulty mode variant/> })
+                    .collect_view()}
+            </div>
+            <p>
+                <PasteToImport/>
+                <ImportFromImage/>
+            </p>
+            <p>
+                <a href="/play/medium/daily">"Daily puzzle"</a>" | "
+                <a href="/weekly">"Weekly challenge"</a>" | "<a href="/packs">"Puzzle packs"</a>" | "
+                <a href="/library">"Library"</a>" | "<a href="/slots">"Saved games"</a>" | "
+                <a href="/trainer">"Trainer"</a>" | "
+                <a href="/stats">"Statistics"</a>" | "
+                <a href="/settings">"Settings"</a>
+            </p>
     }
 }
+
+///
+/// Button starting a game of `difficulty` in `mode` showing a sample board and its key figures.
+///
+/// Co-op games and races are always classic, since all clients of a room generate the same puzzle.
+///
+#[component]
+fn DifficultyPreview(
+    difficulty: Difficulty,
+    mode: RwSignal<GameMode>,
+    variant: RwSignal<GameVariant>,
+) -> impl IntoView {
+    let params = GameParameters::preset(&difficulty, PREVIEW_SEED);
+    let max_bridge_length = params.max_bridge_length;
+    let sample = HexSystem::generate_new(params);
+    let islands = sample
+        .islands
+        .iter()
+        .filter(|i| matches!(i, Island::Bridged(_)))
+        .count();
+    let d = difficulty.as_str();
+    let start = move || match mode.get() {
+        // Each co-op game and race gets a new room to share with the other players.
+        mode @ (GameMode::Coop | GameMode::Race) => format!(
+            "location.href='/{}/{}/{}'",
+            if mode == GameMode::Coop {
+                "coop"
+            } else {
+                "race"
+            },
+            d,
+            (js_sys::Math::random() * 1_000_000.0) as u64
+        ),
+        mode => format!(
+            "location.href='/play/{}/{}/{}'",
+            d,
+            mode.to_string().to_lowercase(),
+            variant.get().as_str()
+        ),
+    };
+    view! {
+        <button class="preview" onclick=start>
+            <div class="thumbnail" inner_html=sample.to_svg()/>
+            <span class="title">{difficulty.to_string()}</span>
+            <span class="details">
+                {format!("{}\u{d7}{} board, {} islands", sample.columns, sample.rows, islands)}
+            </span>
+            <span class="details">{format!("Bridges up to {} long", max_bridge_length)}</span>
+        </button>
+    }
+}
+
+///
+/// Button continuing the game in progress, if there is any.
+///
+#[component]
+fn ContinueCard() -> impl IntoView {
+    load_game().map(|saved| {
+        view! {
+            <div class="row">
+                <button class="preview continue" onclick="location.href='/continue'">
+                    <div
+                        class="thumbnail small"
+                        inner_html=saved.session.system().to_thumbnail(THUMBNAIL_SIZE)
+                    />
+                    <span class="title">"Continue"</span>
+                    <span class="details">
+                        {format!(
+                            "{} ({}), {} played",
+                            saved.difficulty,
+                            saved.mode,
+                            format_time(saved.elapsed_ms),
+                        )}
+                    </span>
+                </button>
+            </div>
+        }
+    })
+}
@@ -0,0 +1,63 @@
+//!
+//! Web worker generating and verifying puzzles in the background.
+//!
+//! Receives the difficulty, the seed and whether to report on the generation as JSON
+//! and answers with the difficulty, the seed the puzzle was generated from, its code
+//! and the report if asked for.
+//!
+//! Receives a puzzle code alone to rate a shared puzzle and answers with the code,
+//! the difficulty the solver rates it and whether its solution is unique.
+//!
+
+use hexhashi_logic::{
+    difficulty::Difficulty,
+    hex::{GameParameters, HexSystem},
+    solver::GenerationReport,
+};
+use wasm_bindgen::{JsCast, JsValue, prelude::Closure};
+use web_sys::{DedicatedWorkerGlobalScope, MessageEvent};
+
+fn main() {
+    console_error_panic_hook::set_once();
+    let scope: DedicatedWorkerGlobalScope = js_sys::global().unchecked_into();
+    let s = scope.clone();
+    let onmessage = Closure::<dyn Fn(MessageEvent)>::new(move |evt: MessageEvent| {
+        let Some(data) = evt.data().as_string() else {
+            return;
+        };
+        if let Ok(code) = serde_json::from_str::<String>(&data) {
+            let Ok(system) = HexSystem::from_code(&code) else {
+                return;
+            };
+            let answer = serde_json::to_string(&(code, system.rate(), system.uniqueness()))
+                .unwrap_or_default();
+            let _ = s.post_message(&JsValue::from_str(&answer));
+            return;
+        }
+        let Ok((difficulty, seed, reported)) = serde_json::from_str::<(String, u64, bool)>(&data)
+        else {
+            return;
+        };
+        let Ok(difficulty) = difficulty.parse::<Difficulty>() else {
+            return;
+        };
+        let params = GameParameters::preset(&difficulty, seed);
+        let (seed, system, report) = if reported {
+            let start = js_sys::Date::now();
+            let (seed, system, report) = HexSystem::generate_reported(params);
+            let report = GenerationReport {
+                elapsed_ms: Some(js_sys::Date::now() - start),
+                ..report
+            };
+            (seed, system, Some(report))
+        } else {
+            let (seed, system) = HexSystem::generate_verified(params);
+            (seed, system, None)
+        };
+        let answer = serde_json::to_string(&(difficulty.as_str(), seed, system.to_code(), report))
+            .unwrap_or_default();
+        let _ = s.post_message(&JsValue::from_str(&answer));
+    });
+    scope.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+    onmessage.forget();
+}
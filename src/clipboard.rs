@@ -0,0 +1,38 @@
+use leptos::prelude::*;
+use serde::Serialize;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+
+use crate::share::{is_tauri, try_invoke};
+
+#[derive(Serialize)]
+struct WriteArgs<'a> {
+    text: &'a str,
+}
+
+///
+/// Copy `text` to the clipboard.
+///
+/// The native clipboard is used in Tauri, the clipboard of the browser otherwise.
+///
+pub async fn write_text(text: &str) -> Result<(), JsValue> {
+    if is_tauri() {
+        let args = serde_wasm_bindgen::to_value(&WriteArgs { text })?;
+        try_invoke("plugin:clipboard-manager|write_text", args).await?;
+    } else {
+        JsFuture::from(window().navigator().clipboard().write_text(text)).await?;
+    }
+    Ok(())
+}
+
+///
+/// Get the text on the clipboard.
+///
+pub async fn read_text() -> Result<String, JsValue> {
+    let text = if is_tauri() {
+        try_invoke("plugin:clipboard-manager|read_text", JsValue::UNDEFINED).await?
+    } else {
+        JsFuture::from(window().navigator().clipboard().read_text()).await?
+    };
+    Ok(text.as_string().unwrap_or_default())
+}
@@ -0,0 +1,57 @@
+///
+/// Time played in a game, which stands still while the game is paused.
+///
+/// All times are in milliseconds as given by `performance.now()`.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GameClock {
+    /// Time the game would have started without pauses
+    started: f64,
+    /// Time the current pause began, if paused
+    paused_at: Option<f64>,
+}
+
+impl GameClock {
+    ///
+    /// Start the clock at `now` with `elapsed` time played before.
+    ///
+    pub fn new(now: f64, elapsed: f64) -> Self {
+        GameClock {
+            started: now - elapsed,
+            paused_at: None,
+        }
+    }
+
+    pub fn elapsed(&self, now: f64) -> f64 {
+        self.paused_at.unwrap_or(now) - self.started
+    }
+
+    pub fn pause(&mut self, now: f64) {
+        self.paused_at.get_or_insert(now);
+    }
+
+    pub fn resume(&mut self, now: f64) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.started += now - paused_at;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::GameClock;
+
+    #[test]
+    fn pause() {
+        let mut clock = GameClock::new(1_000.0, 500.0);
+        assert_eq!(clock.elapsed(2_000.0), 1_500.0);
+        clock.pause(2_000.0);
+        // Pausing twice keeps the first pause.
+        clock.pause(2_500.0);
+        assert_eq!(clock.elapsed(9_000.0), 1_500.0);
+        clock.resume(10_000.0);
+        assert_eq!(clock.elapsed(11_000.0), 2_500.0);
+        clock.resume(12_000.0);
+        assert_eq!(clock.elapsed(12_000.0), 3_500.0);
+    }
+}
@@ -0,0 +1,80 @@
+use std::{cell::RefCell, rc::Rc};
+
+use leptos::{logging::log, prelude::*, task::spawn_local};
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    share::{is_tauri, try_invoke},
+    storage::end_session,
+};
+
+/// Event emitted by the Tauri backend instead of closing the window, see `src-tauri/src/lib.rs`
+const CLOSE_REQUESTED_EVENT: &str = "hexhashi://close-requested";
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "event"], js_name = listen, catch)]
+    async fn try_listen(
+        event: &str,
+        handler: &Closure<dyn FnMut(JsValue)>,
+    ) -> Result<JsValue, JsValue>;
+}
+
+/// Function saving the game of a page, with a number telling the pages apart
+type Flush = (u64, Rc<dyn Fn()>);
+
+thread_local! {
+    /// Flushes the game on the page before the window closes
+    static ON_CLOSE: RefCell<Option<Flush>> = const { RefCell::new(None) };
+}
+
+///
+/// Run `flush` when the window of the app is closed, as long as the current component lives.
+///
+/// It must finish synchronously, since the window is closed right after it.
+///
+pub fn on_app_close(flush: impl Fn() + 'static) {
+    let id = ON_CLOSE.with_borrow_mut(|current| {
+        let id = current.as_ref().map_or(0, |(id, _)| id + 1);
+        *current = Some((id, Rc::new(flush)));
+        id
+    });
+    // The next page may have registered its own already.
+    on_cleanup(move || {
+        ON_CLOSE.with_borrow_mut(|current| {
+            if current.as_ref().is_some_and(|(current, _)| *current == id) {
+                *current = None;
+            }
+        })
+    });
+}
+
+///
+/// Save the game and end the session cleanly when the window of the Tauri app is closed.
+///
+/// The backend waits for the window to be closed by `close_window`, but closes it itself after a while,
+/// so that a broken page cannot keep the app open.
+///
+pub fn handle_app_close() {
+    if !is_tauri() {
+        return;
+    }
+    let handler = Closure::<dyn FnMut(JsValue)>::new(|_| {
+        if let Some((_, flush)) = ON_CLOSE.with_borrow(|f| f.clone()) {
+            flush();
+        }
+        end_session();
+        spawn_local(async {
+            if let Err(e) = try_invoke("close_window", JsValue::UNDEFINED).await {
+                log!("Could not close the window: {:?}", e);
+            }
+        });
+    });
+    spawn_local(async move {
+        if let Err(e) = try_listen(CLOSE_REQUESTED_EVENT, &handler).await {
+            log!("Could not listen for closing the window: {:?}", e);
+        }
+        // The listener stays for the lifetime of the page.
+        handler.forget();
+    });
+}
@@ -0,0 +1,90 @@
+use hexhashi_logic::{
+    difficulty::Difficulty,
+    hex::{GameParameters, HexSystem},
+    shape::BoardShape,
+};
+
+/// Shape of the board of the daily puzzles, setting them apart from the quick games
+pub const DAILY_SHAPE: BoardShape = BoardShape::Hexagon;
+/// Number of columns and rows of the board of the daily puzzle on Mondays
+const MONDAY_SIZE: usize = 7;
+const MS_PER_DAY: f64 = 86_400_000.0;
+
+///
+/// Get the number of the current day since the Unix epoch.
+///
+/// Days start at midnight UTC, so that the puzzle changes at the same time everywhere in the world.
+///
+pub fn current_day() -> u64 {
+    day_of(js_sys::Date::now())
+}
+
+fn day_of(ms_since_epoch: f64) -> u64 {
+    (ms_since_epoch / MS_PER_DAY).floor() as u64
+}
+
+///
+/// Get the number of days since the last Monday.
+///
+fn weekday(day: u64) -> usize {
+    // 1970-01-01 was a Thursday.
+    ((day + 3) % 7) as usize
+}
+
+///
+/// Get the difficulty of the puzzle of `day`, rising over the week.
+///
+pub fn daily_difficulty(day: u64) -> Difficulty {
+    match weekday(day) {
+        0 | 1 => Difficulty::Easy,
+        2 | 3 => Difficulty::Medium,
+        4 | 5 => Difficulty::Hard,
+        _ => Difficulty::Extreme,
+    }
+}
+
+///
+/// Get the parameters of the puzzle of `day`, whose board grows from Monday to Sunday.
+///
+fn daily_params(day: u64) -> GameParameters {
+    let size = MONDAY_SIZE + weekday(day);
+    GameParameters {
+        max_columns: size,
+        max_rows: size,
+        ..GameParameters::preset(&daily_difficulty(day), day)
+    }
+}
+
+///
+/// Get the puzzle of `day`, which is the same for all players.
+///
+pub fn daily_puzzle(day: u64) -> HexSystem {
+    HexSystem::generate_shaped(daily_params(day), DAILY_SHAPE)
+}
+
+#[cfg(test)]
+mod test {
+    use hexhashi_logic::difficulty::Difficulty;
+
+    use super::{DAILY_SHAPE, daily_difficulty, daily_params, daily_puzzle, day_of, weekday};
+
+    #[test]
+    fn days() {
+        // Thursday, 1970-01-01
+        assert_eq!(day_of(0.5 * 86_400_000.0), 0);
+        assert_eq!(weekday(0), 3);
+        // Monday, 1970-01-05, and Sunday, 1970-01-11
+        assert_eq!(weekday(4), 0);
+        assert_eq!(weekday(10), 6);
+        assert_eq!(daily_difficulty(4), Difficulty::Easy);
+        assert_eq!(daily_difficulty(10), Difficulty::Extreme);
+        assert!(daily_params(10).max_columns > daily_params(4).max_columns);
+    }
+
+    #[test]
+    fn puzzle() {
+        let puzzle = daily_puzzle(4);
+        assert_eq!(puzzle.shape, DAILY_SHAPE);
+        assert_eq!(puzzle.to_code(), daily_puzzle(4).to_code());
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+
+///
+/// Axis-aligned rectangle in pixels of the canvas.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    pub left: f64,
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+}
+
+impl Rect {
+    ///
+    /// Get the rectangle around the line from `start` to `end` with `margin` on all sides.
+    ///
+    pub fn around_line(start: (f64, f64), end: (f64, f64), margin: f64) -> Self {
+        Rect {
+            left: start.0.min(end.0) - margin,
+            top: start.1.min(end.1) - margin,
+            right: start.0.max(end.0) + margin,
+            bottom: start.1.max(end.1) + margin,
+        }
+    }
+
+    ///
+    /// Get the square around `center` with `radius`.
+    ///
+    pub fn around_point(center: (f64, f64), radius: f64) -> Self {
+        Rect::around_line(center, center, radius)
+    }
+
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.left < other.right
+            && other.left < self.right
+            && self.top < other.bottom
+            && other.top < self.bottom
+    }
+
+    pub fn width(&self) -> f64 {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> f64 {
+        self.bottom - self.top
+    }
+}
+
+///
+/// Part of the canvas that needs to be cleared and redrawn.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum Region {
+    /// The whole canvas, e.g. for the first frame
+    Full,
+    /// Only the rectangles around changed bridges and islands; nothing if empty
+    Rects(Vec<Rect>),
+}
+
+impl Region {
+    pub fn add(&mut self, rect: Rect) {
+        if let Region::Rects(rects) = self {
+            rects.push(rect);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Region::Rects(rects) if rects.is_empty())
+    }
+
+    ///
+    /// Does `rect` overlap the region, so that whatever is inside must be redrawn?
+    ///
+    pub fn touches(&self, rect: &Rect) -> bool {
+        match self {
+            Region::Full => true,
+            Region::Rects(rects) => rects.iter().any(|r| r.intersects(rect)),
+        }
+    }
+}
+
+///
+/// Get the keys whose value differs between `old` and `new`, including keys only in one of them.
+///
+pub fn changed<K: Ord + Copy, V: PartialEq>(old: &BTreeMap<K, V>, new: &BTreeMap<K, V>) -> Vec<K> {
+    let mut keys: Vec<K> = new
+        .iter()
+        .filter(|(key, value)| old.get(key) != Some(value))
+        .map(|(key, _)| *key)
+        .collect();
+    keys.extend(old.keys().filter(|key| !new.contains_key(key)));
+    keys
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use super::{Rect, Region, changed};
+
+    #[test]
+    fn regions() {
+        let line = Rect::around_line((10.0, 50.0), (30.0, 20.0), 2.0);
+        assert_eq!(
+            line,
+            Rect {
+                left: 8.0,
+                top: 18.0,
+                right: 32.0,
+                bottom: 52.0
+            }
+        );
+        assert_eq!((line.width(), line.height()), (24.0, 34.0));
+        let island = Rect::around_point((40.0, 40.0), 5.0);
+        assert!(!line.intersects(&island));
+        assert!(Rect::around_point((40.0, 40.0), 9.0).intersects(&line));
+
+        let mut region = Region::Rects(vec![]);
+        assert!(region.is_empty());
+        region.add(line);
+        assert!(!region.is_empty());
+        assert!(region.touches(&Rect::around_point((20.0, 30.0), 1.0)));
+        assert!(!region.touches(&island));
+        let mut full = Region::Full;
+        full.add(line);
+        assert_eq!(full, Region::Full);
+        assert!(full.touches(&island));
+    }
+
+    #[test]
+    fn changes() {
+        let old = BTreeMap::from([(1, 'a'), (2, 'b'), (3, 'c')]);
+        let new = BTreeMap::from([(1, 'a'), (2, 'x'), (4, 'd')]);
+        assert_eq!(changed(&old, &new), [2, 4, 3]);
+        assert!(changed(&new, &new).is_empty());
+    }
+}
@@ -0,0 +1,162 @@
+use std::time::Duration;
+
+use js_sys::{Array, Uint8Array};
+use leptos::prelude::*;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{Blob, HtmlElement, Url};
+
+use crate::webgl::Mesh;
+
+/// Time the browser gets to start a download before its data is released
+const REVOKE_DELAY: Duration = Duration::from_secs(10);
+
+/// Rows of the digits 0 to 9 in a font of 3 × 5 cells, the leftmost cell in the highest bit
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+///
+/// Add `number` centered at `center` with digits `height` pixels high to `mesh`.
+///
+/// Images drawn without a canvas have no fonts, so the digits are made of small squares.
+///
+pub fn draw_number(
+    mesh: &mut Mesh,
+    center: (f64, f64),
+    height: f64,
+    number: usize,
+    color: [f32; 4],
+) {
+    let cell = height / 5.0;
+    let digits: Vec<usize> = number
+        .to_string()
+        .bytes()
+        .map(|b| (b - b'0') as usize)
+        .collect();
+    // One empty column between the digits
+    let width = (digits.len() * 4 - 1) as f64 * cell;
+    let (left, top) = (center.0 - width / 2.0, center.1 - height / 2.0);
+    for (i, digit) in digits.into_iter().enumerate() {
+        for (row, bits) in DIGITS[digit].iter().enumerate() {
+            for column in 0..3 {
+                if bits & (0b100 >> column) != 0 {
+                    let x = left + (i * 4 + column) as f64 * cell;
+                    mesh.rect((x, top + row as f64 * cell), cell, cell, color);
+                }
+            }
+        }
+    }
+}
+
+///
+/// Encode `frames` of `width` × `height` RGBA pixels as an animated PNG that loops forever.
+///
+/// Each frame is shown for its delay in milliseconds.
+///
+pub fn encode_apng(frames: &[(Vec<u8>, u16)], width: u32, height: u32) -> Result<Vec<u8>, String> {
+    let mut image = Vec::new();
+    let mut encoder = png::Encoder::new(&mut image, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .set_animated(frames.len() as u32, 0)
+        .map_err(|e| e.to_string())?;
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    for (pixels, delay_ms) in frames {
+        writer
+            .set_frame_delay(*delay_ms, 1000)
+            .map_err(|e| e.to_string())?;
+        writer.write_image_data(pixels).map_err(|e| e.to_string())?;
+    }
+    writer.finish().map_err(|e| e.to_string())?;
+    Ok(image)
+}
+
+///
+/// Let the browser save `data` as a file called `name`.
+///
+pub fn download(data: &[u8], name: &str) -> Result<(), JsValue> {
+    let parts = Array::of1(&Uint8Array::from(data));
+    let blob = Blob::new_with_u8_array_sequence(&parts)?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+    let link = document().create_element("a")?;
+    link.set_attribute("href", &url)?;
+    link.set_attribute("download", name)?;
+    link.unchecked_ref::<HtmlElement>().click();
+    // Some browsers only read the data after the click has been handled.
+    set_timeout(
+        move || {
+            let _ = Url::revoke_object_url(&url);
+        },
+        REVOKE_DELAY,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{draw_number, encode_apng};
+    use crate::webgl::Mesh;
+
+    #[test]
+    fn number() {
+        let black = [0.0, 0.0, 0.0, 1.0];
+        let mut mesh = Mesh::default();
+        draw_number(&mut mesh, (3.5, 2.5), 5.0, 1, black);
+        let pixels = mesh.rasterize(7, 5, [1.0; 4]);
+        let row = |y: usize| -> String {
+            (0..7)
+                .map(|x| {
+                    if pixels[(y * 7 + x) * 4] == 0 {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect()
+        };
+        assert_eq!(row(0), "...#...");
+        assert_eq!(row(1), "..##...");
+        assert_eq!(row(4), "..###..");
+        // Two digits are twice as wide with a gap.
+        let mut mesh = Mesh::default();
+        draw_number(&mut mesh, (3.5, 2.5), 5.0, 11, black);
+        let pixels = mesh.rasterize(7, 5, [1.0; 4]);
+        let bottom: String = (0..7)
+            .map(|x| {
+                if pixels[(4 * 7 + x) * 4] == 0 {
+                    '#'
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        assert_eq!(bottom, "###.###");
+    }
+
+    #[test]
+    fn apng() {
+        let frames = vec![(vec![255; 2 * 2 * 4], 500), (vec![0; 2 * 2 * 4], 2000)];
+        let image = encode_apng(&frames, 2, 2).unwrap();
+        let decoder = png::Decoder::new(std::io::Cursor::new(image));
+        let mut reader = decoder.read_info().unwrap();
+        let animation = reader.info().animation_control.unwrap();
+        assert_eq!(animation.num_frames, 2);
+        assert_eq!(animation.num_plays, 0);
+        let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+        reader.next_frame(&mut pixels).unwrap();
+        assert_eq!(pixels, frames[0].0);
+        reader.next_frame(&mut pixels).unwrap();
+        assert_eq!(pixels, frames[1].0);
+        assert!(encode_apng(&[(vec![0; 3], 100)], 2, 2).is_err());
+    }
+}
@@ -0,0 +1,36 @@
+use leptos::{ev::Custom, html::Dialog, prelude::*};
+use leptos_use::use_event_listener;
+use wasm_bindgen::JsCast;
+use web_sys::{Event, HtmlElement};
+
+///
+/// Show the `dialog` as modal whenever it is mounted, so that the focus stays within.
+///
+/// The focus moves to the element marked `autofocus` when the dialog is shown
+/// and back to the element that had it before when the dialog is removed.
+/// Escape calls `on_close` instead of closing the dialog, which is up to the owner.
+///
+pub fn use_modal(dialog: NodeRef<Dialog>, on_close: impl Fn() + 'static) {
+    let previous = StoredValue::new_local(None::<HtmlElement>);
+    Effect::new(move |_| match dialog.get() {
+        Some(dialog) => {
+            if !dialog.open() {
+                previous.set_value(
+                    document()
+                        .active_element()
+                        .and_then(|element| element.dyn_into::<HtmlElement>().ok()),
+                );
+                let _ = dialog.show_modal();
+            }
+        }
+        None => {
+            if let Some(Some(element)) = previous.try_update_value(|p| p.take()) {
+                let _ = element.focus();
+            }
+        }
+    });
+    let _ = use_event_listener(dialog, Custom::<Event>::new("cancel"), move |evt| {
+        evt.prevent_default();
+        on_close();
+    });
+}
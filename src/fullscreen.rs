@@ -0,0 +1,29 @@
+use leptos::prelude::*;
+use serde::Serialize;
+use wasm_bindgen::JsValue;
+
+use crate::share::{is_tauri, try_invoke};
+
+#[derive(Serialize)]
+struct FullscreenArgs {
+    fullscreen: bool,
+}
+
+///
+/// Enter or leave full screen.
+///
+/// The native window is switched in Tauri, the page of the browser otherwise.
+///
+pub async fn set_fullscreen(fullscreen: bool) -> Result<(), JsValue> {
+    if is_tauri() {
+        let args = serde_wasm_bindgen::to_value(&FullscreenArgs { fullscreen })?;
+        try_invoke("set_fullscreen", args).await?;
+    } else if fullscreen {
+        if let Some(root) = document().document_element() {
+            root.request_fullscreen()?;
+        }
+    } else if document().fullscreen_element().is_some() {
+        document().exit_fullscreen();
+    }
+    Ok(())
+}
@@ -1,65 +1,361 @@
 use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, BTreeSet},
     f64::consts::PI,
     fmt::Display,
+    rc::Rc,
     str::FromStr,
     sync::{Arc, RwLock},
 };
 
-use hexhashi_logic::hex::{BridgeError, BridgeState, GameParameters, HexSystem, Island};
+use hexhashi_logic::{
+    analysis::Analysis,
+    coop::{Operation, OperationLog},
+    difficulty::Difficulty,
+    hex::{BridgeError, BridgeState, GameParameters, HexSystem, Island, Progress},
+    hotseat::HotSeat,
+    layout::{Orientation, ViewTransform},
+    score::{Grade, compute_score, grade, minimal_moves},
+    session::{GameSession, Hint, HintLevel},
+    solver::Technique,
+    variant::GameVariant,
+};
 use leptos::{
-    ev::{mousedown, mouseup},
-    html::Canvas,
+    ev::{
+        contextmenu, dblclick, fullscreenchange, keydown, message, mousedown, mousemove, mouseup,
+        pagehide, pointerdown, touchend, touchmove, touchstart, visibilitychange, wheel,
+    },
+    html::{Button, Canvas, Dialog, Div},
     logging::log,
     prelude::*,
+    task::spawn_local,
 };
 use leptos_router::hooks::use_params;
-use leptos_use::{UseMouseInElementReturn, use_event_listener, use_mouse_in_element};
+use leptos_use::{
+    UseEventListenerOptions, UseMouseInElementReturn, UseWindowSizeReturn, use_debounce_fn,
+    use_event_listener, use_event_listener_with_options, use_interval_fn, use_mouse_in_element,
+    use_raf_fn, use_window_size,
+};
 use serde::{Deserialize, Serialize};
-use wasm_bindgen::JsCast;
-use web_sys::CanvasRenderingContext2d;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{
+    BroadcastChannel, CanvasRenderingContext2d, HtmlCanvasElement, MessageEvent, TouchEvent,
+};
+
+use crate::{
+    animation::{AnimationQueue, BlockedMove},
+    clipboard::write_text,
+    clock::GameClock,
+    close::on_app_close,
+    daily::{current_day, daily_difficulty, daily_puzzle},
+    dirty::{Rect, Region, changed},
+    export::{download, draw_number, encode_apng},
+    focus::use_modal,
+    fullscreen::set_fullscreen,
+    haptics::{self, vibrate},
+    help::HelpOverlay,
+    input::{Action, Binding, DoubleTap, Gesture},
+    library::{LibraryPuzzle, Source, load_library, update_library},
+    packs::{PackPuzzle, load_packs, mark_solved},
+    perf::{FRAME, HIT_TEST, HOVER, measure},
+    prefetch::{next_variant_puzzle, prefetch},
+    settings::{GridDisplay, Renderer, Settings},
+    share::{CUSTOM_PATH, ImportGame, custom_url, is_tauri, qr_svg, share_url},
+    slots::SaveSlotForm,
+    sound::{Sound, play},
+    stats::{SessionHud, count_started, load_stats, today, update_stats},
+    storage::{
+        SavedGame, clear_game, is_reloaded_during_game, load_game, mark_active_page, save_game,
+    },
+    telemetry::{SolveReport, send_report},
+    theme::{IslandProgress, Theme},
+    trainer::explanation,
+    webgl::{GpuRenderer, Mesh, parse_color},
+    weekly::{
+        Replay, WeeklyEntry, current_week, record_weekly, seed_week, weekly_difficulty,
+        weekly_puzzle, weekly_seed,
+    },
+};
 
 use leptos::Params;
 use leptos_router::params::Params;
 
 const LINE_HEIGHT: f64 = 50.0;
+/// Pause after the last move before the game is saved
+const SAVE_DELAY_MS: f64 = 500.0;
+/// Interval of saving the time played
+const AUTOSAVE_INTERVAL_MS: u64 = 10_000;
+/// Rows the board grows by in endless mode, whenever it is solved
+const ENDLESS_ROWS: usize = 4;
+/// Width of the small previews of boards in pixels
+pub const THUMBNAIL_SIZE: u32 = 120;
 const ISLAND_SIZE: f64 = 15.0;
-const BRIDGE_COLOR: &str = "dodgerblue";
-const GRID_COLOR: &str = "dimgrey";
-const ISLAND_COLOR: (&str, &str) = ("white", "black");
-const UNFINISHED_ISLAND_COLOR: (&str, &str) = ("gold", "dimgray");
-const FINISHED_ISLAND_COLOR: (&str, &str) = ("green", "white");
+/// Width and height of the timeline of the analysis in pixels
+const TIMELINE_SIZE: (u32, u32) = (300, 40);
+/// Font size in pt below which islands show their completion instead of numbers
+const MIN_READABLE_FONT_SIZE: f64 = 6.0;
+/// Number of moves and islands listed as taking longest
+const SLOWEST_COUNT: usize = 3;
 const HOVER_BRIDGE: &str = "rgba(143, 188, 143, 0.2)";
 const HOVER_ISLAND: &str = "rgba(143, 188, 143, 0.50)";
+/// Outline of a hovered island that can still get its number of bridges
+const SATISFIABLE_ISLAND: &str = "rgba(0, 128, 0, 0.6)";
+/// Outline of a hovered island that cannot get its number of bridges anymore
+const UNSATISFIABLE_ISLAND: &str = "rgba(255, 0, 0, 0.6)";
+const LOCK_MARKER_SIZE: f64 = 4.0;
+const WRONG_BRIDGE: &str = "rgba(255, 0, 0, 0.5)";
+const MISSING_BRIDGE: &str = "rgba(0, 112, 255, 0.5)";
+const DIMMED_BRIDGE: &str = "rgba(128, 128, 128, 0.2)";
+/// Players of a race, each shown on their own board to spectators
+#[cfg(feature = "multiplayer")]
+const RACE_PLAYERS: usize = 2;
+/// Message of a client joining a co-op room or watching a race, which the players answer with all operations so far
+const SYNC_REQUEST: &str = "sync";
+/// Width and height the exported replay fits into in pixels
+const REPLAY_SIZE: f64 = 480.0;
+/// Time each move is shown in the exported replay in milliseconds
+const REPLAY_FRAME_MS: u16 = 300;
+/// Time the solved board is shown before the exported replay starts over in milliseconds
+const REPLAY_END_MS: u16 = 3000;
+/// Most frames of an exported replay, longer games skip moves
+const REPLAY_MAX_FRAMES: usize = 150;
+const DIRECTION_PREVIEW: &str = "rgba(30, 144, 255, 0.4)";
+const PENCIL_MARK: &str = "rgba(105, 105, 105, 0.8)";
+const BLOCKED_BRIDGE: &str = "rgba(255, 0, 0, 0.8)";
+/// Largest offset of a shaking blocked bridge in line widths
+const SHAKE_AMPLITUDE: f64 = 3.0;
+/// Bridges whose distances to a click differ by less are equally close.
+const AMBIGUOUS_DISTANCE: f64 = 2.0;
+/// Distance of the buttons of the bridge chooser from the click
+const CHOOSER_RADIUS: f64 = 45.0;
+/// Number of mistakes that lose the game in hardcore mode
+const MISTAKE_LIMIT: u32 = 3;
+const MARGIN: f64 = 75.0;
+/// Largest zoom into the neighborhood of a focused island, so that a lonely island is not blown up
+const MAX_FOCUS_ZOOM: f64 = 3.0;
+/// Veil over the parts of the board outside the focused neighborhood
+const FOCUS_DIM_ALPHA: f64 = 0.75;
+/// Windows narrower than this in pixels get the compact layout for phones
+const COMPACT_WIDTH: f64 = 600.0;
+/// Factor of the zoom buttons
+const ZOOM_STEP: f64 = 1.25;
+/// Smallest height of the board in pixels, if the window does not decide it
+const MIN_BOARD_HEIGHT: f64 = 600.0;
+/// Range of the zoom by gestures and buttons
+const MIN_ZOOM: f64 = 0.1;
+const MAX_ZOOM: f64 = 5.0;
+/// Zoom of a double tap next to the bridges
+const DOUBLE_TAP_ZOOM: f64 = 2.0;
+/// Farthest a finger may move in pixels for a tap
+const TAP_SLOP: f64 = 10.0;
+
+///
+/// Dimensions of the board as configured in the settings.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Layout {
+    orientation: Orientation,
+    /// Factor for the distances between islands
+    scale: f64,
+    island_size: f64,
+    /// Factor for the widths of all lines
+    line_width: f64,
+    /// Font size of the numbers in pt
+    font_size: f64,
+    /// Rotation and mirroring of the displayed board
+    transform: ViewTransform,
+    /// Shift of the board in pixels, to bring a focused island into view
+    offset: (f64, f64),
+}
+
+impl Layout {
+    fn new(settings: &Settings) -> Self {
+        Layout {
+            orientation: settings.orientation,
+            scale: settings.scale,
+            island_size: ISLAND_SIZE * settings.scale * settings.island_scale,
+            line_width: settings.scale * settings.line_scale,
+            font_size: settings.font_size * settings.scale,
+            transform: ViewTransform::default(),
+            offset: (0.0, 0.0),
+        }
+    }
+
+    ///
+    /// Get the layout scaled so that the board of `system` fills `width` × `height` pixels.
+    ///
+    fn fitted(self, system: &HexSystem, width: f64, height: f64) -> Self {
+        self.scaled(self.fit_factor(system, width, height))
+    }
+
+    ///
+    /// Get the factor to scale the layout by, so that the board of `system` fills `width` × `height` pixels.
+    ///
+    fn fit_factor(self, system: &HexSystem, width: f64, height: f64) -> f64 {
+        let (board_width, board_height) = get_board_size(system, self);
+        f64::min(width / board_width, height / board_height)
+    }
+
+    ///
+    /// Get the layout with all distances and sizes multiplied by `factor`.
+    ///
+    fn scaled(self, factor: f64) -> Self {
+        Layout {
+            orientation: self.orientation,
+            scale: self.scale * factor,
+            island_size: self.island_size * factor,
+            line_width: self.line_width * factor,
+            font_size: self.font_size * factor,
+            transform: self.transform,
+            offset: self.offset,
+        }
+    }
+
+    ///
+    /// Get the layout zoomed and shifted, so that the island at `index` and its neighbors fill `width` × `height` pixels.
+    ///
+    fn focused(self, system: &HexSystem, index: usize, width: f64, height: f64) -> Self {
+        let unshifted = Layout {
+            offset: (0.0, 0.0),
+            ..self
+        };
+        let ((left, top), (right, bottom)) = neighborhood_bounds(system, index, unshifted);
+        let factor = f64::min(width / (right - left), height / (bottom - top)).min(MAX_FOCUS_ZOOM);
+        let zoomed = unshifted.scaled(factor);
+        let ((left, top), (right, bottom)) = neighborhood_bounds(system, index, zoomed);
+        Layout {
+            offset: ((width - left - right) / 2.0, (height - top - bottom) / 2.0),
+            ..zoomed
+        }
+    }
+}
+
+///
+/// Get the (top left, bottom right) corners of the area covered by the island at `index`,
+/// its neighbors and the bridges between them.
+///
+fn neighborhood_bounds(
+    system: &HexSystem,
+    index: usize,
+    layout: Layout,
+) -> ((f64, f64), (f64, f64)) {
+    let margin = 2.0 * layout.island_size;
+    std::iter::once(index)
+        .chain(system.get_connected_islands(index))
+        .map(|i| get_coordinates_from_index(system, i, layout))
+        .fold(
+            (
+                (f64::INFINITY, f64::INFINITY),
+                (f64::NEG_INFINITY, f64::NEG_INFINITY),
+            ),
+            |((left, top), (right, bottom)), (x, y)| {
+                (
+                    (left.min(x - margin), top.min(y - margin)),
+                    (right.max(x + margin), bottom.max(y + margin)),
+                )
+            },
+        )
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout::new(&Settings::default())
+    }
+}
+
+///
+/// Get the time in milliseconds a puzzle of `difficulty` is expected to be solved in.
+///
+pub fn get_par_time(difficulty: &Difficulty) -> f64 {
+    let minutes = match difficulty {
+        Difficulty::Easy => 3.0,
+        Difficulty::Medium => 6.0,
+        Difficulty::Hard => 10.0,
+        Difficulty::Extreme => 20.0,
+    };
+    minutes * 60_000.0
+}
+
+///
+/// Get the score for solving a puzzle of `difficulty` without any penalties.
+///
+pub fn get_base_score(difficulty: &Difficulty) -> u32 {
+    match difficulty {
+        Difficulty::Easy => 1000,
+        Difficulty::Medium => 2000,
+        Difficulty::Hard => 3000,
+        Difficulty::Extreme => 5000,
+    }
+}
+
+///
+/// How a game is played
+///
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum GameMode {
+    /// Time is shown, but there is no limit.
+    #[default]
+    Normal,
+    /// No timer and relaxed colors
+    Zen,
+    /// Puzzle must be solved within par time.
+    Timed,
+    /// Puzzle of the week, the same for all players
+    Weekly,
+    /// Puzzle of the day on a hexagon board, the same for all players
+    Daily,
+    /// Two players take turns on the same device.
+    HotSeat,
+    /// Several clients edit the same board.
+    Coop,
+    /// Two clients solve the same puzzle on their own boards, watched by spectators.
+    Race,
+    /// The board grows whenever it is solved.
+    Endless,
+}
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
-pub enum Difficulty {
-    Easy,
-    Medium,
-    Hard,
-    Extreme,
+impl Display for GameMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameMode::Normal => f.write_str("Normal"),
+            GameMode::Zen => f.write_str("Zen"),
+            GameMode::Timed => f.write_str("Timed"),
+            GameMode::Weekly => f.write_str("Weekly"),
+            GameMode::Daily => f.write_str("Daily"),
+            GameMode::HotSeat => f.write_str("Hot-seat"),
+            GameMode::Coop => f.write_str("Co-op"),
+            GameMode::Race => f.write_str("Race"),
+            GameMode::Endless => f.write_str("Endless"),
+        }
+    }
 }
 
 #[derive(Debug)]
-pub struct DifficultyConversionError;
+pub struct GameModeConversionError;
 
-impl Display for DifficultyConversionError {
+impl Display for GameModeConversionError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str("Cannot convert to difficulty")
+        f.write_str("Cannot convert to game mode")
     }
 }
 
-impl std::error::Error for DifficultyConversionError {}
+impl std::error::Error for GameModeConversionError {}
 
-impl FromStr for Difficulty {
-    type Err = DifficultyConversionError;
+impl FromStr for GameMode {
+    type Err = GameModeConversionError;
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
-            "easy" => Ok(Difficulty::Easy),
-            "medium" => Ok(Difficulty::Medium),
-            "hard" => Ok(Difficulty::Hard),
-            "extreme" => Ok(Difficulty::Extreme),
-            _ => Err(DifficultyConversionError),
+            "normal" => Ok(GameMode::Normal),
+            "zen" => Ok(GameMode::Zen),
+            "timed" => Ok(GameMode::Timed),
+            "weekly" => Ok(GameMode::Weekly),
+            "daily" => Ok(GameMode::Daily),
+            "hot-seat" => Ok(GameMode::HotSeat),
+            "co-op" => Ok(GameMode::Coop),
+            "race" => Ok(GameMode::Race),
+            "endless" => Ok(GameMode::Endless),
+            _ => Err(GameModeConversionError),
         }
     }
 }
@@ -67,152 +363,2455 @@ impl FromStr for Difficulty {
 #[derive(Params, Debug, PartialEq)]
 pub struct StartGameArgs {
     pub difficulty: Option<Difficulty>,
+    pub mode: Option<GameMode>,
+    pub variant: Option<GameVariant>,
 }
 
 #[component]
 pub fn Game() -> impl IntoView {
-    let seed = window().performance().unwrap().now() as u64;
-    log!("{}", seed);
+    // The puzzle of a custom link is in the fragment rather than generated.
+    if window().location().pathname().ok().as_deref() == Some(CUSTOM_PATH) {
+        return view! { <ImportGame/> }.into_any();
+    }
+    let (difficulty, mode) = get_difficulty_and_mode();
+    let variant = use_params::<StartGameArgs>()
+        .read_untracked()
+        .as_ref()
+        .ok()
+        .and_then(|p| p.variant)
+        .unwrap_or_default();
+    let (difficulty, (seed, system)) = match mode {
+        GameMode::Weekly => {
+            let week = current_week();
+            let (difficulty, seed) = (weekly_difficulty(week), weekly_seed(week));
+            (difficulty, (seed, weekly_puzzle(week)))
+        }
+        GameMode::Daily => {
+            let day = current_day();
+            (daily_difficulty(day), (day, daily_puzzle(day)))
+        }
+        _ => {
+            let puzzle = next_variant_puzzle(&difficulty, variant);
+            (difficulty, puzzle)
+        }
+    };
+    // Puzzles are played one after another without going back to the menu.
+    let puzzle = RwSignal::new((seed, system));
+    let d = difficulty.clone();
+    let on_next = (!matches!(mode, GameMode::Weekly | GameMode::Daily))
+        .then(|| Callback::new(move |_| puzzle.set(next_variant_puzzle(&d, variant))));
 
-    let params = get_difficulty(seed);
+    (move || {
+        let (seed, system) = puzzle.get();
+        log!("{}", seed);
+        if on_next.is_some() && variant == GameVariant::Classic {
+            prefetch(&difficulty);
+        }
+        let session = GameSession::new(system);
+        count_started(&difficulty, &mode);
+        let hot_seat = (mode == GameMode::HotSeat).then(|| HotSeat::new(2));
+        let (difficulty, mode) = (difficulty.clone(), mode.clone());
 
-    let game = Arc::new(RwLock::new(HexSystem::generate_new(params)));
+        view! { <Board difficulty mode session elapsed_ms=0.0 hot_seat seed=Some(seed) on_next/> }
+    })
+    .into_any()
+}
 
-    let canvas = NodeRef::<Canvas>::new();
+#[cfg(feature = "multiplayer")]
+#[derive(Params, Debug, PartialEq)]
+pub struct CoopArgs {
+    pub difficulty: Option<Difficulty>,
+    pub room: Option<u64>,
+}
 
-    let background_color = Memo::new(move |_| {
-        if let Some(c) = window()
-            .document()
-            .unwrap()
-            .get_elements_by_tag_name("html")
-            .item(0)
-        {
-            window()
-                .get_computed_style(&c)
+///
+/// Play the puzzle of `room` together with the other clients that opened the same room.
+///
+#[cfg(feature = "multiplayer")]
+#[component]
+pub fn CoopGame() -> impl IntoView {
+    let (difficulty, room) = room_args();
+    let session = GameSession::new(room_puzzle(&difficulty, room));
+    let mode = GameMode::Coop;
+    count_started(&difficulty, &mode);
+    let link = room_link("coop", &difficulty, room);
+
+    view! {
+        <p class="coop">"Open " <code>{link}</code> " in another window to play together."</p>
+        <Board
+            difficulty
+            mode
+            session
+            elapsed_ms=0.0
+            hot_seat=None
+            coop=room.to_string()
+            seed=Some(room)
+        />
+    }
+}
+
+///
+/// Race another client on the puzzle of `room`, each on their own board.
+///
+#[cfg(feature = "multiplayer")]
+#[component]
+pub fn RaceGame() -> impl IntoView {
+    let (difficulty, room) = room_args();
+    let session = GameSession::new(room_puzzle(&difficulty, room));
+    let mode = GameMode::Race;
+    count_started(&difficulty, &mode);
+    let link = room_link("race", &difficulty, room);
+
+    view! {
+        <p class="coop">
+            "Open " <code>{link.clone()}</code> " in another window to race, or "
+            <code>{format!("{}/watch", link)}</code> " to watch."
+        </p>
+        <Board
+            difficulty
+            mode
+            session
+            elapsed_ms=0.0
+            hot_seat=None
+            coop=room.to_string()
+            seed=Some(room)
+        />
+    }
+}
+
+///
+/// Board of one of the players of a race as seen by a spectator.
+///
+#[cfg(feature = "multiplayer")]
+struct RaceBoard {
+    game: Arc<RwLock<GameSession>>,
+    operations: RwLock<OperationLog>,
+    revision: ReadSignal<usize>,
+    set_revision: WriteSignal<usize>,
+}
+
+///
+/// Watch both players of the race in `room` without taking part.
+///
+/// The players send the moves made before the spectator joined, so late spectators see the whole boards.
+///
+#[cfg(feature = "multiplayer")]
+#[component]
+pub fn RaceSpectator() -> impl IntoView {
+    let (difficulty, room) = room_args();
+    let system = room_puzzle(&difficulty, room);
+    let boards: Rc<Vec<RaceBoard>> = Rc::new(
+        (0..RACE_PLAYERS)
+            .map(|_| {
+                let (revision, set_revision) = signal(0usize);
+                RaceBoard {
+                    game: Arc::new(RwLock::new(GameSession::new(system.clone()))),
+                    // Spectators only apply the operations of the players and never record any.
+                    operations: RwLock::new(OperationLog::new(0)),
+                    revision,
+                    set_revision,
+                }
+            })
+            .collect(),
+    );
+    let seats = RefCell::new(vec![]);
+
+    let channel = room_channel(&GameMode::Race, &room.to_string());
+    if let Some(channel) = &channel {
+        let b = boards.clone();
+        let _ = use_event_listener(channel.clone(), message, move |evt| {
+            let Some(operation) = parse_operation(&evt) else {
+                return;
+            };
+            let Some(seat) = race_seat(&mut seats.borrow_mut(), operation.client) else {
+                return;
+            };
+            let board = &b[seat];
+            if board
+                .operations
+                .write()
                 .unwrap()
-                .and_then(|s| s.get_property_value("background-color").ok())
+                .apply(&mut board.game.write().unwrap(), operation)
+            {
+                board.set_revision.update(|r| *r += 1);
+            }
+        });
+        let _ = channel.post_message(&JsValue::from_str(SYNC_REQUEST));
+    }
+    let channel = StoredValue::new_local(channel);
+    on_cleanup(move || {
+        if let Some(Some(channel)) = channel.try_get_value() {
+            channel.close();
+        }
+    });
+
+    let settings = expect_context::<RwSignal<Settings>>();
+    let show_coordinates = Memo::new(move |_| settings.read().show_coordinates);
+    let settings = settings.get_untracked();
+    let palette = settings.theme.clone().unwrap_or_default();
+    let background_color = background_memo(palette.background.clone());
+    let layout = RwSignal::new(Layout::new(&settings));
+    let players = boards
+        .iter()
+        .zip(["Player 1", "Player 2"])
+        .map(|(board, caption)| {
+            let revision = board.revision;
+            let g = board.game.clone();
+            let progress = Memo::new(move |_| {
+                revision.track();
+                g.read().unwrap().system().progress()
+            });
+            let g = board.game.clone();
+            let solved = Memo::new(move |_| {
+                revision.track();
+                g.read().unwrap().system().is_solved()
+            });
+            view! {
+                <div>
+                    <p>
+                        <span class="progress">
+                            <progress
+                                max=move || progress.get().total
+                                value=move || progress.get().complete
+                            ></progress>
+                            {move || {
+                                let Progress { complete, total } = progress.get();
+                                if solved.get() {
+                                    "Solved".to_string()
+                                } else {
+                                    format!("{}/{} islands complete", complete, total)
+                                }
+                            }}
+                        </span>
+                    </p>
+                    <ComparisonBoard
+                        game=board.game.clone()
+                        revision
+                        caption
+                        layout
+                        palette=palette.clone()
+                        background_color
+                        show_coordinates
+                        grid=settings.grid
+                        bridge_badges=settings.bridge_badges
+                    />
+                </div>
+            }
+        })
+        .collect_view();
+
+    view! {
+        <div><span class="menu">hexhashi</span><a class="menu" href="/">Back</a></div>
+        <p class="coop">{format!("Watching race {} ({})", room, difficulty)}</p>
+        <div class="split active">{players}</div>
+    }
+}
+
+///
+/// Get the board of the race player `client` in `seats`, taking the next free one for new players.
+///
+/// Returns `None` if all boards are taken by other players.
+///
+#[cfg(feature = "multiplayer")]
+fn race_seat(seats: &mut Vec<u32>, client: u32) -> Option<usize> {
+    match seats.iter().position(|c| *c == client) {
+        Some(seat) => Some(seat),
+        None if seats.len() < RACE_PLAYERS => {
+            seats.push(client);
+            Some(seats.len() - 1)
+        }
+        None => None,
+    }
+}
+
+///
+/// Get the difficulty and the room of a co-op game or race from the path.
+///
+#[cfg(feature = "multiplayer")]
+fn room_args() -> (Difficulty, u64) {
+    let params = use_params::<CoopArgs>();
+    let params = params.read_untracked();
+    let params = params.as_ref().ok();
+    let difficulty = params
+        .and_then(|p| p.difficulty.clone())
+        .unwrap_or(Difficulty::Easy);
+    (difficulty, params.and_then(|p| p.room).unwrap_or_default())
+}
+
+///
+/// Get the puzzle of `room`, which all of its clients generate alike.
+///
+#[cfg(feature = "multiplayer")]
+fn room_puzzle(difficulty: &Difficulty, room: u64) -> HexSystem {
+    HexSystem::generate_new(GameParameters::preset(difficulty, room))
+}
+
+///
+/// Get the address other clients open to join `room` of `kind`, i.e. `coop` or `race`.
+///
+#[cfg(feature = "multiplayer")]
+fn room_link(kind: &str, difficulty: &Difficulty, room: u64) -> String {
+    format!(
+        "{}/{}/{}/{}",
+        window().location().origin().unwrap_or_default(),
+        kind,
+        difficulty.as_str(),
+        room
+    )
+}
+
+///
+/// Open the channel the clients of the co-op or race `room` exchange their operations on.
+///
+#[cfg(feature = "multiplayer")]
+fn room_channel(mode: &GameMode, room: &str) -> Option<BroadcastChannel> {
+    let kind = if *mode == GameMode::Race {
+        "race"
+    } else {
+        "coop"
+    };
+    BroadcastChannel::new(&format!("hexhashi.{}.{}", kind, room)).ok()
+}
+
+fn post_operation(channel: &BroadcastChannel, operation: &Operation) {
+    let json = serde_json::to_string(operation).unwrap_or_default();
+    let _ = channel.post_message(&JsValue::from_str(&json));
+}
+
+fn parse_operation(evt: &MessageEvent) -> Option<Operation> {
+    evt.data()
+        .as_string()
+        .and_then(|data| serde_json::from_str(&data).ok())
+}
+
+///
+/// Tell that co-op games and races are not part of this build.
+///
+#[cfg(not(feature = "multiplayer"))]
+#[component]
+pub fn CoopGame() -> impl IntoView {
+    view! {
+        <div><span class="menu">hexhashi</span><a class="menu" href="/">Back</a></div>
+        <p>"Co-op games and races are only available in the desktop app."</p>
+    }
+}
+
+#[cfg(not(feature = "multiplayer"))]
+pub use CoopGame as RaceGame;
+
+#[cfg(not(feature = "multiplayer"))]
+pub use CoopGame as RaceSpectator;
+
+///
+/// Continue the game stored by the last session.
+///
+#[component]
+pub fn ContinueGame() -> impl IntoView {
+    match load_game() {
+        Some(SavedGame {
+            difficulty,
+            mode,
+            session,
+            elapsed_ms,
+            hot_seat,
+            pack,
+            seed,
+        }) => match pack {
+            Some(pack) => {
+                view! { <Board difficulty mode session elapsed_ms hot_seat pack seed/> }.into_any()
+            }
+            None => view! { <Board difficulty mode session elapsed_ms hot_seat seed/> }.into_any(),
+        },
+        None => view! {
+            <div><span class="menu">hexhashi</span><a class="menu" href="/">Back</a></div>
+            <p>"There is no game to continue."</p>
+        }
+        .into_any(),
+    }
+}
+
+///
+/// The board of a game of `difficulty` in `mode` that has already been played for `elapsed_ms`.
+///
+#[component]
+pub fn Board(
+    difficulty: Difficulty,
+    mode: GameMode,
+    mut session: GameSession,
+    elapsed_ms: f64,
+    hot_seat: Option<HotSeat>,
+    /// Room shared with other clients in co-op and race mode
+    #[prop(optional)]
+    coop: Option<String>,
+    /// Puzzle of a downloaded pack
+    #[prop(optional)]
+    pack: Option<PackPuzzle>,
+    /// Seed the puzzle was generated from, if it is known
+    #[prop(optional_no_strip)]
+    seed: Option<u64>,
+    /// Start the next puzzle in place instead of loading the page again
+    #[prop(optional_no_strip)]
+    on_next: Option<Callback<()>>,
+    /// Technique practiced in the trainer
+    #[prop(optional_no_strip)]
+    technique: Option<Technique>,
+) -> impl IntoView {
+    // Turns of the players in hot-seat mode
+    let turns = RwSignal::new(hot_seat);
+    let settings_signal = expect_context::<RwSignal<Settings>>();
+    let show_coordinates = Memo::new(move |_| settings_signal.read().show_coordinates);
+    let settings = settings_signal.get_untracked();
+    let Settings {
+        haptics,
+        sound_pack,
+        effects_volume,
+        fanfare_volume,
+        hardcore,
+        practice,
+        grid,
+        dim_unavailable,
+        island_status,
+        bridge_badges,
+        blocked_feedback_ms,
+        limit_bridge_length,
+        idle_pause_s,
+        left_handed,
+        renderer,
+        ref player_name,
+        ref leaderboard_url,
+        telemetry,
+        ref telemetry_url,
+        ..
+    } = settings;
+    let (player_name, leaderboard_url) = (player_name.clone(), leaderboard_url.clone());
+    // Nothing is reported unless the player opted in.
+    let telemetry_url = (telemetry && !telemetry_url.is_empty()).then(|| telemetry_url.clone());
+    let max_bridge_length = session.system().max_bridge_length;
+    let variant = session.system().variant();
+    // All clients of a co-op room must offer the same bridges.
+    if limit_bridge_length && mode != GameMode::Coop {
+        session.limit_bridge_length();
+    }
+    let game = Arc::new(RwLock::new(session));
+    // Regions solved in endless mode
+    let regions = RwSignal::new(0u32);
+    // Trainer positions are partly solved already, so they do not count either.
+    let recorded = !practice && technique.is_none();
+    let bindings = settings.bindings.clone();
+    let gestures = settings.gestures.clone();
+    let base_layout = Layout::new(&settings);
+    let layout = RwSignal::new(base_layout);
+    // Undoing would mix up the turns or the moves sent to the other clients.
+    let undo_hidden = matches!(mode, GameMode::HotSeat | GameMode::Coop | GameMode::Race);
+
+    let canvas = NodeRef::<Canvas>::new();
+    // Canvas below for the GPU renderer
+    let gpu_canvas = NodeRef::<Canvas>::new();
+    // Canvas on top for the hover highlights
+    let hover_canvas = NodeRef::<Canvas>::new();
+
+    // A custom theme replaces the colors of all modes.
+    let palette = settings.theme.clone().unwrap_or_else(|| {
+        if mode == GameMode::Zen {
+            Theme::zen()
         } else {
-            None
+            Theme::default()
         }
     });
+    let palette_view = palette.clone();
+    let palette_export = palette.clone();
+    let background_color = background_memo(palette.background.clone());
 
     let (read_bridge, update_bridge) = signal(None);
+    // Cycle the next updated bridge in reverse order.
+    let (cycle_back, set_cycle_back) = signal(false);
+    // Bridges marked as guesses
+    let (pencil_marks, set_pencil_marks) = signal(BTreeSet::<(usize, usize)>::new());
     let (solved, set_solved) = signal(false);
-    let (blocked, set_blocked) = signal(None);
+    let (score, set_score) = signal(0);
+    // Solved faster than ever before
+    let (new_record, set_new_record) = signal(false);
+    // Figures shown once the puzzle is solved
+    let (result, set_result) = signal(None::<GameResult>);
+    // Reason why the game is lost, e.g. time is up in timed mode
+    let (failed, set_failed) = signal(None::<&'static str>);
+    // Incremented on every change of the game
+    let (revision, set_revision) = signal(0usize);
+    // Bridges revealed as wrong by the last check
+    let (wrong_bridges, set_wrong_bridges) = signal(vec![]);
+    // Bridges the player did not place, shown when the solution is revealed
+    let (missing_bridges, set_missing_bridges) = signal(vec![]);
+    // Island pointed out by the first level of a hint
+    let (hinted_island, set_hinted_island) = signal(None::<usize>);
+    // Level of the last hint since the last move, so that asking again reveals more
+    let hint_level = RwSignal::new(None::<HintLevel>);
+    let (revealed, set_revealed) = signal(false);
+    // Board of the player before the solution was revealed, to show side by side with the solution
+    let (your_board, set_your_board) = signal(None::<HexSystem>);
+    let comparing = RwSignal::new(false);
+    let help = RwSignal::new(false);
+    // Moves that needed the technique practiced in the trainer
+    let (applied, set_applied) = signal(0u32);
+    // Dead end found by the last check
+    let (stuck, set_stuck) = signal(None::<String>);
+    // Number of moves to undo to get out of the dead end
+    let (rewind, set_rewind) = signal(0);
+    let finished = move || {
+        solved.get_untracked() || failed.get_untracked().is_some() || revealed.get_untracked()
+    };
+
+    // Animations are drawn by the render loop until they are done.
+    let animations = Arc::new(RwLock::new(AnimationQueue::default()));
+
+    // Timer
+    let now = || window().performance().unwrap().now();
+    let game_clock = StoredValue::new(GameClock::new(now(), elapsed_ms));
+    let (elapsed, set_elapsed) = signal(elapsed_ms);
+    let par_time = get_par_time(&difficulty);
+    // Time played so far, to stamp moves, hints and undos with
+    let clock = move || game_clock.with_value(|c| c.elapsed(now()));
+
+    // The timer stops after a while without input or while the page is hidden,
+    // so that the recorded times stay meaningful.
+    let (paused, set_paused) = signal(false);
+    let last_input = StoredValue::new(now());
+    let pause = move || {
+        if !finished() {
+            game_clock.update_value(|c| c.pause(now()));
+            set_paused.set(true);
+        }
+    };
+    let resume = move || {
+        game_clock.update_value(|c| c.resume(now()));
+        last_input.set_value(now());
+        set_paused.set(false);
+    };
+    // The overlay of a paused game takes the focus, so that the keyboard can resume it.
+    let resume_button = NodeRef::<Button>::new();
+    Effect::new(move |_| {
+        if let Some(button) = resume_button.get() {
+            let _ = button.focus();
+        }
+    });
+    let _ = use_event_listener(window(), mousemove, move |_| last_input.set_value(now()));
+    let _ = use_event_listener(window(), pointerdown, move |_| last_input.set_value(now()));
+    let _ = use_event_listener(window(), keydown, move |_| last_input.set_value(now()));
+    let _ = use_event_listener(document(), visibilitychange, move |_| {
+        if document().hidden() {
+            pause();
+        }
+    });
+    let puzzle_id = game.read().unwrap().system().puzzle_id();
+    let solved_before = load_stats().get_puzzle(&puzzle_id).is_some();
 
     let g = game.clone();
-    let _ = use_event_listener(canvas, mousedown, move |evt| {
-        let x = evt.offset_x();
-        let y = evt.offset_y();
-        // log!("click: {},{}", x, y);
-        if let Some((from, to)) = get_bridge_from_coordinates(&g.read().unwrap(), x, y) {
-            // log!("{} -> {}", from, to);
-            update_bridge.set(Some((from, to)));
+    let d = difficulty.clone();
+    let m = mode.clone();
+    let fail = move |reason| {
+        set_failed.set(Some(reason));
+        clear_game();
+        let game = g.read().unwrap();
+        let (mistakes, hints) = (game.mistakes(), hint_counts(&game));
+        drop(game);
+        if recorded {
+            update_stats(|s| {
+                s.record_failed(&d, &m, mistakes);
+                s.record_day(today(), None);
+                s.record_hints(&d, &m, hints);
+            });
+        }
+    };
+
+    let m = mode.clone();
+    let f = fail.clone();
+    let _ = use_interval_fn(
+        move || {
+            if idle_pause_s > 0.0 && now() - last_input.get_value() > idle_pause_s * 1000.0 {
+                pause();
+            }
+            if !finished() && !paused.get_untracked() {
+                let elapsed = clock();
+                set_elapsed.set(elapsed);
+                if m == GameMode::Timed && elapsed >= par_time {
+                    f("Time is up!");
+                }
+            }
+        },
+        1000,
+    );
+
+    let g = game.clone();
+    let d = difficulty.clone();
+    let m = mode.clone();
+    let p = pack.clone();
+    // Co-op games and races cannot be continued without the other clients, trainer positions
+    // would replace the game in progress and count as normal games once continued.
+    let unsaved = matches!(m, GameMode::Coop | GameMode::Race) || technique.is_some();
+    let saved_game = move || SavedGame {
+        difficulty: d.clone(),
+        mode: m.clone(),
+        session: g.read().unwrap().clone(),
+        elapsed_ms: clock(),
+        hot_seat: turns.get_untracked(),
+        pack: p.clone(),
+        seed,
+    };
+    let s = saved_game.clone();
+    // A new game replaces the stored game only once the first move is made,
+    // so that just looking at a puzzle keeps the game offered to continue.
+    let touched = StoredValue::new(elapsed_ms > 0.0);
+    let g = game.clone();
+    let save = move || {
+        if unsaved {
+            return;
+        }
+        if !touched.get_value() {
+            if g.read().unwrap().history().is_empty() {
+                return;
+            }
+            touched.set_value(true);
+        }
+        save_game(&s());
+        mark_active_page();
+    };
+    // A new game must not overwrite the game interrupted by reloading the page until the player decides.
+    let (orphaned, set_orphaned) = signal(elapsed_ms == 0.0 && is_reloaded_during_game());
+    // Moves are saved after a short pause, the time played regularly.
+    let save_later = use_debounce_fn(save.clone(), SAVE_DELAY_MS);
+    let s = save.clone();
+    let _ = use_interval_fn(
+        move || {
+            if !finished() && !orphaned.get_untracked() {
+                s();
+            }
+        },
+        AUTOSAVE_INTERVAL_MS,
+    );
+    let s = save.clone();
+    let _ = use_event_listener(window(), pagehide, move |_| {
+        if !finished() && !orphaned.get_untracked() {
+            s();
+        }
+    });
+    // Closing the app pauses the game, so that the time until the next start is not counted.
+    let s = save.clone();
+    on_app_close(move || {
+        pause();
+        if !finished() && !orphaned.get_untracked() {
+            s();
         }
     });
+    let s = save.clone();
+    let discard_orphaned = move |_| {
+        set_orphaned.set(false);
+        s();
+    };
+
+    // Bridges to choose from after an ambiguous click
+    let (chooser, set_chooser) = signal(None::<Chooser>);
 
     let _ = use_event_listener(canvas, mouseup, move |_| {
         update_bridge.set(None);
-        set_blocked.set(None);
+    });
+
+    // Operations are exchanged with the other clients of a co-op game or race in the same browser.
+    let operations = Arc::new(RwLock::new(OperationLog::new(
+        (js_sys::Math::random() * u32::MAX as f64) as u32,
+    )));
+    let race = mode == GameMode::Race;
+    #[cfg(feature = "multiplayer")]
+    let channel = coop.and_then(|room| room_channel(&mode, &room));
+    // Builds without co-op games never open a channel, which leaves the exchange out.
+    #[cfg(not(feature = "multiplayer"))]
+    let channel = coop.and(None::<BroadcastChannel>);
+    if let Some(channel) = &channel {
+        let g = game.clone();
+        let o = operations.clone();
+        let c = channel.clone();
+        let _ = use_event_listener(channel.clone(), message, move |evt| {
+            // Players and spectators joining late are sent the whole board.
+            if evt.data().as_string().as_deref() == Some(SYNC_REQUEST) {
+                for operation in o.read().unwrap().operations() {
+                    post_operation(&c, operation);
+                }
+                return;
+            }
+            // Racers only send their moves, each solves the puzzle on their own board.
+            if race {
+                return;
+            }
+            let Some(operation) = parse_operation(&evt) else {
+                return;
+            };
+            let mut game = g.write().unwrap();
+            if o.write().unwrap().apply(&mut game, operation) {
+                let solved = game.system().is_solved();
+                drop(game);
+                set_revision.update(|r| *r += 1);
+                if solved {
+                    set_solved.set(true);
+                }
+            }
+        });
+        // Catch up with the moves made before joining.
+        if !race {
+            let _ = channel.post_message(&JsValue::from_str(SYNC_REQUEST));
+        }
+    }
+    let channel = StoredValue::new_local(channel);
+    on_cleanup(move || {
+        if let Some(Some(channel)) = channel.try_get_value() {
+            channel.close();
+        }
     });
 
     let g = game.clone();
+    let a = animations.clone();
+    let s = save_later.clone();
+    let d = difficulty.clone();
+    let m = mode.clone();
+    let o = operations.clone();
+    let id = puzzle_id.clone();
+    let p = pack.clone();
     Effect::new(move |_| {
         if let Some((from, to)) = read_bridge.get() {
             let mut game = g.write().unwrap();
-            match game.cycle_bridge(from, to) {
-                Ok(solved) => set_solved.set(solved),
-                Err(BridgeError::Blocked) => set_blocked.set(Some((from, to))),
-                Err(BridgeError::NotFound) => (), // Ignore
+            game.set_clock(clock());
+            let expected = technique
+                .map(|t| game.system().technique_moves(t))
+                .unwrap_or_default();
+            let mut hot_seat = turns.get_untracked();
+            let result = match (hot_seat.as_mut(), cycle_back.get_untracked()) {
+                (Some(hot_seat), false) => hot_seat.cycle_bridge(&mut game, from, to),
+                (Some(hot_seat), true) => hot_seat.cycle_bridge_back(&mut game, from, to),
+                (None, false) => game.cycle_bridge(from, to),
+                (None, true) => game.cycle_bridge_back(from, to),
+            };
+            if result.is_ok() && hot_seat.is_some() {
+                turns.set(hot_seat);
+            }
+            if result.is_ok() {
+                set_stuck.set(None);
+                set_rewind.set(0);
+                channel.with_value(|channel| {
+                    if let (Some(channel), Some(operation)) =
+                        (channel, o.write().unwrap().record(&game))
+                    {
+                        post_operation(channel, &operation);
+                    }
+                });
+            }
+            if let (Ok(_), Some(last)) = (&result, game.history().last()) {
+                a.write().unwrap().push(
+                    last.bridge,
+                    last.from.clone(),
+                    last.to.clone(),
+                    window().performance().unwrap().now(),
+                );
+                if last.to > last.from && expected.contains(&last.bridge) {
+                    set_applied.update(|a| *a += 1);
+                }
+                // The fanfare of a solved puzzle replaces the sound of the last move.
+                if !matches!(result, Ok(true)) {
+                    let sound = if last.to > last.from {
+                        Sound::Add
+                    } else {
+                        Sound::Remove
+                    };
+                    play(sound_pack, sound, effects_volume);
+                }
+            }
+            let (mistakes, hints) = (game.mistakes(), hint_counts(&game));
+            let elapsed = clock();
+            let score = compute_score(&game, get_base_score(&d), elapsed, par_time);
+            let figures = matches!(result, Ok(true)).then(|| GameResult::new(&game, elapsed));
+            drop(game);
+            match result {
+                // A solved board grows by another region to solve.
+                Ok(true) if m == GameMode::Endless => {
+                    let mut game = g.write().unwrap();
+                    let system = game.system();
+                    let preset = GameParameters::preset(&d, seed.unwrap_or_default());
+                    let params = GameParameters {
+                        seed: preset.seed.wrapping_add(system.rows as u64),
+                        max_rows: system.rows + ENDLESS_ROWS,
+                        num_islands: preset.num_islands / 2,
+                        ..preset
+                    };
+                    game.extend(params);
+                    if limit_bridge_length {
+                        game.limit_bridge_length();
+                    }
+                    drop(game);
+                    regions.update(|r| *r += 1);
+                    // The canvas is sized for the grown board.
+                    layout.update(|_| ());
+                    set_revision.update(|r| *r += 1);
+                    play(sound_pack, Sound::Solved, fanfare_volume);
+                    if haptics {
+                        vibrate(haptics::SOLVED);
+                    }
+                }
+                Ok(true) => {
+                    set_result.set(figures);
+                    set_solved.set(true);
+                    set_score.set(score);
+                    clear_game();
+                    if recorded {
+                        let best = update_stats(|s| {
+                            s.record_solved(&d, &m, elapsed, mistakes, score);
+                            s.record_hints(&d, &m, hints);
+                            s.record_day(today(), Some(elapsed));
+                            s.record_puzzle(&id, elapsed, score)
+                        });
+                        set_new_record.set(best && solved_before);
+                    }
+                    if let Some(pack) = &p {
+                        mark_solved(pack);
+                    }
+                    if let Some(url) = telemetry_url.clone()
+                        && recorded
+                    {
+                        let report = SolveReport::new(&g.read().unwrap(), &d, elapsed);
+                        send_report(report, url);
+                    }
+                    if m == GameMode::Weekly && recorded {
+                        // Games continued after the turn of the week still belong to the week they started in.
+                        let week = seed.map(seed_week).unwrap_or_else(current_week);
+                        record_weekly(
+                            Replay {
+                                entry: WeeklyEntry {
+                                    week,
+                                    name: player_name.clone(),
+                                    elapsed_ms: elapsed,
+                                    score,
+                                },
+                                seed: weekly_seed(week),
+                                difficulty: d.clone(),
+                                moves: g.read().unwrap().history().to_vec(),
+                            },
+                            leaderboard_url.clone(),
+                        );
+                    }
+                    play(sound_pack, Sound::Solved, fanfare_volume);
+                    if haptics {
+                        vibrate(haptics::SOLVED);
+                    }
+                }
+                Ok(false) => {
+                    // Practice games correct wrong bridges right away, flashing like blocked moves.
+                    if practice
+                        && !undo_hidden
+                        && let Some(wrong) = g.write().unwrap().take_back_wrong()
+                    {
+                        let now = window().performance().unwrap().now();
+                        let mut animations = a.write().unwrap();
+                        animations.push(wrong.bridge, wrong.to, wrong.from, now);
+                        animations.block(BlockedMove {
+                            attempted: wrong.bridge,
+                            conflicting: vec![],
+                            start: now,
+                            duration: blocked_feedback_ms,
+                        });
+                        play(sound_pack, Sound::Blocked, effects_volume);
+                        if haptics {
+                            vibrate(haptics::BLOCKED);
+                        }
+                    }
+                    s();
+                }
+                Err(BridgeError::Blocked) => {
+                    let attempted = (std::cmp::min(from, to), std::cmp::max(from, to));
+                    a.write().unwrap().block(BlockedMove {
+                        attempted,
+                        conflicting: g.read().unwrap().system().get_blocking_bridges(from, to),
+                        start: window().performance().unwrap().now(),
+                        duration: blocked_feedback_ms,
+                    });
+                    play(sound_pack, Sound::Blocked, effects_volume);
+                    if haptics {
+                        vibrate(haptics::BLOCKED);
+                    }
+                }
+                Err(BridgeError::NotFound) | Err(BridgeError::Locked) => (), // Ignore
+            }
+            if result.is_ok() {
+                set_wrong_bridges.set(vec![]);
+                set_missing_bridges.set(vec![]);
+                set_hinted_island.set(None);
+                hint_level.set(None);
+                set_revision.update(|r| *r += 1);
             }
         }
     });
 
+    // Fail the game when the limit of mistakes is reached.
+    let g = game.clone();
+    let f = fail.clone();
+    let mistakes = Memo::new(move |_| {
+        revision.track();
+        g.read().unwrap().mistakes()
+    });
+    let g = game.clone();
+    let moves = Memo::new(move |_| {
+        revision.track();
+        g.read().unwrap().moves()
+    });
     Effect::new(move |_| {
-        draw(canvas, game.clone(), read_bridge, blocked, background_color);
+        if hardcore && mistakes.get() >= MISTAKE_LIMIT && !finished() {
+            f("Too many mistakes!");
+        }
     });
 
-    view! {
-        <div><span class="menu">hexhashi</span><a class="menu" href="/">Back</a></div>
+    let g = game.clone();
+    let a = animations.clone();
+    let s = save_later.clone();
+    let undo = move || {
+        if finished() || undo_hidden {
+            return;
+        }
+        let mut game = g.write().unwrap();
+        game.set_clock(clock());
+        let last = game.undo();
+        drop(game);
+        if let Some(last) = last {
+            a.write().unwrap().push(
+                last.bridge,
+                last.to,
+                last.from,
+                window().performance().unwrap().now(),
+            );
+            set_wrong_bridges.set(vec![]);
+            set_missing_bridges.set(vec![]);
+            set_hinted_island.set(None);
+            set_stuck.set(None);
+            set_rewind.set(0);
+            set_revision.update(|r| *r += 1);
+            s();
+        }
+    };
 
-        <canvas node_ref=canvas/>
-        <Show when=move || { solved.get() }>
-            <dialog open >
-                <p>Congratulations! </p>
-                <form method="get" action="/">
-                    <button autofocus>OK</button>
-                </form>
-            </dialog>
-        </Show>
-    }
-}
+    // Undo moves until `count` are left, animated unless the board shows that state already.
+    let g = game.clone();
+    let a = animations.clone();
+    let s = save_later.clone();
+    let rewind_to = move |count: usize, animated: bool| {
+        if finished() {
+            return;
+        }
+        let mut game = g.write().unwrap();
+        game.set_clock(clock());
+        let undone = game.rewind_to(count);
+        drop(game);
+        let now = window().performance().unwrap().now();
+        for last in undone.into_iter().filter(|_| animated) {
+            a.write()
+                .unwrap()
+                .push(last.bridge, last.to, last.from, now);
+        }
+        set_wrong_bridges.set(vec![]);
+        set_missing_bridges.set(vec![]);
+        set_hinted_island.set(None);
+        set_stuck.set(None);
+        set_rewind.set(0);
+        set_revision.update(|r| *r += 1);
+        s();
+    };
+    let g = game.clone();
+    let r = rewind_to.clone();
+    let rewind_to_consistent = move |_| {
+        let game = g.read().unwrap();
+        let count = game.history().len() - game.moves_to_consistent();
+        drop(game);
+        r(count, true);
+    };
+    // Scrubbing through the history only shows earlier states until the player resumes from one.
+    let g = game.clone();
+    let history_len = Memo::new(move |_| {
+        revision.track();
+        g.read().unwrap().history().len()
+    });
+    let preview = RwSignal::new(None::<usize>);
+    Effect::new(move |_| {
+        revision.track();
+        preview.set(None);
+    });
+    let r = rewind_to.clone();
+    let resume_here = move |_| {
+        if let Some(count) = preview.get_untracked() {
+            r(count, false);
+        }
+    };
 
-fn get_difficulty(seed: u64) -> GameParameters {
-    let params = use_params::<StartGameArgs>();
-    match params
-        .read_untracked()
-        .as_ref()
-        .ok()
-        .and_then(|p| p.difficulty.clone())
-    {
-        Some(Difficulty::Medium) => GameParameters {
-            seed,
-            max_columns: 10,
-            max_rows: 10,
-            num_islands: 20,
-            max_bridge_length: 3,
-            ratio_big_island: 0.0,
-            ratio_long_bridge: 0.2,
-        },
-        Some(Difficulty::Hard) => GameParameters {
-            seed,
-            max_columns: 10,
-            max_rows: 10,
-            num_islands: 25,
-            max_bridge_length: 5,
-            ratio_big_island: 0.0,
-            ratio_long_bridge: 0.5,
-        },
-        Some(Difficulty::Extreme) => GameParameters {
-            seed,
-            max_columns: 10,
-            max_rows: 10,
-            num_islands: 50,
-            max_bridge_length: 7,
-            ratio_big_island: 0.0,
-            ratio_long_bridge: 1.0,
-        },
-        // Easy and errors
-        _ => GameParameters {
-            seed,
-            max_columns: 10,
-            max_rows: 10,
-            num_islands: 10,
-            max_bridge_length: 1,
-            ratio_big_island: 0.0,
-            ratio_long_bridge: 0.1,
+    let g = game.clone();
+    let s = save_later.clone();
+    let check = move |_| {
+        if finished() {
+            return;
+        }
+        set_wrong_bridges.set(g.write().unwrap().check());
+        // Tell since which move the puzzle cannot be solved anymore.
+        let session = g.read().unwrap();
+        set_stuck.set(
+            session
+                .is_stuck()
+                .map(|index| match session.history().get(index) {
+                    Some(m) => format!(
+                        "No solution is possible since move {}: {}\u{2013}{}.",
+                        index + 1,
+                        session.system().get_label(m.bridge.0),
+                        session.system().get_label(m.bridge.1)
+                    ),
+                    None => "No solution is possible anymore.".to_string(),
+                }),
+        );
+        set_rewind.set(if stuck.get_untracked().is_some() {
+            session.moves_to_consistent()
+        } else {
+            0
+        });
+        drop(session);
+        set_revision.update(|r| *r += 1);
+        s();
+    };
+
+    // Point out the bridges the practiced technique proves next.
+    let g = game.clone();
+    let show_technique = move |_| {
+        if let Some(technique) = technique {
+            set_missing_bridges.set(g.read().unwrap().system().technique_moves(technique));
+            set_revision.update(|r| *r += 1);
+        }
+    };
+
+    let g = game.clone();
+    let s = save_later.clone();
+    let hint = move || {
+        if finished() {
+            return;
+        }
+        // Each hint since the last move reveals more, up to making the move.
+        let level = hint_level
+            .get_untracked()
+            .and_then(|level| level.next())
+            .unwrap_or(HintLevel::Island);
+        let mut game = g.write().unwrap();
+        game.set_clock(clock());
+        let hint = game.hint_at(level);
+        drop(game);
+        hint_level.set(Some(level));
+        set_hinted_island.set(None);
+        match (hint, level) {
+            (Some(Hint::Island(index)), _) => set_hinted_island.set(Some(index)),
+            (Some(Hint::Wrong(bridge)), HintLevel::Move) => {
+                set_cycle_back.set(true);
+                update_bridge.set(Some(bridge));
+            }
+            (Some(Hint::Missing(bridge)), HintLevel::Move) => {
+                set_cycle_back.set(false);
+                update_bridge.set(Some(bridge));
+            }
+            (Some(Hint::Wrong(bridge)), _) => set_wrong_bridges.set(vec![bridge]),
+            (Some(Hint::Missing(bridge)), _) => set_missing_bridges.set(vec![bridge]),
+            (None, _) => return,
+        }
+        set_revision.update(|r| *r += 1);
+        s();
+    };
+
+    let g = game.clone();
+    let s = save_later.clone();
+    // Apply the `action` to the bridge between `from` and `to`.
+    let select_bridge = move |(from, to): (usize, usize), action: Action| {
+        set_chooser.set(None);
+        match action {
+            Action::Lock => {
+                if g.write().unwrap().toggle_lock(from, to).is_ok() {
+                    set_revision.update(|r| *r += 1);
+                    s();
+                }
+            }
+            Action::PencilMark => set_pencil_marks.update(|marks| {
+                if !marks.remove(&(from, to)) {
+                    marks.insert((from, to));
+                }
+            }),
+            Action::CycleForward | Action::CycleBack => {
+                let session = g.read().unwrap();
+                let system = session.system();
+                // Nothing can be added to a dimmed bridge.
+                if action == Action::CycleBack
+                    || !dim_unavailable
+                    || !is_dimmed(system, &system.available_moves(), (from, to))
+                {
+                    drop(session);
+                    set_cycle_back.set(action == Action::CycleBack);
+                    update_bridge.set(Some((from, to)));
+                }
+            }
+            Action::Hint | Action::Undo | Action::Fullscreen => {}
+        }
+    };
+
+    // In full screen the menu is hidden and the board is scaled to fill the screen.
+    let (fullscreen, set_fullscreen_mode) = signal(false);
+    let UseWindowSizeReturn {
+        width: window_width,
+        height: window_height,
+    } = use_window_size();
+    // The board can be turned and mirrored to spot patterns or to fit a portrait screen.
+    let (transform, set_transform) = signal(ViewTransform::default());
+    // Zoom relative to the scale in the settings
+    let zoom = RwSignal::new(1.0);
+    // On phones, the board takes the height left by the header and the action bar at the bottom.
+    let compact = Memo::new(move |_| window_width.get() < COMPACT_WIDTH);
+    let (menu_open, set_menu_open) = signal(false);
+    // In focus mode, clicking an island zooms to its neighborhood and dims the rest of the board.
+    let focus_mode = RwSignal::new(false);
+    let focus = RwSignal::new(None::<usize>);
+    let board = NodeRef::<Div>::new();
+    let g = game.clone();
+    Effect::new(move |_| {
+        let layout_transformed = Layout {
+            transform: transform.get(),
+            ..base_layout
+        }
+        .scaled(zoom.get());
+        let system = g.read().unwrap();
+        let system = system.system();
+        layout.set(match (focus.get(), board.get()) {
+            (Some(index), Some(element)) => layout_transformed.focused(
+                system,
+                index,
+                element.client_width() as f64,
+                window_height.get(),
+            ),
+            _ if fullscreen.get() => {
+                layout_transformed.fitted(system, window_width.get(), window_height.get())
+            }
+            _ => layout_transformed,
+        });
+    });
+    // Zoom so that the whole puzzle is visible and scroll to it.
+    // When the game starts, large boards are only shrunk and nothing is scrolled.
+    let g = game.clone();
+    let fit = move |automatic: bool| {
+        let Some(element) = board.get_untracked() else {
+            return;
+        };
+        let unzoomed = Layout {
+            transform: transform.get_untracked(),
+            ..base_layout
+        };
+        let factor = unzoomed.fit_factor(
+            g.read().unwrap().system(),
+            element.client_width() as f64,
+            window_height.get_untracked(),
+        );
+        if automatic {
+            zoom.set(factor.min(1.0));
+        } else {
+            focus.set(None);
+            zoom.set(factor);
+            element.scroll_into_view();
+        }
+    };
+    let f = fit.clone();
+    Effect::new(move |fitted: Option<bool>| {
+        if fitted == Some(true) || board.get().is_none() {
+            return fitted.unwrap_or(false);
+        }
+        f(true);
+        true
+    });
+    let toggle_fullscreen = move || {
+        let on = !fullscreen.get_untracked();
+        spawn_local(async move {
+            match set_fullscreen(on).await {
+                Ok(()) => set_fullscreen_mode.set(on),
+                Err(e) => log!("Cannot change full screen: {:?}", e),
+            }
+        });
+    };
+    // The browser also leaves full screen by Escape.
+    let _ = use_event_listener(document(), fullscreenchange, move |_| {
+        if !is_tauri() && document().fullscreen_element().is_none() {
+            set_fullscreen_mode.set(false);
+        }
+    });
+    on_cleanup(move || {
+        if fullscreen.get_untracked() {
+            spawn_local(async {
+                let _ = set_fullscreen(false).await;
+            });
+        }
+    });
+
+    // All mouse buttons and keys are dispatched to actions by the bindings in the settings.
+    let (u, h) = (undo.clone(), hint.clone());
+    let trigger = move |action: Action| match action {
+        Action::Undo => u(),
+        Action::Hint => h(),
+        Action::Fullscreen => toggle_fullscreen(),
+        _ => {}
+    };
+    let g = game.clone();
+    let select = select_bridge.clone();
+    let t = trigger.clone();
+    let b = bindings.clone();
+    let _ = use_event_listener(canvas, mousedown, move |evt| {
+        set_chooser.set(None);
+        if finished() || preview.get_untracked().is_some() {
+            return;
+        }
+        let Some(action) = b.action(&Binding::from_mouse(&evt, left_handed)) else {
+            return;
+        };
+        let layout = layout.get_untracked();
+        evt.prevent_default();
+        if !action.needs_bridge() {
+            t(action);
+            return;
+        }
+        let x = evt.offset_x();
+        let y = evt.offset_y();
+        let session = g.read().unwrap();
+        if focus_mode.get_untracked()
+            && let Some(index) = Hover::at(session.system(), (x as f64, y as f64), layout)
+                .islands
+                .first()
+        {
+            focus.set(Some(*index));
+            if let Some(element) = board.get_untracked() {
+                element.scroll_into_view();
+            }
+            return;
+        }
+        let bridges = hit_test(session.system(), x, y, layout);
+        match bridges.as_slice() {
+            [] => {}
+            [bridge] => {
+                let bridge = *bridge;
+                drop(session);
+                select(bridge, action);
+            }
+            _ => set_chooser.set(Some(Chooser::new(
+                session.system(),
+                (x as f64, y as f64),
+                &bridges,
+                action,
+                layout,
+            ))),
+        }
+    });
+    // Double-clicking next to the bridges recenters the board.
+    let g = game.clone();
+    let f = fit.clone();
+    let _ = use_event_listener(canvas, dblclick, move |evt| {
+        let session = g.read().unwrap();
+        let bridges = hit_test(
+            session.system(),
+            evt.offset_x(),
+            evt.offset_y(),
+            layout.get_untracked(),
+        );
+        drop(session);
+        if bridges.is_empty() {
+            f(false);
+        }
+    });
+    // Wheel, touchpad and touch screen zoom and pan the board alike, as set in the settings.
+    let apply_gesture = move |gesture: Gesture, (x, y): (f64, f64)| {
+        let Some(element) = board.get_untracked() else {
+            return;
+        };
+        match gesture {
+            Gesture::Zoom(factor) => {
+                let previous = zoom.get_untracked();
+                let zoomed = (previous * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+                zoom.set(zoomed);
+                // Keep the point under the pointer or between the fingers in place.
+                let growth = zoomed / previous - 1.0;
+                scroll_board(&element, (x * growth, y * growth));
+            }
+            Gesture::Pan(x, y) => scroll_board(&element, (x, y)),
+        }
+    };
+    let g = gestures.clone();
+    let _ = use_event_listener_with_options(
+        canvas,
+        wheel,
+        move |evt| {
+            evt.prevent_default();
+            apply_gesture(
+                g.scroll(&evt),
+                (evt.offset_x() as f64, evt.offset_y() as f64),
+            );
         },
+        UseEventListenerOptions::default().passive(false),
+    );
+    let touches = StoredValue::new_local(TouchTracker::default());
+    let double_tap = StoredValue::new_local(DoubleTap::default());
+    let _ = use_event_listener(canvas, touchstart, move |evt| {
+        let Some(element) = canvas.get_untracked() else {
+            return;
+        };
+        touches.set_value(TouchTracker::new(&touch_points(&evt, &element)));
+    });
+    let g = gestures.clone();
+    let _ = use_event_listener(canvas, touchmove, move |evt| {
+        let Some(element) = canvas.get_untracked() else {
+            return;
+        };
+        let points = touch_points(&evt, &element);
+        let tracker = touches.get_value();
+        match (points.as_slice(), tracker.distance, tracker.last) {
+            ([a, b], Some(previous), _) => {
+                let center = ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+                apply_gesture(g.pinch(previous, distance(*a, *b)), center);
+            }
+            ([point], None, Some(last)) => {
+                apply_gesture(g.drag((point.0 - last.0, point.1 - last.1)), *point);
+            }
+            _ => {}
+        }
+        touches.update_value(|t| t.moved(&points));
+    });
+    let g = game.clone();
+    let double_tap_zoom = gestures.double_tap_zoom;
+    let _ = use_event_listener(canvas, touchend, move |evt| {
+        let tracker = touches.get_value();
+        let Some(element) = canvas.get_untracked() else {
+            return;
+        };
+        let remaining = touch_points(&evt, &element);
+        touches.set_value(TouchTracker::new(&remaining));
+        let Some(point) = tracker.last.filter(|_| remaining.is_empty() && tracker.tap) else {
+            return;
+        };
+        if !double_tap_zoom
+            || !double_tap
+                .try_update_value(|d| d.tap(now(), point))
+                .unwrap_or(false)
+        {
+            return;
+        }
+        let session = g.read().unwrap();
+        let bridges = hit_test(
+            session.system(),
+            point.0 as i32,
+            point.1 as i32,
+            layout.get_untracked(),
+        );
+        drop(session);
+        // Double tapping a bridge changes it twice, only next to the bridges the board zooms.
+        if bridges.is_empty() {
+            evt.prevent_default();
+            apply_gesture(Gesture::Zoom(DOUBLE_TAP_ZOOM), point);
+        }
+    });
+    let suppress_menu = bindings.uses_secondary_button(left_handed);
+    let _ = use_event_listener(canvas, contextmenu, move |evt| {
+        if suppress_menu {
+            evt.prevent_default();
+        }
+    });
+    let _ = use_event_listener(window(), keydown, move |evt| {
+        if evt.key() == "Escape" && focus.get_untracked().is_some() {
+            focus.set(None);
+        }
+    });
+    let b = bindings.clone();
+    let _ = use_event_listener(window(), keydown, move |evt| {
+        // Keys typed into text fields or dialogs are no commands.
+        if finished()
+            || evt.ctrl_key()
+            || evt.meta_key()
+            || evt
+                .target()
+                .is_some_and(|t| t.dyn_ref::<web_sys::HtmlInputElement>().is_some())
+            || document()
+                .query_selector("dialog[open]")
+                .ok()
+                .flatten()
+                .is_some()
+        {
+            return;
+        }
+        if let Some(action) = b.action(&Binding::from_key(&evt))
+            && !action.needs_bridge()
+        {
+            evt.prevent_default();
+            trigger(action);
+        }
+    });
+
+    let g = game.clone();
+    let d = difficulty.clone();
+    let m = mode.clone();
+    let reveal = move |_| {
+        if finished()
+            || !window()
+                .confirm_with_message("Reveal the solution? The game will count as not solved.")
+                .unwrap_or(false)
+        {
+            return;
+        }
+        let mut game = g.write().unwrap();
+        set_your_board.set(Some(game.system().clone()));
+        let diff = game.reveal();
+        let (mistakes, hints) = (game.mistakes(), hint_counts(&game));
+        drop(game);
+        set_wrong_bridges.set(diff.wrong);
+        set_missing_bridges.set(diff.missing);
+        set_hinted_island.set(None);
+        set_revealed.set(true);
+        set_revision.update(|r| *r += 1);
+        clear_game();
+        if recorded {
+            update_stats(|s| {
+                s.record_failed(&d, &m, mistakes);
+                s.record_day(today(), None);
+                s.record_hints(&d, &m, hints);
+            });
+        }
+    };
+
+    // Keep track of the time played when leaving the game.
+    on_cleanup(move || {
+        if !finished() && !orphaned.get_untracked() {
+            save();
+        }
+    });
+
+    // Copy the moves so far in coordinate notation or the puzzle code.
+    let (copied, set_copied) = signal(None::<&'static str>);
+    let copy = move |text: String, what: &'static str| {
+        spawn_local(async move {
+            match write_text(&text).await {
+                Ok(()) => set_copied.set(Some(what)),
+                Err(e) => log!("Cannot copy to clipboard: {:?}", e),
+            }
+        });
+    };
+    let g = game.clone();
+    let d = difficulty.clone();
+    let copy_moves = move |_| {
+        copy(
+            format!("hexhashi {}\n{}", d, g.read().unwrap().describe_moves()),
+            "moves",
+        )
+    };
+    let g = game.clone();
+    let d = difficulty.clone();
+    let in_library = load_library().contains(&puzzle_id);
+    let (saved, set_saved) = signal(in_library);
+    let save_to_library = move |_| {
+        let puzzle = LibraryPuzzle::new(g.read().unwrap().system(), d.clone(), Source::Saved);
+        update_library(|l| l.add(puzzle));
+        set_saved.set(true);
+    };
+    let toggle_coordinates =
+        move |_| settings_signal.update(|s| s.show_coordinates = !s.show_coordinates);
+    // Share the puzzle itself by a link and its QR code.
+    let (sharing, set_sharing) = signal(false);
+    // Save the game into a named slot besides the game in progress.
+    let (saving_slot, set_saving_slot) = signal(false);
+    let slot_game = Callback::new(move |()| saved_game());
+    let share_link = share_url(&difficulty, game.read().unwrap().system());
+    let share_qr = qr_svg(&share_link);
+    let offline_link = custom_url(game.read().unwrap().system());
+    let code = game.read().unwrap().system().to_code();
+    let copy_code = move |_| copy(code.clone(), "code");
+
+    let restart = format!(
+        "/play/{}/{}",
+        difficulty.as_str(),
+        mode.to_string().to_lowercase()
+    );
+    let replay = format!(
+        "/import/{}/{}",
+        difficulty.as_str(),
+        game.read().unwrap().system().to_code()
+    );
+    // The weekly and daily puzzles and the shared puzzle of a co-op or race room have no successor.
+    let next_path = match (&pack, &mode) {
+        (Some(pack), _) => load_packs()
+            .iter()
+            .find(|p| p.id == pack.pack)
+            .filter(|p| pack.index + 1 < p.puzzles.len())
+            .map(|_| format!("/pack/{}/{}", pack.pack, pack.index + 1)),
+        (None, GameMode::Weekly | GameMode::Daily | GameMode::Coop | GameMode::Race) => None,
+        (None, _) => Some(restart.clone()),
+    };
+    let endless = mode == GameMode::Endless;
+    let timer = move || match mode {
+        GameMode::Normal
+        | GameMode::Weekly
+        | GameMode::Daily
+        | GameMode::HotSeat
+        | GameMode::Coop
+        | GameMode::Race => format_time(elapsed.get()),
+        GameMode::Zen | GameMode::Endless => String::new(),
+        GameMode::Timed => format_time(f64::max(0.0, par_time - elapsed.get())),
+    };
+    let mistake_counter = move || {
+        if hardcore {
+            format!("Mistakes: {}/{}", mistakes.get(), MISTAKE_LIMIT)
+        } else {
+            format!("Mistakes: {}", mistakes.get())
+        }
+    };
+    // Islands with the right number of bridges, updated with each move
+    let g = game.clone();
+    let progress = Memo::new(move |_| {
+        revision.track();
+        g.read().unwrap().system().progress()
+    });
+    // Groups of islands cut off from the others, the most common mistake
+    let g = game.clone();
+    let disconnected = Memo::new(move |_| {
+        revision.track();
+        !g.read().unwrap().system().can_be_connected()
+    });
+
+    let info = {
+        let game = game.read().unwrap();
+        let system = game.system();
+        let stats = system.stats();
+        (
+            seed.map_or("–".to_string(), |seed| seed.to_string()),
+            format!("{} × {}", system.columns, system.rows),
+            stats.islands(),
+            stats.candidate_bridges,
+            stats.longest_bridge,
+            system
+                .rate()
+                .map_or("Unknown".to_string(), |rating| rating.to_string()),
+        )
+    };
+    let (info_seed, info_size, info_islands, info_bridges, info_longest, info_rating) = info;
+    // Updated once the puzzle is solved
+    let puzzle_record = move || {
+        solved.track();
+        load_stats().get_puzzle(&puzzle_id).cloned()
+    };
+    let p = puzzle_record.clone();
+    let best_time = move || {
+        p().and_then(|r| r.best_ms)
+            .map_or("–".to_string(), format_time)
+    };
+    let p = puzzle_record.clone();
+    let best_score = move || {
+        p().and_then(|r| r.best_score)
+            .map_or("–".to_string(), |score| score.to_string())
+    };
+    // Shown when playing a puzzle again
+    let previous_record = puzzle_record().map(|record| {
+        format!(
+            "Your record on this puzzle: {}, {} points",
+            record.best_ms.map_or("–".to_string(), format_time),
+            record.best_score.unwrap_or_default()
+        )
+    });
+
+    let replay_game = game.clone();
+    Effect::new(move |_| {
+        // The board gets narrower next to another one.
+        comparing.track();
+        draw(
+            canvas,
+            BoardState {
+                game: game.clone(),
+                animations: animations.clone(),
+                bridge_update: read_bridge,
+                pencil_marks,
+                wrong_bridges,
+                missing_bridges,
+                hinted_island,
+                preview: preview.read_only(),
+                focus: focus.read_only(),
+                revision,
+                show_coordinates,
+                grid,
+                dim_unavailable,
+                island_status,
+                bridge_badges,
+                background_color,
+                gpu_canvas: (renderer == Renderer::WebGl).then_some(gpu_canvas),
+                hover_canvas,
+                layout: layout.get(),
+                min_height: match board.get_untracked() {
+                    Some(element) if compact.get() => {
+                        window_height.track();
+                        element.client_height() as f64
+                    }
+                    _ => MIN_BOARD_HEIGHT,
+                },
+                palette: palette.clone(),
+            },
+        );
+    });
+
+    let players = move || {
+        turns.get().map(|hot_seat| {
+            hot_seat
+                .scores()
+                .iter()
+                .enumerate()
+                .map(|(index, player)| {
+                    view! {
+                        <span class="player" class:current=index == hot_seat.current_player()>
+                            {format!(
+                                "Player {}: {} islands, {} moves",
+                                index + 1,
+                                player.islands,
+                                player.moves,
+                            )}
+                        </span>
+                    }
+                })
+                .collect_view()
+        })
+    };
+    let winner = move || {
+        turns.get().map(|hot_seat| match hot_seat.winner() {
+            Some(index) => format!("Player {} wins!", index + 1),
+            None => "It's a draw!".to_string(),
+        })
+    };
+
+    // The replay is saved as an animated image to post it elsewhere.
+    let save_replay = move |_| {
+        let layout = Layout {
+            offset: (0.0, 0.0),
+            ..layout.get_untracked()
+        };
+        let background = background_color.get_untracked();
+        let image = export_replay(
+            &replay_game.read().unwrap(),
+            &palette_export,
+            background.as_deref().unwrap_or("white"),
+            layout,
+        );
+        if let Err(e) = image
+            .map_err(JsValue::from)
+            .and_then(|image| download(&image, "hexhashi-replay.png"))
+        {
+            log!("Cannot save the replay: {:?}", e);
+        }
+    };
+
+    // Dialogs keep the focus while they are shown.
+    let (share_dialog, slot_dialog, orphaned_dialog, results_dialog, failed_dialog) = (
+        NodeRef::<Dialog>::new(),
+        NodeRef::<Dialog>::new(),
+        NodeRef::<Dialog>::new(),
+        NodeRef::<Dialog>::new(),
+        NodeRef::<Dialog>::new(),
+    );
+    use_modal(share_dialog, move || set_sharing.set(false));
+    use_modal(slot_dialog, move || set_saving_slot.set(false));
+    // The game cannot go on without a decision.
+    use_modal(orphaned_dialog, || ());
+    use_modal(results_dialog, || ());
+    use_modal(failed_dialog, || ());
+
+    view! {
+        <div class="status" class:mirrored=left_handed class:compact=compact hidden=fullscreen>
+            <span class="menu">hexhashi</span>
+            <span class="timer">{timer}</span>
+            <span class="mistakes">{mistake_counter}</span>
+            <span class="moves">{move || format!("Moves: {}", moves.get())}</span>
+            {max_bridge_length
+                .map(|max| {
+                    view! {
+                        <span class="bridge-length">{format!("Bridges up to {} long", max)}</span>
+                    }
+                })}
+            {(variant != GameVariant::Classic)
+                .then(|| view! { <span class="variant">{variant.to_string()}</span> })}
+            {practice.then(|| view! { <span class="practice">"Practice"</span> })}
+            // The coordinates of the islands stay the same in any view, so hints still match.
+            <Show when=move || transform.get() != ViewTransform::default()>
+                <span class="view" title="Only the view is changed, the coordinates stay the same">
+                    {move || transform.get().to_string()}
+                    " "
+                    <button on:click=move |_| set_transform.set(ViewTransform::default())>
+                        "Reset view"
+                    </button>
+                </span>
+            </Show>
+            {endless
+                .then(|| {
+                    view! {
+                        <span class="regions">{move || format!("Regions: {}", regions.get())}</span>
+                    }
+                })}
+            <span class="progress">
+                <progress
+                    max=move || progress.get().total
+                    value=move || progress.get().complete
+                ></progress>
+                {move || {
+                    let Progress { complete, total } = progress.get();
+                    format!("{}/{} islands complete", complete, total)
+                }}
+            </span>
+            <Show when=move || disconnected.get()>
+                <span
+                    class="warning"
+                    title="Some islands can no longer be connected to the others."
+                >
+                    "\u{26a0}"
+                </span>
+            </Show>
+            <a class="menu" href="/">Back</a>
+        </div>
+        <p class="record" hidden=compact>{previous_record}</p>
+        {technique
+            .map(|technique| {
+                view! {
+                    <div class="trainer">
+                        <strong>{technique.to_string()}</strong>
+                        " "
+                        {move || format!("applied {} times", applied.get())}
+                        " "
+                        <button on:click=show_technique.clone()>"Show where"</button>
+                        <details>
+                            <summary>"Explain"</summary>
+                            <p>{explanation(technique)}</p>
+                        </details>
+                    </div>
+                }
+            })}
+        <div class="players">{players}</div>
+        // On phones, the controls become an action bar at the bottom within reach of the thumb,
+        // with the rest behind the menu button.
+        <div
+            class="controls"
+            class:mirrored=left_handed
+            class:compact=compact
+            class:open=menu_open
+            // Picking an entry of the menu closes it.
+            on:click=move |ev| {
+                if event_target::<web_sys::Element>(&ev).closest(".more").ok().flatten().is_some() {
+                    set_menu_open.set(false);
+                }
+            }
+        >
+            <button on:click=move |_| undo() hidden=undo_hidden>"Undo"</button>
+            <button on:click=move |_| hint()>"Hint"</button>
+            <button
+                class="zoom"
+                title="Zoom out"
+                hidden=move || !compact.get()
+                on:click=move |_| zoom.update(|z| *z = (*z / ZOOM_STEP).max(MIN_ZOOM))
+            >
+                "\u{2212}"
+            </button>
+            <button
+                class="zoom"
+                title="Zoom in"
+                hidden=move || !compact.get()
+                on:click=move |_| zoom.update(|z| *z = (*z * ZOOM_STEP).min(MAX_ZOOM))
+            >
+                "+"
+            </button>
+            <button
+                aria-expanded=move || menu_open.get().to_string()
+                hidden=move || !compact.get()
+                on:click=move |_| set_menu_open.update(|open| *open = !*open)
+            >
+                "Menu"
+            </button>
+            <button class="more" on:click=move |_| toggle_fullscreen()>"Full screen"</button>
+            <button
+                class="more"
+                title="Click an island to look at its neighborhood alone, Escape to see the whole board"
+                aria-pressed=move || focus_mode.get().to_string()
+                on:click=move |_| {
+                    focus_mode.update(|on| *on = !*on);
+                    focus.set(None);
+                }
+            >
+                "Focus"
+            </button>
+            <button
+                class="more"
+                title="Show the games solved today in a corner of the board"
+                aria-pressed=move || settings_signal.read().session_hud.to_string()
+                on:click=move |_| settings_signal.update(|s| s.session_hud = !s.session_hud)
+            >
+                "Today"
+            </button>
+            <button class="more" title="Show the whole puzzle" on:click=move |_| fit(false)>
+                "Fit"
+            </button>
+            <button
+                class="more"
+                title="Turn the board by 60°"
+                on:click=move |_| set_transform.update(|t| *t = t.rotated())
+            >
+                "Rotate"
+            </button>
+            <button class="more" on:click=move |_| set_transform.update(|t| *t = t.flipped())>"Mirror"</button>
+            <button
+                class="more"
+                title="Show the board turned or mirrored at random to see it afresh"
+                on:click=move |_| {
+                    let choice = (js_sys::Math::random() * 11.0) as u64;
+                    set_transform.update(|t| *t = t.shuffled(choice))
+                }
+            >
+                "Shuffle view"
+            </button>
+            <button class="more" on:click=check>"Check"</button>
+            <button class="more" on:click=reveal>"Reveal solution"</button>
+            <button class="more" on:click=toggle_coordinates>"Coordinates"</button>
+            <button class="more" on:click=copy_moves>
+                {move || if copied.get() == Some("moves") { "Copied!" } else { "Copy moves" }}
+            </button>
+            <button class="more" on:click=move |_| set_sharing.set(true)>"Share"</button>
+            <button class="more" title="Rules" on:click=move |_| help.set(true)>"?"</button>
+            <button class="more" on:click=save_to_library disabled=saved>
+                {move || if saved.get() { "In library" } else { "Save to library" }}
+            </button>
+            <button class="more" hidden=unsaved on:click=move |_| set_saving_slot.set(true)>
+                "Save to slot"
+            </button>
+        </div>
+        <div class="history" hidden=move || undo_hidden || history_len.get() == 0>
+            <label>
+                "History "
+                <input
+                    type="range"
+                    min="0"
+                    max=move || history_len.get()
+                    prop:value=move || preview.get().unwrap_or(history_len.get())
+                    on:input=move |ev| {
+                        if let Ok(count) = event_target_value(&ev).parse::<usize>() {
+                            preview.set((count < history_len.get_untracked()).then_some(count));
+                        }
+                    }
+                />
+            </label>
+            " "
+            {move || {
+                format!("Move {} of {}", preview.get().unwrap_or(history_len.get()), history_len.get())
+            }}
+            <Show when=move || preview.get().is_some()>
+                <button on:click=resume_here.clone()>"Resume from here"</button>
+                <button on:click=move |_| preview.set(None)>"Back to the last move"</button>
+            </Show>
+        </div>
+        <p class="error">
+            {move || stuck.get()}
+            <Show when=move || { rewind.get() > 0 && !undo_hidden }>
+                <button on:click=rewind_to_consistent.clone()>
+                    {move || format!("Rewind {} moves", rewind.get())}
+                </button>
+            </Show>
+        </p>
+        <Show when=move || sharing.get()>
+            <dialog class="share" node_ref=share_dialog>
+                <p>"Scan or open the link to play this puzzle."</p>
+                <div class="qr" inner_html=share_qr.clone()/>
+                <p>
+                    <input type="text" readonly prop:value=share_link.clone()/>
+                </p>
+                <p>
+                    <label>
+                        "Link that works without a server "
+                        <input type="text" readonly prop:value=offline_link.clone()/>
+                    </label>
+                </p>
+                <button on:click=copy_code.clone()>
+                    {move || if copied.get() == Some("code") { "Copied!" } else { "Copy code" }}
+                </button>
+                <button autofocus on:click=move |_| set_sharing.set(false)>
+                    "Close"
+                </button>
+            </dialog>
+        </Show>
+
+        <Show when=move || saving_slot.get()>
+            <dialog class="save-slot" node_ref=slot_dialog>
+                <SaveSlotForm game=slot_game on_close=Callback::new(move |()| set_saving_slot.set(false))/>
+            </dialog>
+        </Show>
+
+        <details class="info" hidden=move || fullscreen.get() || compact.get()>
+            <summary>"Puzzle info"</summary>
+            <dl>
+                <dt>"Seed"</dt>
+                <dd>{info_seed}</dd>
+                <dt>"Size"</dt>
+                <dd>{info_size}</dd>
+                <dt>"Islands"</dt>
+                <dd>{info_islands}</dd>
+                <dt>"Possible bridges"</dt>
+                <dd>{info_bridges}</dd>
+                <dt>"Longest bridge"</dt>
+                <dd>{info_longest}</dd>
+                <dt>"Rated difficulty"</dt>
+                <dd>{info_rating}</dd>
+                <dt>"Par time"</dt>
+                <dd>{format_time(par_time)}</dd>
+                <dt>"Best time"</dt>
+                <dd>{best_time}</dd>
+                <dt>"Best score"</dt>
+                <dd>{best_score}</dd>
+            </dl>
+        </details>
+
+        <Show when=move || your_board.read().is_some()>
+            <button aria-pressed=move || comparing.get().to_string() on:click=move |_| comparing.update(|c| *c = !*c)>
+                "Compare with your board"
+            </button>
+        </Show>
+        <div class="split" class:active=comparing>
+        <div class="board" class:fullscreen=fullscreen class:compact=compact node_ref=board>
+            <canvas class="gpu" node_ref=gpu_canvas/>
+            <canvas node_ref=canvas/>
+            <canvas class="hover" node_ref=hover_canvas/>
+            <Show when=move || settings_signal.read().session_hud>
+                <SessionHud finished=Signal::derive(move || {
+                    solved.get() || failed.get().is_some() || revealed.get()
+                })/>
+            </Show>
+            <Show when=move || paused.get()>
+                <div class="paused">
+                    <p>"Paused"</p>
+                    <button node_ref=resume_button on:click=move |_| resume()>
+                        "Resume"
+                    </button>
+                </div>
+            </Show>
+            <Show when=move || fullscreen.get()>
+                <button class="leave-fullscreen" on:click=move |_| toggle_fullscreen()>
+                    "Exit full screen"
+                </button>
+            </Show>
+            {move || {
+                chooser
+                    .get()
+                    .map(|chooser| {
+                        let action = chooser.action;
+                        chooser
+                            .options
+                            .into_iter()
+                            .map(|ChooserOption { bridge, label, position: (x, y) }| {
+                                let select = select_bridge.clone();
+                                view! {
+                                    <button
+                                        class="chooser"
+                                        style=format!("left: {:.0}px; top: {:.0}px", x, y)
+                                        on:click=move |_| select(bridge, action)
+                                    >
+                                        {label}
+                                    </button>
+                                }
+                            })
+                            .collect_view()
+                    })
+            }}
+        </div>
+        {move || {
+            your_board
+                .get()
+                .filter(|_| comparing.get())
+                .map(|system| {
+                    let game = Arc::new(RwLock::new(GameSession::new(system)));
+                    let (revision, _) = signal(0);
+                    view! {
+                        <ComparisonBoard
+                            game
+                            revision
+                            caption="Your board"
+                            layout
+                            palette=palette_view.clone()
+                            background_color
+                            show_coordinates
+                            grid
+                            bridge_badges
+                        />
+                    }
+                })
+        }}
+        </div>
+        <Show when=move || orphaned.get()>
+            <dialog node_ref=orphaned_dialog>
+                <p>"The page was reloaded while a game was in progress."</p>
+                <form method="get" action="/continue">
+                    <button autofocus>"Recover game"</button>
+                </form>
+                <button on:click=discard_orphaned.clone()>"Play the new game"</button>
+            </dialog>
+        </Show>
+        <HelpOverlay open=help/>
+        <Show when=move || { solved.get() }>
+            <dialog class="results" node_ref=results_dialog>
+                <h2>"Congratulations!"</h2>
+                <p>{winner}</p>
+                {practice.then(|| view! { <p>"Practice games do not count for the statistics."</p> })}
+                <Show when=move || new_record.get()>
+                    <p class="badge">"New record"</p>
+                </Show>
+                <dl>
+                    {move || {
+                        result
+                            .get()
+                            .map(|r| {
+                                view! {
+                                    <dt>"Time"</dt>
+                                    <dd>{format_time(r.elapsed_ms)}</dd>
+                                    <dt>"Par"</dt>
+                                    <dd>{compare_to_par(r.elapsed_ms, par_time)}</dd>
+                                    <dt>"Moves"</dt>
+                                    <dd>{format!("{} (fewest: {})", r.moves, r.minimal_moves)}</dd>
+                                    <dt>"Grade"</dt>
+                                    <dd class="grade">{r.grade.to_string()}</dd>
+                                    <dt>"Undos"</dt>
+                                    <dd>{r.undos}</dd>
+                                    <dt>"Hints"</dt>
+                                    <dd>{describe_hints(r.hints)}</dd>
+                                }
+                            })
+                    }}
+                    <dt>"Score"</dt>
+                    <dd>{move || score.get()}</dd>
+                </dl>
+                {move || {
+                    result
+                        .get()
+                        .map(|r| {
+                            view! {
+                                <details class="analysis">
+                                    <summary>"Analysis"</summary>
+                                    <div class="timeline" inner_html=r.timeline/>
+                                    <p>
+                                        "Bars show the time spent on each move, "
+                                        "gold lines hints and red lines undos."
+                                    </p>
+                                    <p>"Longest moves: " {r.slowest_moves.join(", ")}</p>
+                                    <p>"Longest islands: " {r.slowest_islands.join(", ")}</p>
+                                </details>
+                            }
+                        })
+                }}
+                <div class="actions">
+                    <button on:click=move |_| set_sharing.set(true)>"Share"</button>
+                    <button on:click=save_replay.clone()>"Save replay"</button>
+                    <form method="get" action=replay.clone()>
+                        <button>"Replay"</button>
+                    </form>
+                    {match on_next {
+                        Some(on_next) => {
+                            Some(
+                                view! {
+                                    <button autofocus on:click=move |_| on_next.run(())>
+                                        "Next puzzle"
+                                    </button>
+                                }
+                                    .into_any(),
+                            )
+                        }
+                        None => {
+                            next_path
+                                .clone()
+                                .map(|next| {
+                                    view! {
+                                        <form method="get" action=next>
+                                            <button autofocus>"Next puzzle"</button>
+                                        </form>
+                                    }
+                                        .into_any()
+                                })
+                        }
+                    }}
+                    <form method="get" action="/">
+                        <button>"Menu"</button>
+                    </form>
+                </div>
+            </dialog>
+        </Show>
+        <Show when=move || { failed.get().is_some() }>
+            <dialog node_ref=failed_dialog>
+                <p>{move || failed.get()}</p>
+                <form method="get" action=restart.clone()>
+                    <button autofocus>"Restart"</button>
+                </form>
+                <form method="get" action="/">
+                    <button>"OK"</button>
+                </form>
+            </dialog>
+        </Show>
+    }
+}
+
+///
+/// Fingers on the board, to turn their movements into gestures.
+///
+#[derive(Clone, Copy, Debug, Default)]
+struct TouchTracker {
+    /// Where the only finger touched the board first
+    start: Option<(f64, f64)>,
+    /// Where the only finger was last
+    last: Option<(f64, f64)>,
+    /// Distance between two fingers
+    distance: Option<f64>,
+    /// Whether the only finger stayed close to where it started
+    tap: bool,
+}
+
+impl TouchTracker {
+    fn new(points: &[(f64, f64)]) -> Self {
+        match points {
+            [point] => TouchTracker {
+                start: Some(*point),
+                last: Some(*point),
+                distance: None,
+                tap: true,
+            },
+            [a, b] => TouchTracker {
+                distance: Some(distance(*a, *b)),
+                ..Default::default()
+            },
+            _ => TouchTracker::default(),
+        }
+    }
+
+    ///
+    /// Update the tracker for the fingers moved to `points`.
+    ///
+    fn moved(&mut self, points: &[(f64, f64)]) {
+        match points {
+            [point] => {
+                self.tap &= self
+                    .start
+                    .is_some_and(|start| distance(start, *point) <= TAP_SLOP);
+                self.last = Some(*point);
+            }
+            [a, b] => self.distance = Some(distance(*a, *b)),
+            _ => {}
+        }
+    }
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+///
+/// Get the fingers on the board of `evt` in pixels on the `canvas`.
+///
+fn touch_points(evt: &TouchEvent, canvas: &HtmlCanvasElement) -> Vec<(f64, f64)> {
+    let rect = canvas.get_bounding_client_rect();
+    let touches = evt.touches();
+    (0..touches.length())
+        .filter_map(|i| touches.get(i))
+        .map(|t| {
+            (
+                t.client_x() as f64 - rect.left(),
+                t.client_y() as f64 - rect.top(),
+            )
+        })
+        .collect()
+}
+
+///
+/// Scroll the board by `delta` pixels, or the page if the board does not scroll on its own.
+///
+fn scroll_board(board: &web_sys::HtmlDivElement, (x, y): (f64, f64)) {
+    if board.scroll_height() > board.client_height() || board.scroll_width() > board.client_width()
+    {
+        board.scroll_by_with_x_and_y(x, y);
+    } else {
+        window().scroll_by_with_x_and_y(x, y);
+    }
+}
+
+fn get_difficulty_and_mode() -> (Difficulty, GameMode) {
+    let params = use_params::<StartGameArgs>();
+    let params = params.read_untracked();
+    let params = params.as_ref().ok();
+    (
+        params
+            .and_then(|p| p.difficulty.clone())
+            .unwrap_or(Difficulty::Easy),
+        params.and_then(|p| p.mode.clone()).unwrap_or_default(),
+    )
+}
+
+///
+/// Figures of a solved game for the results.
+///
+#[derive(Clone, Debug, PartialEq)]
+struct GameResult {
+    elapsed_ms: f64,
+    moves: u32,
+    /// Fewest moves needed to solve the puzzle
+    minimal_moves: u32,
+    grade: Grade,
+    undos: u32,
+    /// Hints at each level from the least to the most revealing
+    hints: [u32; 3],
+    /// SVG image of the time spent on each move, the hints and undos
+    timeline: String,
+    /// Bridges of the moves that took longest with their times
+    slowest_moves: Vec<String>,
+    /// Islands that took longest with their times
+    slowest_islands: Vec<String>,
+}
+
+impl GameResult {
+    fn new(game: &GameSession, elapsed_ms: f64) -> Self {
+        let analysis = Analysis::new(game);
+        let system = game.system();
+        let slowest_moves = analysis
+            .slowest_moves(SLOWEST_COUNT)
+            .into_iter()
+            .map(|index| {
+                let (from, to) = game.history()[index].bridge;
+                format!(
+                    "{}\u{2013}{} ({})",
+                    system.get_label(from),
+                    system.get_label(to),
+                    format_time(analysis.moves[index].1)
+                )
+            })
+            .collect();
+        let slowest_islands = analysis
+            .islands
+            .iter()
+            .take(SLOWEST_COUNT)
+            .map(|(island, spent)| {
+                format!("{} ({})", system.get_label(*island), format_time(*spent))
+            })
+            .collect();
+        GameResult {
+            elapsed_ms,
+            moves: game.moves(),
+            minimal_moves: minimal_moves(game),
+            grade: grade(game),
+            undos: game.undos(),
+            hints: hint_counts(game),
+            timeline: analysis.timeline_svg(TIMELINE_SIZE.0, TIMELINE_SIZE.1),
+            slowest_moves,
+            slowest_islands,
+        }
     }
 }
 
 ///
-/// Draw grid and islands.
+/// Get the number of hints of `game` at each level from the least to the most revealing.
+///
+fn hint_counts(game: &GameSession) -> [u32; 3] {
+    HintLevel::ALL.map(|level| game.hints_at(level))
+}
+
 ///
+/// Describe the number of `hints` at each level.
 ///
-fn draw(
-    canvas: NodeRef<Canvas>,
-    game: Arc<RwLock<HexSystem>>,
+fn describe_hints([island, bridge, moves]: [u32; 3]) -> String {
+    let total = island + bridge + moves;
+    if total == 0 {
+        return "0".to_string();
+    }
+    format!("{total} ({island} islands, {bridge} bridges, {moves} moves)")
+}
+
+///
+/// Describe how much faster or slower than `par_time` the puzzle was solved in `elapsed`.
+///
+fn compare_to_par(elapsed: f64, par_time: f64) -> String {
+    if elapsed <= par_time {
+        format!("{} under par", format_time(par_time - elapsed))
+    } else {
+        format!("{} over par", format_time(elapsed - par_time))
+    }
+}
+
+///
+/// Format `ms` milliseconds as minutes and seconds.
+///
+pub fn format_time(ms: f64) -> String {
+    let seconds = (ms / 1000.0) as u64;
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+///
+/// State of the board the drawing depends on.
+///
+#[derive(Clone)]
+struct BoardState {
+    game: Arc<RwLock<GameSession>>,
+    animations: Arc<RwLock<AnimationQueue>>,
     bridge_update: ReadSignal<Option<(usize, usize)>>,
-    bridge_blocked: ReadSignal<Option<(usize, usize)>>,
+    pencil_marks: ReadSignal<BTreeSet<(usize, usize)>>,
+    wrong_bridges: ReadSignal<Vec<(usize, usize)>>,
+    missing_bridges: ReadSignal<Vec<(usize, usize)>>,
+    /// Island pointed out by a hint
+    hinted_island: ReadSignal<Option<usize>>,
+    /// Number of moves of the history shown instead of the current board
+    preview: ReadSignal<Option<usize>>,
+    /// Island whose neighborhood is focused, with the rest of the board dimmed
+    focus: ReadSignal<Option<usize>>,
+    revision: ReadSignal<usize>,
+    /// Label islands with their coordinates
+    show_coordinates: Memo<bool>,
+    grid: GridDisplay,
+    /// Dim the bridges that cannot be added
+    dim_unavailable: bool,
+    /// Outline hovered islands by whether they can still be completed
+    island_status: bool,
+    /// Show the number of bridges on each placed bridge
+    bridge_badges: bool,
     background_color: Memo<Option<String>>,
-) {
+    /// Canvas to draw grid, bridges and islands with WebGL, if enabled
+    gpu_canvas: Option<NodeRef<Canvas>>,
+    /// Canvas to draw the hover highlights on, so that pointer movements leave the board alone
+    hover_canvas: NodeRef<Canvas>,
+    layout: Layout,
+    /// Smallest height of the canvas in pixels, so that it fills the board
+    min_height: f64,
+    palette: Theme,
+}
+
+///
+/// Read-only board drawn next to another one, e.g. the board of the player next to the revealed solution.
+///
+/// It is drawn again whenever `revision` changes. It follows the zoom of `layout`,
+/// but is turned and mirrored on its own.
+///
+#[component]
+fn ComparisonBoard(
+    game: Arc<RwLock<GameSession>>,
+    revision: ReadSignal<usize>,
+    caption: &'static str,
+    layout: RwSignal<Layout>,
+    palette: Theme,
+    background_color: Memo<Option<String>>,
+    show_coordinates: Memo<bool>,
+    grid: GridDisplay,
+    bridge_badges: bool,
+) -> impl IntoView {
+    let canvas = NodeRef::<Canvas>::new();
+    let hover_canvas = NodeRef::<Canvas>::new();
+    let (transform, set_transform) = signal(ViewTransform::default());
+    let animations = Arc::new(RwLock::new(AnimationQueue::default()));
+    // Nothing is played on this board.
+    let (bridge_update, _) = signal(None);
+    let (pencil_marks, _) = signal(BTreeSet::new());
+    let (no_bridges, _) = signal(vec![]);
+    let (no_island, _) = signal(None);
+    Effect::new(move |_| {
+        draw(
+            canvas,
+            BoardState {
+                game: game.clone(),
+                animations: animations.clone(),
+                bridge_update,
+                pencil_marks,
+                wrong_bridges: no_bridges,
+                missing_bridges: no_bridges,
+                hinted_island: no_island,
+                preview: no_island,
+                focus: no_island,
+                revision,
+                show_coordinates,
+                grid,
+                dim_unavailable: false,
+                island_status: false,
+                bridge_badges,
+                background_color,
+                gpu_canvas: None,
+                hover_canvas,
+                layout: Layout {
+                    transform: transform.get(),
+                    ..layout.get()
+                },
+                min_height: MIN_BOARD_HEIGHT,
+                palette: palette.clone(),
+            },
+        );
+    });
+
+    view! {
+        <figure class="comparison">
+            <figcaption>
+                {caption}
+                " "
+                <button
+                    title="Turn the board by 60°"
+                    on:click=move |_| set_transform.update(|t| *t = t.rotated())
+                >
+                    "Rotate"
+                </button>
+                <button on:click=move |_| set_transform.update(|t| *t = t.flipped())>
+                    "Mirror"
+                </button>
+            </figcaption>
+            <div class="board">
+                <canvas node_ref=canvas/>
+                <canvas class="hover" node_ref=hover_canvas/>
+            </div>
+        </figure>
+    }
+}
+
+///
+/// Get the color of the board background, taken from the page unless the theme sets `background`.
+///
+fn background_memo(background: Option<String>) -> Memo<Option<String>> {
+    Memo::new(move |_| {
+        if background.is_some() {
+            background.clone()
+        } else if let Some(c) = window()
+            .document()
+            .unwrap()
+            .get_elements_by_tag_name("html")
+            .item(0)
+        {
+            window()
+                .get_computed_style(&c)
+                .unwrap()
+                .and_then(|s| s.get_property_value("background-color").ok())
+        } else {
+            None
+        }
+    })
+}
+
+///
+/// Size the canvas and start the render loop drawing grid and islands.
+///
+fn draw(canvas: NodeRef<Canvas>, state: BoardState) {
+    let layout = state.layout;
     // Resize to have sharp lines
     let canvas = canvas.get().unwrap();
     let rect = canvas.get_bounding_client_rect();
     let width = rect.width();
-    let height = 600.0;
+    let (_, board_height) = get_board_size(state.game.read().unwrap().system(), layout);
+    let height = f64::max(state.min_height, board_height);
     canvas.set_width(width as u32);
     canvas.set_height(height as u32);
+    // Fall back to the canvas alone if WebGL is not available.
+    let gpu = state
+        .gpu_canvas
+        .and_then(|c| c.get())
+        .and_then(|gpu_canvas| {
+            gpu_canvas.set_width(width as u32);
+            gpu_canvas.set_height(height as u32);
+            GpuRenderer::new(&gpu_canvas)
+                .map_err(|e| log!("Cannot use WebGL: {}", e))
+                .ok()
+        });
+
+    let hover_canvas = state.hover_canvas.get().unwrap();
+    hover_canvas.set_width(width as u32);
+    hover_canvas.set_height(height as u32);
 
     // log!("{}x{}", rect.width(), rect.height());
 
@@ -222,6 +2821,12 @@ fn draw(
         .unwrap()
         .dyn_into::<web_sys::CanvasRenderingContext2d>()
         .unwrap();
+    let hover_ctx = hover_canvas
+        .get_context("2d")
+        .unwrap()
+        .unwrap()
+        .dyn_into::<web_sys::CanvasRenderingContext2d>()
+        .unwrap();
 
     let UseMouseInElementReturn {
         element_x,
@@ -231,262 +2836,1155 @@ fn draw(
     } = use_mouse_in_element(canvas);
     // TODO throttle mouse move event?
 
-    let memo_game = game.clone();
-    let highlighted_bridges = Memo::new(move |_| {
-        // Highlight all bridges going to the island the mouse is pointing to.
-        let game = memo_game.read().unwrap();
-        let mut highlighted_bridges = vec![];
-        let point = (element_x.get(), element_y.get());
-        for (index, _) in game.islands.iter().enumerate() {
-            let (x, y) = get_coordinates_from_index(&game, index);
-            if ((x - point.0).powf(2.0) + (y - point.1).powf(2.0)).sqrt() <= ISLAND_SIZE
-                && !is_outside.get()
-            {
-                highlighted_bridges = game
-                    .get_connected_islands(index)
-                    .iter()
-                    .map(|to| (std::cmp::min(index, *to), std::cmp::max(index, *to)))
-                    .collect();
-                break;
-            }
-        }
-        // Highlight a bridge if mouse curser is close to it
-        for (start_index, end_index) in game.bridges.keys() {
-            let start = get_coordinates_from_index(&game, *start_index);
-            let end = get_coordinates_from_index(&game, *end_index);
-            if !is_outside.get() && point_close_to_line(point, start, end, 10.0) {
-                highlighted_bridges.push((*start_index, *end_index));
-            }
-        }
-        highlighted_bridges
-    });
-
-    let memo_game = game.clone();
-    let highlighted_islands = Memo::new(move |_| {
-        let game = memo_game.read().unwrap();
-        let mut highlighted_islands = vec![];
-        let point = (element_x.get(), element_y.get());
-        for (index, _) in game.islands.iter().enumerate() {
-            let (x, y) = get_coordinates_from_index(&game, index);
-            if ((x - point.0).powf(2.0) + (y - point.1).powf(2.0)).sqrt() <= ISLAND_SIZE
-                && !is_outside.get()
+    // The pointer only affects the hover highlights, which are drawn on their own canvas.
+    let memo_game = state.game.clone();
+    let hover = Memo::new(move |_| {
+        if is_outside.get() {
+            return Hover::default();
+        }
+        let session = memo_game.read().unwrap();
+        Hover::at(session.system(), (element_x.get(), element_y.get()), layout)
+    });
+
+    // Changes of the inputs only mark the board to be rendered with the next animation frame,
+    // so that several changes within one frame are drawn at once.
+    let invalidated = Rc::new(Cell::new(true));
+    let i = invalidated.clone();
+    let s = state.clone();
+    Effect::new(move |_| {
+        s.revision.track();
+        s.pencil_marks.track();
+        s.wrong_bridges.track();
+        s.missing_bridges.track();
+        s.hinted_island.track();
+        s.preview.track();
+        s.bridge_update.track();
+        s.show_coordinates.track();
+        s.background_color.track();
+        i.set(true);
+    });
+    let hover_invalidated = Rc::new(Cell::new(true));
+    let h = hover_invalidated.clone();
+    let s = state.clone();
+    Effect::new(move |_| {
+        // Moves change the directions still open from the hovered islands.
+        s.revision.track();
+        s.bridge_update.track();
+        s.focus.track();
+        hover.track();
+        h.set(true);
+    });
+
+    // Only the parts of the board that changed since the previous frame are redrawn.
+    let previous = RefCell::new(None::<Snapshot>);
+    let _ = use_raf_fn(move |_| {
+        // Animations are drawn on each frame until they are done.
+        let animated = !state.animations.read().unwrap().is_empty();
+        if hover_invalidated.replace(false) {
+            measure(HOVER, || {
+                untrack(|| render_hover(&hover_ctx, &state, &hover.get()))
+            });
+        }
+        if !invalidated.replace(false) && !animated {
+            return;
+        }
+        let now = window().performance().unwrap().now();
+        state.animations.write().unwrap().prune(now);
+        measure(FRAME, || {
+            untrack(|| {
+                let snapshot = render(&ctx, gpu.as_ref(), &state, previous.take(), now);
+                previous.replace(Some(snapshot));
+            })
+        });
+    });
+}
+
+///
+/// Render the changes of the board since the `previous` frame to `ctx`.
+///
+/// Returns what is drawn now, to be compared with the next frame.
+///
+fn render(
+    ctx: &CanvasRenderingContext2d,
+    gpu: Option<&GpuRenderer>,
+    state: &BoardState,
+    previous: Option<Snapshot>,
+    now: f64,
+) -> Snapshot {
+    let layout = state.layout;
+    let canvas = ctx.canvas().unwrap();
+    let (width, height) = (canvas.width() as f64, canvas.height() as f64);
+    let session = state.game.read().unwrap();
+    let previewed = state.preview.get().map(|count| session.system_at(count));
+    let game = previewed.as_ref().unwrap_or(session.system());
+    let animations = state.animations.read().unwrap();
+    let snapshot = Snapshot::new(game, state, &animations);
+    let region = snapshot.dirty_region(previous.as_ref(), game, layout);
+    if region.is_empty() {
+        return snapshot;
+    }
+
+    if let Some(renderer) = gpu {
+        let mesh = board_mesh(game, state, &animations);
+        renderer.render(&mesh, width, height);
+    }
+
+    ctx.save();
+    match &region {
+        Region::Full => ctx.clear_rect(0.0, 0.0, width, height),
+        Region::Rects(rects) => {
+            ctx.begin_path();
+            for rect in rects {
+                ctx.rect(rect.left, rect.top, rect.width(), rect.height());
+            }
+            ctx.clip();
+            for rect in rects {
+                ctx.clear_rect(rect.left, rect.top, rect.width(), rect.height());
+            }
+        }
+    }
+    let frame = Frame {
+        region,
+        gpu: gpu.is_some(),
+        animations: &animations,
+        now,
+    };
+
+    draw_grid(ctx, game, state, &frame);
+
+    draw_islands(ctx, game, state, &frame);
+    ctx.restore();
+    snapshot
+}
+
+///
+/// Bridges and islands the pointer is on.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Hover {
+    bridges: Vec<(usize, usize)>,
+    islands: Vec<usize>,
+}
+
+impl Hover {
+    ///
+    /// Get what is highlighted with the pointer at `point` on the board.
+    ///
+    fn at(game: &HexSystem, point: (f64, f64), layout: Layout) -> Self {
+        let islands: Vec<usize> = (0..game.islands.len())
+            .filter(|index| {
+                let (x, y) = get_coordinates_from_index(game, *index, layout);
+                ((x - point.0).powf(2.0) + (y - point.1).powf(2.0)).sqrt() <= layout.island_size
+            })
+            .collect();
+        // Highlight all bridges going to the island the pointer is on.
+        // Otherwise highlight the bridge a click would change, or the ones it would offer to choose from.
+        let bridges = match islands.first() {
+            Some(index) => game
+                .get_connected_islands(*index)
+                .iter()
+                .map(|to| (std::cmp::min(*index, *to), std::cmp::max(*index, *to)))
+                .collect(),
+            None => get_bridges_from_coordinates(game, point.0 as i32, point.1 as i32, layout),
+        };
+        Hover { bridges, islands }
+    }
+}
+
+///
+/// Draw the hover highlights on their own canvas on top of the board.
+///
+fn render_hover(ctx: &CanvasRenderingContext2d, state: &BoardState, hover: &Hover) {
+    let layout = state.layout;
+    let line_width = layout.line_width;
+    let canvas = ctx.canvas().unwrap();
+    ctx.clear_rect(0.0, 0.0, canvas.width() as f64, canvas.height() as f64);
+    let session = state.game.read().unwrap();
+    let game = session.system();
+    if let Some(index) = state.focus.get() {
+        draw_focus(ctx, game, state, index);
+    }
+    let available = game.available_moves();
+    for (start_index, end_index) in &hover.bridges {
+        let key = (*start_index, *end_index);
+        let (start, end) = (
+            get_coordinates_from_index(game, *start_index, layout),
+            get_coordinates_from_index(game, *end_index, layout),
+        );
+        // Placed bridges cover their grid lines.
+        if state.grid == GridDisplay::Hover
+            && game
+                .get_bridge(*start_index, *end_index)
+                .is_some_and(|b| *b.get_state() == BridgeState::Empty)
+        {
+            let (start, end) = between_islands(start, end, layout);
+            let dimmed = state.dim_unavailable && is_dimmed(game, &available, key);
+            ctx.begin_path();
+            ctx.set_line_width(0.5 * line_width);
+            ctx.set_stroke_style_str(if dimmed {
+                DIMMED_BRIDGE
+            } else {
+                &state.palette.grid
+            });
+            ctx.move_to(start.0, start.1);
+            ctx.line_to(end.0, end.1);
+            ctx.stroke();
+        }
+        if state.bridge_update.get() != Some(key) {
+            ctx.begin_path();
+            ctx.set_line_width(10.0 * line_width);
+            ctx.set_stroke_style_str(HOVER_BRIDGE);
+            ctx.move_to(start.0, start.1);
+            ctx.line_to(end.0, end.1);
+            ctx.stroke();
+        }
+    }
+    draw_directions(ctx, game, &hover.islands, layout);
+    for index in &hover.islands {
+        let (x, y) = get_coordinates_from_index(game, *index, layout);
+        ctx.begin_path();
+        ctx.set_line_width(3.0 * line_width);
+        let numbered = matches!(game.islands[*index], Island::Bridged(_));
+        ctx.set_stroke_style_str(if !state.island_status || !numbered {
+            HOVER_ISLAND
+        } else if game.island_satisfiable(*index) {
+            SATISFIABLE_ISLAND
+        } else {
+            UNSATISFIABLE_ISLAND
+        });
+        let _ = ctx.arc(x, y, layout.island_size + 5.0 * layout.scale, 0.0, 2.0 * PI);
+        ctx.stroke();
+    }
+}
+
+///
+/// Dim the board except for the island at `index`, its neighbors and the bridges between them.
+///
+fn draw_focus(ctx: &CanvasRenderingContext2d, game: &HexSystem, state: &BoardState, index: usize) {
+    let layout = state.layout;
+    let canvas = ctx.canvas().unwrap();
+    ctx.set_global_alpha(FOCUS_DIM_ALPHA);
+    ctx.set_fill_style_str(&state.background_color.get().unwrap_or("white".to_string()));
+    ctx.fill_rect(0.0, 0.0, canvas.width() as f64, canvas.height() as f64);
+    ctx.set_global_alpha(1.0);
+    // Cut the neighborhood out of the veil.
+    let _ = ctx.set_global_composite_operation("destination-out");
+    let center = get_coordinates_from_index(game, index, layout);
+    let radius = layout.island_size + 5.0 * layout.scale;
+    ctx.set_line_width(2.0 * radius);
+    ctx.set_line_cap("round");
+    for neighbor in game.get_connected_islands(index) {
+        let (x, y) = get_coordinates_from_index(game, neighbor, layout);
+        ctx.begin_path();
+        ctx.move_to(center.0, center.1);
+        ctx.line_to(x, y);
+        ctx.stroke();
+    }
+    ctx.begin_path();
+    let _ = ctx.arc(center.0, center.1, radius, 0.0, 2.0 * PI);
+    ctx.fill();
+    ctx.set_line_cap("butt");
+    let _ = ctx.set_global_composite_operation("source-over");
+}
+
+///
+/// Get the part of the line from `start` to `end` outside the islands at both ends.
+///
+fn between_islands(start: (f64, f64), end: (f64, f64), layout: Layout) -> ((f64, f64), (f64, f64)) {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let length = (dx * dx + dy * dy).sqrt();
+    if length <= 2.0 * layout.island_size {
+        return (start, start);
+    }
+    let (ox, oy) = (
+        dx / length * layout.island_size,
+        dy / length * layout.island_size,
+    );
+    ((start.0 + ox, start.1 + oy), (end.0 - ox, end.1 - oy))
+}
+
+///
+/// What to draw in the current frame.
+///
+struct Frame<'a> {
+    /// Part of the canvas to redraw
+    region: Region,
+    /// Grid, bridges and islands are drawn by the GPU renderer
+    gpu: bool,
+    animations: &'a AnimationQueue,
+    /// Time of the frame for the animations
+    now: f64,
+}
+
+///
+/// Everything that determines how a bridge is drawn.
+///
+#[derive(Clone, Debug, PartialEq)]
+struct BridgeLook {
+    state: BridgeState,
+    locked: bool,
+    dimmed: bool,
+    pencil_mark: bool,
+    wrong: bool,
+    missing: bool,
+    blocked: bool,
+    /// Animated bridges change with every frame.
+    animated: bool,
+}
+
+///
+/// Looks of the bridges and islands as drawn, to find out which parts of the board changed.
+///
+#[derive(Clone, Debug, PartialEq)]
+struct Snapshot {
+    bridges: BTreeMap<(usize, usize), BridgeLook>,
+    /// Colors of the islands
+    islands: BTreeMap<usize, IslandProgress>,
+    hinted_island: Option<usize>,
+    /// Changing the theme or the labels affects the whole board.
+    background_color: Option<String>,
+    show_coordinates: bool,
+}
+
+impl Snapshot {
+    fn new(game: &HexSystem, state: &BoardState, animations: &AnimationQueue) -> Self {
+        let available = game.available_moves();
+        let (pencil_marks, wrong, missing) = (
+            state.pencil_marks.get(),
+            state.wrong_bridges.get(),
+            state.missing_bridges.get(),
+        );
+        let bridges = game
+            .bridges
+            .iter()
+            .map(|(key, bridge)| {
+                let blocked = animations.blocked().is_some_and(|b| b.involves(*key));
+                let look = BridgeLook {
+                    state: bridge.get_state().clone(),
+                    locked: bridge.is_locked(),
+                    dimmed: state.dim_unavailable && is_dimmed(game, &available, *key),
+                    pencil_mark: pencil_marks.contains(key),
+                    wrong: wrong.contains(key),
+                    missing: missing.contains(key),
+                    blocked,
+                    animated: animations.get(*key).is_some() || blocked,
+                };
+                (*key, look)
+            })
+            .collect();
+        let islands = game
+            .islands
+            .iter()
+            .enumerate()
+            .filter_map(|(index, island)| match island {
+                Island::Bridged(target) => Some((index, island_progress(game, index, *target))),
+                _ => None,
+            })
+            .collect();
+        Snapshot {
+            bridges,
+            islands,
+            hinted_island: state.hinted_island.get(),
+            background_color: state.background_color.get(),
+            show_coordinates: state.show_coordinates.get(),
+        }
+    }
+
+    ///
+    /// Get the region to redraw for the changes since the `previous` frame.
+    ///
+    fn dirty_region(
+        &self,
+        previous: Option<&Snapshot>,
+        game: &HexSystem,
+        layout: Layout,
+    ) -> Region {
+        let Some(previous) = previous.filter(|p| {
+            p.background_color == self.background_color
+                && p.show_coordinates == self.show_coordinates
+        }) else {
+            return Region::Full;
+        };
+        let mut region = Region::Rects(vec![]);
+        let animated = self
+            .bridges
+            .iter()
+            .filter(|(_, look)| look.animated)
+            .map(|(key, _)| *key);
+        for bridge in changed(&previous.bridges, &self.bridges)
+            .into_iter()
+            .chain(animated)
+        {
+            region.add(bridge_rect(game, bridge, layout));
+        }
+        for index in changed(&previous.islands, &self.islands) {
+            region.add(island_rect(game, index, layout));
+        }
+        if previous.hinted_island != self.hinted_island {
+            for index in [previous.hinted_island, self.hinted_island]
+                .into_iter()
+                .flatten()
             {
-                highlighted_islands.push(index);
+                region.add(island_rect(game, index, layout));
             }
         }
-        highlighted_islands
-    });
-
-    Effect::new(move |_| {
-        ctx.clear_rect(0.0, 0.0, width, height);
+        region
+    }
+}
 
-        let game = game.read().unwrap();
+///
+/// Get the area covered by the bridge between `from` and `to`, including highlights and labels.
+///
+fn bridge_rect(game: &HexSystem, (from, to): (usize, usize), layout: Layout) -> Rect {
+    Rect::around_line(
+        get_coordinates_from_index(game, from, layout),
+        get_coordinates_from_index(game, to, layout),
+        6.0 * layout.line_width + layout.font_size,
+    )
+}
 
-        draw_grid(
-            &ctx,
-            &game,
-            bridge_update,
-            background_color,
-            bridge_blocked,
-            highlighted_bridges,
-        );
+///
+/// Get the area covered by the island at `index`, including its label.
+///
+fn island_rect(game: &HexSystem, index: usize, layout: Layout) -> Rect {
+    Rect::around_point(
+        get_coordinates_from_index(game, index, layout),
+        2.0 * layout.island_size + 2.0 * layout.font_size,
+    )
+}
 
-        draw_islands(&ctx, &game, highlighted_islands);
-    });
+///
+/// Check if the `bridge` is empty and not among the `available` moves, e.g. because one of its islands is complete.
+///
+fn is_dimmed(game: &HexSystem, available: &[(usize, usize)], bridge: (usize, usize)) -> bool {
+    game.get_bridge(bridge.0, bridge.1)
+        .is_some_and(|b| *b.get_state() == BridgeState::Empty)
+        && !available.contains(&bridge)
 }
 
 ///
-/// Draw the lines between islands and the bridges
+/// Get the lines of the grid to draw on the board.
 ///
-fn draw_grid(
-    ctx: &CanvasRenderingContext2d,
-    game: &HexSystem,
-    bridge_update: ReadSignal<Option<(usize, usize)>>,
-    background_color: Memo<Option<String>>,
-    bridge_blocked: ReadSignal<Option<(usize, usize)>>,
-    highlighted_bridges: Memo<Vec<(usize, usize)>>,
-) {
-    ctx.set_stroke_style_str(GRID_COLOR);
-    ctx.set_line_width(0.5);
-    // Draw grid
-    for index in 0..game.islands.len() {
-        let (start_x, start_y) = get_coordinates_from_index(game, index);
-        let connections = HexSystem::get_connected_indices(game.columns, game.rows, index);
-        for c in connections.into_iter().flatten() {
-            let (end_x, end_y) = get_coordinates_from_index(game, c);
-            ctx.begin_path();
-            ctx.move_to(start_x, start_y);
-            ctx.line_to(end_x, end_y);
-            ctx.stroke();
+/// The lines shown on hover are drawn with the other hover highlights.
+///
+fn grid_lines(game: &HexSystem, state: &BoardState) -> Vec<(usize, usize)> {
+    match state.grid {
+        GridDisplay::All => {
+            let inside = |index: usize| game.shape.contains(game.columns, game.rows, index);
+            (0..game.islands.len())
+                .filter(|index| inside(*index))
+                .flat_map(|index| {
+                    HexSystem::get_connected_indices(game.columns, game.rows, index)
+                        .into_iter()
+                        .flatten()
+                        .filter(|c| inside(*c))
+                        .map(move |c| (index, c))
+                })
+                .collect()
         }
+        GridDisplay::Bridges => game.bridges.keys().copied().collect(),
+        GridDisplay::Hover => vec![],
     }
-    // Draw actual bridges
+}
+
+///
+/// Build the grid, the bridges and the islands as a mesh for the GPU renderer.
+///
+/// Animated bridges, highlights and text are left to the canvas on top.
+///
+fn board_mesh(game: &HexSystem, state: &BoardState, animations: &AnimationQueue) -> Mesh {
+    let layout = state.layout;
+    let line_width = layout.line_width;
+    let palette = &state.palette;
+    let mut mesh = Mesh::default();
+    let available = game.available_moves();
+    for (start_index, end_index) in grid_lines(game, state) {
+        let key = (
+            std::cmp::min(start_index, end_index),
+            std::cmp::max(start_index, end_index),
+        );
+        let dimmed = state.dim_unavailable && is_dimmed(game, &available, key);
+        mesh.line(
+            get_coordinates_from_index(game, start_index, layout),
+            get_coordinates_from_index(game, end_index, layout),
+            0.5 * line_width,
+            parse_color(if dimmed { DIMMED_BRIDGE } else { &palette.grid }),
+        );
+    }
+    let background = parse_color(&state.background_color.get().unwrap_or("white".to_string()));
     for ((start_index, end_index), bridge) in &game.bridges {
-        let start = get_coordinates_from_index(game, *start_index);
-        let end = get_coordinates_from_index(game, *end_index);
-        ctx.begin_path();
+        if animations.get((*start_index, *end_index)).is_some() {
+            continue;
+        }
+        let start = get_coordinates_from_index(game, *start_index, layout);
+        let end = get_coordinates_from_index(game, *end_index, layout);
         match bridge.get_state() {
             BridgeState::Empty => {}
             BridgeState::Partial => {
-                ctx.set_line_width(4.0);
-                ctx.set_stroke_style_str(BRIDGE_COLOR);
-                ctx.move_to(start.0, start.1);
-                ctx.line_to(end.0, end.1);
+                mesh.line(start, end, 4.0 * line_width, parse_color(&palette.bridge))
             }
             BridgeState::Full => {
-                let bc = background_color.get();
-                ctx.set_line_width(10.0);
-                ctx.set_stroke_style_str(BRIDGE_COLOR);
-                ctx.move_to(start.0, start.1);
-                ctx.line_to(end.0, end.1);
-                ctx.stroke();
-                ctx.begin_path();
-                ctx.set_line_width(4.0);
-                ctx.set_stroke_style_str(&bc.unwrap_or("white".to_string()));
-                ctx.move_to(start.0, start.1);
-                ctx.line_to(end.0, end.1);
-                ctx.stroke();
-                ctx.begin_path();
-                ctx.set_line_width(0.5);
-                ctx.set_stroke_style_str(GRID_COLOR);
-                ctx.move_to(start.0, start.1);
-                ctx.line_to(end.0, end.1);
+                mesh.line(start, end, 10.0 * line_width, parse_color(&palette.bridge));
+                mesh.line(start, end, 4.0 * line_width, background);
+                mesh.line(start, end, 0.5 * line_width, parse_color(&palette.grid));
+            }
+        }
+    }
+    for (index, island) in game.islands.iter().enumerate() {
+        if let Island::Bridged(target) = island {
+            let (color, _) = state
+                .palette
+                .island_colors(island_progress(game, index, *target));
+            let center = get_coordinates_from_index(game, index, layout);
+            if let Some(ring) = game.ring_color(index) {
+                mesh.disc(
+                    center,
+                    layout.island_size + 3.0 * layout.line_width,
+                    parse_color(ring),
+                );
+            }
+            mesh.disc(center, layout.island_size, parse_color(color));
+        }
+    }
+    mesh
+}
+
+///
+/// Render the moves of `session` from the start of the puzzle into an animated PNG.
+///
+/// The board is drawn in software, so that no canvas is needed.
+///
+fn export_replay(
+    session: &GameSession,
+    palette: &Theme,
+    background: &str,
+    layout: Layout,
+) -> Result<Vec<u8>, String> {
+    let layout = layout.fitted(session.system(), REPLAY_SIZE, REPLAY_SIZE);
+    let (width, height) = get_board_size(session.system(), layout);
+    let (width, height) = (width.ceil() as u32, height.ceil() as u32);
+    let background = parse_color(background);
+    let moves = session.history().len();
+    let step = moves.div_ceil(REPLAY_MAX_FRAMES).max(1);
+    let mut counts: Vec<usize> = (0..moves).step_by(step).collect();
+    counts.push(moves);
+    let frames: Vec<_> = counts
+        .iter()
+        .map(|count| {
+            let mesh = replay_mesh(&session.system_at(*count), palette, layout, background);
+            let delay = if *count == moves {
+                REPLAY_END_MS
+            } else {
+                REPLAY_FRAME_MS
+            };
+            (mesh.rasterize(width, height, background), delay)
+        })
+        .collect();
+    encode_apng(&frames, width, height)
+}
+
+///
+/// Build the grid, the bridges with their locks and the numbered islands of `game` with their colors
+/// as a mesh to draw a frame of a replay.
+///
+fn replay_mesh(game: &HexSystem, palette: &Theme, layout: Layout, background: [f32; 4]) -> Mesh {
+    let line_width = layout.line_width;
+    let mut mesh = Mesh::default();
+    let (grid, bridge) = (parse_color(&palette.grid), parse_color(&palette.bridge));
+    for ((start_index, end_index), state) in &game.bridges {
+        let start = get_coordinates_from_index(game, *start_index, layout);
+        let end = get_coordinates_from_index(game, *end_index, layout);
+        match state.get_state() {
+            BridgeState::Empty => mesh.line(start, end, 0.5 * line_width, grid),
+            BridgeState::Partial => mesh.line(start, end, 4.0 * line_width, bridge),
+            BridgeState::Full => {
+                mesh.line(start, end, 10.0 * line_width, bridge);
+                mesh.line(start, end, 4.0 * line_width, background);
+                mesh.line(start, end, 0.5 * line_width, grid);
+            }
+        }
+        if state.is_locked() {
+            let middle = ((start.0 + end.0) / 2.0, (start.1 + end.1) / 2.0);
+            mesh.disc(middle, LOCK_MARKER_SIZE * line_width, bridge);
+        }
+    }
+    for (index, island) in game.islands.iter().enumerate() {
+        if let Island::Bridged(target) = island {
+            let (color, text) = palette.island_colors(island_progress(game, index, *target));
+            let center = get_coordinates_from_index(game, index, layout);
+            if let Some(ring) = game.ring_color(index) {
+                mesh.disc(
+                    center,
+                    layout.island_size + 3.0 * line_width,
+                    parse_color(ring),
+                );
             }
+            mesh.disc(center, layout.island_size, parse_color(color));
+            draw_number(
+                &mut mesh,
+                center,
+                layout.island_size,
+                *target,
+                parse_color(text),
+            );
+        }
+    }
+    mesh
+}
+
+///
+/// Draw the lines between islands and the bridges
+///
+/// With `gpu`, grid and bridges are already drawn except for the animated bridges.
+///
+fn draw_grid(ctx: &CanvasRenderingContext2d, game: &HexSystem, state: &BoardState, frame: &Frame) {
+    let layout = state.layout;
+    let line_width = layout.line_width;
+    let palette = &state.palette;
+    let (animations, now, gpu) = (frame.animations, frame.now, frame.gpu);
+    let visible =
+        |from: usize, to: usize| frame.region.touches(&bridge_rect(game, (from, to), layout));
+    ctx.set_stroke_style_str(&palette.grid);
+    ctx.set_line_width(0.5 * line_width);
+    // Draw grid
+    let grid_lines = if gpu { vec![] } else { grid_lines(game, state) };
+    let available = game.available_moves();
+    for (start_index, end_index) in grid_lines {
+        if !visible(start_index, end_index) {
+            continue;
         }
+        let (start_x, start_y) = get_coordinates_from_index(game, start_index, layout);
+        let (end_x, end_y) = get_coordinates_from_index(game, end_index, layout);
+        let key = (
+            std::cmp::min(start_index, end_index),
+            std::cmp::max(start_index, end_index),
+        );
+        let dimmed = state.dim_unavailable && is_dimmed(game, &available, key);
+        ctx.begin_path();
+        ctx.set_stroke_style_str(if dimmed { DIMMED_BRIDGE } else { &palette.grid });
+        ctx.move_to(start_x, start_y);
+        ctx.line_to(end_x, end_y);
         ctx.stroke();
     }
-    // Draw hovering
+    // Draw actual bridges
+    let bc = state.background_color.get().unwrap_or("white".to_string());
+    for ((start_index, end_index), bridge) in &game.bridges {
+        if !visible(*start_index, *end_index) {
+            continue;
+        }
+        let start = get_coordinates_from_index(game, *start_index, layout);
+        let end = get_coordinates_from_index(game, *end_index, layout);
+        match animations.get((*start_index, *end_index)) {
+            // Let the new bridge grow from one island to the other.
+            Some(animation) if animation.to > animation.from => {
+                let progress = animation.progress(now);
+                let tip = (
+                    start.0 + (end.0 - start.0) * progress,
+                    start.1 + (end.1 - start.1) * progress,
+                );
+                draw_bridge(ctx, &animation.from, start, end, &bc, palette, line_width);
+                draw_bridge(ctx, &animation.to, start, tip, &bc, palette, line_width);
+            }
+            // Fade out the removed bridge.
+            Some(animation) => {
+                ctx.set_global_alpha(1.0 - animation.progress(now));
+                draw_bridge(ctx, &animation.from, start, end, &bc, palette, line_width);
+                ctx.set_global_alpha(1.0);
+                draw_bridge(ctx, &animation.to, start, end, &bc, palette, line_width);
+            }
+            None if gpu => {}
+            None => draw_bridge(
+                ctx,
+                bridge.get_state(),
+                start,
+                end,
+                &bc,
+                palette,
+                line_width,
+            ),
+        }
+    }
+    // Draw pencil marks of empty bridges
+    ctx.set_line_width(2.0 * line_width);
+    ctx.set_stroke_style_str(PENCIL_MARK);
+    let _ = ctx.set_line_dash(&js_sys::Array::of2(
+        &JsValue::from_f64(3.0 * line_width),
+        &JsValue::from_f64(3.0 * line_width),
+    ));
+    for (start_index, end_index) in state.pencil_marks.get() {
+        if visible(start_index, end_index)
+            && game
+                .get_bridge(start_index, end_index)
+                .is_some_and(|b| *b.get_state() == BridgeState::Empty)
+        {
+            let start = get_coordinates_from_index(game, start_index, layout);
+            let end = get_coordinates_from_index(game, end_index, layout);
+            ctx.begin_path();
+            ctx.move_to(start.0, start.1);
+            ctx.line_to(end.0, end.1);
+            ctx.stroke();
+        }
+    }
+    let _ = ctx.set_line_dash(&js_sys::Array::new());
+    // Mark locked bridges
+    ctx.set_fill_style_str(&palette.bridge);
+    for ((start_index, end_index), _) in game
+        .bridges
+        .iter()
+        .filter(|((from, to), b)| b.is_locked() && visible(*from, *to))
+    {
+        let start = get_coordinates_from_index(game, *start_index, layout);
+        let end = get_coordinates_from_index(game, *end_index, layout);
+        ctx.begin_path();
+        ctx.arc(
+            (start.0 + end.0) / 2.0,
+            (start.1 + end.1) / 2.0,
+            LOCK_MARKER_SIZE * line_width,
+            0.0,
+            2.0 * PI,
+        )
+        .unwrap();
+        ctx.fill();
+    }
+    if state.bridge_badges {
+        draw_badges(ctx, game, state, &visible);
+    }
+    // Draw bridges revealed as wrong or missing
+    ctx.set_line_width(12.0 * line_width);
+    for (color, bridges) in [
+        (WRONG_BRIDGE, state.wrong_bridges.get()),
+        (MISSING_BRIDGE, state.missing_bridges.get()),
+    ] {
+        ctx.set_stroke_style_str(color);
+        for (start_index, end_index) in bridges {
+            if !visible(start_index, end_index) {
+                continue;
+            }
+            let start = get_coordinates_from_index(game, start_index, layout);
+            let end = get_coordinates_from_index(game, end_index, layout);
+            ctx.begin_path();
+            ctx.move_to(start.0, start.1);
+            ctx.line_to(end.0, end.1);
+            ctx.stroke();
+        }
+    }
     for (start_index, end_index) in game.bridges.keys() {
-        let start = get_coordinates_from_index(game, *start_index);
-        let end = get_coordinates_from_index(game, *end_index);
-        // log!(
-        //     "{} {} {:?} {:?} {:?} {}",
-        //     start_index,
-        //     end_index,
-        //     point,
-        //     start,
-        //     end,
-        //     point_close_to_line(point, start, end, 10.0)
-        // );
-        if bridge_update.get() != Some((*start_index, *end_index))
-            && highlighted_bridges
-                .get()
-                .contains(&(*start_index, *end_index))
+        if !visible(*start_index, *end_index) {
+            continue;
+        }
+        let start = get_coordinates_from_index(game, *start_index, layout);
+        let end = get_coordinates_from_index(game, *end_index, layout);
+        // Shake the blocked bridge and the bridges in its way
+        if let Some(blocked) = animations.blocked()
+            && blocked.involves((*start_index, *end_index))
         {
+            let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+            let length = (dx * dx + dy * dy).sqrt().max(1.0);
+            let offset = blocked.shake(now) * SHAKE_AMPLITUDE * line_width;
+            let (nx, ny) = (-dy / length * offset, dx / length * offset);
+            let width = if blocked.attempted == (*start_index, *end_index) {
+                6.0
+            } else {
+                10.0
+            };
             ctx.begin_path();
-            ctx.set_line_width(10.0);
-            ctx.set_stroke_style_str(HOVER_BRIDGE);
+            ctx.set_global_alpha(blocked.flash(now));
+            ctx.set_line_width(width * line_width);
+            ctx.set_stroke_style_str(BLOCKED_BRIDGE);
+            ctx.move_to(start.0 + nx, start.1 + ny);
+            ctx.line_to(end.0 + nx, end.1 + ny);
+            ctx.stroke();
+            ctx.set_global_alpha(1.0);
+        }
+    }
+}
+
+///
+/// Draw the number of bridges in a badge in the middle of each placed bridge.
+///
+/// Locked bridges get inverted badges, since the badge covers their marker.
+///
+fn draw_badges(
+    ctx: &CanvasRenderingContext2d,
+    game: &HexSystem,
+    state: &BoardState,
+    visible: &impl Fn(usize, usize) -> bool,
+) {
+    let layout = state.layout;
+    let background = state.background_color.get().unwrap_or("white".to_string());
+    ctx.set_font(&format!("{}pt Arial", layout.font_size * 0.6));
+    ctx.set_text_align("center");
+    ctx.set_text_baseline("middle");
+    ctx.set_line_width(layout.line_width);
+    for ((start_index, end_index), bridge) in &game.bridges {
+        let count = match bridge.get_state() {
+            BridgeState::Empty => continue,
+            BridgeState::Partial => "1",
+            BridgeState::Full => "2",
+        };
+        if !visible(*start_index, *end_index) {
+            continue;
+        }
+        let start = get_coordinates_from_index(game, *start_index, layout);
+        let end = get_coordinates_from_index(game, *end_index, layout);
+        let center = ((start.0 + end.0) / 2.0, (start.1 + end.1) / 2.0);
+        let (fill, text) = if bridge.is_locked() {
+            (state.palette.bridge.as_str(), background.as_str())
+        } else {
+            (background.as_str(), state.palette.bridge.as_str())
+        };
+        ctx.begin_path();
+        let _ = ctx.arc(center.0, center.1, layout.font_size * 0.6, 0.0, 2.0 * PI);
+        ctx.set_fill_style_str(fill);
+        ctx.fill();
+        ctx.set_stroke_style_str(&state.palette.bridge);
+        ctx.stroke();
+        ctx.set_fill_style_str(text);
+        let _ = ctx.fill_text(count, center.0, center.1);
+    }
+}
+
+///
+/// Draw a single bridge in `state` from `start` to `end` with lines scaled by `line_width`.
+///
+fn draw_bridge(
+    ctx: &CanvasRenderingContext2d,
+    state: &BridgeState,
+    start: (f64, f64),
+    end: (f64, f64),
+    background_color: &str,
+    palette: &Theme,
+    line_width: f64,
+) {
+    ctx.begin_path();
+    match state {
+        BridgeState::Empty => {}
+        BridgeState::Partial => {
+            ctx.set_line_width(4.0 * line_width);
+            ctx.set_stroke_style_str(&palette.bridge);
+            ctx.move_to(start.0, start.1);
+            ctx.line_to(end.0, end.1);
+        }
+        BridgeState::Full => {
+            ctx.set_line_width(10.0 * line_width);
+            ctx.set_stroke_style_str(&palette.bridge);
             ctx.move_to(start.0, start.1);
             ctx.line_to(end.0, end.1);
             ctx.stroke();
+            ctx.begin_path();
+            ctx.set_line_width(4.0 * line_width);
+            ctx.set_stroke_style_str(background_color);
+            ctx.move_to(start.0, start.1);
+            ctx.line_to(end.0, end.1);
+            ctx.stroke();
+            ctx.begin_path();
+            ctx.set_line_width(0.5 * line_width);
+            ctx.set_stroke_style_str(&palette.grid);
+            ctx.move_to(start.0, start.1);
+            ctx.line_to(end.0, end.1);
         }
-        // Draw blocked bridge
-        if bridge_blocked.get() == Some((*start_index, *end_index)) {
+    }
+    ctx.stroke();
+}
+
+///
+/// Draw the directions the hovered islands can still be connected to.
+///
+/// The number of bridges that can still be added is shown in the middle of each direction.
+///
+fn draw_directions(
+    ctx: &CanvasRenderingContext2d,
+    game: &HexSystem,
+    islands: &[usize],
+    layout: Layout,
+) {
+    for index in islands {
+        let center = get_coordinates_from_index(game, *index, layout);
+        for other in game.get_connected_islands(*index) {
+            let capacity = game.remaining_capacity(*index, other);
+            if capacity == 0 {
+                continue;
+            }
+            // The highlights are drawn on top, so the lines stop at the islands.
+            let (start, end) = between_islands(
+                center,
+                get_coordinates_from_index(game, other, layout),
+                layout,
+            );
             ctx.begin_path();
-            ctx.set_line_width(6.0);
-            ctx.set_stroke_style_str("rgba(255.0,0.0,0.0,0.8");
+            ctx.set_line_width(2.0 * layout.line_width);
+            ctx.set_stroke_style_str(DIRECTION_PREVIEW);
+            let _ = ctx.set_line_dash(&js_sys::Array::of2(
+                &JsValue::from_f64(6.0 * layout.line_width),
+                &JsValue::from_f64(4.0 * layout.line_width),
+            ));
             ctx.move_to(start.0, start.1);
             ctx.line_to(end.0, end.1);
             ctx.stroke();
+            let _ = ctx.set_line_dash(&js_sys::Array::new());
+
+            ctx.set_font(&format!("{}pt Arial", layout.font_size * 0.6));
+            ctx.set_fill_style_str(DIRECTION_PREVIEW);
+            ctx.set_text_align("center");
+            ctx.set_text_baseline("bottom");
+            let _ = ctx.fill_text(
+                &capacity.to_string(),
+                (start.0 + end.0) / 2.0,
+                (start.1 + end.1) / 2.0,
+            );
         }
     }
 }
 
+///
+/// Get how far the bridges of the island at `index` with `target` bridges are.
+///
+fn island_progress(game: &HexSystem, index: usize, target: usize) -> IslandProgress {
+    let actual = game.get_actual_bridges(index);
+    if actual == 0 {
+        IslandProgress::Empty
+    } else if actual != target {
+        IslandProgress::Unfinished
+    } else {
+        IslandProgress::Finished
+    }
+}
+
+///
+/// Get the part of the island with `target` bridges that is done with `actual` bridges, from 0 to 1.
+///
+fn fill_ratio(actual: usize, target: usize) -> f64 {
+    if target == 0 {
+        1.0
+    } else {
+        f64::min(1.0, actual as f64 / target as f64)
+    }
+}
+
 ///
 /// Draw islands, including highlighting.
 ///
 fn draw_islands(
     ctx: &CanvasRenderingContext2d,
     game: &HexSystem,
-    highlighted_islands: Memo<Vec<usize>>,
+    state: &BoardState,
+    frame: &Frame,
 ) {
+    let layout = state.layout;
+    let palette = &state.palette;
     for (index, island) in game.islands.iter().enumerate() {
-        if let Island::Bridged(target) = island {
-            let actual = game.get_actual_bridges(index);
-            let (island_color, text_color) = if actual == 0 {
-                ISLAND_COLOR
-            } else if actual != *target {
-                UNFINISHED_ISLAND_COLOR
-            } else {
-                FINISHED_ISLAND_COLOR
-            };
-            let (x, y) = get_coordinates_from_index(game, index);
-            ctx.begin_path();
-            ctx.arc(x, y, ISLAND_SIZE, 0.0, 2.0 * PI).unwrap();
-            ctx.set_fill_style_str(island_color);
-            ctx.fill();
-            ctx.set_line_width(3.0);
-            ctx.set_stroke_style_str("transparent");
-            ctx.stroke();
-
-            // Draw hovering
-            // Order of the two conditions is important here: If it was different, there is no update when moved within element.
-            if highlighted_islands.get().contains(&index) {
+        if let Island::Bridged(target) = island
+            && frame.region.touches(&island_rect(game, index, layout))
+        {
+            let (island_color, text_color) =
+                palette.island_colors(island_progress(game, index, *target));
+            let (x, y) = get_coordinates_from_index(game, index, layout);
+            // The GPU renderer has drawn the island already.
+            if !frame.gpu {
+                ctx.begin_path();
+                ctx.arc(x, y, layout.island_size, 0.0, 2.0 * PI).unwrap();
+                ctx.set_fill_style_str(island_color);
+                ctx.fill();
+                ctx.set_line_width(3.0 * layout.line_width);
+                ctx.set_stroke_style_str(game.ring_color(index).unwrap_or("transparent"));
+                ctx.stroke();
+            }
+            if state.hinted_island.get() == Some(index) {
                 ctx.begin_path();
-                ctx.set_line_width(3.0);
-                ctx.set_stroke_style_str(HOVER_ISLAND);
-                ctx.arc(x, y, ISLAND_SIZE + 5.0, 0.0, 2.0 * PI).unwrap();
-                ctx.set_fill_style_str("transparent");
+                ctx.arc(
+                    x,
+                    y,
+                    layout.island_size + 4.0 * layout.line_width,
+                    0.0,
+                    2.0 * PI,
+                )
+                .unwrap();
+                ctx.set_line_width(4.0 * layout.line_width);
+                ctx.set_stroke_style_str(MISSING_BRIDGE);
                 ctx.stroke();
             }
+
+            // Numbers would be unreadable when zoomed out far, so show how complete the island is.
+            if layout.font_size < MIN_READABLE_FONT_SIZE {
+                let ratio = fill_ratio(game.get_actual_bridges(index), *target);
+                if ratio > 0.0 {
+                    ctx.begin_path();
+                    ctx.move_to(x, y);
+                    let _ = ctx.arc(
+                        x,
+                        y,
+                        layout.island_size * 0.7,
+                        -PI / 2.0,
+                        -PI / 2.0 + ratio * 2.0 * PI,
+                    );
+                    ctx.close_path();
+                    ctx.set_fill_style_str(text_color);
+                    ctx.fill();
+                }
+                continue;
+            }
             ctx.begin_path();
-            ctx.set_line_width(3.0);
+            ctx.set_line_width(3.0 * layout.line_width);
             ctx.set_stroke_style_str("transparent");
             // Text
-            ctx.set_font("12pt Arial");
+            ctx.set_font(&format!("{}pt Arial", layout.font_size));
             ctx.set_fill_style_str(text_color);
             ctx.set_text_align("center");
             ctx.set_text_baseline("middle");
             // ctx.fill_text(&index.to_string(), x, y).unwrap();
             ctx.fill_text(&target.to_string(), x, y).unwrap();
             ctx.stroke();
+
+            if state.show_coordinates.get() {
+                ctx.set_font(&format!("{}pt Arial", layout.font_size * 0.6));
+                ctx.set_fill_style_str(&palette.bridge);
+                ctx.set_text_align("left");
+                ctx.set_text_baseline("bottom");
+                ctx.fill_text(
+                    &game.get_label(index),
+                    x + layout.island_size * 0.7,
+                    y - layout.island_size * 0.7,
+                )
+                .unwrap();
+            }
         }
     }
 }
 
 ///
-/// Get bridge tuple for (x, y) coordinates within canvas.
+/// Get the bridges at (x, y) like `get_bridges_from_coordinates` and record how long it took.
+///
+fn hit_test(game: &HexSystem, x: i32, y: i32, layout: Layout) -> Vec<(usize, usize)> {
+    measure(HIT_TEST, || {
+        get_bridges_from_coordinates(game, x, y, layout)
+    })
+}
+
+///
+/// Get the bridges close to (x, y) coordinates within canvas.
 ///
+/// Returns the closest bridge or, if the click is ambiguous, all bridges about as close as the closest one.
 ///
-fn get_bridge_from_coordinates(game: &HexSystem, x: i32, y: i32) -> Option<(usize, usize)> {
-    for (start_index, end_index) in game.bridges.keys() {
-        let start = get_coordinates_from_index(game, *start_index);
-        let end = get_coordinates_from_index(game, *end_index);
-        if point_close_to_line((x as f64, y as f64), start, end, 10.0) {
-            return Some((*start_index, *end_index));
-        }
+fn get_bridges_from_coordinates(
+    game: &HexSystem,
+    x: i32,
+    y: i32,
+    layout: Layout,
+) -> Vec<(usize, usize)> {
+    let point = (x as f64, y as f64);
+    let mut candidates: Vec<_> = game
+        .bridges
+        .keys()
+        .map(|(start_index, end_index)| {
+            let start = get_coordinates_from_index(game, *start_index, layout);
+            let end = get_coordinates_from_index(game, *end_index, layout);
+            (
+                distance_to_line(point, start, end),
+                (*start_index, *end_index),
+            )
+        })
+        .filter(|(distance, _)| *distance < 10.0 * layout.scale)
+        .collect();
+    candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let Some((closest, _)) = candidates.first().copied() else {
+        return vec![];
+    };
+    candidates
+        .into_iter()
+        .take_while(|(distance, _)| distance - closest < AMBIGUOUS_DISTANCE * layout.scale)
+        .map(|(_, bridge)| bridge)
+        .collect()
+}
+
+///
+/// Bridges offered after an ambiguous click.
+///
+#[derive(Clone, Debug, PartialEq)]
+struct Chooser {
+    options: Vec<ChooserOption>,
+    /// Action applied to the chosen bridge
+    action: Action,
+}
+
+///
+/// Button of the bridge chooser.
+///
+#[derive(Clone, Debug, PartialEq)]
+struct ChooserOption {
+    bridge: (usize, usize),
+    label: String,
+    /// Position of the button within canvas
+    position: (f64, f64),
+}
+
+impl Chooser {
+    ///
+    /// Arrange `bridges` around `point`, each in the direction of its middle.
+    ///
+    fn new(
+        game: &HexSystem,
+        point: (f64, f64),
+        bridges: &[(usize, usize)],
+        action: Action,
+        layout: Layout,
+    ) -> Self {
+        let options = bridges
+            .iter()
+            .map(|(from, to)| {
+                let start = get_coordinates_from_index(game, *from, layout);
+                let end = get_coordinates_from_index(game, *to, layout);
+                let angle =
+                    ((start.1 + end.1) / 2.0 - point.1).atan2((start.0 + end.0) / 2.0 - point.0);
+                let radius = CHOOSER_RADIUS * layout.scale;
+                ChooserOption {
+                    bridge: (*from, *to),
+                    label: format!("{}–{}", game.get_label(*from), game.get_label(*to)),
+                    position: (
+                        point.0 + radius * angle.cos(),
+                        point.1 + radius * angle.sin(),
+                    ),
+                }
+            })
+            .collect();
+        Chooser { options, action }
     }
-    None
 }
 
 ///
 /// Get (x, y) coordinates within canvas for `index` of island.
 ///
-///
-fn get_coordinates_from_index(game: &HexSystem, index: usize) -> (f64, f64) {
-    let triangle_thigh: f64 = LINE_HEIGHT / (60.0 * PI / 180.0).sin();
-    let (row, column) = game.get_row_column_for_index(index);
-    let even_row = row % 2 == 0;
-    // log!("{} {} {} {} {} {}", index, game.islands.len(), game.columns, even_row, row, column);
+fn get_coordinates_from_index(game: &HexSystem, index: usize, layout: Layout) -> (f64, f64) {
+    let (x, y) = game.transformed_position(
+        index,
+        LINE_HEIGHT * layout.scale,
+        layout.orientation,
+        layout.transform,
+    );
+    (
+        MARGIN * layout.scale + x + layout.offset.0,
+        y + layout.offset.1,
+    )
+}
 
-    let x = 75.0
-        + triangle_thigh
-        + column as f64 * triangle_thigh
-        + if even_row { 0.0 } else { -triangle_thigh * 0.5 };
-    let y = LINE_HEIGHT + row as f64 * LINE_HEIGHT;
-    (x, y)
+///
+/// Get (width, height) of the area within canvas covered by the board including a margin.
+///
+fn get_board_size(game: &HexSystem, layout: Layout) -> (f64, f64) {
+    let (width, height) = game.transformed_bounding_box(
+        LINE_HEIGHT * layout.scale,
+        layout.orientation,
+        layout.transform,
+    );
+    (
+        MARGIN * layout.scale + width + layout.offset.0,
+        height + layout.offset.1,
+    )
 }
 
 ///
-/// Is `point` closer to line defined by `start` and `end` points as `max_distance``.
-///       
+/// Get the distance of `point` to the line defined by `start` and `end` points.
 ///
-fn point_close_to_line(
-    point: (f64, f64),
-    start: (f64, f64),
-    end: (f64, f64),
-    max_distance: f64,
-) -> bool {
+fn distance_to_line(point: (f64, f64), start: (f64, f64), end: (f64, f64)) -> f64 {
     let start_end = (end.0 - start.0, end.1 - start.1);
     let start_point = (point.0 - start.0, point.1 - start.1);
     let ab_len_squared = start_end.0 * start_end.0 + start_end.1 * start_end.1;
@@ -503,72 +4001,237 @@ fn point_close_to_line(
         start.0 + t_clamped * start_end.0,
         start.1 + t_clamped * start_end.1,
     );
-    let distance = ((point.0 - closest.0).powf(2.0) + (point.1 - closest.1).powf(2.0)).sqrt();
-    distance < max_distance
+    ((point.0 - closest.0).powf(2.0) + (point.1 - closest.1).powf(2.0)).sqrt()
 }
 
 #[cfg(test)]
+#[allow(clippy::legacy_numeric_constants)]
 mod test {
-    use std::{collections::BTreeMap, f64::EPSILON};
+    use std::f64::EPSILON;
+
+    use hexhashi_logic::{
+        hex::{HexSystem, Island},
+        layout::Orientation,
+    };
+
+    use crate::{game::LINE_HEIGHT, theme::Theme};
+
+    use super::{
+        Hover, Layout, MAX_FOCUS_ZOOM, between_islands, compare_to_par, distance_to_line,
+        fill_ratio, format_time, get_bridges_from_coordinates, get_coordinates_from_index,
+        replay_mesh,
+    };
+
+    #[cfg(feature = "multiplayer")]
+    #[test]
+    fn race_seats() {
+        use super::race_seat;
+
+        let mut seats = vec![];
+        assert_eq!(race_seat(&mut seats, 7), Some(0));
+        assert_eq!(race_seat(&mut seats, 3), Some(1));
+        assert_eq!(race_seat(&mut seats, 7), Some(0));
+        // Only two players race, further clients are left out.
+        assert_eq!(race_seat(&mut seats, 5), None);
+        assert_eq!(seats, [7, 3]);
+    }
 
-    use hexhashi_logic::hex::{HexSystem, Island};
+    #[test]
+    fn replay_frame() {
+        let mut islands = vec![Island::Empty; 22];
+        islands[0] = Island::Bridged(1);
+        islands[4] = Island::Bridged(1);
+        let mut sys = HexSystem::from_islands(4, 5, islands);
+        let triangles = |sys: &HexSystem| {
+            replay_mesh(sys, &Theme::default(), Layout::default(), [1.0; 4]).triangles()
+        };
+        let plain = triangles(&sys);
+        // Locked bridges are marked and colored islands get their rings.
+        sys.toggle_lock(0, 4).unwrap();
+        let locked = triangles(&sys);
+        assert!(locked > plain);
+        sys.colors = vec![0; 22];
+        assert!(triangles(&sys) > locked);
+    }
 
-    use crate::game::LINE_HEIGHT;
+    #[test]
+    fn time_format() {
+        assert_eq!(format_time(0.0), "00:00");
+        assert_eq!(format_time(999.0), "00:00");
+        assert_eq!(format_time(61_500.0), "01:01");
+        assert_eq!(format_time(3_600_000.0), "60:00");
+        assert_eq!(compare_to_par(90_000.0, 120_000.0), "00:30 under par");
+        assert_eq!(compare_to_par(125_000.0, 120_000.0), "00:05 over par");
+    }
 
-    use super::{get_coordinates_from_index, point_close_to_line};
+    #[test]
+    fn completion() {
+        assert_eq!(fill_ratio(0, 4), 0.0);
+        assert_eq!(fill_ratio(1, 4), 0.25);
+        assert_eq!(fill_ratio(6, 4), 1.0);
+        assert_eq!(fill_ratio(0, 0), 1.0);
+    }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn distance() {
         let start = (20.0, 20.0);
         let end = (40.0, 40.0);
         let point = (30.0, 30.0);
-        let distance = point_close_to_line(point, start, end, 5.0);
+        let distance = distance_to_line(point, start, end) < 5.0;
         assert_eq!(distance, true);
-        let distance = point_close_to_line(point, end, start, 5.0);
+        let distance = distance_to_line(point, end, start) < 5.0;
         assert_eq!(distance, true);
         let point = (32.0, 32.0);
-        let distance = point_close_to_line(point, start, end, 5.0);
+        let distance = distance_to_line(point, start, end) < 5.0;
         assert_eq!(distance, true);
-        let distance = point_close_to_line(point, end, start, 5.0);
+        let distance = distance_to_line(point, end, start) < 5.0;
         assert_eq!(distance, true);
         let point = (5.0, 5.0);
-        let distance = point_close_to_line(point, start, end, 5.0);
+        let distance = distance_to_line(point, start, end) < 5.0;
         assert_eq!(distance, false);
         let point = (60.0, 60.0);
-        let distance = point_close_to_line(point, start, end, 5.0);
+        let distance = distance_to_line(point, start, end) < 5.0;
         assert_eq!(distance, false);
-        let distance = point_close_to_line(point, end, start, 5.0);
+        let distance = distance_to_line(point, end, start) < 5.0;
         assert_eq!(distance, false);
         let point = (40.0, 20.0);
-        let distance = point_close_to_line(point, start, end, 5.0);
+        let distance = distance_to_line(point, start, end) < 5.0;
         assert_eq!(distance, false);
-        let distance = point_close_to_line(point, end, start, 5.0);
+        let distance = distance_to_line(point, end, start) < 5.0;
         assert_eq!(distance, false);
     }
 
     #[test]
-    fn index_to_coordinate() {
-        let sys = HexSystem {
-            columns: 4,
-            rows: 5,
-            islands: vec![Island::Empty; 22],
-            bridges: BTreeMap::new(),
+    fn click_disambiguation() {
+        let mut islands = ['0'; 22];
+        islands[0] = '2';
+        islands[4] = '1';
+        islands[15] = '1';
+        let code = format!("4.5.{}.00", islands.iter().collect::<String>());
+        let sys = HexSystem::from_code(&code).unwrap();
+        let layout = Layout::default();
+        let at = |index| {
+            let (x, y) = get_coordinates_from_index(&sys, index, layout);
+            (x as i32, y as i32)
         };
+        // Both bridges start at the island.
+        let (x, y) = at(0);
+        assert_eq!(
+            get_bridges_from_coordinates(&sys, x, y, layout),
+            vec![(0, 4), (0, 15)]
+        );
+        // Close to the island, but closer to one of the bridges
+        let ((x0, y0), (x4, y4)) = (at(0), at(4));
+        let (x, y) = (x0 + (x4 - x0) / 3, y0 + (y4 - y0) / 3);
+        assert_eq!(
+            get_bridges_from_coordinates(&sys, x, y, layout),
+            vec![(0, 4)]
+        );
+        assert!(get_bridges_from_coordinates(&sys, 0, 0, layout).is_empty());
+    }
+
+    #[test]
+    fn hover() {
+        let mut islands = ['0'; 22];
+        islands[0] = '2';
+        islands[4] = '1';
+        islands[15] = '1';
+        let code = format!("4.5.{}.00", islands.iter().collect::<String>());
+        let sys = HexSystem::from_code(&code).unwrap();
+        let layout = Layout::default();
+        let (start, end) = (
+            get_coordinates_from_index(&sys, 0, layout),
+            get_coordinates_from_index(&sys, 4, layout),
+        );
+        let island = Hover::at(&sys, start, layout);
+        assert_eq!(island.islands, [0]);
+        assert_eq!(island.bridges, [(0, 4), (0, 15)]);
+        // Only the bridge a click would change
+        let point = (
+            start.0 + (end.0 - start.0) / 2.0,
+            start.1 + (end.1 - start.1) / 2.0,
+        );
+        let bridge = Hover::at(&sys, point, layout);
+        assert!(bridge.islands.is_empty());
+        assert_eq!(bridge.bridges, [(0, 4)]);
+        assert_eq!(Hover::at(&sys, (0.0, 0.0), layout), Hover::default());
 
-        let (x, y) = get_coordinates_from_index(&sys, 0);
+        let (from, to) = between_islands(start, end, layout);
+        let length = |(x0, y0): (f64, f64), (x1, y1): (f64, f64)| (x1 - x0).hypot(y1 - y0);
+        assert!((length(start, from) - layout.island_size).abs() < 1e-9);
+        assert!((length(to, end) - layout.island_size).abs() < 1e-9);
+    }
+
+    #[test]
+    fn focused() {
+        let mut islands = ['0'; 22];
+        islands[0] = '2';
+        islands[4] = '1';
+        islands[15] = '1';
+        islands[21] = '1';
+        let code = format!("4.5.{}.00", islands.iter().collect::<String>());
+        let sys = HexSystem::from_code(&code).unwrap();
+        let (width, height) = (800.0, 600.0);
+        let layout = Layout::default().focused(&sys, 0, width, height);
+        assert!(layout.scale > Layout::default().scale);
+        let inside = |(x, y): (f64, f64)| (0.0..=width).contains(&x) && (0.0..=height).contains(&y);
+        for index in [0, 4, 15] {
+            assert!(inside(get_coordinates_from_index(&sys, index, layout)));
+        }
+        // A lonely island is not zoomed without limit.
+        assert!(sys.get_connected_islands(21).is_empty());
+        let lonely = Layout::default().focused(&sys, 21, width, height);
+        assert_eq!(lonely.scale, MAX_FOCUS_ZOOM * Layout::default().scale);
+        let (x, y) = get_coordinates_from_index(&sys, 21, lonely);
+        assert!((x - width / 2.0).abs() < 1e-9 && (y - height / 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn index_to_coordinate() {
+        let sys = HexSystem::from_islands(4, 5, vec![Island::Empty; 22]);
+
+        let (x, y) = get_coordinates_from_index(&sys, 0, Layout::default());
         assert!((x - 132.73502691896257).abs() < EPSILON);
         assert!((y - LINE_HEIGHT).abs() < EPSILON);
 
-        let (x, y) = get_coordinates_from_index(&sys, 3);
+        let (x, y) = get_coordinates_from_index(&sys, 3, Layout::default());
         assert!((x - 305.9401076758503).abs() < EPSILON);
         assert!((y - LINE_HEIGHT).abs() < EPSILON);
 
-        let (x, y) = get_coordinates_from_index(&sys, 4);
+        let (x, y) = get_coordinates_from_index(&sys, 4, Layout::default());
         assert!((x - 103.86751345948129).abs() < EPSILON);
         assert!((y - 2.0 * LINE_HEIGHT).abs() < EPSILON);
 
-        let (x, y) = get_coordinates_from_index(&sys, 21);
+        let (x, y) = get_coordinates_from_index(&sys, 21, Layout::default());
         assert!((x - 305.9401076758503).abs() < EPSILON);
         assert!((y - 5.0 * LINE_HEIGHT).abs() < EPSILON);
+
+        let scaled = Layout {
+            scale: 2.0,
+            ..Layout::default()
+        };
+        let (x, y) = get_coordinates_from_index(&sys, 21, scaled);
+        assert!((x - 2.0 * 305.9401076758503).abs() < 1e-9);
+        assert!((y - 10.0 * LINE_HEIGHT).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn index_to_coordinate_flat_top() {
+        let sys = HexSystem::from_islands(4, 5, vec![Island::Empty; 22]);
+
+        for index in 0..sys.islands.len() {
+            let (px, py) = get_coordinates_from_index(&sys, index, Layout::default());
+            let (fx, fy) = get_coordinates_from_index(
+                &sys,
+                index,
+                Layout {
+                    orientation: Orientation::FlatTop,
+                    ..Layout::default()
+                },
+            );
+            assert!((px - 75.0 - fy).abs() < 1e-9);
+            assert!((py + 75.0 - fx).abs() < 1e-9);
+        }
     }
 }
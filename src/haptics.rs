@@ -0,0 +1,21 @@
+use js_sys::Array;
+use leptos::prelude::window;
+use wasm_bindgen::JsValue;
+
+/// Vibration pattern for a blocked move in milliseconds.
+pub const BLOCKED: &[u32] = &[50];
+/// Vibration pattern for a solved puzzle in milliseconds (vibrate, pause, vibrate, ...).
+pub const SOLVED: &[u32] = &[100, 50, 100, 50, 200];
+
+///
+/// Let the device vibrate with `pattern`.
+///
+/// Does nothing on devices without vibration support.
+///
+pub fn vibrate(pattern: &[u32]) {
+    let pattern = pattern
+        .iter()
+        .map(|duration| JsValue::from(*duration))
+        .collect::<Array>();
+    let _ = window().navigator().vibrate_with_pattern(&pattern);
+}
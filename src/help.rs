@@ -0,0 +1,96 @@
+use hexhashi_logic::hex::HexSystem;
+use leptos::{html::Dialog, prelude::*};
+
+use crate::focus::use_modal;
+
+///
+/// A rule of the game with the code of a small puzzle showing it when solved.
+///
+struct Rule {
+    text: &'static str,
+    example: &'static str,
+}
+
+const RULES: [Rule; 4] = [
+    Rule {
+        text: "Connect the islands with bridges, so that each island has as many bridges as its number.",
+        example: "4.5.2031000000000000000000.21",
+    },
+    Rule {
+        text: "Two islands are connected by at most two bridges.",
+        example: "4.5.2020000000000000000000.2",
+    },
+    Rule {
+        text: "Bridges go along the lines of the grid and cannot cross each other.",
+        example: "4.5.0022001000000003200000.11102",
+    },
+    Rule {
+        text: "All islands must be connected with each other, so that no group of islands is cut off.",
+        example: "4.5.2002000000100001000000.1110",
+    },
+];
+
+///
+/// Get the puzzle of `code` with all bridges of its solution placed.
+///
+fn solved(code: &str) -> Option<HexSystem> {
+    let mut system = HexSystem::from_code(code).ok()?;
+    let keys: Vec<_> = system.bridges.keys().copied().collect();
+    for (from, to) in keys {
+        while system.get_bridge(from, to)?.get_state()
+            != system.get_bridge(from, to)?.get_solution()
+        {
+            system.cycle_bridge(from, to).ok()?;
+        }
+    }
+    Some(system)
+}
+
+///
+/// Overlay summarizing the rules, shown on top of the board while `open`.
+///
+#[component]
+pub fn HelpOverlay(open: RwSignal<bool>) -> impl IntoView {
+    let dialog = NodeRef::<Dialog>::new();
+    use_modal(dialog, move || open.set(false));
+    let rules = || {
+        RULES
+            .iter()
+            .map(|rule| {
+                let example = solved(rule.example)
+                    .map(|system| system.to_svg())
+                    .unwrap_or_default();
+                view! {
+                    <li>
+                        <div class="example" inner_html=example/>
+                        <p>{rule.text}</p>
+                    </li>
+                }
+            })
+            .collect_view()
+    };
+
+    view! {
+        <Show when=move || open.get()>
+            <dialog class="help" node_ref=dialog>
+                <h2>"Rules"</h2>
+                <ul>{rules}</ul>
+                <button autofocus on:click=move |_| open.set(false)>
+                    "Back to the game"
+                </button>
+            </dialog>
+        </Show>
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{RULES, solved};
+
+    #[test]
+    fn examples() {
+        for rule in RULES {
+            assert!(solved(rule.example).unwrap().is_solved(), "{}", rule.text);
+        }
+    }
+}
@@ -0,0 +1,478 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+use web_sys::{KeyboardEvent, MouseEvent, WheelEvent};
+
+/// Pixels per line of `WheelEvent.deltaMode` 1, e.g. for mouse wheels in Firefox
+const LINE_PIXELS: f64 = 16.0;
+/// Pixels per page of `WheelEvent.deltaMode` 2
+const PAGE_PIXELS: f64 = 800.0;
+/// Zoom factor per pixel scrolled with Ctrl or pinched on a touchpad at sensitivity 1
+const WHEEL_ZOOM_RATE: f64 = 0.002;
+/// Longest time in milliseconds between the taps of a double tap
+const DOUBLE_TAP_MS: f64 = 300.0;
+/// Farthest distance in pixels between the taps of a double tap
+const DOUBLE_TAP_DISTANCE: f64 = 30.0;
+
+///
+/// What the player can do on the board by mouse or keyboard.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Action {
+    /// Add a bridge, or remove all bridges if there are two already.
+    CycleForward,
+    /// Remove a bridge, or add two bridges if there are none.
+    CycleBack,
+    /// Mark a bridge as a guess without placing it.
+    PencilMark,
+    /// Protect a bridge against changes.
+    Lock,
+    Hint,
+    Undo,
+    /// Enter or leave the distraction-free full screen mode.
+    Fullscreen,
+}
+
+impl Action {
+    pub const ALL: [Action; 7] = [
+        Action::CycleForward,
+        Action::CycleBack,
+        Action::PencilMark,
+        Action::Lock,
+        Action::Hint,
+        Action::Undo,
+        Action::Fullscreen,
+    ];
+
+    ///
+    /// Does the action apply to the bridge it is triggered on?
+    ///
+    pub fn needs_bridge(&self) -> bool {
+        matches!(
+            self,
+            Action::CycleForward | Action::CycleBack | Action::PencilMark | Action::Lock
+        )
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::CycleForward => f.write_str("Add bridge"),
+            Action::CycleBack => f.write_str("Remove bridge"),
+            Action::PencilMark => f.write_str("Pencil mark"),
+            Action::Lock => f.write_str("Lock bridge"),
+            Action::Hint => f.write_str("Hint"),
+            Action::Undo => f.write_str("Undo"),
+            Action::Fullscreen => f.write_str("Full screen"),
+        }
+    }
+}
+
+///
+/// A mouse button or key that triggers an action.
+///
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Binding {
+    /// Mouse `button` as in `MouseEvent.button`, i.e. 0 for the primary button
+    Mouse { button: i16, shift: bool, alt: bool },
+    /// Key as in `KeyboardEvent.key` in lower case
+    Key(String),
+}
+
+impl Binding {
+    fn click(button: i16) -> Self {
+        Binding::Mouse {
+            button,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    ///
+    /// Get the binding of the mouse button pressed in `evt`.
+    ///
+    /// With `swap_buttons` the physical buttons are mapped for left-handed use,
+    /// so that the right button acts as the primary one and vice versa.
+    ///
+    pub fn from_mouse(evt: &MouseEvent, swap_buttons: bool) -> Self {
+        let binding = Binding::Mouse {
+            button: evt.button(),
+            shift: evt.shift_key(),
+            alt: evt.alt_key(),
+        };
+        if swap_buttons {
+            binding.swapped()
+        } else {
+            binding
+        }
+    }
+
+    pub fn from_key(evt: &KeyboardEvent) -> Self {
+        Binding::Key(evt.key().to_lowercase())
+    }
+
+    ///
+    /// Get the binding with primary and secondary mouse button exchanged.
+    ///
+    /// Keys and other buttons stay the same.
+    ///
+    pub fn swapped(&self) -> Self {
+        match self {
+            Binding::Mouse { button, shift, alt } => Binding::Mouse {
+                button: match button {
+                    0 => 2,
+                    2 => 0,
+                    b => *b,
+                },
+                shift: *shift,
+                alt: *alt,
+            },
+            key => key.clone(),
+        }
+    }
+}
+
+impl Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Binding::Mouse { button, shift, alt } => {
+                if *shift {
+                    f.write_str("Shift + ")?;
+                }
+                if *alt {
+                    f.write_str("Alt + ")?;
+                }
+                match button {
+                    0 => f.write_str("Left click"),
+                    1 => f.write_str("Middle click"),
+                    2 => f.write_str("Right click"),
+                    b => write!(f, "Mouse button {}", b + 1),
+                }
+            }
+            Binding::Key(key) if key == " " => f.write_str("Space"),
+            Binding::Key(key) => write!(f, "Key {}", key.to_uppercase()),
+        }
+    }
+}
+
+///
+/// Bindings of mouse buttons and keys to actions.
+///
+/// Each binding triggers at most one action, but an action can have several bindings.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Bindings(Vec<(Binding, Action)>);
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings(vec![
+            (Binding::click(0), Action::CycleForward),
+            (Binding::click(2), Action::CycleBack),
+            (
+                Binding::Mouse {
+                    button: 0,
+                    shift: false,
+                    alt: true,
+                },
+                Action::PencilMark,
+            ),
+            (Binding::click(1), Action::Lock),
+            (
+                Binding::Mouse {
+                    button: 0,
+                    shift: true,
+                    alt: false,
+                },
+                Action::Lock,
+            ),
+            (Binding::Key("h".to_string()), Action::Hint),
+            (Binding::Key("z".to_string()), Action::Undo),
+            (Binding::Key("f".to_string()), Action::Fullscreen),
+            (Binding::Key("f11".to_string()), Action::Fullscreen),
+        ])
+    }
+}
+
+impl Bindings {
+    ///
+    /// Get the action triggered by `binding`, if there is any.
+    ///
+    pub fn action(&self, binding: &Binding) -> Option<Action> {
+        self.0.iter().find(|(b, _)| b == binding).map(|(_, a)| *a)
+    }
+
+    ///
+    /// Get the bindings triggering `action`.
+    ///
+    pub fn bindings(&self, action: Action) -> Vec<&Binding> {
+        self.0
+            .iter()
+            .filter(|(_, a)| *a == action)
+            .map(|(b, _)| b)
+            .collect()
+    }
+
+    ///
+    /// Let `binding` trigger `action` instead of the current bindings of `action`.
+    ///
+    /// The binding no longer triggers any other action.
+    /// Keys cannot trigger actions on bridges, since they do not point at any bridge.
+    /// Returns `false` if the binding is rejected for this reason.
+    ///
+    pub fn set(&mut self, action: Action, binding: Binding) -> bool {
+        if action.needs_bridge() && matches!(binding, Binding::Key(_)) {
+            return false;
+        }
+        self.0.retain(|(b, a)| *a != action && *b != binding);
+        self.0.push((binding, action));
+        true
+    }
+
+    ///
+    /// Is the physical right mouse button bound, so that the context menu must be suppressed?
+    ///
+    /// With `swap_buttons` it triggers the actions bound to the primary button.
+    ///
+    pub fn uses_secondary_button(&self, swap_buttons: bool) -> bool {
+        let button = if swap_buttons { 0 } else { 2 };
+        self.0
+            .iter()
+            .any(|(b, _)| matches!(b, Binding::Mouse { button: m, .. } if *m == button))
+    }
+}
+
+///
+/// How zooming and panning by mouse wheel, touchpad and touch screen feel.
+///
+/// Missing fields fall back to their default, so that settings stored by older versions can still be read.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Gestures {
+    /// Factor for the zoom of Ctrl + wheel, touchpad and pinch gestures
+    pub zoom_sensitivity: f64,
+    /// Factor for the distance the board moves when scrolled or dragged
+    pub pan_sensitivity: f64,
+    /// Move the board in the direction of the wheel instead of the content
+    pub invert_scroll: bool,
+    /// Zoom in by tapping twice next to the bridges
+    pub double_tap_zoom: bool,
+}
+
+impl Default for Gestures {
+    fn default() -> Self {
+        Gestures {
+            zoom_sensitivity: 1.0,
+            pan_sensitivity: 1.0,
+            invert_scroll: false,
+            double_tap_zoom: true,
+        }
+    }
+}
+
+///
+/// What a gesture does to the view of the board.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Gesture {
+    /// Multiply the zoom by the factor.
+    Zoom(f64),
+    /// Scroll the board by (x, y) pixels.
+    Pan(f64, f64),
+}
+
+impl Gestures {
+    ///
+    /// Get the gesture of the wheel or touchpad in `evt`.
+    ///
+    pub fn scroll(&self, evt: &WheelEvent) -> Gesture {
+        self.wheel(
+            (evt.delta_x(), evt.delta_y()),
+            evt.delta_mode(),
+            evt.ctrl_key(),
+        )
+    }
+
+    ///
+    /// Get the gesture of scrolling by `delta` in `delta_mode` as in `WheelEvent`.
+    ///
+    /// Scrolling with Ctrl zooms, as do touchpads when pinched.
+    ///
+    fn wheel(&self, (x, y): (f64, f64), delta_mode: u32, ctrl: bool) -> Gesture {
+        let pixels = match delta_mode {
+            1 => LINE_PIXELS,
+            2 => PAGE_PIXELS,
+            _ => 1.0,
+        };
+        if ctrl {
+            Gesture::Zoom((-y * pixels * WHEEL_ZOOM_RATE * self.zoom_sensitivity).exp())
+        } else {
+            let direction = if self.invert_scroll { -1.0 } else { 1.0 };
+            let factor = pixels * self.pan_sensitivity * direction;
+            Gesture::Pan(x * factor, y * factor)
+        }
+    }
+
+    ///
+    /// Get the zoom factor of two fingers moving from `previous` to `distance` pixels apart.
+    ///
+    pub fn pinch(&self, previous: f64, distance: f64) -> Gesture {
+        if previous <= 0.0 || distance <= 0.0 {
+            return Gesture::Zoom(1.0);
+        }
+        Gesture::Zoom((distance / previous).powf(self.zoom_sensitivity))
+    }
+
+    ///
+    /// Get the scrolling of a finger dragged by `delta` pixels, which moves the board along.
+    ///
+    pub fn drag(&self, (x, y): (f64, f64)) -> Gesture {
+        Gesture::Pan(-x * self.pan_sensitivity, -y * self.pan_sensitivity)
+    }
+}
+
+///
+/// Recognizes two taps close to each other in time and place.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DoubleTap {
+    /// Time and place of the previous tap
+    last: Option<(f64, (f64, f64))>,
+}
+
+impl DoubleTap {
+    ///
+    /// Register a tap at `point` at `now` and tell whether it completes a double tap.
+    ///
+    pub fn tap(&mut self, now: f64, point: (f64, f64)) -> bool {
+        let double = self.last.is_some_and(|(at, (x, y))| {
+            now - at <= DOUBLE_TAP_MS && (point.0 - x).hypot(point.1 - y) <= DOUBLE_TAP_DISTANCE
+        });
+        // A third tap starts over.
+        self.last = (!double).then_some((now, point));
+        double
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Action, Binding, Bindings, DoubleTap, Gesture, Gestures};
+
+    #[test]
+    fn dispatch() {
+        let mut bindings = Bindings::default();
+        assert_eq!(
+            bindings.action(&Binding::Mouse {
+                button: 0,
+                shift: true,
+                alt: false
+            }),
+            Some(Action::Lock)
+        );
+        assert_eq!(
+            bindings.action(&Binding::Key("z".to_string())),
+            Some(Action::Undo)
+        );
+        assert_eq!(bindings.action(&Binding::Key("q".to_string())), None);
+        assert_eq!(
+            bindings.action(&Binding::Key("f11".to_string())),
+            Some(Action::Fullscreen)
+        );
+        assert!(bindings.uses_secondary_button(false));
+
+        // Rebinding undo to the right mouse button replaces both previous bindings.
+        assert!(bindings.set(Action::Undo, Binding::click(2)));
+        assert_eq!(bindings.action(&Binding::click(2)), Some(Action::Undo));
+        assert_eq!(bindings.action(&Binding::Key("z".to_string())), None);
+        assert!(bindings.bindings(Action::CycleBack).is_empty());
+        assert_eq!(bindings.bindings(Action::Lock).len(), 2);
+        assert_eq!(Binding::click(1).to_string(), "Middle click");
+        assert_eq!(Binding::Key(" ".to_string()).to_string(), "Space");
+
+        // Keys cannot add bridges, so the click stays bound.
+        assert!(!bindings.set(Action::CycleForward, Binding::Key("a".to_string())));
+        assert_eq!(
+            bindings.action(&Binding::click(0)),
+            Some(Action::CycleForward)
+        );
+        assert_eq!(bindings.action(&Binding::Key("a".to_string())), None);
+    }
+
+    #[test]
+    fn swap_buttons() {
+        let mut bindings = Bindings::default();
+        // Left-handed, the right button adds bridges and the left button removes them.
+        assert_eq!(
+            bindings.action(&Binding::click(2).swapped()),
+            Some(Action::CycleForward)
+        );
+        assert_eq!(
+            bindings.action(&Binding::click(0).swapped()),
+            Some(Action::CycleBack)
+        );
+        assert_eq!(Binding::click(1).swapped(), Binding::click(1));
+        assert_eq!(
+            Binding::Key("h".to_string()).swapped(),
+            Binding::Key("h".to_string())
+        );
+
+        assert!(bindings.set(
+            Action::CycleBack,
+            Binding::Mouse {
+                button: 0,
+                shift: false,
+                alt: true
+            }
+        ));
+        assert!(!bindings.uses_secondary_button(false));
+        assert!(bindings.uses_secondary_button(true));
+    }
+
+    #[test]
+    fn gestures() {
+        let mut gestures = Gestures::default();
+        // A mouse wheel scrolling by lines moves as far as a touchpad scrolling by pixels.
+        assert_eq!(
+            gestures.wheel((0.0, 3.0), 1, false),
+            Gesture::Pan(0.0, 48.0)
+        );
+        assert_eq!(
+            gestures.wheel((0.0, 48.0), 0, false),
+            Gesture::Pan(0.0, 48.0)
+        );
+        let Gesture::Zoom(zoom_in) = gestures.wheel((0.0, -100.0), 0, true) else {
+            panic!("Ctrl + wheel should zoom");
+        };
+        assert!(zoom_in > 1.0);
+        assert_eq!(gestures.pinch(100.0, 200.0), Gesture::Zoom(2.0));
+        assert_eq!(gestures.pinch(0.0, 200.0), Gesture::Zoom(1.0));
+        assert_eq!(gestures.drag((10.0, -5.0)), Gesture::Pan(-10.0, 5.0));
+
+        gestures.invert_scroll = true;
+        gestures.pan_sensitivity = 2.0;
+        gestures.zoom_sensitivity = 2.0;
+        assert_eq!(
+            gestures.wheel((1.0, 3.0), 0, false),
+            Gesture::Pan(-2.0, -6.0)
+        );
+        assert_eq!(gestures.pinch(100.0, 200.0), Gesture::Zoom(4.0));
+        let Gesture::Zoom(faster) = gestures.wheel((0.0, -100.0), 0, true) else {
+            panic!("Ctrl + wheel should zoom");
+        };
+        assert!(faster > zoom_in);
+    }
+
+    #[test]
+    fn double_tap() {
+        let mut taps = DoubleTap::default();
+        assert!(!taps.tap(1_000.0, (10.0, 10.0)));
+        assert!(taps.tap(1_200.0, (20.0, 15.0)));
+        // A third tap does not make another double tap.
+        assert!(!taps.tap(1_400.0, (20.0, 15.0)));
+        // Too slow
+        assert!(!taps.tap(2_000.0, (20.0, 15.0)));
+        // Too far away
+        assert!(!taps.tap(2_100.0, (200.0, 15.0)));
+    }
+}
@@ -0,0 +1,407 @@
+use std::collections::BTreeSet;
+
+use hexhashi_logic::{difficulty::Difficulty, hex::HexSystem};
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{THUMBNAIL_SIZE, format_time},
+    packs::PackPuzzle,
+    share::capacity_warning,
+    stats::load_stats,
+    storage::{load, save},
+};
+
+const LIBRARY_KEY: &str = "hexhashi.library";
+
+///
+/// Where a puzzle of the library came from.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Source {
+    /// Shared by its code or link
+    Imported,
+    /// Part of a downloaded pack
+    Downloaded,
+    /// Saved by the player while playing it
+    Saved,
+}
+
+impl Source {
+    fn label(&self) -> &'static str {
+        match self {
+            Source::Imported => "Imported",
+            Source::Downloaded => "Downloaded",
+            Source::Saved => "Saved",
+        }
+    }
+}
+
+///
+/// A puzzle kept in the library to be played again.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LibraryPuzzle {
+    /// Identifier of the puzzle, see `HexSystem::puzzle_id`
+    pub id: String,
+    pub code: String,
+    pub difficulty: Difficulty,
+    pub columns: usize,
+    pub rows: usize,
+    pub source: Source,
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+    /// Puzzle of a downloaded pack, so that solving it counts for the pack
+    #[serde(default)]
+    pub pack: Option<PackPuzzle>,
+}
+
+impl LibraryPuzzle {
+    pub fn new(system: &HexSystem, difficulty: Difficulty, source: Source) -> Self {
+        LibraryPuzzle {
+            id: system.puzzle_id(),
+            code: system.to_code(),
+            difficulty,
+            columns: system.columns,
+            rows: system.rows,
+            source,
+            tags: BTreeSet::new(),
+            pack: None,
+        }
+    }
+
+    ///
+    /// Get the path to play the puzzle.
+    ///
+    fn play_path(&self) -> String {
+        match &self.pack {
+            Some(pack) => format!("/pack/{}/{}", pack.pack, pack.index),
+            None => format!("/import/{}/{}", self.difficulty.as_str(), self.code),
+        }
+    }
+}
+
+///
+/// Criteria to search the library; `None` matches all puzzles.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Filter {
+    pub difficulty: Option<Difficulty>,
+    /// Columns and rows
+    pub size: Option<(usize, usize)>,
+    pub tag: Option<String>,
+}
+
+impl Filter {
+    fn matches(&self, puzzle: &LibraryPuzzle) -> bool {
+        self.difficulty
+            .as_ref()
+            .is_none_or(|d| *d == puzzle.difficulty)
+            && self
+                .size
+                .is_none_or(|size| size == (puzzle.columns, puzzle.rows))
+            && self.tag.as_ref().is_none_or(|t| puzzle.tags.contains(t))
+    }
+}
+
+///
+/// Puzzles collected by the player, grouped into collections by tags.
+///
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Library {
+    puzzles: Vec<LibraryPuzzle>,
+}
+
+impl Library {
+    ///
+    /// Add `puzzle` unless it is already in the library.
+    ///
+    /// Returns `true` if it was added.
+    ///
+    pub fn add(&mut self, puzzle: LibraryPuzzle) -> bool {
+        if self.contains(&puzzle.id) {
+            return false;
+        }
+        self.puzzles.push(puzzle);
+        true
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.puzzles.iter().any(|p| p.id == id)
+    }
+
+    pub fn remove(&mut self, id: &str) {
+        self.puzzles.retain(|p| p.id != id);
+    }
+
+    ///
+    /// Add the puzzle with `id` to the collection `tag`.
+    ///
+    pub fn tag(&mut self, id: &str, tag: &str) {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            return;
+        }
+        if let Some(puzzle) = self.puzzles.iter_mut().find(|p| p.id == id) {
+            puzzle.tags.insert(tag.to_string());
+        }
+    }
+
+    ///
+    /// Remove the puzzle with `id` from the collection `tag`.
+    ///
+    pub fn untag(&mut self, id: &str, tag: &str) {
+        if let Some(puzzle) = self.puzzles.iter_mut().find(|p| p.id == id) {
+            puzzle.tags.remove(tag);
+        }
+    }
+
+    ///
+    /// Get the tags of all puzzles.
+    ///
+    pub fn tags(&self) -> BTreeSet<String> {
+        self.puzzles
+            .iter()
+            .flat_map(|p| p.tags.iter().cloned())
+            .collect()
+    }
+
+    ///
+    /// Get the sizes of all puzzles as columns and rows.
+    ///
+    pub fn sizes(&self) -> BTreeSet<(usize, usize)> {
+        self.puzzles.iter().map(|p| (p.columns, p.rows)).collect()
+    }
+
+    ///
+    /// Get the puzzles matching `filter` in the order they were added.
+    ///
+    pub fn search(&self, filter: &Filter) -> Vec<&LibraryPuzzle> {
+        self.puzzles.iter().filter(|p| filter.matches(p)).collect()
+    }
+}
+
+///
+/// Load the stored library.
+///
+pub fn load_library() -> Library {
+    load(LIBRARY_KEY).unwrap_or_default()
+}
+
+///
+/// Update the stored library with `f`.
+///
+pub fn update_library<R>(f: impl FnOnce(&mut Library) -> R) -> R {
+    let mut library = load_library();
+    let result = f(&mut library);
+    save(LIBRARY_KEY, &library);
+    result
+}
+
+///
+/// Update the stored library with `f` and show the result in `library`.
+///
+fn update(library: RwSignal<Library>, f: impl FnOnce(&mut Library)) {
+    library.set(update_library(|l| {
+        f(l);
+        l.clone()
+    }));
+}
+
+#[component]
+pub fn LibraryPage() -> impl IntoView {
+    let library = RwSignal::new(load_library());
+    let filter = RwSignal::new(Filter::default());
+    let stats = load_stats();
+
+    let puzzles = move || {
+        let entries = library.read();
+        entries
+            .search(&filter.read())
+            .into_iter()
+            .map(|puzzle| {
+                let system = HexSystem::from_code(&puzzle.code).ok();
+                let preview = system
+                    .as_ref()
+                    .map(|system| system.to_thumbnail(THUMBNAIL_SIZE))
+                    .unwrap_or_default();
+                let warning = system
+                    .as_ref()
+                    .and_then(capacity_warning)
+                    .map(|warning| view! { <p class="warning">{warning}</p> });
+                let best = stats
+                    .get_puzzle(&puzzle.id)
+                    .and_then(|r| r.best_ms)
+                    .map_or("–".to_string(), format_time);
+                let tags = puzzle
+                    .tags
+                    .iter()
+                    .map(|tag| {
+                        let (id, tag) = (puzzle.id.clone(), tag.clone());
+                        let label = format!("{} \u{00d7}", tag);
+                        view! {
+                            <button
+                                class="tag"
+                                title="Remove from collection"
+                                on:click=move |_| update(library, |l| l.untag(&id, &tag))
+                            >
+                                {label}
+                            </button>
+                        }
+                    })
+                    .collect_view();
+                let id = puzzle.id.clone();
+                let add_tag = move |ev| {
+                    let tag = event_target_value(&ev);
+                    update(library, |l| l.tag(&id, &tag));
+                };
+                let id = puzzle.id.clone();
+                let remove = move |_| update(library, |l| l.remove(&id));
+                view! {
+                    <div class="entry">
+                        <a class="preview" href=puzzle.play_path()>
+                            <div class="thumbnail small" inner_html=preview/>
+                            <span class="title">{puzzle.difficulty.to_string()}</span>
+                        </a>
+                        <p>
+                            {format!(
+                                "{} \u{2013} {} \u{00d7} {} \u{2013} Best: {}",
+                                puzzle.source.label(),
+                                puzzle.columns,
+                                puzzle.rows,
+                                best,
+                            )}
+                        </p>
+                        {warning}
+                        <p>
+                            {tags}
+                            <input type="text" placeholder="Add to collection" on:change=add_tag/>
+                        </p>
+                        <button on:click=remove>"Remove"</button>
+                    </div>
+                }
+            })
+            .collect_view()
+    };
+
+    let sizes = move || {
+        library
+            .read()
+            .sizes()
+            .into_iter()
+            .map(|(columns, rows)| {
+                view! {
+                    <option value=format!("{}x{}", columns, rows)>
+                        {format!("{} \u{00d7} {}", columns, rows)}
+                    </option>
+                }
+            })
+            .collect_view()
+    };
+    let tags = move || {
+        library
+            .read()
+            .tags()
+            .into_iter()
+            .map(|tag| view! { <option value=tag.clone()>{tag.clone()}</option> })
+            .collect_view()
+    };
+
+    view! {
+        <div><span class="menu">hexhashi</span><a class="menu" href="/">Back</a></div>
+        <h1>"Library"</h1>
+        <p>
+            <label>
+                "Difficulty "
+                <select on:change=move |ev| {
+                    filter.write().difficulty = event_target_value(&ev).parse().ok();
+                }>
+                    <option value="">"All"</option>
+                    {Difficulty::ALL
+                        .into_iter()
+                        .map(|d| view! { <option value=d.as_str()>{d.to_string()}</option> })
+                        .collect_view()}
+                </select>
+            </label>
+            " "
+            <label>
+                "Size "
+                <select on:change=move |ev| {
+                    filter.write().size = event_target_value(&ev)
+                        .split_once('x')
+                        .and_then(|(c, r)| Some((c.parse().ok()?, r.parse().ok()?)));
+                }>
+                    <option value="">"All"</option>
+                    {sizes}
+                </select>
+            </label>
+            " "
+            <label>
+                "Collection "
+                <select on:change=move |ev| {
+                    let tag = event_target_value(&ev);
+                    filter.write().tag = (!tag.is_empty()).then_some(tag);
+                }>
+                    <option value="">"All"</option>
+                    {tags}
+                </select>
+            </label>
+        </p>
+        <Show when=move || library.read().search(&Filter::default()).is_empty()>
+            <p>"Imported and downloaded puzzles you play and puzzles you save show up here."</p>
+        </Show>
+        <div class="row library">{puzzles}</div>
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use hexhashi_logic::{
+        difficulty::Difficulty,
+        hex::{GameParameters, HexSystem},
+    };
+
+    use super::{Filter, Library, LibraryPuzzle, Source};
+
+    fn puzzle(difficulty: Difficulty, seed: u64) -> LibraryPuzzle {
+        let system = HexSystem::generate_new(GameParameters::preset(&difficulty, seed));
+        LibraryPuzzle::new(&system, difficulty, Source::Imported)
+    }
+
+    #[test]
+    fn collections() {
+        let mut library = Library::default();
+        let easy = puzzle(Difficulty::Easy, 1);
+        let hard = puzzle(Difficulty::Hard, 1);
+        assert!(library.add(easy.clone()));
+        assert!(!library.add(easy.clone()));
+        assert!(library.add(hard.clone()));
+        library.tag(&easy.id, " favorites ");
+        library.tag(&hard.id, "favorites");
+        library.tag(&hard.id, "");
+        library.untag(&hard.id, "favorites");
+        assert_eq!(
+            library.tags().into_iter().collect::<Vec<_>>(),
+            ["favorites"]
+        );
+
+        let found = library.search(&Filter {
+            tag: Some("favorites".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, easy.id);
+        let found = library.search(&Filter {
+            difficulty: Some(Difficulty::Hard),
+            size: Some((hard.columns, hard.rows)),
+            ..Default::default()
+        });
+        assert_eq!(found, [&hard]);
+        assert_eq!(library.search(&Filter::default()).len(), 2);
+
+        library.remove(&easy.id);
+        assert!(!library.contains(&easy.id));
+        assert!(library.tags().is_empty());
+    }
+}
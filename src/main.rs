@@ -1,5 +1,37 @@
+mod animation;
 mod app;
+mod clipboard;
+mod clock;
+mod close;
+mod daily;
+mod dirty;
+mod export;
+mod focus;
+mod fullscreen;
 mod game;
+mod haptics;
+mod help;
+mod input;
+mod library;
+mod migration;
+mod net;
+mod packs;
+mod perf;
+mod prefetch;
+mod profile;
+mod settings;
+mod share;
+mod slots;
+mod sound;
+mod stats;
+mod storage;
+mod sync;
+mod telemetry;
+mod theme;
+mod trainer;
+mod users;
+mod webgl;
+mod weekly;
 
 use app::*;
 use leptos::prelude::*;
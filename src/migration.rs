@@ -0,0 +1,125 @@
+use serde_json::{Map, Value, json};
+
+use crate::stats::STATS_KEY;
+
+/// Field of the stored objects holding the version of their format
+const VERSION_FIELD: &str = "save_version";
+
+///
+/// Upgrade of the value stored under `key` from version `from` to the next one.
+///
+/// Values without a version are version 1. Only values stored as JSON objects can be migrated.
+///
+struct Migration {
+    key: &'static str,
+    from: u32,
+    upgrade: fn(&mut Map<String, Value>),
+}
+
+/// All migrations, in the order they are applied
+const MIGRATIONS: [Migration; 1] = [Migration {
+    key: STATS_KEY,
+    from: 1,
+    upgrade: puzzle_records,
+}];
+
+///
+/// Turn the best time kept for each solved puzzle into its record.
+///
+/// Each puzzle with a best time was solved at least once.
+///
+fn puzzle_records(stats: &mut Map<String, Value>) {
+    let Some(Value::Object(puzzles)) = stats.get_mut("puzzles") else {
+        return;
+    };
+    for record in puzzles.values_mut() {
+        if let Value::Number(best_ms) = record {
+            *record = json!({ "solved": 1, "best_ms": best_ms });
+        }
+    }
+}
+
+///
+/// Get the version of the format of the values stored under `key`.
+///
+fn current_version(key: &str) -> u32 {
+    MIGRATIONS
+        .iter()
+        .filter(|m| m.key == key)
+        .map(|m| m.from + 1)
+        .max()
+        .unwrap_or(1)
+}
+
+///
+/// Mark `value` to be stored under `key` with the current version of its format.
+///
+pub fn stamp(key: &str, value: &mut Value) {
+    let version = current_version(key);
+    if version > 1
+        && let Value::Object(map) = value
+    {
+        map.insert(VERSION_FIELD.to_string(), version.into());
+    }
+}
+
+///
+/// Upgrade `value` stored under `key` by an older version to the current format.
+///
+pub fn migrate(key: &str, value: &mut Value) {
+    let Value::Object(map) = value else {
+        return;
+    };
+    let stored = map.get(VERSION_FIELD).and_then(Value::as_u64).unwrap_or(1) as u32;
+    let mut version = stored;
+    for migration in MIGRATIONS
+        .iter()
+        .filter(|m| m.key == key && m.from >= stored)
+    {
+        (migration.upgrade)(map);
+        version = migration.from + 1;
+    }
+    if version > 1 {
+        map.insert(VERSION_FIELD.to_string(), version.into());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{Value, json};
+
+    use crate::{settings::SETTINGS_KEY, stats::STATS_KEY};
+
+    use super::{migrate, stamp};
+
+    #[test]
+    fn versions() {
+        let mut value = json!({ "puzzles": {} });
+        stamp(STATS_KEY, &mut value);
+        assert_eq!(value["save_version"], 2);
+        // Values without migrations stay as they are.
+        let mut value = json!({ "haptics": false });
+        stamp(SETTINGS_KEY, &mut value);
+        assert_eq!(value, json!({ "haptics": false }));
+        let mut value = json!([1, 2]);
+        migrate(STATS_KEY, &mut value);
+        assert_eq!(value, json!([1, 2]));
+    }
+
+    #[test]
+    fn puzzle_records() {
+        // Statistics as stored with the best time of each puzzle only
+        let mut stats = json!({ "records": {}, "puzzles": { "abc": 61000.0 } });
+        migrate(STATS_KEY, &mut stats);
+        assert_eq!(stats["save_version"], 2);
+        assert_eq!(
+            stats["puzzles"]["abc"],
+            json!({ "solved": 1, "best_ms": 61000.0 })
+        );
+
+        // Statistics of the current version are not changed again.
+        let before: Value = stats.clone();
+        migrate(STATS_KEY, &mut stats);
+        assert_eq!(stats, before);
+    }
+}
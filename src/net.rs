@@ -0,0 +1,106 @@
+use leptos::prelude::window;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Headers, Request, RequestInit, Response};
+
+///
+/// Get the text at `url`.
+///
+pub async fn fetch_text(url: &str) -> Result<String, JsValue> {
+    let response: Response = JsFuture::from(window().fetch_with_str(url))
+        .await?
+        .dyn_into()?;
+    if !response.ok() {
+        return Err(JsValue::from(response.status()));
+    }
+    let text = JsFuture::from(response.text()?).await?;
+    Ok(text.as_string().unwrap_or_default())
+}
+
+///
+/// Get the data at `url` as it is, e.g. to verify its hash.
+///
+pub async fn fetch_bytes(url: &str) -> Result<Vec<u8>, JsValue> {
+    let response: Response = JsFuture::from(window().fetch_with_str(url))
+        .await?
+        .dyn_into()?;
+    if !response.ok() {
+        return Err(JsValue::from(response.status()));
+    }
+    let buffer = JsFuture::from(response.array_buffer()?).await?;
+    Ok(js_sys::Uint8Array::new(&buffer).to_vec())
+}
+
+///
+/// Get the text at `url` together with its `ETag`, if the server sent one.
+///
+pub async fn fetch_tagged(url: &str) -> Result<(String, Option<String>), JsValue> {
+    let response: Response = JsFuture::from(window().fetch_with_str(url))
+        .await?
+        .dyn_into()?;
+    if !response.ok() {
+        return Err(JsValue::from(response.status()));
+    }
+    let etag = response.headers().get("ETag")?;
+    let text = JsFuture::from(response.text()?).await?;
+    Ok((text.as_string().unwrap_or_default(), etag))
+}
+
+///
+/// Get the header making a request fail unless the data at the URL still has `etag`.
+///
+/// Without `etag` the request fails if there is any data already.
+///
+fn precondition(etag: Option<&str>) -> (&'static str, &str) {
+    match etag {
+        Some(etag) => ("If-Match", etag),
+        None => ("If-None-Match", "*"),
+    }
+}
+
+///
+/// Post `body` as JSON to `url`.
+///
+pub async fn post_json(url: &str, body: &str) -> Result<(), JsValue> {
+    send_json(url, body, Headers::new()?).await
+}
+
+///
+/// Post `body` as JSON to `url`, unless it was changed since it had `etag`.
+///
+/// Fails with status 412 if it was changed.
+///
+pub async fn post_json_if(url: &str, body: &str, etag: Option<&str>) -> Result<(), JsValue> {
+    let headers = Headers::new()?;
+    let (name, value) = precondition(etag);
+    headers.set(name, value)?;
+    send_json(url, body, headers).await
+}
+
+async fn send_json(url: &str, body: &str, headers: Headers) -> Result<(), JsValue> {
+    headers.set("Content-Type", "application/json")?;
+    let init = RequestInit::new();
+    init.set_method("POST");
+    init.set_headers(&headers);
+    init.set_body(&JsValue::from_str(body));
+    let request = Request::new_with_str_and_init(url, &init)?;
+    let response: Response = JsFuture::from(window().fetch_with_request(&request))
+        .await?
+        .dyn_into()?;
+    if response.ok() {
+        Ok(())
+    } else {
+        Err(JsValue::from(response.status()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::precondition;
+
+    #[test]
+    fn preconditions() {
+        assert_eq!(precondition(Some("\"7\"")), ("If-Match", "\"7\""));
+        assert_eq!(precondition(None), ("If-None-Match", "*"));
+    }
+}
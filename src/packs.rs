@@ -0,0 +1,211 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use hexhashi_logic::{
+    difficulty::Difficulty,
+    pack::{PackInfo, PuzzlePack, verify_pack},
+    session::GameSession,
+};
+use leptos::{logging::log, prelude::*, task::spawn_local};
+use leptos_router::{hooks::use_params, params::Params};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{Board, GameMode},
+    library::{LibraryPuzzle, Source, update_library},
+    net::{fetch_bytes, fetch_text},
+    settings::Settings,
+    stats::count_started,
+    storage::{load, save},
+};
+
+const PACKS_KEY: &str = "hexhashi.packs";
+const PROGRESS_KEY: &str = "hexhashi.pack_progress";
+
+///
+/// Puzzle number `index` of the pack with id `pack`.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PackPuzzle {
+    pub pack: String,
+    pub index: usize,
+}
+
+///
+/// Load all downloaded packs.
+///
+pub fn load_packs() -> Vec<PuzzlePack> {
+    load(PACKS_KEY).unwrap_or_default()
+}
+
+///
+/// Load the indices of the solved puzzles of each pack.
+///
+fn load_progress() -> BTreeMap<String, BTreeSet<usize>> {
+    load(PROGRESS_KEY).unwrap_or_default()
+}
+
+///
+/// Remember that `puzzle` is solved.
+///
+pub fn mark_solved(puzzle: &PackPuzzle) {
+    let mut progress = load_progress();
+    progress
+        .entry(puzzle.pack.clone())
+        .or_default()
+        .insert(puzzle.index);
+    save(PROGRESS_KEY, &progress);
+}
+
+///
+/// Download the pack listed as `info` from the server at `url`, verify and store it.
+///
+async fn download_pack(url: &str, info: &PackInfo) -> Result<PuzzlePack, String> {
+    let data = fetch_bytes(&format!("{}/{}", url, info.file))
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    let pack = verify_pack(info, &data).map_err(|e| e.to_string())?;
+    let mut packs = load_packs();
+    packs.retain(|p| p.id != pack.id);
+    packs.push(pack.clone());
+    save(PACKS_KEY, &packs);
+    Ok(pack)
+}
+
+#[component]
+pub fn PacksPage() -> impl IntoView {
+    let url = expect_context::<RwSignal<Settings>>()
+        .get_untracked()
+        .pack_url;
+    let (packs, set_packs) = signal(load_packs());
+    let progress = load_progress();
+    let (available, set_available) = signal(Vec::<PackInfo>::new());
+    let (error, set_error) = signal(None::<String>);
+
+    if !url.is_empty() {
+        let url = url.clone();
+        spawn_local(async move {
+            match fetch_text(&format!("{}/index.json", url)).await {
+                Ok(index) => match serde_json::from_str(&index) {
+                    Ok(index) => set_available.set(index),
+                    Err(e) => set_error.set(Some(format!("Cannot read pack index: {}", e))),
+                },
+                Err(e) => log!("Cannot load pack index: {:?}", e),
+            }
+        });
+    }
+
+    let server = url.clone();
+    let downloads = move || {
+        available
+            .get()
+            .into_iter()
+            .filter(|info| !packs.read().iter().any(|p| p.id == info.id))
+            .map(|info| {
+                let url = server.clone();
+                let name = format!("{} ({}) ", info.name, info.month);
+                let download = move |_| {
+                    let url = url.clone();
+                    let info = info.clone();
+                    spawn_local(async move {
+                        match download_pack(&url, &info).await {
+                            Ok(pack) => set_packs.update(|packs| packs.push(pack)),
+                            Err(e) => set_error.set(Some(e)),
+                        }
+                    });
+                };
+                view! {
+                    <li>
+                        {name}
+                        <button on:click=download>"Download"</button>
+                    </li>
+                }
+            })
+            .collect_view()
+    };
+
+    let downloaded = move || {
+        packs
+            .get()
+            .into_iter()
+            .map(|pack| {
+                let solved = progress.get(&pack.id).cloned().unwrap_or_default();
+                let puzzles = pack
+                    .puzzles
+                    .iter()
+                    .enumerate()
+                    .map(|(index, puzzle)| {
+                        view! {
+                            <a
+                                class="preview"
+                                class:solved=solved.contains(&index)
+                                href=format!("/pack/{}/{}", pack.id, index)
+                            >
+                                <div class="thumbnail" inner_html=puzzle.to_svg()/>
+                                <span class="title">{format!("#{}", index + 1)}</span>
+                            </a>
+                        }
+                    })
+                    .collect_view();
+                view! {
+                    <h2>
+                        {format!("{} \u{2013} {}/{} solved", pack.name, solved.len(), pack.puzzles.len())}
+                    </h2>
+                    <div class="row previews">{puzzles}</div>
+                }
+            })
+            .collect_view()
+    };
+
+    view! {
+        <div><span class="menu">hexhashi</span><a class="menu" href="/">Back</a></div>
+        <h1>"Puzzle packs"</h1>
+        <Show when=move || url.is_empty()>
+            <p>"Set the address of a pack server in the settings to download new packs."</p>
+        </Show>
+        <p class="error">{move || error.get()}</p>
+        <ul>{downloads}</ul>
+        {downloaded}
+    }
+}
+
+#[derive(Params, Debug, PartialEq)]
+pub struct PackGameArgs {
+    pub id: Option<String>,
+    pub index: Option<usize>,
+}
+
+///
+/// Play a puzzle of a downloaded pack.
+///
+#[component]
+pub fn PackGame() -> impl IntoView {
+    let params = use_params::<PackGameArgs>();
+    let params = params.read_untracked();
+    let params = params.as_ref().ok();
+    let id = params.and_then(|p| p.id.clone()).unwrap_or_default();
+    let index = params.and_then(|p| p.index).unwrap_or_default();
+    let pack = load_packs().into_iter().find(|p| p.id == id);
+    match pack.and_then(|p| {
+        let difficulty = p.difficulty.unwrap_or(Difficulty::Medium);
+        p.puzzles
+            .into_iter()
+            .nth(index)
+            .map(|puzzle| (difficulty, puzzle))
+    }) {
+        Some((difficulty, puzzle)) => {
+            let mode = GameMode::Normal;
+            count_started(&difficulty, &mode);
+            let pack = PackPuzzle { pack: id, index };
+            let mut entry = LibraryPuzzle::new(&puzzle, difficulty.clone(), Source::Downloaded);
+            entry.pack = Some(pack.clone());
+            update_library(|l| l.add(entry));
+            let session = GameSession::new(puzzle);
+            view! { <Board difficulty mode session elapsed_ms=0.0 hot_seat=None pack/> }.into_any()
+        }
+        None => view! {
+            <div><span class="menu">hexhashi</span><a class="menu" href="/packs">Back</a></div>
+            <p>"This puzzle is not downloaded."</p>
+        }
+        .into_any(),
+    }
+}
@@ -0,0 +1,156 @@
+use std::{cell::RefCell, collections::BTreeMap};
+
+use hexhashi_logic::instrument::{self, Hooks};
+use leptos::{ev::keydown, prelude::*};
+use leptos_use::{use_event_listener, use_interval_fn};
+
+/// Time between updates of the overlay in milliseconds
+const REFRESH_MS: u64 = 500;
+/// Drawing a frame of the board
+pub const FRAME: &str = "frame";
+/// Drawing the highlights of the hovered islands
+pub const HOVER: &str = "hover";
+/// Finding the bridges at a point of the board
+pub const HIT_TEST: &str = "hit test";
+
+///
+/// How often an operation ran and how long it took.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Timing {
+    pub count: usize,
+    pub last_ms: f64,
+    pub total_ms: f64,
+    pub max_ms: f64,
+}
+
+impl Timing {
+    fn add(&mut self, ms: f64) {
+        self.count += 1;
+        self.last_ms = ms;
+        self.total_ms += ms;
+        self.max_ms = self.max_ms.max(ms);
+    }
+
+    pub fn mean_ms(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.total_ms / self.count as f64
+        }
+    }
+}
+
+thread_local! {
+    /// Timings of the operations by their names
+    static TIMINGS: RefCell<BTreeMap<&'static str, Timing>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+fn now() -> f64 {
+    window().performance().map_or(0.0, |p| p.now())
+}
+
+///
+/// Record that the operation `name` took `ms` milliseconds.
+///
+pub fn record(name: &'static str, ms: f64) {
+    TIMINGS.with_borrow_mut(|timings| timings.entry(name).or_default().add(ms));
+}
+
+///
+/// Run `f` and record how long it took as the operation `name`.
+///
+pub fn measure<R>(name: &'static str, f: impl FnOnce() -> R) -> R {
+    let start = now();
+    let result = f();
+    record(name, now() - start);
+    result
+}
+
+///
+/// Record the timings of the solver and the generator as well.
+///
+pub fn instrument_logic() {
+    instrument::set_hooks(Hooks { now, record });
+}
+
+///
+/// Overlay for developers with the timings of drawing, hit-testing and solving,
+/// toggled by Ctrl+Alt+P.
+///
+#[component]
+pub fn PerfOverlay() -> impl IntoView {
+    let (visible, set_visible) = signal(false);
+    let timings = RwSignal::new(BTreeMap::new());
+    let _ = use_event_listener(window(), keydown, move |evt| {
+        if evt.ctrl_key() && evt.alt_key() && evt.code() == "KeyP" {
+            evt.prevent_default();
+            set_visible.update(|v| *v = !*v);
+        }
+    });
+    let _ = use_interval_fn(
+        move || {
+            if visible.get_untracked() {
+                timings.set(TIMINGS.with_borrow(|t| t.clone()));
+            }
+        },
+        REFRESH_MS,
+    );
+    let reset = move |_| {
+        TIMINGS.with_borrow_mut(|t| t.clear());
+        timings.set(BTreeMap::new());
+    };
+    let rows = move || {
+        timings
+            .get()
+            .into_iter()
+            .map(|(name, timing)| {
+                view! {
+                    <tr>
+                        <th>{name}</th>
+                        <td>{timing.count}</td>
+                        <td>{format!("{:.2}", timing.last_ms)}</td>
+                        <td>{format!("{:.2}", timing.mean_ms())}</td>
+                        <td>{format!("{:.2}", timing.max_ms)}</td>
+                    </tr>
+                }
+            })
+            .collect_view()
+    };
+
+    view! {
+        <Show when=move || visible.get()>
+            <aside class="perf">
+                <table>
+                    <tr>
+                        <th></th>
+                        <th>"Count"</th>
+                        <th>"Last ms"</th>
+                        <th>"Mean ms"</th>
+                        <th>"Max ms"</th>
+                    </tr>
+                    {rows}
+                </table>
+                <button on:click=reset>"Reset"</button>
+            </aside>
+        </Show>
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Timing;
+
+    #[test]
+    fn timing() {
+        let mut timing = Timing::default();
+        assert_eq!(timing.mean_ms(), 0.0);
+        for ms in [2.0, 6.0, 4.0] {
+            timing.add(ms);
+        }
+        assert_eq!(timing.count, 3);
+        assert_eq!(timing.last_ms, 4.0);
+        assert_eq!(timing.mean_ms(), 4.0);
+        assert_eq!(timing.max_ms, 6.0);
+    }
+}
@@ -0,0 +1,250 @@
+use std::{cell::RefCell, time::Duration};
+
+use hexhashi_logic::{
+    difficulty::Difficulty,
+    hex::{GameParameters, HexSystem},
+    solver::{GenerationReport, Uniqueness},
+    variant::GameVariant,
+};
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{JsCast, JsValue, prelude::Closure};
+use web_sys::{MessageEvent, Worker};
+
+use crate::{
+    storage::{load, remove, save},
+    telemetry::{report_generation, telemetry_url},
+};
+
+pub const PREFETCH_KEY: &str = "hexhashi.prefetched";
+/// Script starting the worker built from `src/bin/prefetch_worker.rs`
+const WORKER_SCRIPT: &str = "/prefetch_worker_loader.js";
+/// Time to wait before generating without a worker, so that the board of the current game is shown first
+const PREFETCH_DELAY: Duration = Duration::from_secs(2);
+
+///
+/// Puzzle generated and verified in advance.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Prefetched {
+    /// Difficulty as in `Difficulty::as_str`
+    difficulty: String,
+    seed: u64,
+    code: String,
+}
+
+impl Prefetched {
+    fn new(difficulty: &Difficulty, seed: u64, system: &HexSystem) -> Self {
+        Prefetched {
+            difficulty: difficulty.as_str().to_string(),
+            seed,
+            code: system.to_code(),
+        }
+    }
+
+    ///
+    /// Get the seed and puzzle if it is of `difficulty`.
+    ///
+    fn puzzle(&self, difficulty: &Difficulty) -> Option<(u64, HexSystem)> {
+        if self.difficulty != difficulty.as_str() {
+            return None;
+        }
+        HexSystem::from_code(&self.code)
+            .ok()
+            .map(|system| (self.seed, system))
+    }
+}
+
+///
+/// Difficulty the solver rates a puzzle and whether its solution is unique.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Rating {
+    /// `None` if the solver cannot solve the puzzle
+    pub difficulty: Option<Difficulty>,
+    pub uniqueness: Uniqueness,
+}
+
+impl Rating {
+    fn new(system: &HexSystem) -> Self {
+        Rating {
+            difficulty: system.rate(),
+            uniqueness: system.uniqueness(),
+        }
+    }
+}
+
+/// Called with the rating of a puzzle by its code
+type RatingCallback = Box<dyn FnOnce(Rating)>;
+
+thread_local! {
+    /// Worker generating the puzzles, started on first use
+    static WORKER: RefCell<Option<Worker>> = const { RefCell::new(None) };
+    /// Callbacks waiting for the worker to rate a puzzle
+    static RATINGS: RefCell<Vec<(String, RatingCallback)>> = const { RefCell::new(Vec::new()) };
+}
+
+///
+/// Get a seed for a new puzzle.
+///
+fn new_seed() -> u64 {
+    window().performance().unwrap().now() as u64
+}
+
+fn start_worker() -> Option<Worker> {
+    let worker = Worker::new(WORKER_SCRIPT).ok()?;
+    let onmessage = Closure::<dyn Fn(MessageEvent)>::new(|evt: MessageEvent| {
+        let Some(data) = evt.data().as_string() else {
+            return;
+        };
+        if let Ok((code, difficulty, uniqueness)) = serde_json::from_str::<(String, _, _)>(&data) {
+            let waiting = RATINGS.with_borrow_mut(|ratings| {
+                let (done, waiting) = ratings.drain(..).partition(|(c, _)| *c == code);
+                *ratings = waiting;
+                done
+            });
+            let rating = Rating {
+                difficulty,
+                uniqueness,
+            };
+            for (_, callback) in waiting {
+                callback(rating.clone());
+            }
+            return;
+        }
+        let Ok((difficulty, seed, code, report)) =
+            serde_json::from_str::<(String, u64, String, Option<GenerationReport>)>(&data)
+        else {
+            return;
+        };
+        if let (Some(report), Ok(difficulty)) = (report, difficulty.parse()) {
+            report_generation(difficulty, report);
+        }
+        save(
+            PREFETCH_KEY,
+            &Prefetched {
+                difficulty,
+                seed,
+                code,
+            },
+        );
+    });
+    worker.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+    onmessage.forget();
+    Some(worker)
+}
+
+///
+/// Post `message` to the worker, starting it if needed.
+///
+fn post(message: &str) -> bool {
+    WORKER.with_borrow_mut(|worker| {
+        if worker.is_none() {
+            *worker = start_worker();
+        }
+        worker
+            .as_ref()
+            .is_some_and(|w| w.post_message(&JsValue::from_str(message)).is_ok())
+    })
+}
+
+///
+/// Rate `system` in the worker and call `done` with the rating, so that the page stays responsive meanwhile.
+///
+/// Rates on the page itself if there is no worker.
+///
+pub fn rate(system: &HexSystem, done: impl FnOnce(Rating) + 'static) {
+    let code = system.to_code();
+    RATINGS.with_borrow_mut(|ratings| ratings.push((code.clone(), Box::new(done))));
+    if !post(&serde_json::to_string(&code).unwrap_or_default()) {
+        let waiting = RATINGS.with_borrow_mut(|ratings| ratings.pop());
+        if let Some((_, done)) = waiting {
+            let system = system.clone();
+            set_timeout(move || done(Rating::new(&system)), Duration::ZERO);
+        }
+    }
+}
+
+///
+/// Generate and verify the next puzzle of `difficulty` while the player is busy with the current one.
+///
+/// The puzzle is kept in the local storage, so that it is also used after the page is loaded again.
+///
+pub fn prefetch(difficulty: &Difficulty) {
+    if load::<Prefetched>(PREFETCH_KEY).is_some_and(|p| p.difficulty == difficulty.as_str()) {
+        return;
+    }
+    // The generation is only reported to players who opted in to telemetry.
+    let reported = telemetry_url().is_some();
+    let request =
+        serde_json::to_string(&(difficulty.as_str(), new_seed(), reported)).unwrap_or_default();
+    if !post(&request) {
+        let difficulty = difficulty.clone();
+        set_timeout(
+            move || {
+                let (seed, system) =
+                    HexSystem::generate_verified(GameParameters::preset(&difficulty, new_seed()));
+                save(PREFETCH_KEY, &Prefetched::new(&difficulty, seed, &system));
+            },
+            PREFETCH_DELAY,
+        );
+    }
+}
+
+///
+/// Get the seed and puzzle to play next at `difficulty`.
+///
+/// Uses the prefetched puzzle if there is one, otherwise generates it right away.
+///
+pub fn next_puzzle(difficulty: &Difficulty) -> (u64, HexSystem) {
+    let prefetched = load::<Prefetched>(PREFETCH_KEY).and_then(|p| p.puzzle(difficulty));
+    if prefetched.is_some() {
+        remove(PREFETCH_KEY);
+    }
+    prefetched.unwrap_or_else(|| {
+        HexSystem::generate_verified(GameParameters::preset(difficulty, new_seed()))
+    })
+}
+
+///
+/// Get a new puzzle of `difficulty` played by the rules of `variant`.
+///
+/// Only classic puzzles are prefetched.
+///
+pub fn next_variant_puzzle(difficulty: &Difficulty, variant: GameVariant) -> (u64, HexSystem) {
+    match variant {
+        GameVariant::Classic => next_puzzle(difficulty),
+        variant => {
+            let seed = new_seed();
+            let params = GameParameters::preset(difficulty, seed);
+            (seed, HexSystem::generate_variant(params, variant))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use hexhashi_logic::{
+        difficulty::Difficulty,
+        hex::{GameParameters, HexSystem},
+    };
+
+    use super::Prefetched;
+
+    #[test]
+    fn prefetched() {
+        let system = HexSystem::generate_new(GameParameters::preset(&Difficulty::Hard, 3));
+        let prefetched = Prefetched::new(&Difficulty::Hard, 3, &system);
+        let (seed, puzzle) = prefetched.puzzle(&Difficulty::Hard).unwrap();
+        assert_eq!(seed, 3);
+        assert_eq!(puzzle.to_code(), system.to_code());
+        // Kept for the next game of its own difficulty
+        assert!(prefetched.puzzle(&Difficulty::Easy).is_none());
+
+        let broken = Prefetched {
+            code: "nonsense".to_string(),
+            ..prefetched
+        };
+        assert!(broken.puzzle(&Difficulty::Hard).is_none());
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+
+use leptos::{prelude::*, task::spawn_local};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen_futures::JsFuture;
+
+use crate::{
+    migration::migrate,
+    prefetch::PREFETCH_KEY,
+    settings::Settings,
+    storage::{KEY_PREFIX, load_all, save_raw},
+    sync::SYNC_TIMES_KEY,
+    users::USERS_KEY,
+};
+
+/// Version of the format of exported profiles
+const PROFILE_VERSION: u32 = 1;
+/// Stored values that only matter on this device, e.g. caches and the list of its players
+pub const EXCLUDED_KEYS: [&str; 3] = [PREFETCH_KEY, SYNC_TIMES_KEY, USERS_KEY];
+
+///
+/// Everything stored by the player, e.g. settings, statistics, saved game and library,
+/// as exported to a file to move it to another device.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Profile {
+    #[serde(default)]
+    version: u32,
+    /// Stored values by their key in the local storage
+    data: BTreeMap<String, Value>,
+}
+
+impl Profile {
+    ///
+    /// Read an exported profile and upgrade its values to their current formats, see `migrate`.
+    ///
+    /// Values that do not belong to hexhashi are dropped.
+    ///
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut profile: Profile = serde_json::from_str(text)
+            .map_err(|e| format!("This is no hexhashi profile: {}", e))?;
+        if profile.version == 0 || profile.version > PROFILE_VERSION {
+            return Err(format!(
+                "The profile has version {}, but only versions up to {} are supported.",
+                profile.version, PROFILE_VERSION
+            ));
+        }
+        profile
+            .data
+            .retain(|key, _| key.starts_with(KEY_PREFIX) && !EXCLUDED_KEYS.contains(&key.as_str()));
+        for (key, value) in &mut profile.data {
+            migrate(key, value);
+        }
+        Ok(profile)
+    }
+}
+
+///
+/// Get the profile of all values stored by hexhashi as JSON.
+///
+fn export_profile() -> String {
+    let data = load_all()
+        .into_iter()
+        .filter(|(key, _)| !EXCLUDED_KEYS.contains(&key.as_str()))
+        .filter_map(|(key, json)| Some((key, serde_json::from_str(&json).ok()?)))
+        .collect();
+    let profile = Profile {
+        version: PROFILE_VERSION,
+        data,
+    };
+    serde_json::to_string_pretty(&profile).unwrap_or_default()
+}
+
+///
+/// Replace the stored values by the ones of the exported profile in `text`.
+///
+fn import_profile(text: &str) -> Result<(), String> {
+    let profile = Profile::parse(text)?;
+    for (key, value) in profile.data {
+        save_raw(&key, &value.to_string());
+    }
+    Ok(())
+}
+
+///
+/// Buttons to export all data of the player to a file and to import it from one.
+///
+#[component]
+pub fn ProfileTransfer() -> impl IntoView {
+    let settings = expect_context::<RwSignal<Settings>>();
+    let (error, set_error) = signal(None::<String>);
+    // The settings changed on this page are stored right away, so export them as well.
+    let href = move || {
+        settings.track();
+        format!(
+            "data:application/json;charset=utf-8,{}",
+            js_sys::encode_uri_component(&export_profile())
+        )
+    };
+    let import = move |ev: leptos::ev::Event| {
+        let input: web_sys::HtmlInputElement = event_target(&ev);
+        let Some(file) = input.files().and_then(|files| files.get(0)) else {
+            return;
+        };
+        spawn_local(async move {
+            let Some(text) = JsFuture::from(file.text())
+                .await
+                .ok()
+                .and_then(|t| t.as_string())
+            else {
+                return;
+            };
+            let confirmed = window()
+                .confirm_with_message("Replace the data on this device by the imported profile?")
+                .unwrap_or(false);
+            if !confirmed {
+                return;
+            }
+            match import_profile(&text) {
+                Ok(()) => {
+                    let _ = window().location().reload();
+                }
+                Err(e) => set_error.set(Some(e)),
+            }
+        });
+    };
+
+    view! {
+        <p>
+            <a download="hexhashi-profile.json" href=href>
+                "Export profile"
+            </a>
+            " "
+            <label>
+                "Import profile "
+                <input type="file" accept="application/json,.json" on:change=import/>
+            </label>
+            <span class="error">{move || error.get()}</span>
+        </p>
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PROFILE_VERSION, Profile};
+
+    #[test]
+    fn parse() {
+        let profile = Profile::parse(
+            r#"{"version": 1, "data": {
+                "hexhashi.settings": {"haptics": false},
+                "hexhashi.stats": {"puzzles": {"abc": 61000.0}},
+                "hexhashi.prefetched": {"seed": 1},
+                "other.app": 1
+            }}"#,
+        )
+        .unwrap();
+        assert_eq!(profile.version, PROFILE_VERSION);
+        // Caches and values of other apps are not imported.
+        assert_eq!(
+            profile.data.keys().collect::<Vec<_>>(),
+            ["hexhashi.settings", "hexhashi.stats"]
+        );
+        assert_eq!(profile.data["hexhashi.settings"]["haptics"], false);
+        // Values of older formats are upgraded.
+        assert_eq!(
+            profile.data["hexhashi.stats"]["puzzles"]["abc"]["solved"],
+            1
+        );
+
+        assert!(Profile::parse(r#"{"version": 99, "data": {}}"#).is_err());
+        assert!(Profile::parse(r#"{"data": {}}"#).is_err());
+        assert!(Profile::parse("nonsense").is_err());
+    }
+}
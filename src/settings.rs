@@ -0,0 +1,712 @@
+use hexhashi_logic::layout::Orientation;
+use leptos::{ev::keydown, prelude::*};
+use leptos_use::use_event_listener;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    input::{Action, Binding, Bindings, Gestures},
+    profile::ProfileTransfer,
+    sound::{Sound, SoundPack, play},
+    storage::{load, save},
+    theme::{Theme, ThemeEditor},
+};
+
+pub const SETTINGS_KEY: &str = "hexhashi.settings";
+
+///
+/// Which lines of the grid are drawn.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum GridDisplay {
+    /// All lines between neighboring cells
+    #[default]
+    All,
+    /// Only lines where bridges between islands are possible
+    Bridges,
+    /// Only the possible bridges of the island or bridge the mouse is pointing to
+    Hover,
+}
+
+///
+/// How the board is drawn
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Renderer {
+    /// Canvas 2D, fine for the usual board sizes
+    #[default]
+    Canvas,
+    /// WebGL, drawing grid, bridges and islands in a single call for very large boards
+    WebGl,
+}
+
+///
+/// User preferences
+///
+/// Missing fields fall back to their default, so that settings stored by older versions can still be read.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub orientation: Orientation,
+    /// Vibrate on blocked moves and solved puzzles
+    pub haptics: bool,
+    /// Sounds for moves and solved puzzles
+    pub sound_pack: SoundPack,
+    /// Volume of the sounds of moves from 0 to 1
+    pub effects_volume: f64,
+    /// Volume of the fanfare of solved puzzles from 0 to 1
+    pub fanfare_volume: f64,
+    /// Lose the game after a limited number of mistakes
+    pub hardcore: bool,
+    /// Take back wrong bridges right away and leave the games out of the statistics
+    pub practice: bool,
+    /// Factor for the size of the whole user interface
+    pub scale: f64,
+    /// Factor for the radius of the islands
+    pub island_scale: f64,
+    /// Factor for the widths of bridges and lines
+    pub line_scale: f64,
+    /// Font size of the numbers on the islands in pt
+    pub font_size: f64,
+    /// Label islands with their coordinates, e.g. "C4"
+    pub show_coordinates: bool,
+    /// Dim the bridges that cannot be added anymore and ignore clicks on them
+    pub dim_unavailable: bool,
+    /// Outline hovered islands by whether they can still get their number of bridges
+    pub island_status: bool,
+    /// Show "1" or "2" in the middle of each placed bridge
+    pub bridge_badges: bool,
+    /// Duration of the shaking of a blocked move in milliseconds
+    pub blocked_feedback_ms: f64,
+    /// Do not offer bridges longer than the longest bridge of the puzzle
+    pub limit_bridge_length: bool,
+    /// Pause the timer after so many seconds without input, never if 0
+    pub idle_pause_s: f64,
+    /// Show the games solved today in a corner of the board
+    pub session_hud: bool,
+    /// Custom colors of the board, the ones of the mode if none
+    pub theme: Option<Theme>,
+    pub grid: GridDisplay,
+    pub renderer: Renderer,
+    /// Name shown on the leaderboard
+    pub player_name: String,
+    /// Address of the leaderboard server, no leaderboard if empty
+    pub leaderboard_url: String,
+    /// Address of the server providing puzzle packs
+    pub pack_url: String,
+    /// Address of the server to share the profile with other devices, no sync if empty
+    pub sync_url: String,
+    /// Report anonymous outcomes of solved puzzles, only if the player opts in
+    pub telemetry: bool,
+    /// Address of the server collecting the reports
+    pub telemetry_url: String,
+    /// Mouse buttons and keys triggering the actions on the board
+    pub bindings: Bindings,
+    /// Zooming and panning by wheel, touchpad and touch
+    pub gestures: Gestures,
+    /// Swap primary and secondary mouse button and mirror the controls around the board
+    pub left_handed: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            orientation: Orientation::default(),
+            haptics: true,
+            sound_pack: SoundPack::default(),
+            effects_volume: 0.5,
+            fanfare_volume: 0.5,
+            hardcore: false,
+            practice: false,
+            scale: 1.0,
+            island_scale: 1.0,
+            line_scale: 1.0,
+            font_size: 12.0,
+            show_coordinates: false,
+            dim_unavailable: true,
+            island_status: true,
+            bridge_badges: false,
+            limit_bridge_length: false,
+            idle_pause_s: 60.0,
+            session_hud: false,
+            theme: None,
+            blocked_feedback_ms: 600.0,
+            grid: GridDisplay::default(),
+            renderer: Renderer::default(),
+            player_name: String::new(),
+            leaderboard_url: String::new(),
+            pack_url: String::new(),
+            sync_url: String::new(),
+            telemetry: false,
+            telemetry_url: String::new(),
+            bindings: Bindings::default(),
+            gestures: Gestures::default(),
+            left_handed: false,
+        }
+    }
+}
+
+///
+/// Load the stored settings or the defaults.
+///
+pub fn load_settings() -> Settings {
+    load(SETTINGS_KEY).unwrap_or_default()
+}
+
+///
+/// Store the `settings`.
+///
+pub fn save_settings(settings: &Settings) {
+    save(SETTINGS_KEY, settings);
+}
+
+#[component]
+pub fn SettingsPage() -> impl IntoView {
+    let settings = expect_context::<RwSignal<Settings>>();
+
+    view! {
+        <div><span class="menu">hexhashi</span><a class="menu" href="/">Back</a></div>
+        <h1>"Settings"</h1>
+        <div class="settings">
+            <label>
+                "Orientation "
+                <select on:change=move |ev| {
+                    let orientation = match event_target_value(&ev).as_str() {
+                        "flat" => Orientation::FlatTop,
+                        _ => Orientation::PointyTop,
+                    };
+                    settings.update(|s| s.orientation = orientation);
+                }>
+                    <option
+                        value="pointy"
+                        selected=move || settings.get().orientation == Orientation::PointyTop
+                    >
+                        "Pointy top"
+                    </option>
+                    <option
+                        value="flat"
+                        selected=move || settings.get().orientation == Orientation::FlatTop
+                    >
+                        "Flat top"
+                    </option>
+                </select>
+            </label>
+            <label>
+                "Grid "
+                <select on:change=move |ev| {
+                    let grid = match event_target_value(&ev).as_str() {
+                        "bridges" => GridDisplay::Bridges,
+                        "hover" => GridDisplay::Hover,
+                        _ => GridDisplay::All,
+                    };
+                    settings.update(|s| s.grid = grid);
+                }>
+                    <option value="all" selected=move || settings.get().grid == GridDisplay::All>
+                        "Full grid"
+                    </option>
+                    <option
+                        value="bridges"
+                        selected=move || settings.get().grid == GridDisplay::Bridges
+                    >
+                        "Possible bridges only"
+                    </option>
+                    <option value="hover" selected=move || settings.get().grid == GridDisplay::Hover>
+                        "Possible bridges on hover only"
+                    </option>
+                </select>
+            </label>
+            <label hidden=!cfg!(feature = "webgl")>
+                "Renderer "
+                <select on:change=move |ev| {
+                    let renderer = match event_target_value(&ev).as_str() {
+                        "webgl" => Renderer::WebGl,
+                        _ => Renderer::Canvas,
+                    };
+                    settings.update(|s| s.renderer = renderer);
+                }>
+                    <option
+                        value="canvas"
+                        selected=move || settings.get().renderer == Renderer::Canvas
+                    >
+                        "Canvas"
+                    </option>
+                    <option
+                        value="webgl"
+                        selected=move || settings.get().renderer == Renderer::WebGl
+                    >
+                        "WebGL for very large boards"
+                    </option>
+                </select>
+            </label>
+            <label>
+                <input
+                    type="checkbox"
+                    prop:checked=move || settings.get().haptics
+                    on:change=move |ev| settings.update(|s| s.haptics = event_target_checked(&ev))
+                />
+                " Vibrate on blocked moves and solved puzzles"
+            </label>
+            <label>
+                "Sounds "
+                <select on:change=move |ev| {
+                    let value = event_target_value(&ev);
+                    if let Some(pack) = SoundPack::ALL.into_iter().find(|p| p.as_str() == value) {
+                        settings.update(|s| s.sound_pack = pack);
+                        play(pack, Sound::Add, settings.get_untracked().effects_volume);
+                    }
+                }>
+                    {SoundPack::ALL
+                        .into_iter()
+                        .map(|pack| {
+                            view! {
+                                <option
+                                    value=pack.as_str()
+                                    selected=move || settings.get().sound_pack == pack
+                                >
+                                    {pack.to_string()}
+                                </option>
+                            }
+                        })
+                        .collect_view()}
+                </select>
+            </label>
+            <label>
+                "Effects volume "
+                <input
+                    type="range"
+                    min="0"
+                    max="1"
+                    step="0.05"
+                    prop:value=move || settings.get().effects_volume
+                    on:change=move |ev| {
+                        if let Ok(volume) = event_target_value(&ev).parse() {
+                            settings.update(|s| s.effects_volume = volume);
+                            play(settings.get_untracked().sound_pack, Sound::Add, volume);
+                        }
+                    }
+                />
+            </label>
+            <label>
+                "Fanfare volume "
+                <input
+                    type="range"
+                    min="0"
+                    max="1"
+                    step="0.05"
+                    prop:value=move || settings.get().fanfare_volume
+                    on:change=move |ev| {
+                        if let Ok(volume) = event_target_value(&ev).parse() {
+                            settings.update(|s| s.fanfare_volume = volume);
+                            play(settings.get_untracked().sound_pack, Sound::Solved, volume);
+                        }
+                    }
+                />
+            </label>
+            <label>
+                <input
+                    type="checkbox"
+                    prop:checked=move || settings.get().hardcore
+                    on:change=move |ev| settings.update(|s| s.hardcore = event_target_checked(&ev))
+                />
+                " Restart after 3 mistakes"
+            </label>
+            <label>
+                <input
+                    type="checkbox"
+                    prop:checked=move || settings.get().practice
+                    on:change=move |ev| settings.update(|s| s.practice = event_target_checked(&ev))
+                />
+                " Practice: take back wrong bridges right away, not counted in the statistics"
+            </label>
+            <label>
+                <input
+                    type="checkbox"
+                    prop:checked=move || settings.get().limit_bridge_length
+                    on:change=move |ev| {
+                        settings.update(|s| s.limit_bridge_length = event_target_checked(&ev))
+                    }
+                />
+                " Only offer bridges up to the maximum length of the puzzle"
+            </label>
+            <label>
+                <input
+                    type="checkbox"
+                    prop:checked=move || settings.get().show_coordinates
+                    on:change=move |ev| {
+                        settings.update(|s| s.show_coordinates = event_target_checked(&ev))
+                    }
+                />
+                " Label islands with coordinates"
+            </label>
+            <label>
+                <input
+                    type="checkbox"
+                    prop:checked=move || settings.get().dim_unavailable
+                    on:change=move |ev| {
+                        settings.update(|s| s.dim_unavailable = event_target_checked(&ev))
+                    }
+                />
+                " Dim bridges of completed islands"
+            </label>
+            <label>
+                <input
+                    type="checkbox"
+                    prop:checked=move || settings.get().island_status
+                    on:change=move |ev| {
+                        settings.update(|s| s.island_status = event_target_checked(&ev))
+                    }
+                />
+                " Show on hover if an island can still be completed"
+            </label>
+            <label>
+                <input
+                    type="checkbox"
+                    prop:checked=move || settings.get().bridge_badges
+                    on:change=move |ev| {
+                        settings.update(|s| s.bridge_badges = event_target_checked(&ev))
+                    }
+                />
+                " Show the number of bridges on each bridge"
+            </label>
+            <label>
+                "Blocked move feedback "
+                <input
+                    type="number"
+                    min="0"
+                    max="3000"
+                    step="100"
+                    prop:value=move || settings.get().blocked_feedback_ms
+                    on:change=move |ev| {
+                        if let Ok(duration) = event_target_value(&ev).parse() {
+                            settings.update(|s| s.blocked_feedback_ms = duration);
+                        }
+                    }
+                />
+                " ms"
+            </label>
+            <label>
+                "Pause the timer after "
+                <input
+                    type="number"
+                    min="0"
+                    max="3600"
+                    step="10"
+                    prop:value=move || settings.get().idle_pause_s
+                    on:change=move |ev| {
+                        if let Ok(seconds) = event_target_value(&ev).parse() {
+                            settings.update(|s| s.idle_pause_s = seconds);
+                        }
+                    }
+                />
+                " s without input (0 for never)"
+            </label>
+            <label>
+                <input
+                    type="checkbox"
+                    prop:checked=move || settings.get().session_hud
+                    on:change=move |ev| settings.update(|s| s.session_hud = event_target_checked(&ev))
+                />
+                " Show the games solved today next to the board"
+            </label>
+            <h2>"Weekly challenge"</h2>
+            <label>
+                "Name "
+                <input
+                    type="text"
+                    prop:value=move || settings.get().player_name
+                    on:change=move |ev| settings.update(|s| s.player_name = event_target_value(&ev))
+                />
+            </label>
+            <label>
+                "Leaderboard "
+                <input
+                    type="url"
+                    placeholder="https://..."
+                    prop:value=move || settings.get().leaderboard_url
+                    on:change=move |ev| {
+                        settings
+                            .update(|s| {
+                                s.leaderboard_url = event_target_value(&ev)
+                                    .trim_end_matches('/')
+                                    .to_string()
+                            })
+                    }
+                />
+            </label>
+            <h2>"Puzzle packs"</h2>
+            <label>
+                "Pack server "
+                <input
+                    type="url"
+                    placeholder="https://..."
+                    prop:value=move || settings.get().pack_url
+                    on:change=move |ev| {
+                        settings
+                            .update(|s| {
+                                s.pack_url = event_target_value(&ev).trim_end_matches('/').to_string()
+                            })
+                    }
+                />
+            </label>
+            <h2>"Controls"</h2>
+            <label>
+                <input
+                    type="checkbox"
+                    prop:checked=move || settings.get().left_handed
+                    on:change=move |ev| settings.update(|s| s.left_handed = event_target_checked(&ev))
+                />
+                " Left-handed: swap mouse buttons and mirror the controls"
+            </label>
+            <BindingEditor settings/>
+            <label>
+                "Zoom sensitivity "
+                <input
+                    type="range"
+                    min="0.25"
+                    max="4"
+                    step="0.25"
+                    prop:value=move || settings.get().gestures.zoom_sensitivity
+                    on:change=move |ev| {
+                        if let Ok(sensitivity) = event_target_value(&ev).parse() {
+                            settings.update(|s| s.gestures.zoom_sensitivity = sensitivity);
+                        }
+                    }
+                />
+            </label>
+            <label>
+                "Pan sensitivity "
+                <input
+                    type="range"
+                    min="0.25"
+                    max="4"
+                    step="0.25"
+                    prop:value=move || settings.get().gestures.pan_sensitivity
+                    on:change=move |ev| {
+                        if let Ok(sensitivity) = event_target_value(&ev).parse() {
+                            settings.update(|s| s.gestures.pan_sensitivity = sensitivity);
+                        }
+                    }
+                />
+            </label>
+            <label>
+                <input
+                    type="checkbox"
+                    prop:checked=move || settings.get().gestures.invert_scroll
+                    on:change=move |ev| {
+                        settings.update(|s| s.gestures.invert_scroll = event_target_checked(&ev))
+                    }
+                />
+                " Invert the scroll direction of wheel and touchpad"
+            </label>
+            <label>
+                <input
+                    type="checkbox"
+                    prop:checked=move || settings.get().gestures.double_tap_zoom
+                    on:change=move |ev| {
+                        settings.update(|s| s.gestures.double_tap_zoom = event_target_checked(&ev))
+                    }
+                />
+                " Double-tap next to the bridges to zoom in"
+            </label>
+            <h2>"Accessibility"</h2>
+            <label>
+                "UI scale "
+                <input
+                    type="range"
+                    min="0.5"
+                    max="3"
+                    step="0.1"
+                    prop:value=move || settings.get().scale
+                    on:change=move |ev| {
+                        if let Ok(scale) = event_target_value(&ev).parse() {
+                            settings.update(|s| s.scale = scale);
+                        }
+                    }
+                />
+            </label>
+            <label>
+                "Island size "
+                <input
+                    type="range"
+                    min="0.5"
+                    max="2"
+                    step="0.1"
+                    prop:value=move || settings.get().island_scale
+                    on:change=move |ev| {
+                        if let Ok(scale) = event_target_value(&ev).parse() {
+                            settings.update(|s| s.island_scale = scale);
+                        }
+                    }
+                />
+            </label>
+            <label>
+                "Line width "
+                <input
+                    type="range"
+                    min="0.5"
+                    max="3"
+                    step="0.1"
+                    prop:value=move || settings.get().line_scale
+                    on:change=move |ev| {
+                        if let Ok(scale) = event_target_value(&ev).parse() {
+                            settings.update(|s| s.line_scale = scale);
+                        }
+                    }
+                />
+            </label>
+            <label>
+                "Number font size "
+                <input
+                    type="number"
+                    min="6"
+                    max="48"
+                    prop:value=move || settings.get().font_size
+                    on:change=move |ev| {
+                        if let Ok(size) = event_target_value(&ev).parse() {
+                            settings.update(|s| s.font_size = size);
+                        }
+                    }
+                />
+                " pt"
+            </label>
+            <h2>"Profile"</h2>
+            <p>"Move settings, statistics, saved game and library to another device."</p>
+            <ThemeEditor/>
+            <ProfileTransfer/>
+            <label>
+                "Sync server "
+                <input
+                    type="url"
+                    placeholder="https://..."
+                    prop:value=move || settings.get().sync_url
+                    on:change=move |ev| {
+                        settings
+                            .update(|s| {
+                                s.sync_url = event_target_value(&ev).trim_end_matches('/').to_string()
+                            })
+                    }
+                />
+                " (used after reloading)"
+            </label>
+            <h2>"Telemetry"</h2>
+            <p>
+                "Help to rate the puzzles better by reporting solved puzzles anonymously: "
+                "the hash of the puzzle, its difficulty, your time, hints and mistakes, "
+                "and how generating the next puzzles went."
+            </p>
+            <label>
+                <input
+                    type="checkbox"
+                    prop:checked=move || settings.get().telemetry
+                    on:change=move |ev| settings.update(|s| s.telemetry = event_target_checked(&ev))
+                />
+                " Report solved puzzles"
+            </label>
+            <label>
+                "Telemetry server "
+                <input
+                    type="url"
+                    placeholder="https://..."
+                    prop:value=move || settings.get().telemetry_url
+                    on:change=move |ev| {
+                        settings
+                            .update(|s| {
+                                s.telemetry_url = event_target_value(&ev)
+                                    .trim_end_matches('/')
+                                    .to_string()
+                            })
+                    }
+                />
+            </label>
+        </div>
+    }
+}
+
+///
+/// Table of the actions on the board with their bindings, which can be changed by pressing a new key or button.
+///
+#[component]
+fn BindingEditor(settings: RwSignal<Settings>) -> impl IntoView {
+    // Action waiting for its new binding
+    let (capturing, set_capturing) = signal(None::<Action>);
+    // Keys are ignored for actions on bridges, which keeps waiting for a click.
+    let bind = move |binding: Binding| {
+        if let Some(action) = capturing.get_untracked() {
+            let mut bound = false;
+            settings.update(|s| bound = s.bindings.set(action, binding));
+            if bound {
+                set_capturing.set(None);
+            }
+        }
+    };
+    let _ = use_event_listener(window(), keydown, move |evt| {
+        if capturing.get_untracked().is_none() {
+            return;
+        }
+        evt.prevent_default();
+        if evt.key() == "Escape" {
+            set_capturing.set(None);
+        } else {
+            bind(Binding::from_key(&evt));
+        }
+    });
+
+    let rows = Action::ALL
+        .into_iter()
+        .map(|action| {
+            let current = move || {
+                let settings = settings.read();
+                settings
+                    .bindings
+                    .bindings(action)
+                    .iter()
+                    .map(|b| {
+                        // Show the physical buttons to press.
+                        if settings.left_handed {
+                            b.swapped().to_string()
+                        } else {
+                            b.to_string()
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            view! {
+                <tr>
+                    <td>{action.to_string()}</td>
+                    <td>
+                        <Show
+                            when=move || capturing.get() == Some(action)
+                            fallback=move || view! { <span>{current}</span> }
+                        >
+                            <span
+                                class="capture"
+                                on:mousedown=move |ev| {
+                                    ev.prevent_default();
+                                    bind(Binding::from_mouse(&ev, settings.get_untracked().left_handed));
+                                }
+                                on:contextmenu=move |ev| ev.prevent_default()
+                            >
+                                {if action.needs_bridge() {
+                                    "Click here, Escape to cancel"
+                                } else {
+                                    "Press a key or click here, Escape to cancel"
+                                }}
+                            </span>
+                        </Show>
+                    </td>
+                    <td>
+                        <button on:click=move |_| set_capturing.set(Some(action))>"Change"</button>
+                    </td>
+                </tr>
+            }
+        })
+        .collect_view();
+
+    view! {
+        <table class="bindings">{rows}</table>
+        <button on:click=move |_| {
+            settings.update(|s| s.bindings = Default::default())
+        }>"Reset controls"</button>
+    }
+}
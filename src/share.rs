@@ -0,0 +1,322 @@
+use hexhashi_logic::{
+    calibration::RatingModel, difficulty::Difficulty, hex::HexSystem, session::GameSession,
+    solver::Uniqueness,
+};
+use leptos::{logging::log, prelude::*, task::spawn_local};
+use leptos_router::{hooks::use_params, params::Params};
+use qrcode::{QrCode, render::svg};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+
+use crate::{
+    clipboard::read_text,
+    game::{Board, GameMode, format_time, get_par_time},
+    library::{LibraryPuzzle, Source, update_library},
+    prefetch::{Rating, rate},
+    stats::count_started,
+};
+
+/// Path of the puzzles given by the fragment of the URL, see `custom_url`
+pub const CUSTOM_PATH: &str = "/play/custom";
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], js_name = invoke, catch)]
+    pub(crate) async fn try_invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+///
+/// Get the address to import the puzzle `system` of `difficulty`.
+///
+pub fn share_url(difficulty: &Difficulty, system: &HexSystem) -> String {
+    format!(
+        "{}/import/{}/{}",
+        window().location().origin().unwrap_or_default(),
+        difficulty.as_str(),
+        system.to_code()
+    )
+}
+
+///
+/// Get a link to play the puzzle `system` with the whole puzzle in the fragment of the URL.
+///
+/// Browsers do not send the fragment to the server, so that the link works without any backend.
+///
+pub fn custom_url(system: &HexSystem) -> String {
+    format!(
+        "{}{}#{}",
+        window().location().origin().unwrap_or_default(),
+        CUSTOM_PATH,
+        system.to_code()
+    )
+}
+
+///
+/// Render `text` as QR code in SVG.
+///
+pub fn qr_svg(text: &str) -> String {
+    match QrCode::new(text.as_bytes()) {
+        Ok(code) => code.render::<svg::Color>().min_dimensions(200, 200).build(),
+        Err(e) => {
+            log!("Cannot create QR code: {}", e);
+            String::new()
+        }
+    }
+}
+
+///
+/// Is the app running in Tauri rather than in a browser?
+///
+pub fn is_tauri() -> bool {
+    js_sys::Reflect::has(&window(), &JsValue::from_str("__TAURI__")).unwrap_or(false)
+}
+
+///
+/// Get the path to import the puzzle shared as `text`, which is either a share URL or a plain puzzle code.
+///
+pub fn import_path(text: &str) -> String {
+    let text = text.trim();
+    if let Some((_, code)) = text.split_once(&format!("{}#", CUSTOM_PATH)) {
+        return format!("{}#{}", CUSTOM_PATH, code);
+    }
+    match text.split_once("/import/") {
+        Some((_, path)) => format!("/import/{}", path),
+        None => format!("/import/medium/{}", text),
+    }
+}
+
+///
+/// Get the puzzle code in the `fragment` of a link made by `custom_url`.
+///
+fn fragment_code(fragment: &str) -> String {
+    let code = fragment.strip_prefix('#').unwrap_or(fragment);
+    // Some apps escape the link when passing it on.
+    js_sys::decode_uri_component(code)
+        .map(String::from)
+        .unwrap_or_else(|_| code.to_string())
+        .trim()
+        .to_string()
+}
+
+///
+/// Get a warning if islands of `system` need more bridges than they can take, so that it cannot be solved.
+///
+pub fn capacity_warning(system: &HexSystem) -> Option<String> {
+    match system.over_capacity().len() {
+        0 => None,
+        1 => Some(
+            "An island needs more bridges than it can take, so the puzzle cannot be solved."
+                .to_string(),
+        ),
+        n => Some(format!(
+            "{} islands need more bridges than they can take, so the puzzle cannot be solved.",
+            n
+        )),
+    }
+}
+
+#[derive(Params, Debug, PartialEq)]
+pub struct ImportArgs {
+    pub difficulty: Option<Difficulty>,
+    pub code: Option<String>,
+}
+
+///
+/// Play a puzzle shared by its code, either in the path or in the fragment of a link made by `custom_url`.
+///
+/// The solver rates the puzzle in the background first, so that the player knows what to expect before starting.
+///
+#[component]
+pub fn ImportGame() -> impl IntoView {
+    let params = use_params::<ImportArgs>();
+    let params = params.read_untracked();
+    let params = params.as_ref().ok();
+    let shared_difficulty = params.and_then(|p| p.difficulty.clone());
+    let code = params
+        .and_then(|p| p.code.clone())
+        .unwrap_or_else(|| fragment_code(&window().location().hash().unwrap_or_default()));
+    let system = match HexSystem::from_code(&code) {
+        Ok(system) => system,
+        Err(e) => {
+            return view! {
+                <div><span class="menu">hexhashi</span><a class="menu" href="/">Back</a></div>
+                <p>{e.to_string()}</p>
+            }
+            .into_any();
+        }
+    };
+    let warning = capacity_warning(&system);
+    let shared_label = shared_difficulty
+        .as_ref()
+        .map_or("Not given".to_string(), |d| d.to_string());
+    let rating = RwSignal::new(None::<Rating>);
+    rate(&system, move |r| rating.set(Some(r)));
+    // The rating of the solver is more reliable than the label of whoever shared the puzzle.
+    let difficulty = Memo::new(move |_| {
+        rating
+            .get()
+            .and_then(|r| r.difficulty)
+            .or_else(|| shared_difficulty.clone())
+            .unwrap_or(Difficulty::Medium)
+    });
+    let par_time = {
+        let system = system.clone();
+        move || {
+            RatingModel::default()
+                .predict_ms(&system)
+                .unwrap_or_else(|| get_par_time(&difficulty.get()))
+        }
+    };
+    let started = RwSignal::new(false);
+
+    (move || {
+        if started.get() {
+            let difficulty = difficulty.get_untracked();
+            let mode = GameMode::Normal;
+            count_started(&difficulty, &mode);
+            update_library(|l| {
+                l.add(LibraryPuzzle::new(
+                    &system,
+                    difficulty.clone(),
+                    Source::Imported,
+                ))
+            });
+            let session = GameSession::new(system.clone());
+            view! { <Board difficulty mode session elapsed_ms=0.0 hot_seat=None/> }.into_any()
+        } else {
+            let par_time = par_time.clone();
+            view! {
+                <div><span class="menu">hexhashi</span><a class="menu" href="/">Back</a></div>
+                <h1>"Shared puzzle"</h1>
+                <dl class="import-summary">
+                    <dt>"Shared as"</dt>
+                    <dd>{shared_label.clone()}</dd>
+                    <dt>"Rated by the solver"</dt>
+                    <dd>
+                        {move || match rating.get() {
+                            None => "Rating…".to_string(),
+                            Some(r) => r.difficulty.map_or("Unknown".to_string(), |d| d.to_string()),
+                        }}
+                    </dd>
+                    <dt>"Estimated par"</dt>
+                    <dd>{move || format_time(par_time())}</dd>
+                    <dt>"Solution"</dt>
+                    <dd class:error=move || {
+                        rating.get().is_some_and(|r| r.uniqueness != Uniqueness::Unique)
+                    }>
+                        {move || rating.get().map_or("Checking…".to_string(), |r| r.uniqueness.to_string())}
+                    </dd>
+                </dl>
+                {warning.clone().map(|warning| view! { <p class="warning">{warning}</p> })}
+                <button disabled=move || rating.get().is_none() on:click=move |_| started.set(true)>
+                    "Play"
+                </button>
+            }
+            .into_any()
+        }
+    })
+    .into_any()
+}
+
+///
+/// Import the puzzle whose code or share link is on the clipboard.
+///
+#[component]
+pub fn PasteToImport() -> impl IntoView {
+    let (error, set_error) = signal(None::<String>);
+    let paste = move |_| {
+        spawn_local(async move {
+            match read_text().await {
+                Ok(text) if !text.trim().is_empty() => {
+                    let _ = window().location().set_href(&import_path(&text));
+                }
+                Ok(_) => set_error.set(Some("The clipboard is empty.".to_string())),
+                Err(e) => log!("Cannot read clipboard: {:?}", e),
+            }
+        });
+    };
+
+    view! {
+        <button on:click=paste>"Paste to import"</button>
+        <span class="error">{move || error.get()}</span>
+    }
+}
+
+#[derive(Serialize)]
+struct DecodeArgs {
+    data: Vec<u8>,
+}
+
+///
+/// Import a puzzle from an image of its QR code, decoded by the Tauri backend.
+///
+#[component]
+pub fn ImportFromImage() -> impl IntoView {
+    let (error, set_error) = signal(None::<String>);
+    let import = move |ev: leptos::ev::Event| {
+        let input: web_sys::HtmlInputElement = event_target(&ev);
+        let Some(file) = input.files().and_then(|files| files.get(0)) else {
+            return;
+        };
+        spawn_local(async move {
+            let Ok(buffer) = JsFuture::from(file.array_buffer()).await else {
+                return;
+            };
+            let data = js_sys::Uint8Array::new(&buffer).to_vec();
+            let args = serde_wasm_bindgen::to_value(&DecodeArgs { data }).unwrap();
+            match try_invoke("decode_qr", args).await {
+                Ok(text) => {
+                    let path = import_path(&text.as_string().unwrap_or_default());
+                    let _ = window().location().set_href(&path);
+                }
+                Err(e) => set_error.set(e.as_string()),
+            }
+        });
+    };
+
+    view! {
+        <Show when=is_tauri>
+            <label>
+                "Import from QR code image "
+                <input type="file" accept="image/*" on:change=import/>
+            </label>
+            <span class="error">{move || error.get()}</span>
+        </Show>
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use hexhashi_logic::hex::{HexSystem, Island};
+
+    use super::{capacity_warning, import_path};
+
+    #[test]
+    fn import_paths() {
+        assert_eq!(
+            import_path("https://example.org/import/hard/4.5.0.0"),
+            "/import/hard/4.5.0.0"
+        );
+        assert_eq!(import_path(" 4.5.0.0\n"), "/import/medium/4.5.0.0");
+        assert_eq!(
+            import_path("https://example.org/play/custom#4.5.0.0"),
+            "/play/custom#4.5.0.0"
+        );
+    }
+
+    #[test]
+    fn capacity() {
+        let mut islands = vec![Island::Empty; 22];
+        islands[0] = Island::Bridged(2);
+        islands[4] = Island::Bridged(2);
+        let mut system = HexSystem::from_islands(4, 5, islands);
+        assert_eq!(capacity_warning(&system), None);
+        system.islands[0] = Island::Bridged(3);
+        system.islands[4] = Island::Bridged(3);
+        assert_eq!(
+            capacity_warning(&system).as_deref(),
+            Some("2 islands need more bridges than they can take, so the puzzle cannot be solved.")
+        );
+    }
+}
@@ -0,0 +1,367 @@
+use leptos::{prelude::*, task::spawn_local};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+
+use crate::{
+    game::{THUMBNAIL_SIZE, format_time},
+    share::{is_tauri, try_invoke},
+    storage::{SavedGame, load_game, save, save_game, try_load},
+};
+
+const SLOTS_KEY: &str = "hexhashi.slots";
+
+///
+/// A game saved by the player under a name to be continued later.
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SaveSlot {
+    pub name: String,
+    #[serde(default)]
+    pub note: String,
+    /// Time of saving in milliseconds since the Unix epoch
+    pub saved_at: f64,
+    pub game: SavedGame,
+}
+
+///
+/// Games saved into named slots besides the game in progress.
+///
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SaveSlots {
+    slots: Vec<SaveSlot>,
+}
+
+impl SaveSlots {
+    ///
+    /// Save `slot`, replacing the slot with the same name.
+    ///
+    pub fn put(&mut self, slot: SaveSlot) {
+        self.remove(&slot.name);
+        self.slots.push(slot);
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.slots.retain(|s| s.name != name);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SaveSlot> {
+        self.slots.iter().find(|s| s.name == name)
+    }
+
+    ///
+    /// Get the slots with the latest saved first.
+    ///
+    pub fn list(&self) -> Vec<&SaveSlot> {
+        let mut slots: Vec<_> = self.slots.iter().collect();
+        slots.sort_by(|a, b| b.saved_at.total_cmp(&a.saved_at));
+        slots
+    }
+}
+
+#[derive(Serialize)]
+struct WriteArgs<'a> {
+    json: &'a str,
+}
+
+///
+/// Load the save slots.
+///
+/// They are kept in a file of the app in Tauri, so that they do not depend on the storage of the webview,
+/// and in the local storage otherwise.
+/// Slots that cannot be read are an error rather than none, so that they are not overwritten.
+///
+pub async fn load_slots() -> Result<SaveSlots, JsValue> {
+    let unreadable = |e: serde_json::Error| JsValue::from_str(&e.to_string());
+    if is_tauri() {
+        let json = try_invoke("read_slots", JsValue::UNDEFINED)
+            .await?
+            .as_string()
+            .unwrap_or_default();
+        // No file is written before the first slot is saved.
+        if json.is_empty() {
+            return Ok(SaveSlots::default());
+        }
+        serde_json::from_str(&json).map_err(unreadable)
+    } else {
+        try_load(SLOTS_KEY)
+            .map(Option::unwrap_or_default)
+            .map_err(unreadable)
+    }
+}
+
+///
+/// Store the save slots where `load_slots` finds them.
+///
+pub async fn store_slots(slots: &SaveSlots) -> Result<(), JsValue> {
+    if is_tauri() {
+        let json = serde_json::to_string(slots).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let args = serde_wasm_bindgen::to_value(&WriteArgs { json: &json })?;
+        try_invoke("write_slots", args).await?;
+    } else {
+        save(SLOTS_KEY, slots);
+    }
+    Ok(())
+}
+
+///
+/// Update the stored save slots with `f`.
+///
+/// Nothing is stored if the current slots cannot be read.
+///
+pub async fn update_slots(f: impl FnOnce(&mut SaveSlots)) -> Result<SaveSlots, JsValue> {
+    let mut slots = load_slots().await?;
+    f(&mut slots);
+    store_slots(&slots).await?;
+    Ok(slots)
+}
+
+///
+/// Get the local date and time of `ms` since the Unix epoch.
+///
+fn format_timestamp(ms: f64) -> String {
+    js_sys::Date::new(&JsValue::from_f64(ms))
+        .to_locale_string("default", &JsValue::UNDEFINED)
+        .into()
+}
+
+#[component]
+pub fn SlotsPage() -> impl IntoView {
+    let slots = RwSignal::new(None::<SaveSlots>);
+    let (error, set_error) = signal(None::<String>);
+    spawn_local(async move {
+        match load_slots().await {
+            Ok(loaded) => slots.set(Some(loaded)),
+            Err(e) => set_error.set(Some(format!("Could not read the slots: {:?}", e))),
+        }
+    });
+
+    let update = move |f: Box<dyn FnOnce(&mut SaveSlots)>| {
+        spawn_local(async move {
+            match update_slots(f).await {
+                Ok(updated) => slots.set(Some(updated)),
+                Err(e) => set_error.set(Some(format!("Could not save the slots: {:?}", e))),
+            }
+        })
+    };
+    let entries = move || {
+        let slots = slots.read();
+        let Some(slots) = slots.as_ref() else {
+            return (error.read().is_none())
+                .then(|| view! { <p>"Loading\u{2026}"</p> })
+                .into_any();
+        };
+        if slots.list().is_empty() {
+            return view! { <p>"No saved games yet. Save a game to a slot while playing it."</p> }
+                .into_any();
+        }
+        slots
+            .list()
+            .into_iter()
+            .map(|slot| {
+                let game = slot.game.clone();
+                let name = slot.name.clone();
+                let resume = move |_| {
+                    // The game in progress is replaced by the one of the slot.
+                    if load_game().is_some()
+                        && !window()
+                            .confirm_with_message("Replace the game in progress with this one?")
+                            .unwrap_or(false)
+                    {
+                        return;
+                    }
+                    save_game(&game);
+                    let _ = window().location().set_href("/continue");
+                };
+                let remove = move |_| {
+                    let name = name.clone();
+                    update(Box::new(move |s| s.remove(&name)))
+                };
+                view! {
+                    <div class="entry">
+                        <button class="preview" on:click=resume>
+                            <div
+                                class="thumbnail small"
+                                inner_html=slot.game.session.system().to_thumbnail(THUMBNAIL_SIZE)
+                            />
+                            <span class="title">{slot.name.clone()}</span>
+                        </button>
+                        <p>
+                            {format!(
+                                "{} ({}) \u{2013} {} played \u{2013} Saved {}",
+                                slot.game.difficulty,
+                                slot.game.mode,
+                                format_time(slot.game.elapsed_ms),
+                                format_timestamp(slot.saved_at),
+                            )}
+                        </p>
+                        <p class="note">{slot.note.clone()}</p>
+                        <button on:click=remove>"Delete"</button>
+                    </div>
+                }
+            })
+            .collect_view()
+            .into_any()
+    };
+
+    view! {
+        <div><span class="menu">hexhashi</span><a class="menu" href="/">Back</a></div>
+        <h1>"Saved games"</h1>
+        <p class="error">{move || error.get()}</p>
+        <div class="library">{entries}</div>
+    }
+}
+
+///
+/// Form saving `game` into a named slot with a note.
+///
+#[component]
+pub fn SaveSlotForm(game: Callback<(), SavedGame>, on_close: Callback<()>) -> impl IntoView {
+    let (name, set_name) = signal(String::new());
+    let (note, set_note) = signal(String::new());
+    let (message, set_message) = signal(None::<String>);
+    let existing = RwSignal::new(SaveSlots::default());
+    spawn_local(async move {
+        match load_slots().await {
+            Ok(slots) => existing.set(slots),
+            Err(e) => set_message.set(Some(format!("Could not read the slots: {:?}", e))),
+        }
+    });
+
+    let store = move |ev: leptos::ev::SubmitEvent| {
+        ev.prevent_default();
+        let name = name.get_untracked().trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        let slot = SaveSlot {
+            name,
+            note: note.get_untracked().trim().to_string(),
+            saved_at: js_sys::Date::now(),
+            game: game.run(()),
+        };
+        spawn_local(async move {
+            match update_slots(move |s| s.put(slot)).await {
+                Ok(updated) => {
+                    existing.set(updated);
+                    set_message.set(Some("Saved.".to_string()));
+                }
+                Err(e) => set_message.set(Some(format!("Could not save the game: {:?}", e))),
+            }
+        })
+    };
+    let replaces = move || existing.read().get(name.read().trim()).is_some();
+
+    view! {
+        <form class="save-slot" on:submit=store>
+            <p>
+                <label>
+                    "Name "
+                    <input
+                        type="text"
+                        required
+                        autofocus
+                        list="slot-names"
+                        prop:value=name
+                        on:input=move |ev| set_name.set(event_target_value(&ev))
+                    />
+                </label>
+                <datalist id="slot-names">
+                    {move || {
+                        existing
+                            .read()
+                            .list()
+                            .into_iter()
+                            .map(|s| view! { <option value=s.name.clone()/> })
+                            .collect_view()
+                    }}
+                </datalist>
+            </p>
+            <p>
+                <label>
+                    "Note "
+                    <textarea
+                        prop:value=note
+                        on:input=move |ev| set_note.set(event_target_value(&ev))
+                    ></textarea>
+                </label>
+            </p>
+            <p class="message">
+                {move || message.get().or(replaces().then(|| "Replaces the slot of this name.".to_string()))}
+            </p>
+            <button type="submit">"Save"</button>
+            " "
+            <button type="button" on:click=move |_| on_close.run(())>
+                "Close"
+            </button>
+        </form>
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use hexhashi_logic::{
+        difficulty::Difficulty,
+        hex::{GameParameters, HexSystem},
+        session::GameSession,
+    };
+
+    use std::rc::Rc;
+
+    use super::{SLOTS_KEY, SaveSlot, SaveSlots};
+    use crate::{
+        game::GameMode,
+        storage::{MemoryStore, ProfileStore, SavedGame, set_store, try_load},
+    };
+
+    fn slot(name: &str, saved_at: f64) -> SaveSlot {
+        let system = HexSystem::generate_new(GameParameters::preset(&Difficulty::Easy, 1));
+        SaveSlot {
+            name: name.to_string(),
+            note: String::new(),
+            saved_at,
+            game: SavedGame {
+                difficulty: Difficulty::Easy,
+                mode: GameMode::default(),
+                session: GameSession::new(system),
+                elapsed_ms: saved_at,
+                hot_seat: None,
+                pack: None,
+                seed: Some(1),
+            },
+        }
+    }
+
+    #[test]
+    fn slots() {
+        let mut slots = SaveSlots::default();
+        slots.put(slot("a", 1.0));
+        slots.put(slot("b", 3.0));
+        slots.put(slot("c", 2.0));
+        let names = |slots: &SaveSlots| {
+            slots
+                .list()
+                .iter()
+                .map(|s| s.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&slots), ["b", "c", "a"]);
+        // Saving under the same name replaces the slot.
+        slots.put(slot("a", 4.0));
+        assert_eq!(names(&slots), ["a", "b", "c"]);
+        assert_eq!(slots.get("a").unwrap().game.elapsed_ms, 4.0);
+        slots.remove("b");
+        assert_eq!(names(&slots), ["a", "c"]);
+        assert!(slots.get("b").is_none());
+    }
+
+    #[test]
+    fn unreadable() {
+        let memory = Rc::new(MemoryStore::default());
+        set_store(memory.clone());
+        assert!(try_load::<SaveSlots>(SLOTS_KEY).unwrap().is_none());
+        // Slots of another format must not be taken for none, which would overwrite them.
+        memory.set(SLOTS_KEY, r#"{"slots": 3}"#);
+        assert!(try_load::<SaveSlots>(SLOTS_KEY).is_err());
+    }
+}
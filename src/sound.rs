@@ -0,0 +1,194 @@
+use std::{cell::RefCell, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+use web_sys::{AudioContext, OscillatorType};
+
+/// Volume of a tone at its end relative to its start, fading out like a struck note
+const FADE: f32 = 0.001;
+
+thread_local! {
+    /// Created with the first sound, since browsers only allow audio after the player did something
+    static CONTEXT: RefCell<Option<AudioContext>> = const { RefCell::new(None) };
+}
+
+///
+/// Set of sounds played for moves and solved puzzles.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum SoundPack {
+    /// Silence
+    #[default]
+    Off,
+    /// Short, soft clicks
+    Clicks,
+    /// Hollow knocks like on wooden planks
+    Wood,
+    /// Electronic tones
+    Synth,
+}
+
+impl SoundPack {
+    pub const ALL: [SoundPack; 4] = [
+        SoundPack::Off,
+        SoundPack::Clicks,
+        SoundPack::Wood,
+        SoundPack::Synth,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SoundPack::Off => "off",
+            SoundPack::Clicks => "clicks",
+            SoundPack::Wood => "wood",
+            SoundPack::Synth => "synth",
+        }
+    }
+
+    ///
+    /// Get the tones of `sound` in this pack.
+    ///
+    pub fn tones(&self, sound: Sound) -> Vec<Tone> {
+        let (wave, base, length) = match self {
+            SoundPack::Off => return vec![],
+            SoundPack::Clicks => (Wave::Sine, 1_800.0, 0.03),
+            SoundPack::Wood => (Wave::Triangle, 220.0, 0.12),
+            SoundPack::Synth => (Wave::Square, 440.0, 0.15),
+        };
+        let tone = |factor: f32, start_s: f64, duration_s: f64| Tone {
+            wave,
+            frequency: base * factor,
+            start_s,
+            duration_s,
+        };
+        match sound {
+            Sound::Add => vec![tone(1.0, 0.0, length)],
+            Sound::Remove => vec![tone(0.75, 0.0, length)],
+            Sound::Blocked => vec![tone(0.5, 0.0, length), tone(0.5, 1.5 * length, length)],
+            // A rising major chord, one note after the other
+            Sound::Solved => [1.0, 1.25, 1.5, 2.0]
+                .into_iter()
+                .enumerate()
+                .map(|(i, factor)| tone(factor, i as f64 * 0.15, 0.15 + 3.0 * length))
+                .collect(),
+        }
+    }
+}
+
+impl Display for SoundPack {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SoundPack::Off => f.write_str("Off"),
+            SoundPack::Clicks => f.write_str("Minimal clicks"),
+            SoundPack::Wood => f.write_str("Wooden bridges"),
+            SoundPack::Synth => f.write_str("Synth"),
+        }
+    }
+}
+
+///
+/// Events on the board that make a sound
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sound {
+    /// A bridge was added.
+    Add,
+    /// A bridge was removed.
+    Remove,
+    /// A move was blocked by a crossing bridge.
+    Blocked,
+    /// The puzzle was solved, played as a fanfare.
+    Solved,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Wave {
+    Sine,
+    Triangle,
+    Square,
+}
+
+///
+/// Single note of a sound.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tone {
+    pub wave: Wave,
+    /// Pitch in Hz
+    pub frequency: f32,
+    /// Time after the start of the sound in seconds
+    pub start_s: f64,
+    pub duration_s: f64,
+}
+
+///
+/// Play `sound` of `pack` at `volume` from 0 to 1.
+///
+/// Does nothing if audio is not available.
+///
+pub fn play(pack: SoundPack, sound: Sound, volume: f64) {
+    if volume <= 0.0 {
+        return;
+    }
+    let tones = pack.tones(sound);
+    if tones.is_empty() {
+        return;
+    }
+    CONTEXT.with_borrow_mut(|context| {
+        if context.is_none() {
+            *context = AudioContext::new().ok();
+        }
+        if let Some(context) = context {
+            for tone in tones {
+                let _ = play_tone(context, tone, volume as f32);
+            }
+        }
+    });
+}
+
+fn play_tone(context: &AudioContext, tone: Tone, volume: f32) -> Result<(), wasm_bindgen::JsValue> {
+    let oscillator = context.create_oscillator()?;
+    oscillator.set_type(match tone.wave {
+        Wave::Sine => OscillatorType::Sine,
+        Wave::Triangle => OscillatorType::Triangle,
+        Wave::Square => OscillatorType::Square,
+    });
+    oscillator.frequency().set_value(tone.frequency);
+    let gain = context.create_gain()?;
+    let (start, end) = (
+        context.current_time() + tone.start_s,
+        context.current_time() + tone.start_s + tone.duration_s,
+    );
+    gain.gain().set_value_at_time(volume, start)?;
+    gain.gain()
+        .exponential_ramp_to_value_at_time(volume * FADE, end)?;
+    oscillator.connect_with_audio_node(&gain)?;
+    gain.connect_with_audio_node(&context.destination())?;
+    oscillator.start_with_when(start)?;
+    oscillator.stop_with_when(end)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Sound, SoundPack};
+
+    #[test]
+    fn packs() {
+        assert!(SoundPack::Off.tones(Sound::Solved).is_empty());
+        for pack in &SoundPack::ALL[1..] {
+            let add = pack.tones(Sound::Add);
+            let solved = pack.tones(Sound::Solved);
+            assert_eq!(add.len(), 1);
+            // The fanfare rises and lasts longer than a move.
+            assert!(solved.windows(2).all(|t| t[0].frequency < t[1].frequency));
+            let end = |tones: &[super::Tone]| {
+                tones
+                    .iter()
+                    .map(|t| t.start_s + t.duration_s)
+                    .fold(0.0, f64::max)
+            };
+            assert!(end(&solved) > end(&add));
+            assert!(pack.tones(Sound::Remove)[0].frequency < add[0].frequency);
+        }
+    }
+}
@@ -0,0 +1,402 @@
+use std::collections::BTreeMap;
+
+use hexhashi_logic::difficulty::Difficulty;
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{GameMode, format_time},
+    settings::load_settings,
+    storage::{load, save},
+};
+
+pub const STATS_KEY: &str = "hexhashi.stats";
+const MS_PER_DAY: f64 = 86_400_000.0;
+
+///
+/// Statistics of all games of one difficulty played in one mode.
+///
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Record {
+    pub played: u32,
+    pub solved: u32,
+    pub failed: u32,
+    pub best_ms: Option<f64>,
+    /// Sum of the times of all solved games
+    pub total_ms: f64,
+    /// Sum of the mistakes of all finished games
+    pub mistakes: u32,
+    pub best_score: Option<u32>,
+    /// Sum of the scores of all solved games
+    pub total_score: u64,
+    /// Sum of the hints of all finished games at each level, from island over bridge to move
+    pub hints: [u32; 3],
+}
+
+impl Record {
+    pub fn average_ms(&self) -> Option<f64> {
+        if self.solved > 0 {
+            Some(self.total_ms / self.solved as f64)
+        } else {
+            None
+        }
+    }
+
+    pub fn average_score(&self) -> Option<u64> {
+        if self.solved > 0 {
+            Some(self.total_score / self.solved as u64)
+        } else {
+            None
+        }
+    }
+}
+
+///
+/// Best results of one puzzle over all games it was solved in.
+///
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PuzzleRecord {
+    pub solved: u32,
+    pub best_ms: Option<f64>,
+    pub best_score: Option<u32>,
+}
+
+///
+/// Games solved on one day.
+///
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DayRecord {
+    pub solved: u32,
+    /// Sum of the times of the solved games
+    pub total_ms: f64,
+}
+
+impl DayRecord {
+    pub fn average_ms(&self) -> Option<f64> {
+        (self.solved > 0).then(|| self.total_ms / self.solved as f64)
+    }
+}
+
+///
+/// Statistics of all games, separated by difficulty and mode.
+///
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    records: BTreeMap<String, Record>,
+    /// Results of each solved puzzle by its identifier, see `HexSystem::puzzle_id`
+    puzzles: BTreeMap<String, PuzzleRecord>,
+    /// Solved games by the local day since the Unix epoch
+    days: BTreeMap<u64, DayRecord>,
+    /// Games solved in a row since the last one that was not
+    streak: u32,
+}
+
+impl Stats {
+    pub fn get(&self, difficulty: &Difficulty, mode: &GameMode) -> Option<&Record> {
+        self.records.get(&Stats::key(difficulty, mode))
+    }
+
+    ///
+    /// Count a newly started game.
+    ///
+    pub fn record_started(&mut self, difficulty: &Difficulty, mode: &GameMode) {
+        self.record(difficulty, mode).played += 1;
+    }
+
+    ///
+    /// Count a solved game that took `elapsed_ms` with `mistakes` and got `score`.
+    ///
+    /// Returns `true` if this is a new best time.
+    ///
+    pub fn record_solved(
+        &mut self,
+        difficulty: &Difficulty,
+        mode: &GameMode,
+        elapsed_ms: f64,
+        mistakes: u32,
+        score: u32,
+    ) -> bool {
+        let record = self.record(difficulty, mode);
+        record.solved += 1;
+        record.total_ms += elapsed_ms;
+        record.mistakes += mistakes;
+        record.total_score += score as u64;
+        record.best_score = record.best_score.max(Some(score));
+        let best = record.best_ms.is_none_or(|b| elapsed_ms < b);
+        if best {
+            record.best_ms = Some(elapsed_ms);
+        }
+        best
+    }
+
+    ///
+    /// Count a game with `mistakes` that was not solved, e.g. because the time ran out.
+    ///
+    pub fn record_failed(&mut self, difficulty: &Difficulty, mode: &GameMode, mistakes: u32) {
+        let record = self.record(difficulty, mode);
+        record.failed += 1;
+        record.mistakes += mistakes;
+    }
+
+    ///
+    /// Count the `hints` at each level of a finished game.
+    ///
+    pub fn record_hints(&mut self, difficulty: &Difficulty, mode: &GameMode, hints: [u32; 3]) {
+        let record = self.record(difficulty, mode);
+        for (sum, count) in record.hints.iter_mut().zip(hints) {
+            *sum += count;
+        }
+    }
+
+    ///
+    /// Count solving the puzzle with identifier `puzzle_id` in `elapsed_ms` with `score`.
+    ///
+    /// Returns `true` if this is a new best time for the puzzle.
+    ///
+    pub fn record_puzzle(&mut self, puzzle_id: &str, elapsed_ms: f64, score: u32) -> bool {
+        let record = self.puzzles.entry(puzzle_id.to_string()).or_default();
+        record.solved += 1;
+        record.best_score = record.best_score.max(Some(score));
+        let best = record.best_ms.is_none_or(|b| elapsed_ms < b);
+        if best {
+            record.best_ms = Some(elapsed_ms);
+        }
+        best
+    }
+
+    ///
+    /// Count a game finished on `day`, solved in `elapsed_ms` or not solved if `None`.
+    ///
+    pub fn record_day(&mut self, day: u64, elapsed_ms: Option<f64>) {
+        match elapsed_ms {
+            Some(elapsed_ms) => {
+                let record = self.days.entry(day).or_default();
+                record.solved += 1;
+                record.total_ms += elapsed_ms;
+                self.streak += 1;
+            }
+            None => self.streak = 0,
+        }
+    }
+
+    ///
+    /// Get the games solved on `day`.
+    ///
+    pub fn get_day(&self, day: u64) -> DayRecord {
+        self.days.get(&day).cloned().unwrap_or_default()
+    }
+
+    pub fn streak(&self) -> u32 {
+        self.streak
+    }
+
+    ///
+    /// Get the results of the puzzle with identifier `puzzle_id`, if it was ever solved.
+    ///
+    pub fn get_puzzle(&self, puzzle_id: &str) -> Option<&PuzzleRecord> {
+        self.puzzles.get(puzzle_id)
+    }
+
+    fn record(&mut self, difficulty: &Difficulty, mode: &GameMode) -> &mut Record {
+        self.records
+            .entry(Stats::key(difficulty, mode))
+            .or_default()
+    }
+
+    fn key(difficulty: &Difficulty, mode: &GameMode) -> String {
+        format!("{}/{}", difficulty, mode).to_lowercase()
+    }
+}
+
+///
+/// Load the stored statistics.
+///
+pub fn load_stats() -> Stats {
+    load(STATS_KEY).unwrap_or_default()
+}
+
+///
+/// Update the stored statistics with `f`.
+///
+pub fn update_stats<R>(f: impl FnOnce(&mut Stats) -> R) -> R {
+    let mut stats = load_stats();
+    let result = f(&mut stats);
+    save(STATS_KEY, &stats);
+    result
+}
+
+///
+/// Get the current day since the Unix epoch in the local time zone.
+///
+pub fn today() -> u64 {
+    let now = js_sys::Date::new_0();
+    let local_ms = now.get_time() - now.get_timezone_offset() * 60_000.0;
+    (local_ms / MS_PER_DAY).floor() as u64
+}
+
+///
+/// Count a game of `difficulty` in `mode` as started, unless it is a practice game.
+///
+pub fn count_started(difficulty: &Difficulty, mode: &GameMode) {
+    if !load_settings().practice {
+        update_stats(|s| s.record_started(difficulty, mode));
+    }
+}
+
+///
+/// Corner of the board showing the games solved today, the current streak and the average time today.
+///
+/// The figures are read again whenever `finished` changes.
+///
+#[component]
+pub fn SessionHud(#[prop(into)] finished: Signal<bool>) -> impl IntoView {
+    let figures = move || {
+        finished.track();
+        let stats = load_stats();
+        let day = stats.get_day(today());
+        view! {
+            <dt>"Solved today"</dt>
+            <dd>{day.solved}</dd>
+            <dt>"Streak"</dt>
+            <dd>{stats.streak()}</dd>
+            <dt>"Average today"</dt>
+            <dd>{day.average_ms().map_or("–".to_string(), format_time)}</dd>
+        }
+    };
+    view! { <dl class="hud">{figures}</dl> }
+}
+
+#[component]
+pub fn StatsPage() -> impl IntoView {
+    let stats = load_stats();
+    let difficulties = Difficulty::ALL;
+    let modes = [
+        GameMode::Normal,
+        GameMode::Zen,
+        GameMode::Timed,
+        GameMode::Weekly,
+        GameMode::Daily,
+        GameMode::HotSeat,
+        GameMode::Coop,
+        GameMode::Race,
+        GameMode::Endless,
+    ];
+    let rows = modes
+        .iter()
+        .flat_map(|mode| difficulties.iter().map(move |d| (d.clone(), mode.clone())))
+        .filter_map(|(difficulty, mode)| {
+            stats.get(&difficulty, &mode).cloned().map(|record| {
+                view! {
+                    <tr>
+                        <td>{difficulty.to_string()}</td>
+                        <td>{mode.to_string()}</td>
+                        <td>{record.played}</td>
+                        <td>{record.solved}</td>
+                        <td>{record.failed}</td>
+                        <td>{record.best_ms.map(format_time)}</td>
+                        <td>{record.average_ms().map(format_time)}</td>
+                        <td>{record.mistakes}</td>
+                        <td>{record.hints.map(|h| h.to_string()).join(" / ")}</td>
+                        <td>{record.best_score}</td>
+                        <td>{record.average_score()}</td>
+                    </tr>
+                }
+            })
+        })
+        .collect_view();
+
+    view! {
+        <div><span class="menu">hexhashi</span><a class="menu" href="/">Back</a></div>
+        <h1>"Statistics"</h1>
+        <table class="stats">
+            <tr>
+                <th>"Difficulty"</th>
+                <th>"Mode"</th>
+                <th>"Played"</th>
+                <th>"Solved"</th>
+                <th>"Failed"</th>
+                <th>"Best"</th>
+                <th>"Average"</th>
+                <th>"Mistakes"</th>
+                <th title="Island / bridge / move">"Hints"</th>
+                <th>"Best score"</th>
+                <th>"Average score"</th>
+            </tr>
+            {rows}
+        </table>
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use hexhashi_logic::difficulty::Difficulty;
+
+    use crate::game::GameMode;
+
+    use super::{DayRecord, PuzzleRecord, Stats};
+
+    #[test]
+    fn records_per_mode() {
+        let mut stats = Stats::default();
+        stats.record_started(&Difficulty::Easy, &GameMode::Normal);
+        stats.record_started(&Difficulty::Easy, &GameMode::Zen);
+        assert!(stats.record_solved(&Difficulty::Easy, &GameMode::Normal, 2000.0, 1, 800));
+        assert!(!stats.record_solved(&Difficulty::Easy, &GameMode::Normal, 3000.0, 0, 1000));
+        assert!(stats.record_solved(&Difficulty::Easy, &GameMode::Normal, 1000.0, 2, 600));
+        let record = stats.get(&Difficulty::Easy, &GameMode::Normal).unwrap();
+        assert_eq!(record.played, 1);
+        assert_eq!(record.solved, 3);
+        assert_eq!(record.best_ms, Some(1000.0));
+        assert_eq!(record.average_ms(), Some(2000.0));
+        assert_eq!(record.mistakes, 3);
+        assert_eq!(record.hints, [0; 3]);
+        assert_eq!(record.best_score, Some(1000));
+        assert_eq!(record.average_score(), Some(800));
+        stats.record_hints(&Difficulty::Easy, &GameMode::Zen, [1, 2, 0]);
+        stats.record_hints(&Difficulty::Easy, &GameMode::Zen, [1, 0, 3]);
+        let record = stats.get(&Difficulty::Easy, &GameMode::Zen).unwrap();
+        assert_eq!(record.hints, [2, 2, 3]);
+        assert_eq!(record.played, 1);
+        assert_eq!(record.solved, 0);
+        assert!(stats.get(&Difficulty::Hard, &GameMode::Normal).is_none());
+    }
+
+    #[test]
+    fn puzzle_records() {
+        let mut stats = Stats::default();
+        assert_eq!(stats.get_puzzle("a"), None);
+        assert!(stats.record_puzzle("a", 2000.0, 500));
+        assert!(!stats.record_puzzle("a", 3000.0, 900));
+        assert!(stats.record_puzzle("b", 3000.0, 100));
+        assert_eq!(
+            stats.get_puzzle("a"),
+            Some(&PuzzleRecord {
+                solved: 2,
+                best_ms: Some(2000.0),
+                best_score: Some(900),
+            })
+        );
+        assert_eq!(stats.get_puzzle("b").and_then(|r| r.best_ms), Some(3000.0));
+    }
+
+    #[test]
+    fn days() {
+        let mut stats = Stats::default();
+        assert_eq!(stats.get_day(100), DayRecord::default());
+        stats.record_day(100, Some(1000.0));
+        stats.record_day(100, Some(3000.0));
+        assert_eq!(stats.streak(), 2);
+        stats.record_day(100, None);
+        assert_eq!(stats.streak(), 0);
+        stats.record_day(101, Some(5000.0));
+        assert_eq!(stats.streak(), 1);
+        let day = stats.get_day(100);
+        assert_eq!(day.solved, 2);
+        assert_eq!(day.average_ms(), Some(2000.0));
+        assert_eq!(stats.get_day(101).average_ms(), Some(5000.0));
+    }
+}
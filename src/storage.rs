@@ -0,0 +1,271 @@
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use hexhashi_logic::{difficulty::Difficulty, hotseat::HotSeat, session::GameSession};
+use leptos::{logging::log, prelude::window};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{
+    game::GameMode,
+    migration::{migrate, stamp},
+    packs::PackPuzzle,
+};
+
+/// Start of the keys of all values stored by hexhashi
+pub const KEY_PREFIX: &str = "hexhashi.";
+const CURRENT_GAME_KEY: &str = "hexhashi.current_game";
+/// Path of the page playing the stored game in this browser tab
+const ACTIVE_PAGE_KEY: &str = "hexhashi.active_page";
+
+///
+/// A game in progress as it is stored to be continued later.
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SavedGame {
+    pub difficulty: Difficulty,
+    #[serde(default)]
+    pub mode: GameMode,
+    pub session: GameSession,
+    pub elapsed_ms: f64,
+    /// Turns of the players in hot-seat mode
+    #[serde(default)]
+    pub hot_seat: Option<HotSeat>,
+    /// Puzzle of a downloaded pack
+    #[serde(default)]
+    pub pack: Option<PackPuzzle>,
+    /// Seed the puzzle was generated from
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+///
+/// Store the game in progress.
+///
+pub fn save_game(game: &SavedGame) {
+    save(CURRENT_GAME_KEY, game);
+}
+
+///
+/// Load the game in progress, if there is any.
+///
+pub fn load_game() -> Option<SavedGame> {
+    load(CURRENT_GAME_KEY)
+}
+
+///
+/// Forget the game in progress, e.g. because it is solved.
+///
+pub fn clear_game() {
+    remove(CURRENT_GAME_KEY);
+    end_session();
+}
+
+///
+/// Forget that the stored game is played in this browser tab, e.g. because the window is closed.
+///
+/// The stored game can still be continued, but the page is no longer seen as reloaded during the game.
+///
+pub fn end_session() {
+    if let Some(storage) = window().session_storage().ok().flatten() {
+        let _ = storage.remove_item(ACTIVE_PAGE_KEY);
+    }
+}
+
+///
+/// Remember that the stored game is played on the current page of this browser tab.
+///
+pub fn mark_active_page() {
+    if let (Some(storage), Ok(path)) = (
+        window().session_storage().ok().flatten(),
+        window().location().pathname(),
+    ) {
+        let _ = storage.set_item(ACTIVE_PAGE_KEY, &path);
+    }
+}
+
+///
+/// Check if the current page is reloaded while the stored game was played on it,
+/// e.g. after a crash or a hot-reload during development.
+///
+/// The session storage survives reloads, but not closing the tab.
+///
+pub fn is_reloaded_during_game() -> bool {
+    let active = window()
+        .session_storage()
+        .ok()
+        .flatten()
+        .and_then(|storage| storage.get_item(ACTIVE_PAGE_KEY).ok().flatten());
+    active.is_some() && active == window().location().pathname().ok() && load_game().is_some()
+}
+
+///
+/// Place where the values of the profile are kept, as JSON by their keys.
+///
+pub trait ProfileStore {
+    fn get(&self, key: &str) -> Option<String>;
+
+    ///
+    /// Store `json` under `key`.
+    ///
+    /// Returns `false` if it cannot be stored, e.g. because the storage is full.
+    ///
+    fn set(&self, key: &str, json: &str) -> bool;
+
+    fn remove(&self, key: &str);
+
+    ///
+    /// Get the keys of all values stored by hexhashi.
+    ///
+    fn keys(&self) -> Vec<String>;
+}
+
+///
+/// The local storage of the browser.
+///
+pub struct LocalStore;
+
+impl ProfileStore for LocalStore {
+    fn get(&self, key: &str) -> Option<String> {
+        let storage = window().local_storage().ok().flatten()?;
+        storage.get_item(key).ok().flatten()
+    }
+
+    fn set(&self, key: &str, json: &str) -> bool {
+        window()
+            .local_storage()
+            .ok()
+            .flatten()
+            .is_some_and(|storage| storage.set_item(key, json).is_ok())
+    }
+
+    fn remove(&self, key: &str) {
+        if let Some(storage) = window().local_storage().ok().flatten() {
+            let _ = storage.remove_item(key);
+        }
+    }
+
+    fn keys(&self) -> Vec<String> {
+        let Some(storage) = window().local_storage().ok().flatten() else {
+            return vec![];
+        };
+        (0..storage.length().unwrap_or(0))
+            .filter_map(|index| storage.key(index).ok().flatten())
+            .filter(|key| key.starts_with(KEY_PREFIX))
+            .collect()
+    }
+}
+
+thread_local! {
+    static STORE: RefCell<Rc<dyn ProfileStore>> = RefCell::new(Rc::new(LocalStore));
+}
+
+///
+/// Keep all values in `store` from now on instead of the local storage.
+///
+pub fn set_store(store: Rc<dyn ProfileStore>) {
+    STORE.set(store);
+}
+
+///
+/// Get the store all values are kept in.
+///
+pub fn current_store() -> Rc<dyn ProfileStore> {
+    STORE.with_borrow(Rc::clone)
+}
+
+///
+/// Store `value` as JSON under `key`.
+///
+pub fn save<T: Serialize>(key: &str, value: &T) {
+    let json = serde_json::to_value(value).map(|mut value| {
+        stamp(key, &mut value);
+        value.to_string()
+    });
+    match json {
+        Ok(json) => save_raw(key, &json),
+        Err(e) => log!("Cannot serialize {}: {}", key, e),
+    }
+}
+
+///
+/// Load the value stored under `key`, if there is any and it can be read.
+///
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    match try_load(key) {
+        Ok(value) => value,
+        Err(e) => {
+            log!("Cannot deserialize {}: {}", key, e);
+            None
+        }
+    }
+}
+
+///
+/// Load the value stored under `key`, if there is any.
+///
+/// Unlike `load`, a value that cannot be read is an error, so that it is not replaced by mistake.
+///
+pub fn try_load<T: DeserializeOwned>(key: &str) -> Result<Option<T>, serde_json::Error> {
+    let Some(json) = current_store().get(key) else {
+        return Ok(None);
+    };
+    let mut value = serde_json::from_str(&json)?;
+    migrate(key, &mut value);
+    serde_json::from_value(value).map(Some)
+}
+
+///
+/// Load the JSON of all values stored by hexhashi by their keys.
+///
+pub fn load_all() -> BTreeMap<String, String> {
+    let store = current_store();
+    store
+        .keys()
+        .into_iter()
+        .filter_map(|key| Some((key.clone(), store.get(&key)?)))
+        .collect()
+}
+
+///
+/// Store the `json` under `key` as it is.
+///
+pub fn save_raw(key: &str, json: &str) {
+    if !current_store().set(key, json) {
+        log!("Cannot store {}", key);
+    }
+}
+
+///
+/// Remove the value stored under `key`.
+///
+pub fn remove(key: &str) {
+    current_store().remove(key);
+}
+
+///
+/// Store keeping the values in memory only, for tests.
+///
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemoryStore(RefCell<BTreeMap<String, String>>);
+
+#[cfg(test)]
+impl ProfileStore for MemoryStore {
+    fn get(&self, key: &str) -> Option<String> {
+        self.0.borrow().get(key).cloned()
+    }
+
+    fn set(&self, key: &str, json: &str) -> bool {
+        self.0
+            .borrow_mut()
+            .insert(key.to_string(), json.to_string());
+        true
+    }
+
+    fn remove(&self, key: &str) {
+        self.0.borrow_mut().remove(key);
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.0.borrow().keys().cloned().collect()
+    }
+}
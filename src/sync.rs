@@ -0,0 +1,301 @@
+use std::{cell::Cell, collections::BTreeMap, rc::Rc, time::Duration};
+
+use leptos::{logging::log, prelude::*, task::spawn_local};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    net::{fetch_tagged, post_json_if},
+    profile::EXCLUDED_KEYS,
+    storage::{ProfileStore, current_store, set_store},
+};
+
+/// Times the values were last changed on this device
+pub const SYNC_TIMES_KEY: &str = "hexhashi.sync_times";
+/// Pause after the last change before it is uploaded
+const PUSH_DELAY: Duration = Duration::from_secs(5);
+/// Number of times the snapshot is merged again when another device uploaded in between
+const SYNC_ATTEMPTS: usize = 3;
+/// Status of the response to an upload based on a snapshot that is out of date
+const PRECONDITION_FAILED: f64 = 412.0;
+
+thread_local! {
+    static PUSH_PENDING: Cell<bool> = const { Cell::new(false) };
+}
+
+///
+/// Value of the profile as shared with the server.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Entry {
+    /// Time of the last change in milliseconds since the epoch
+    modified_ms: f64,
+    /// JSON of the value, `None` if it was removed
+    json: Option<String>,
+}
+
+type Snapshot = BTreeMap<String, Entry>;
+
+///
+/// Combine the `local` and `remote` snapshots, taking the entry changed last for each key.
+///
+/// Returns the entries to change locally and the combined snapshot. Local entries win ties.
+///
+fn merge(local: &Snapshot, remote: &Snapshot) -> (Snapshot, Snapshot) {
+    let mut merged = local.clone();
+    let mut changes = Snapshot::new();
+    for (key, entry) in remote {
+        let newer = local
+            .get(key)
+            .is_none_or(|l| entry.modified_ms > l.modified_ms);
+        if newer {
+            merged.insert(key.clone(), entry.clone());
+            changes.insert(key.clone(), entry.clone());
+        }
+    }
+    (changes, merged)
+}
+
+///
+/// Profile store keeping the values in another store and sharing them with a server at `url`.
+///
+/// The server only has to return the last snapshot it was sent by `GET` with an `ETag`
+/// and store snapshots sent by `POST`. It has to reject snapshots with status 412,
+/// unless their `If-Match` header is the `ETag` of its last snapshot, or their
+/// `If-None-Match: *` header is sent while it has none yet.
+///
+#[derive(Clone)]
+pub struct SyncStore {
+    local: Rc<dyn ProfileStore>,
+    url: String,
+}
+
+impl SyncStore {
+    pub fn new(local: Rc<dyn ProfileStore>, url: &str) -> Self {
+        SyncStore {
+            local,
+            url: format!("{}/profile", url.trim_end_matches('/')),
+        }
+    }
+
+    fn times(&self) -> BTreeMap<String, f64> {
+        self.local
+            .get(SYNC_TIMES_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn set_times(&self, times: &BTreeMap<String, f64>) {
+        let json = serde_json::to_string(times).unwrap_or_default();
+        self.local.set(SYNC_TIMES_KEY, &json);
+    }
+
+    ///
+    /// Remember that the value of `key` changed just now and upload it soon.
+    ///
+    fn touch(&self, key: &str) {
+        let mut times = self.times();
+        times.insert(key.to_string(), js_sys::Date::now());
+        self.set_times(&times);
+        if !PUSH_PENDING.replace(true) {
+            let store = self.clone();
+            set_timeout(
+                move || {
+                    PUSH_PENDING.set(false);
+                    spawn_local(async move {
+                        if let Err(e) = sync(&store).await {
+                            log!("Cannot sync profile: {}", e);
+                        }
+                    });
+                },
+                PUSH_DELAY,
+            );
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        let times = self.times();
+        let keys = self.keys().into_iter().chain(times.keys().cloned());
+        keys.map(|key| {
+            let entry = Entry {
+                modified_ms: times.get(&key).copied().unwrap_or(0.0),
+                json: self.local.get(&key),
+            };
+            (key, entry)
+        })
+        .collect()
+    }
+
+    ///
+    /// Take over the `changes` of the server without uploading them again.
+    ///
+    fn apply(&self, changes: Snapshot) {
+        let mut times = self.times();
+        for (key, entry) in changes {
+            if EXCLUDED_KEYS.contains(&key.as_str()) {
+                continue;
+            }
+            match &entry.json {
+                Some(json) => {
+                    self.local.set(&key, json);
+                }
+                None => self.local.remove(&key),
+            }
+            times.insert(key, entry.modified_ms);
+        }
+        self.set_times(&times);
+    }
+}
+
+impl ProfileStore for SyncStore {
+    fn get(&self, key: &str) -> Option<String> {
+        self.local.get(key)
+    }
+
+    fn set(&self, key: &str, json: &str) -> bool {
+        // Values stored again unchanged must not win over newer changes on other devices.
+        if self.local.get(key).as_deref() == Some(json) {
+            return true;
+        }
+        let stored = self.local.set(key, json);
+        if stored && !EXCLUDED_KEYS.contains(&key) {
+            self.touch(key);
+        }
+        stored
+    }
+
+    fn remove(&self, key: &str) {
+        if self.local.get(key).is_some() {
+            self.local.remove(key);
+            self.touch(key);
+        }
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.local
+            .keys()
+            .into_iter()
+            .filter(|key| !EXCLUDED_KEYS.contains(&key.as_str()))
+            .collect()
+    }
+}
+
+///
+/// Exchange the changes of this device with the server.
+///
+/// The snapshot is merged again if another device uploaded one in between,
+/// so that its changes are not overwritten.
+/// Returns if values changed locally, so that the pages showing them need to be loaded again.
+///
+pub async fn sync(store: &SyncStore) -> Result<bool, String> {
+    let mut changed = false;
+    for _ in 0..SYNC_ATTEMPTS {
+        let (remote, etag): (Snapshot, _) = match fetch_tagged(&store.url).await {
+            Ok((text, etag)) => (
+                serde_json::from_str(&text).map_err(|e| e.to_string())?,
+                etag,
+            ),
+            // Nothing was uploaded yet.
+            Err(e) if e.as_f64() == Some(404.0) => (Snapshot::new(), None),
+            Err(e) => return Err(format!("{:?}", e)),
+        };
+        let (changes, merged) = merge(&store.snapshot(), &remote);
+        changed |= !changes.is_empty();
+        store.apply(changes);
+        let json = serde_json::to_string(&merged).map_err(|e| e.to_string())?;
+        match post_json_if(&store.url, &json, etag.as_deref()).await {
+            Ok(()) => return Ok(changed),
+            Err(e) if e.as_f64() == Some(PRECONDITION_FAILED) => {}
+            Err(e) => return Err(format!("{:?}", e)),
+        }
+    }
+    Err(format!(
+        "Profile changed on the server during {} attempts",
+        SYNC_ATTEMPTS
+    ))
+}
+
+///
+/// Share the profile with the server at `url` from now on, unless it is empty.
+///
+/// The page is loaded again if the server had newer values.
+///
+pub fn start_sync(url: &str) {
+    if url.is_empty() {
+        return;
+    }
+    let store = SyncStore::new(current_store(), url);
+    set_store(Rc::new(store.clone()));
+    spawn_local(async move {
+        match sync(&store).await {
+            Ok(true) => {
+                let _ = window().location().reload();
+            }
+            Ok(false) => {}
+            Err(e) => log!("Cannot sync profile: {}", e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+
+    use crate::storage::{MemoryStore, ProfileStore};
+
+    use super::{Entry, Snapshot, SyncStore, merge};
+
+    fn entry(modified_ms: f64, json: Option<&str>) -> Entry {
+        Entry {
+            modified_ms,
+            json: json.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn merge_by_time() {
+        let local = Snapshot::from([
+            ("a".to_string(), entry(10.0, Some("1"))),
+            ("b".to_string(), entry(20.0, Some("2"))),
+            ("c".to_string(), entry(30.0, Some("3"))),
+        ]);
+        let remote = Snapshot::from([
+            ("a".to_string(), entry(15.0, Some("4"))),
+            ("b".to_string(), entry(20.0, Some("5"))),
+            ("d".to_string(), entry(5.0, None)),
+        ]);
+        let (changes, merged) = merge(&local, &remote);
+        assert_eq!(
+            changes.keys().collect::<Vec<_>>(),
+            ["a", "d"],
+            "newer and unknown remote entries are taken"
+        );
+        assert_eq!(merged["a"].json.as_deref(), Some("4"));
+        assert_eq!(merged["b"].json.as_deref(), Some("2"));
+        assert_eq!(merged["c"].json.as_deref(), Some("3"));
+        assert_eq!(merged["d"].json, None);
+    }
+
+    #[test]
+    fn apply_changes() {
+        let local = Rc::new(MemoryStore::default());
+        local.set("hexhashi.stats", "{}");
+        local.set("hexhashi.library", "{}");
+        let store = SyncStore::new(local.clone(), "https://example.org/");
+        assert_eq!(store.url, "https://example.org/profile");
+        store.apply(Snapshot::from([
+            ("hexhashi.stats".to_string(), entry(7.0, Some("[]"))),
+            ("hexhashi.library".to_string(), entry(8.0, None)),
+            ("hexhashi.prefetched".to_string(), entry(9.0, Some("{}"))),
+        ]));
+        assert_eq!(local.get("hexhashi.stats").as_deref(), Some("[]"));
+        assert_eq!(local.get("hexhashi.library"), None);
+        // Caches and the times themselves are not shared.
+        assert_eq!(local.get("hexhashi.prefetched"), None);
+        assert_eq!(store.keys(), ["hexhashi.stats"]);
+
+        let snapshot = store.snapshot();
+        assert_eq!(snapshot["hexhashi.stats"], entry(7.0, Some("[]")));
+        assert_eq!(snapshot["hexhashi.library"], entry(8.0, None));
+        assert_eq!(snapshot.len(), 2);
+    }
+}
@@ -0,0 +1,130 @@
+use hexhashi_logic::{difficulty::Difficulty, session::GameSession, solver::GenerationReport};
+use leptos::{logging::log, task::spawn_local};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+
+use crate::{net::post_json, settings::load_settings};
+
+///
+/// Anonymous outcome of a solved puzzle, used to calibrate the difficulty ratings against real players.
+///
+/// Neither the player nor the puzzle are included, only the hash identifying the puzzle.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SolveReport {
+    /// Hash of the puzzle as in the statistics
+    pub puzzle: String,
+    /// Difficulty the puzzle was generated for
+    pub difficulty: Difficulty,
+    /// Difficulty by the techniques the solver needs, if it could be rated
+    pub rating: Option<Difficulty>,
+    pub elapsed_ms: f64,
+    pub hints: u32,
+    pub mistakes: u32,
+}
+
+impl SolveReport {
+    pub fn new(game: &GameSession, difficulty: &Difficulty, elapsed_ms: f64) -> Self {
+        SolveReport {
+            puzzle: game.system().puzzle_id(),
+            difficulty: difficulty.clone(),
+            rating: game.system().rate(),
+            elapsed_ms: elapsed_ms.round(),
+            hints: game.hints(),
+            mistakes: game.mistakes(),
+        }
+    }
+}
+
+///
+/// How generating a puzzle in the background went, used to tune the parameters of the generator.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GenerationHealth {
+    /// Difficulty the puzzle was generated for
+    pub difficulty: Difficulty,
+    #[serde(flatten)]
+    pub report: GenerationReport,
+}
+
+///
+/// Get the address of the telemetry server, if the player opted in.
+///
+pub fn telemetry_url() -> Option<String> {
+    let settings = load_settings();
+    (settings.telemetry && !settings.telemetry_url.is_empty()).then_some(settings.telemetry_url)
+}
+
+///
+/// Send the `report` on a puzzle generated for `difficulty` to the telemetry server, if the player opted in.
+///
+pub fn report_generation(difficulty: Difficulty, report: GenerationReport) {
+    let Some(url) = telemetry_url() else {
+        return;
+    };
+    let health = GenerationHealth { difficulty, report };
+    spawn_local(async move {
+        let body = serde_json::to_string(&health).unwrap_or_default();
+        if let Err(e) = post_json(&format!("{}/generations", url), &body).await {
+            log!("Cannot send telemetry: {:?}", e);
+        }
+    });
+}
+
+///
+/// Send the `report` to the telemetry server at `url` in the background.
+///
+pub fn send_report(report: SolveReport, url: String) {
+    spawn_local(async move {
+        if let Err(e) = submit_report(&url, &report).await {
+            log!("Cannot send telemetry: {:?}", e);
+        }
+    });
+}
+
+async fn submit_report(url: &str, report: &SolveReport) -> Result<(), JsValue> {
+    let body = serde_json::to_string(report).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    post_json(&format!("{}/solves", url), &body).await
+}
+
+#[cfg(test)]
+mod test {
+    use hexhashi_logic::{
+        difficulty::Difficulty,
+        hex::{GameParameters, HexSystem},
+        session::GameSession,
+        solver::GenerationReport,
+    };
+
+    use super::{GenerationHealth, SolveReport};
+
+    #[test]
+    fn anonymous() {
+        let system = HexSystem::generate_new(GameParameters::preset(&Difficulty::Easy, 5));
+        let code = system.to_code();
+        let report = SolveReport::new(&GameSession::new(system), &Difficulty::Easy, 1234.4);
+        assert_eq!(report.elapsed_ms, 1234.0);
+        assert_eq!(report.puzzle.len(), 16);
+        let json = serde_json::to_string(&report).unwrap();
+        // The puzzle cannot be told from the report.
+        assert!(!json.contains(&code));
+        assert!(json.contains("\"difficulty\":\"easy\""));
+    }
+
+    #[test]
+    fn generation() {
+        let (_, _, report) =
+            HexSystem::generate_reported(GameParameters::preset(&Difficulty::Easy, 5));
+        let health = GenerationHealth {
+            difficulty: Difficulty::Easy,
+            report: GenerationReport {
+                elapsed_ms: Some(12.0),
+                ..report
+            },
+        };
+        let json = serde_json::to_value(&health).unwrap();
+        assert_eq!(json["difficulty"], "easy");
+        assert_eq!(json["elapsed_ms"], 12.0);
+        assert!(json["attempts"].as_u64().unwrap() >= 1);
+    }
+}
@@ -0,0 +1,344 @@
+use leptos::{prelude::*, task::spawn_local};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen_futures::JsFuture;
+
+use crate::{
+    clipboard::{read_text, write_text},
+    settings::Settings,
+    webgl::{parse_color, try_parse_color},
+};
+
+///
+/// Colors used to draw the board
+///
+/// Missing fields fall back to the default theme, so that themes can be shared with only some colors changed.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    /// Bridges placed by the player
+    pub bridge: String,
+    /// Lines between the cells islands can be connected through
+    pub grid: String,
+    /// Background of the board, the one of the page if none
+    pub background: Option<String>,
+    /// Island without bridges
+    pub island: String,
+    /// Number on an island without bridges
+    pub island_text: String,
+    /// Island with too few or too many bridges
+    pub unfinished_island: String,
+    /// Number on an island with too few or too many bridges
+    pub unfinished_text: String,
+    /// Island with the right number of bridges
+    pub finished_island: String,
+    /// Number on an island with the right number of bridges
+    pub finished_text: String,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            bridge: "dodgerblue".to_string(),
+            grid: "dimgrey".to_string(),
+            background: None,
+            island: "white".to_string(),
+            island_text: "black".to_string(),
+            unfinished_island: "gold".to_string(),
+            unfinished_text: "dimgray".to_string(),
+            finished_island: "green".to_string(),
+            finished_text: "white".to_string(),
+        }
+    }
+}
+
+impl Theme {
+    ///
+    /// Get the relaxed colors of the zen mode.
+    ///
+    pub fn zen() -> Self {
+        Theme {
+            bridge: "cadetblue".to_string(),
+            grid: "darkgrey".to_string(),
+            background: None,
+            island: "ivory".to_string(),
+            island_text: "dimgray".to_string(),
+            unfinished_island: "wheat".to_string(),
+            unfinished_text: "dimgray".to_string(),
+            finished_island: "darkseagreen".to_string(),
+            finished_text: "white".to_string(),
+        }
+    }
+
+    ///
+    /// Get the (island, text) colors of an island with `progress`.
+    ///
+    pub fn island_colors(&self, progress: IslandProgress) -> (&str, &str) {
+        match progress {
+            IslandProgress::Empty => (&self.island, &self.island_text),
+            IslandProgress::Unfinished => (&self.unfinished_island, &self.unfinished_text),
+            IslandProgress::Finished => (&self.finished_island, &self.finished_text),
+        }
+    }
+
+    ///
+    /// Read a theme exported as JSON.
+    ///
+    /// Colors the WebGL renderer cannot draw are rejected, see `parse_color`.
+    ///
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut theme: Theme =
+            serde_json::from_str(text).map_err(|e| format!("This is no hexhashi theme: {}", e))?;
+        let background = theme.background.clone().map(|b| ("Background", b));
+        let colors = theme
+            .colors_mut()
+            .map(|(label, color)| (label, color.clone()))
+            .into_iter()
+            .chain(background);
+        for (label, color) in colors {
+            if try_parse_color(&color).is_none() {
+                return Err(format!(
+                    "The color \"{}\" of {} is not supported, use #rrggbb instead.",
+                    color,
+                    label.to_lowercase()
+                ));
+            }
+        }
+        Ok(theme)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    ///
+    /// Get the editable colors with their labels.
+    ///
+    fn colors_mut(&mut self) -> [(&'static str, &mut String); 8] {
+        [
+            ("Bridges", &mut self.bridge),
+            ("Grid", &mut self.grid),
+            ("Empty islands", &mut self.island),
+            ("Numbers on empty islands", &mut self.island_text),
+            ("Unfinished islands", &mut self.unfinished_island),
+            ("Numbers on unfinished islands", &mut self.unfinished_text),
+            ("Finished islands", &mut self.finished_island),
+            ("Numbers on finished islands", &mut self.finished_text),
+        ]
+    }
+}
+
+///
+/// How far the bridges of an island are, which decides its colors.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IslandProgress {
+    /// No bridges yet
+    Empty,
+    /// Too few or too many bridges
+    Unfinished,
+    /// The right number of bridges
+    Finished,
+}
+
+///
+/// Get the color as `#rrggbb`, as needed by color inputs.
+///
+fn to_hex(color: &str) -> String {
+    let [r, g, b, _] = parse_color(color).map(|c| (c * 255.0).round() as u8);
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+///
+/// Editor of the colors of the board with a sample board as preview.
+///
+/// Themes are exported and imported as JSON, so that they can be shared with others.
+///
+#[component]
+pub fn ThemeEditor() -> impl IntoView {
+    let settings = expect_context::<RwSignal<Settings>>();
+    let (message, set_message) = signal(None::<String>);
+    let theme = move || settings.read().theme.clone().unwrap_or_default();
+    let import = move |text: String| match Theme::parse(&text) {
+        Ok(imported) => {
+            settings.update(|s| s.theme = Some(imported));
+            set_message.set(Some("Theme imported.".to_string()));
+        }
+        Err(e) => set_message.set(Some(e)),
+    };
+    let import_file = move |ev: leptos::ev::Event| {
+        let input: web_sys::HtmlInputElement = event_target(&ev);
+        let Some(file) = input.files().and_then(|files| files.get(0)) else {
+            return;
+        };
+        spawn_local(async move {
+            if let Some(text) = JsFuture::from(file.text())
+                .await
+                .ok()
+                .and_then(|t| t.as_string())
+            {
+                import(text);
+            }
+        });
+    };
+    let paste = move |_| {
+        spawn_local(async move {
+            match read_text().await {
+                Ok(text) => import(text),
+                Err(_) => set_message.set(Some("Could not read the clipboard.".to_string())),
+            }
+        })
+    };
+    let copy = move |_| {
+        let json = theme().to_json();
+        spawn_local(async move {
+            set_message.set(Some(match write_text(&json).await {
+                Ok(()) => "Theme copied.".to_string(),
+                Err(_) => "Could not copy the theme.".to_string(),
+            }));
+        })
+    };
+    let href = move || {
+        format!(
+            "data:application/json;charset=utf-8,{}",
+            js_sys::encode_uri_component(&theme().to_json())
+        )
+    };
+    let inputs = (0..Theme::default().colors_mut().len())
+        .map(|i| {
+            let label = Theme::default().colors_mut()[i].0;
+            view! {
+                <label>
+                    <input
+                        type="color"
+                        prop:value=move || to_hex(theme().colors_mut()[i].1)
+                        on:input=move |ev| {
+                            let mut changed = theme();
+                            *changed.colors_mut()[i].1 = event_target_value(&ev);
+                            settings.update(|s| s.theme = Some(changed));
+                        }
+                    />
+                    " "
+                    {label}
+                </label>
+            }
+        })
+        .collect_view();
+
+    view! {
+        <fieldset class="theme">
+            <legend>"Colors"</legend>
+            <ThemePreview theme=Signal::derive(theme)/>
+            <div class="colors">
+                {inputs}
+                <label>
+                    <input
+                        type="color"
+                        prop:value=move || to_hex(theme().background.as_deref().unwrap_or("white"))
+                        on:input=move |ev| {
+                            let mut changed = theme();
+                            changed.background = Some(event_target_value(&ev));
+                            settings.update(|s| s.theme = Some(changed));
+                        }
+                    />
+                    " Background"
+                </label>
+            </div>
+            <p>
+                <button
+                    disabled=move || settings.read().theme.is_none()
+                    on:click=move |_| settings.update(|s| s.theme = None)
+                >
+                    "Reset"
+                </button>
+                " "
+                <button on:click=copy>"Copy"</button>
+                " "
+                <button on:click=paste>"Paste"</button>
+                " "
+                <a download="hexhashi-theme.json" href=href>
+                    "Export theme"
+                </a>
+                " "
+                <label>
+                    "Import theme "
+                    <input type="file" accept="application/json,.json" on:change=import_file/>
+                </label>
+            </p>
+            <p class="message">{move || message.get()}</p>
+        </fieldset>
+    }
+}
+
+///
+/// Small board with an island of each kind to preview `theme`.
+///
+#[component]
+fn ThemePreview(#[prop(into)] theme: Signal<Theme>) -> impl IntoView {
+    // Islands of the sample board with their number and progress
+    let islands = [
+        (20.0, 30.0, "2", IslandProgress::Finished),
+        (80.0, 30.0, "3", IslandProgress::Unfinished),
+        (140.0, 30.0, "1", IslandProgress::Empty),
+    ];
+    let islands = islands
+        .into_iter()
+        .map(|(x, y, number, progress)| {
+            let colors = move || {
+                let theme = theme.get();
+                let (island, text) = theme.island_colors(progress);
+                (island.to_string(), text.to_string())
+            };
+            view! {
+                <circle cx=x cy=y r="12" fill=move || colors().0/>
+                <text x=x y=y fill=move || colors().1>
+                    {number}
+                </text>
+            }
+        })
+        .collect_view();
+
+    view! {
+        <svg class="preview" viewBox="0 0 160 60" role="img" aria-label="Preview of the colors">
+            <rect
+                width="160"
+                height="60"
+                fill=move || theme.get().background.unwrap_or("white".to_string())
+            />
+            <line x1="80" y1="30" x2="140" y2="30" stroke=move || theme.get().grid stroke-width="0.5"/>
+            <line x1="20" y1="27" x2="80" y2="27" stroke=move || theme.get().bridge stroke-width="2"/>
+            <line x1="20" y1="33" x2="80" y2="33" stroke=move || theme.get().bridge stroke-width="2"/>
+            {islands}
+        </svg>
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Theme, to_hex};
+
+    #[test]
+    fn parse() {
+        let theme = Theme {
+            bridge: "#123456".to_string(),
+            background: Some("#000000".to_string()),
+            ..Theme::zen()
+        };
+        assert_eq!(Theme::parse(&theme.to_json()), Ok(theme));
+        // Colors left out are the default ones.
+        let theme = Theme::parse(r##"{"grid": "#ff0000"}"##).unwrap();
+        assert_eq!(theme.grid, "#ff0000");
+        assert_eq!(theme.bridge, Theme::default().bridge);
+        assert!(Theme::parse("nonsense").is_err());
+        // Colors that cannot be drawn are rejected.
+        assert!(Theme::parse(r#"{"grid": "rebeccapurple"}"#).is_err());
+        assert!(Theme::parse(r##"{"background": "#12"}"##).is_err());
+    }
+
+    #[test]
+    fn hex() {
+        assert_eq!(to_hex("dodgerblue"), "#1e90ff");
+        assert_eq!(to_hex("#abcdef"), "#abcdef");
+        assert_eq!(to_hex("rgb(255, 0, 0)"), "#ff0000");
+    }
+}
@@ -0,0 +1,104 @@
+use hexhashi_logic::{hex::HexSystem, session::GameSession, solver::Technique};
+use leptos::{logging::log, prelude::*};
+use leptos_router::{hooks::use_params, params::Params};
+
+use crate::game::{Board, GameMode};
+
+///
+/// Techniques offered by the trainer.
+///
+/// Crossing bridges hardly ever decide on their own in the generated puzzles, so they are left out.
+///
+const TRAINED: [Technique; 2] = [Technique::Sums, Technique::Connectivity];
+
+///
+/// Get a short explanation of `technique` for the players.
+///
+pub fn explanation(technique: Technique) -> &'static str {
+    match technique {
+        Technique::Sums => {
+            "The bridges of an island add up to its number. If the neighbors can take just as many \
+             bridges as the island needs, all of them are needed. If a neighbor takes fewer, the \
+             others must make up for it."
+        }
+        Technique::Crossings => {
+            "Bridges cannot cross. A placed bridge rules out all bridges crossing it, which leaves \
+             the islands next to them with fewer options."
+        }
+        Technique::Connectivity => {
+            "All islands must be connected. Two islands cannot complete each other, since they \
+             would be cut off from the rest. A bridge that is the only way left to reach a group \
+             of islands is needed."
+        }
+    }
+}
+
+#[component]
+pub fn TrainerPage() -> impl IntoView {
+    let techniques = TRAINED
+        .into_iter()
+        .map(|technique| {
+            view! {
+                <h2>{technique.to_string()}</h2>
+                <p>{explanation(technique)}</p>
+                <form method="get" action=format!("/trainer/{}", technique.as_str())>
+                    <button>"Practice"</button>
+                </form>
+            }
+        })
+        .collect_view();
+
+    view! {
+        <div><span class="menu">hexhashi</span><a class="menu" href="/">Back</a></div>
+        <h1>"Trainer"</h1>
+        <p>
+            "Each position is partly solved and can only be completed by applying the technique. "
+            "The bridges placed already are locked."
+        </p>
+        {techniques}
+    }
+}
+
+#[derive(Params, Debug, PartialEq)]
+pub struct TrainerArgs {
+    pub technique: Option<Technique>,
+}
+
+///
+/// Play positions that need the technique of the route one after another.
+///
+#[component]
+pub fn TrainerGame() -> impl IntoView {
+    let params = use_params::<TrainerArgs>();
+    let technique = params
+        .read_untracked()
+        .as_ref()
+        .ok()
+        .and_then(|p| p.technique)
+        .unwrap_or(Technique::Sums);
+    let new_position = move || {
+        let seed = (js_sys::Math::random() * u32::MAX as f64) as u64;
+        HexSystem::generate_for_technique(technique, seed)
+    };
+    let position = RwSignal::new(new_position());
+    let on_next = Some(Callback::new(move |_| position.set(new_position())));
+
+    move || {
+        let (seed, system) = position.get();
+        log!("{}", seed);
+        let session = GameSession::new(system);
+        let (difficulty, mode) = (technique.difficulty(), GameMode::Normal);
+        view! {
+            <Board
+                difficulty
+                mode
+                session
+                elapsed_ms=0.0
+                hot_seat=None
+                seed=Some(seed)
+                on_next
+                technique=Some(technique)
+            />
+        }
+    }
+}
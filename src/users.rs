@@ -0,0 +1,308 @@
+use std::rc::Rc;
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    prefetch::PREFETCH_KEY,
+    storage::{KEY_PREFIX, ProfileStore, current_store, load, save, set_store},
+};
+
+pub const USERS_KEY: &str = "hexhashi.users";
+/// Values shared by all users of the device
+const SHARED_KEYS: [&str; 2] = [USERS_KEY, PREFETCH_KEY];
+/// Colors of the avatars to choose from
+const AVATAR_COLORS: [&str; 6] = [
+    "dodgerblue",
+    "crimson",
+    "darkseagreen",
+    "gold",
+    "darkorange",
+    "mediumpurple",
+];
+
+///
+/// Player with an own profile, i.e. settings, statistics, saved game and library.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct User {
+    id: u32,
+    name: String,
+    /// CSS color of the avatar
+    color: String,
+}
+
+///
+/// All players of this device and who is playing now.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Users {
+    users: Vec<User>,
+    active: u32,
+}
+
+impl Default for Users {
+    fn default() -> Self {
+        Users {
+            users: vec![User {
+                id: 0,
+                name: "Player 1".to_string(),
+                color: AVATAR_COLORS[0].to_string(),
+            }],
+            active: 0,
+        }
+    }
+}
+
+impl Users {
+    ///
+    /// Add a player named `name` with an avatar in `color`.
+    ///
+    /// Returns the identifier of the new player.
+    ///
+    pub fn add(&mut self, name: &str, color: &str) -> u32 {
+        let id = self.users.iter().map(|u| u.id + 1).max().unwrap_or(0);
+        let name = match name.trim() {
+            "" => format!("Player {}", self.users.len() + 1),
+            name => name.to_string(),
+        };
+        self.users.push(User {
+            id,
+            name,
+            color: color.to_string(),
+        });
+        id
+    }
+
+    pub fn select(&mut self, id: u32) {
+        if self.users.iter().any(|u| u.id == id) {
+            self.active = id;
+        }
+    }
+
+    pub fn active(&self) -> u32 {
+        self.active
+    }
+}
+
+///
+/// Get the key the value `key` of the profile of user `id` is stored under.
+///
+/// The first user keeps the keys used before there were several users.
+///
+fn scoped_key(id: u32, key: &str) -> String {
+    match key.strip_prefix(KEY_PREFIX) {
+        Some(rest) if id != 0 && !SHARED_KEYS.contains(&key) => {
+            format!("{}user{}.{}", KEY_PREFIX, id, rest)
+        }
+        _ => key.to_string(),
+    }
+}
+
+///
+/// Get the key of the profile of user `id` stored under `scoped`, if it belongs to the user.
+///
+fn unscoped_key(id: u32, scoped: &str) -> Option<String> {
+    if SHARED_KEYS.contains(&scoped) {
+        return None;
+    }
+    let rest = scoped.strip_prefix(KEY_PREFIX)?;
+    let other_user = rest
+        .strip_prefix("user")
+        .and_then(|r| r.split_once('.'))
+        .is_some_and(|(digits, _)| digits.parse::<u32>().is_ok());
+    match (id, other_user) {
+        (0, false) => Some(scoped.to_string()),
+        (0, true) => None,
+        _ => rest
+            .strip_prefix(&format!("user{}.", id))
+            .map(|key| format!("{}{}", KEY_PREFIX, key)),
+    }
+}
+
+///
+/// Profile store keeping the values of one user apart from the ones of the other users.
+///
+struct UserStore {
+    inner: Rc<dyn ProfileStore>,
+    user: u32,
+}
+
+impl ProfileStore for UserStore {
+    fn get(&self, key: &str) -> Option<String> {
+        self.inner.get(&scoped_key(self.user, key))
+    }
+
+    fn set(&self, key: &str, json: &str) -> bool {
+        self.inner.set(&scoped_key(self.user, key), json)
+    }
+
+    fn remove(&self, key: &str) {
+        self.inner.remove(&scoped_key(self.user, key));
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.inner
+            .keys()
+            .iter()
+            .filter_map(|key| unscoped_key(self.user, key))
+            .collect()
+    }
+}
+
+///
+/// Keep the values of the active user apart from now on.
+///
+pub fn activate_user() {
+    let users: Users = load(USERS_KEY).unwrap_or_default();
+    set_store(Rc::new(UserStore {
+        inner: current_store(),
+        user: users.active(),
+    }));
+}
+
+///
+/// Update the stored players with `f` and load the page again for the active one.
+///
+fn update_users(f: impl FnOnce(&mut Users)) {
+    let mut users: Users = load(USERS_KEY).unwrap_or_default();
+    f(&mut users);
+    save(USERS_KEY, &users);
+    let _ = window().location().reload();
+}
+
+///
+/// Avatars of the players to switch between them and a form to add another one.
+///
+#[component]
+pub fn UserSelect() -> impl IntoView {
+    let users: Users = load(USERS_KEY).unwrap_or_default();
+    let name = RwSignal::new(String::new());
+    let color = RwSignal::new(AVATAR_COLORS[users.users.len() % AVATAR_COLORS.len()].to_string());
+    let avatars = users
+        .users
+        .iter()
+        .map(|user| {
+            let id = user.id;
+            let initial = user
+                .name
+                .chars()
+                .next()
+                .unwrap_or('?')
+                .to_uppercase()
+                .to_string();
+            view! {
+                <button
+                    class="user"
+                    class:active=id == users.active
+                    on:click=move |_| update_users(|u| u.select(id))
+                >
+                    <span class="avatar" style=format!("background-color: {}", user.color)>
+                        {initial}
+                    </span>
+                    {user.name.clone()}
+                </button>
+            }
+        })
+        .collect_view();
+    let add = move |_| {
+        let (name, color) = (name.get_untracked(), color.get_untracked());
+        update_users(|u| {
+            let id = u.add(&name, &color);
+            u.select(id);
+        });
+    };
+
+    view! {
+        <div class="users">
+            {avatars}
+            <details>
+                <summary>"New player"</summary>
+                <input
+                    type="text"
+                    placeholder="Name"
+                    prop:value=name
+                    on:input=move |ev| name.set(event_target_value(&ev))
+                />
+                <select on:change=move |ev| color.set(event_target_value(&ev))>
+                    {AVATAR_COLORS
+                        .into_iter()
+                        .map(|c| {
+                            view! {
+                                <option value=c selected=move || color.get() == c>
+                                    {c}
+                                </option>
+                            }
+                        })
+                        .collect_view()}
+                </select>
+                <button on:click=add>"Add"</button>
+            </details>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+
+    use crate::storage::{MemoryStore, ProfileStore};
+
+    use super::{UserStore, Users, scoped_key, unscoped_key};
+
+    #[test]
+    fn users() {
+        let mut users = Users::default();
+        let id = users.add(" Anna ", "crimson");
+        assert_eq!(id, 1);
+        assert_eq!(users.users[1].name, "Anna");
+        assert_eq!(users.add("", "gold"), 2);
+        assert_eq!(users.users[2].name, "Player 3");
+        users.select(id);
+        assert_eq!(users.active(), 1);
+        users.select(7);
+        assert_eq!(users.active(), 1);
+    }
+
+    #[test]
+    fn keys() {
+        assert_eq!(scoped_key(0, "hexhashi.stats"), "hexhashi.stats");
+        assert_eq!(scoped_key(2, "hexhashi.stats"), "hexhashi.user2.stats");
+        assert_eq!(scoped_key(2, "hexhashi.users"), "hexhashi.users");
+        assert_eq!(
+            unscoped_key(2, "hexhashi.user2.stats").as_deref(),
+            Some("hexhashi.stats")
+        );
+        assert_eq!(unscoped_key(2, "hexhashi.user12.stats"), None);
+        assert_eq!(unscoped_key(0, "hexhashi.user2.stats"), None);
+        assert_eq!(
+            unscoped_key(0, "hexhashi.stats").as_deref(),
+            Some("hexhashi.stats")
+        );
+        assert_eq!(unscoped_key(0, "hexhashi.users"), None);
+    }
+
+    #[test]
+    fn separate_profiles() {
+        let memory = Rc::new(MemoryStore::default());
+        let first = UserStore {
+            inner: memory.clone(),
+            user: 0,
+        };
+        let second = UserStore {
+            inner: memory.clone(),
+            user: 1,
+        };
+        first.set("hexhashi.stats", "1");
+        second.set("hexhashi.stats", "2");
+        second.set("hexhashi.prefetched", "{}");
+        assert_eq!(first.get("hexhashi.stats").as_deref(), Some("1"));
+        assert_eq!(second.get("hexhashi.stats").as_deref(), Some("2"));
+        // Shared values are the same for all users, but not part of their profiles.
+        assert_eq!(first.get("hexhashi.prefetched").as_deref(), Some("{}"));
+        assert_eq!(first.keys(), ["hexhashi.stats"]);
+        assert_eq!(second.keys(), ["hexhashi.stats"]);
+        second.remove("hexhashi.stats");
+        assert_eq!(memory.keys(), ["hexhashi.prefetched", "hexhashi.stats"]);
+    }
+}
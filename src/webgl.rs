@@ -0,0 +1,372 @@
+use std::f64::consts::PI;
+
+#[cfg(feature = "webgl")]
+use wasm_bindgen::JsCast;
+use web_sys::HtmlCanvasElement;
+#[cfg(feature = "webgl")]
+use web_sys::{WebGl2RenderingContext as Gl, WebGlBuffer, WebGlProgram, WebGlShader};
+
+/// Number of triangles approximating a disc
+const DISC_SEGMENTS: usize = 24;
+/// Floats per vertex: x, y, r, g, b, a
+const VERTEX_SIZE: i32 = 6;
+
+#[cfg(feature = "webgl")]
+const VERTEX_SHADER: &str = r#"#version 300 es
+in vec2 position;
+in vec4 color;
+uniform vec2 resolution;
+out vec4 v_color;
+void main() {
+    vec2 clip = position / resolution * 2.0 - 1.0;
+    gl_Position = vec4(clip.x, -clip.y, 0.0, 1.0);
+    v_color = color;
+}
+"#;
+
+#[cfg(feature = "webgl")]
+const FRAGMENT_SHADER: &str = r#"#version 300 es
+precision mediump float;
+in vec4 v_color;
+out vec4 out_color;
+void main() {
+    out_color = v_color;
+}
+"#;
+
+///
+/// Colored triangles in pixel coordinates of the canvas, drawn in a single call.
+///
+#[derive(Clone, Debug, Default)]
+pub struct Mesh {
+    vertices: Vec<f32>,
+}
+
+impl Mesh {
+    fn vertex(&mut self, (x, y): (f64, f64), color: [f32; 4]) {
+        self.vertices.extend([x as f32, y as f32]);
+        self.vertices.extend(color);
+    }
+
+    ///
+    /// Add a straight line from `start` to `end` as a rectangle of two triangles.
+    ///
+    pub fn line(&mut self, start: (f64, f64), end: (f64, f64), width: f64, color: [f32; 4]) {
+        let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+        let length = (dx * dx + dy * dy).sqrt();
+        if length == 0.0 {
+            return;
+        }
+        // Half the width perpendicular to the line
+        let (nx, ny) = (-dy / length * width / 2.0, dx / length * width / 2.0);
+        let corners = [
+            (start.0 + nx, start.1 + ny),
+            (start.0 - nx, start.1 - ny),
+            (end.0 + nx, end.1 + ny),
+            (end.0 - nx, end.1 - ny),
+        ];
+        for index in [0, 1, 2, 2, 1, 3] {
+            self.vertex(corners[index], color);
+        }
+    }
+
+    ///
+    /// Add a filled circle around `center`.
+    ///
+    pub fn disc(&mut self, center: (f64, f64), radius: f64, color: [f32; 4]) {
+        let point = |segment: usize| {
+            let angle = 2.0 * PI * segment as f64 / DISC_SEGMENTS as f64;
+            (
+                center.0 + radius * angle.cos(),
+                center.1 + radius * angle.sin(),
+            )
+        };
+        for segment in 0..DISC_SEGMENTS {
+            self.vertex(center, color);
+            self.vertex(point(segment), color);
+            self.vertex(point(segment + 1), color);
+        }
+    }
+
+    ///
+    /// Add a filled rectangle with its top left corner at `origin`.
+    ///
+    pub fn rect(&mut self, origin: (f64, f64), width: f64, height: f64, color: [f32; 4]) {
+        let middle = origin.1 + height / 2.0;
+        self.line(
+            (origin.0, middle),
+            (origin.0 + width, middle),
+            height,
+            color,
+        );
+    }
+
+    ///
+    /// Draw the mesh without a GPU into an image of `width` × `height` pixels filled with `background`.
+    ///
+    /// Returns the pixels in RGBA row by row. Triangles later in the mesh are drawn over earlier ones.
+    ///
+    pub fn rasterize(&self, width: u32, height: u32, background: [f32; 4]) -> Vec<u8> {
+        let to_byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let mut pixels: Vec<u8> = (0..width * height)
+            .flat_map(|_| background.map(to_byte))
+            .collect();
+        for triangle in self.vertices.chunks_exact(3 * VERTEX_SIZE as usize) {
+            let corner = |i: usize| {
+                let v = &triangle[i * VERTEX_SIZE as usize..];
+                (v[0], v[1])
+            };
+            let (a, b, c) = (corner(0), corner(1), corner(2));
+            let color = &triangle[2..6];
+            // Sign of the area tells on which side of an edge a point is.
+            let edge = |p: (f32, f32), q: (f32, f32), r: (f32, f32)| {
+                (q.0 - p.0) * (r.1 - p.1) - (q.1 - p.1) * (r.0 - p.0)
+            };
+            let area = edge(a, b, c);
+            if area == 0.0 {
+                continue;
+            }
+            let clip = |v: f32, max: u32| (v.max(0.0) as u32).min(max);
+            let (left, right) = (
+                clip(a.0.min(b.0).min(c.0), width),
+                clip(a.0.max(b.0).max(c.0).ceil(), width),
+            );
+            let (top, bottom) = (
+                clip(a.1.min(b.1).min(c.1), height),
+                clip(a.1.max(b.1).max(c.1).ceil(), height),
+            );
+            for y in top..bottom {
+                for x in left..right {
+                    let p = (x as f32 + 0.5, y as f32 + 0.5);
+                    let inside = [edge(b, c, p), edge(c, a, p), edge(a, b, p)]
+                        .iter()
+                        .all(|e| e * area >= 0.0);
+                    if inside {
+                        let i = ((y * width + x) * 4) as usize;
+                        let alpha = color[3];
+                        for channel in 0..3 {
+                            let under = pixels[i + channel] as f32 / 255.0;
+                            pixels[i + channel] =
+                                to_byte(color[channel] * alpha + under * (1.0 - alpha));
+                        }
+                        pixels[i + 3] = 255;
+                    }
+                }
+            }
+        }
+        pixels
+    }
+
+    #[cfg(any(feature = "webgl", test))]
+    pub fn triangles(&self) -> usize {
+        self.vertices.len() / VERTEX_SIZE as usize / 3
+    }
+}
+
+///
+/// Get the color in RGBA from 0 to 1 of the CSS `color`.
+///
+/// Supports `rgb()`, `rgba()`, `#rrggbb` and the named colors of the built-in themes. Other colors are black.
+///
+pub fn parse_color(color: &str) -> [f32; 4] {
+    try_parse_color(color).unwrap_or([0.0, 0.0, 0.0, 1.0])
+}
+
+///
+/// Get the color in RGBA from 0 to 1 of the CSS `color`, if it is one `parse_color` supports.
+///
+pub fn try_parse_color(color: &str) -> Option<[f32; 4]> {
+    let color = color.trim();
+    if let Some(args) = color
+        .strip_prefix("rgba(")
+        .or_else(|| color.strip_prefix("rgb("))
+        .and_then(|c| c.strip_suffix(')'))
+    {
+        let values: Vec<f32> = args
+            .split(',')
+            .filter_map(|v| v.trim().parse().ok())
+            .collect();
+        return match values[..] {
+            [r, g, b] => Some([r / 255.0, g / 255.0, b / 255.0, 1.0]),
+            [r, g, b, a] => Some([r / 255.0, g / 255.0, b / 255.0, a]),
+            _ => None,
+        };
+    }
+    let rgb = match color {
+        "white" => 0xffffff,
+        "black" => 0x000000,
+        "dodgerblue" => 0x1e90ff,
+        "dimgray" | "dimgrey" => 0x696969,
+        "darkgray" | "darkgrey" => 0xa9a9a9,
+        "gold" => 0xffd700,
+        "green" => 0x008000,
+        "cadetblue" => 0x5f9ea0,
+        "ivory" => 0xfffff0,
+        "wheat" => 0xf5deb3,
+        "darkseagreen" => 0x8fbc8f,
+        hex => hex
+            .strip_prefix('#')
+            .filter(|h| h.len() == 6)
+            .and_then(|h| u32::from_str_radix(h, 16).ok())?,
+    };
+    Some([
+        (rgb >> 16 & 0xff) as f32 / 255.0,
+        (rgb >> 8 & 0xff) as f32 / 255.0,
+        (rgb & 0xff) as f32 / 255.0,
+        1.0,
+    ])
+}
+
+///
+/// Draws meshes with WebGL 2 on a canvas.
+///
+#[cfg(feature = "webgl")]
+pub struct GpuRenderer {
+    gl: Gl,
+    program: WebGlProgram,
+    buffer: WebGlBuffer,
+}
+
+#[cfg(feature = "webgl")]
+impl GpuRenderer {
+    ///
+    /// Set up the shaders for `canvas`, or fail if WebGL 2 is not available.
+    ///
+    pub fn new(canvas: &HtmlCanvasElement) -> Result<Self, String> {
+        let gl = canvas
+            .get_context("webgl2")
+            .ok()
+            .flatten()
+            .and_then(|c| c.dyn_into::<Gl>().ok())
+            .ok_or("WebGL 2 is not supported.")?;
+        let vertex = compile(&gl, Gl::VERTEX_SHADER, VERTEX_SHADER)?;
+        let fragment = compile(&gl, Gl::FRAGMENT_SHADER, FRAGMENT_SHADER)?;
+        let program = gl.create_program().ok_or("Cannot create program.")?;
+        gl.attach_shader(&program, &vertex);
+        gl.attach_shader(&program, &fragment);
+        gl.link_program(&program);
+        if !gl
+            .get_program_parameter(&program, Gl::LINK_STATUS)
+            .as_bool()
+            .unwrap_or(false)
+        {
+            return Err(gl.get_program_info_log(&program).unwrap_or_default());
+        }
+        let buffer = gl.create_buffer().ok_or("Cannot create buffer.")?;
+        Ok(GpuRenderer {
+            gl,
+            program,
+            buffer,
+        })
+    }
+
+    ///
+    /// Clear the canvas of `width` × `height` pixels and draw the `mesh`.
+    ///
+    pub fn render(&self, mesh: &Mesh, width: f64, height: f64) {
+        let gl = &self.gl;
+        gl.viewport(0, 0, width as i32, height as i32);
+        gl.clear_color(0.0, 0.0, 0.0, 0.0);
+        gl.clear(Gl::COLOR_BUFFER_BIT);
+        gl.enable(Gl::BLEND);
+        gl.blend_func(Gl::SRC_ALPHA, Gl::ONE_MINUS_SRC_ALPHA);
+        gl.use_program(Some(&self.program));
+        gl.uniform2f(
+            gl.get_uniform_location(&self.program, "resolution")
+                .as_ref(),
+            width as f32,
+            height as f32,
+        );
+
+        gl.bind_buffer(Gl::ARRAY_BUFFER, Some(&self.buffer));
+        let vertices = js_sys::Float32Array::from(mesh.vertices.as_slice());
+        gl.buffer_data_with_array_buffer_view(Gl::ARRAY_BUFFER, &vertices, Gl::DYNAMIC_DRAW);
+        let stride = VERTEX_SIZE * 4;
+        for (name, size, offset) in [("position", 2, 0), ("color", 4, 2 * 4)] {
+            let location = gl.get_attrib_location(&self.program, name) as u32;
+            gl.enable_vertex_attrib_array(location);
+            gl.vertex_attrib_pointer_with_i32(location, size, Gl::FLOAT, false, stride, offset);
+        }
+        gl.draw_arrays(Gl::TRIANGLES, 0, (mesh.triangles() * 3) as i32);
+    }
+}
+
+#[cfg(feature = "webgl")]
+fn compile(gl: &Gl, kind: u32, source: &str) -> Result<WebGlShader, String> {
+    let shader = gl.create_shader(kind).ok_or("Cannot create shader.")?;
+    gl.shader_source(&shader, source);
+    gl.compile_shader(&shader);
+    if gl
+        .get_shader_parameter(&shader, Gl::COMPILE_STATUS)
+        .as_bool()
+        .unwrap_or(false)
+    {
+        Ok(shader)
+    } else {
+        Err(gl.get_shader_info_log(&shader).unwrap_or_default())
+    }
+}
+
+///
+/// Stand-in of the WebGL renderer in builds without the `webgl` feature, so that the canvas is used.
+///
+#[cfg(not(feature = "webgl"))]
+pub struct GpuRenderer;
+
+#[cfg(not(feature = "webgl"))]
+impl GpuRenderer {
+    pub fn new(_canvas: &HtmlCanvasElement) -> Result<Self, String> {
+        Err("This version is built without WebGL.".to_string())
+    }
+
+    pub fn render(&self, _mesh: &Mesh, _width: f64, _height: f64) {}
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DISC_SEGMENTS, Mesh, parse_color};
+
+    #[test]
+    fn mesh() {
+        let mut mesh = Mesh::default();
+        mesh.line((0.0, 0.0), (10.0, 0.0), 4.0, [1.0; 4]);
+        assert_eq!(mesh.triangles(), 2);
+        // The rectangle is as wide as the line.
+        let ys: Vec<f32> = mesh.vertices.chunks(6).map(|v| v[1]).collect();
+        assert_eq!(ys, [2.0, -2.0, 2.0, 2.0, -2.0, -2.0]);
+        // Nothing to draw for a line without length
+        mesh.line((1.0, 1.0), (1.0, 1.0), 4.0, [1.0; 4]);
+        assert_eq!(mesh.triangles(), 2);
+        mesh.disc((5.0, 5.0), 3.0, [1.0; 4]);
+        assert_eq!(mesh.triangles(), 2 + DISC_SEGMENTS);
+    }
+
+    #[test]
+    fn rasterize() {
+        let (red, white) = ([1.0, 0.0, 0.0, 1.0], [1.0; 4]);
+        let mut mesh = Mesh::default();
+        mesh.rect((1.0, 1.0), 2.0, 2.0, red);
+        // Half transparent over white is pink.
+        mesh.rect((2.0, 2.0), 1.0, 1.0, [1.0, 0.0, 0.0, 0.5]);
+        let pixels = mesh.rasterize(4, 4, white);
+        let at = |x: usize, y: usize| &pixels[(y * 4 + x) * 4..][..4];
+        assert_eq!(pixels.len(), 4 * 4 * 4);
+        assert_eq!(at(0, 0), [255, 255, 255, 255]);
+        assert_eq!(at(1, 1), [255, 0, 0, 255]);
+        assert_eq!(at(2, 2), [255, 0, 0, 255]);
+        assert_eq!(at(3, 3), [255, 255, 255, 255]);
+        // Parts outside of the image are left out.
+        mesh.rect((-2.0, -2.0), 10.0, 3.0, [0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(mesh.rasterize(4, 4, white)[..4], [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn colors() {
+        assert_eq!(parse_color("white"), [1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(parse_color("#ff0000"), [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(parse_color("rgb(0, 0, 255)"), [0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(parse_color("rgba(255, 255, 0, 0.5)"), [1.0, 1.0, 0.0, 0.5]);
+        assert_eq!(parse_color("dimgrey"), parse_color("dimgray"));
+        assert_eq!(parse_color("nonsense"), [0.0, 0.0, 0.0, 1.0]);
+    }
+}
@@ -0,0 +1,209 @@
+use hexhashi_logic::{
+    difficulty::Difficulty,
+    hex::{GameParameters, HexSystem},
+    session::Move,
+};
+use leptos::{logging::log, prelude::*, task::spawn_local};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+
+use crate::{
+    game::{THUMBNAIL_SIZE, format_time},
+    net::{fetch_text, post_json},
+    settings::Settings,
+    storage::{load, save},
+};
+
+const WEEKLY_KEY: &str = "hexhashi.weekly";
+const MS_PER_DAY: f64 = 86_400_000.0;
+
+///
+/// Get the number of the current week since the Unix epoch.
+///
+/// Weeks start on Monday, so that the challenge changes at the same time everywhere in the world.
+///
+pub fn current_week() -> u64 {
+    week_of(js_sys::Date::now())
+}
+
+fn week_of(ms_since_epoch: f64) -> u64 {
+    let days = (ms_since_epoch / MS_PER_DAY).floor() as u64;
+    // 1970-01-01 was a Thursday.
+    (days + 3) / 7
+}
+
+///
+/// Get the difficulty of the challenge of `week`, rotating through all difficulties.
+///
+pub fn weekly_difficulty(week: u64) -> Difficulty {
+    match week % 4 {
+        0 => Difficulty::Easy,
+        1 => Difficulty::Medium,
+        2 => Difficulty::Hard,
+        _ => Difficulty::Extreme,
+    }
+}
+
+///
+/// Get the seed of the puzzle of `week`, which is the same for all players.
+///
+pub fn weekly_seed(week: u64) -> u64 {
+    week
+}
+
+///
+/// Get the week of the challenge generated from `seed`, the reverse of `weekly_seed`.
+///
+pub fn seed_week(seed: u64) -> u64 {
+    seed
+}
+
+///
+/// Get the puzzle of `week`.
+///
+pub fn weekly_puzzle(week: u64) -> HexSystem {
+    let params = GameParameters::preset(&weekly_difficulty(week), weekly_seed(week));
+    HexSystem::generate_new(params)
+}
+
+///
+/// Result of a solved weekly challenge.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WeeklyEntry {
+    pub week: u64,
+    #[serde(default)]
+    pub name: String,
+    pub elapsed_ms: f64,
+    pub score: u32,
+}
+
+///
+/// Solved weekly challenge as it is submitted to the leaderboard.
+///
+/// The leaderboard regenerates the puzzle from the seed and verifies the moves.
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Replay {
+    pub entry: WeeklyEntry,
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub moves: Vec<Move>,
+}
+
+///
+/// Load the results of all weekly challenges solved on this device.
+///
+pub fn load_weekly() -> Vec<WeeklyEntry> {
+    load(WEEKLY_KEY).unwrap_or_default()
+}
+
+///
+/// Store the result of a solved weekly challenge and submit it to the leaderboard at `url`, if there is one.
+///
+pub fn record_weekly(replay: Replay, url: String) {
+    let mut entries = load_weekly();
+    entries.push(replay.entry.clone());
+    save(WEEKLY_KEY, &entries);
+    if !url.is_empty() {
+        spawn_local(async move {
+            if let Err(e) = submit_replay(&url, &replay).await {
+                log!("Cannot submit replay: {:?}", e);
+            }
+        });
+    }
+}
+
+async fn submit_replay(url: &str, replay: &Replay) -> Result<(), JsValue> {
+    let body = serde_json::to_string(replay).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    post_json(&format!("{}/weekly", url), &body).await
+}
+
+async fn fetch_leaderboard(url: &str, week: u64) -> Result<Vec<WeeklyEntry>, JsValue> {
+    let text = fetch_text(&format!("{}/weekly/{}", url, week)).await?;
+    serde_json::from_str(&text).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+#[component]
+pub fn WeeklyPage() -> impl IntoView {
+    let settings = expect_context::<RwSignal<Settings>>().get_untracked();
+    let week = current_week();
+    let difficulty = weekly_difficulty(week);
+    let play = format!("location.href='/play/{}/weekly'", difficulty.as_str());
+    let thumbnail = weekly_puzzle(week).to_thumbnail(THUMBNAIL_SIZE);
+
+    // Show the online leaderboard if it can be reached, the results on this device otherwise.
+    let local: Vec<_> = load_weekly()
+        .into_iter()
+        .filter(|e| e.week == week)
+        .collect();
+    let (entries, set_entries) = signal(local);
+    let (online, set_online) = signal(false);
+    if !settings.leaderboard_url.is_empty() {
+        spawn_local(async move {
+            match fetch_leaderboard(&settings.leaderboard_url, week).await {
+                Ok(leaderboard) => {
+                    set_entries.set(leaderboard);
+                    set_online.set(true);
+                }
+                Err(e) => log!("Cannot load leaderboard: {:?}", e),
+            }
+        });
+    }
+    let rows = move || {
+        let mut entries = entries.get();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.score));
+        entries
+            .into_iter()
+            .enumerate()
+            .map(|(rank, entry)| {
+                view! {
+                    <tr>
+                        <td>{rank + 1}</td>
+                        <td>{entry.name}</td>
+                        <td>{entry.score}</td>
+                        <td>{format_time(entry.elapsed_ms)}</td>
+                    </tr>
+                }
+            })
+            .collect_view()
+    };
+
+    view! {
+        <div><span class="menu">hexhashi</span><a class="menu" href="/">Back</a></div>
+        <h1>"Weekly challenge"</h1>
+        <p>"This week's puzzle is " {difficulty.to_string()} ". Everybody plays the same puzzle."</p>
+        <div class="thumbnail small" inner_html=thumbnail/>
+        <button onclick=play>"Play"</button>
+        <h2>{move || if online.get() { "Leaderboard" } else { "Your results (offline)" }}</h2>
+        <table class="stats">
+            <tr>
+                <th>"Rank"</th>
+                <th>"Name"</th>
+                <th>"Score"</th>
+                <th>"Time"</th>
+            </tr>
+            {rows}
+        </table>
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use hexhashi_logic::difficulty::Difficulty;
+
+    use super::{seed_week, week_of, weekly_difficulty, weekly_seed};
+
+    #[test]
+    fn weeks() {
+        // Thursday, 1970-01-01
+        assert_eq!(week_of(0.0), 0);
+        // Sunday, 1970-01-04
+        assert_eq!(week_of(3.5 * 86_400_000.0), 0);
+        // Monday, 1970-01-05
+        assert_eq!(week_of(4.0 * 86_400_000.0), 1);
+        assert_eq!(weekly_difficulty(1), Difficulty::Medium);
+        assert_eq!(weekly_difficulty(4), Difficulty::Easy);
+        assert_eq!(seed_week(weekly_seed(2900)), 2900);
+    }
+}